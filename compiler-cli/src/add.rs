@@ -7,7 +7,7 @@ use gleam_core::{
 
 use crate::{
     cli,
-    dependencies::{parse_gleam_add_specifier, UseManifest},
+    dependencies::{parse_gleam_add_specifier, Connectivity, UseManifest},
     fs,
 };
 
@@ -26,6 +26,7 @@ pub fn command(packages_to_add: Vec<String>, dev: bool) -> Result<()> {
         cli::Reporter::new(),
         Some((new_package_requirements.clone(), dev)),
         UseManifest::Yes,
+        Connectivity::Online,
     )?;
 
     // Read gleam.toml and manifest.toml so we can insert new deps into it
@@ -0,0 +1,150 @@
+//! Comparing the public API of the project with that of a previous version,
+//! to check that the new version number says how compatible they are.
+
+use camino::Utf8PathBuf;
+use gleam_core::{
+    analyse::TargetSupport,
+    build::{Codegen, Mode, Options, Package},
+    config::PackageConfig,
+    error::{FileIoAction, FileKind},
+    hex::HEXPM_PUBLIC_KEY,
+    io::HttpClient as _,
+    package_interface::{
+        diff::{ApiDiff, ChangeKind},
+        PackageInterface,
+    },
+    Error, Result,
+};
+use hexpm::version::Version;
+
+use crate::{fs, http::HttpClient};
+
+/// Compare the API of the project with a previous version, given either as
+/// the path of an exported package interface or as a version published to
+/// Hex.
+pub fn command(previous: String) -> Result<()> {
+    let config = crate::config::root_config()?;
+    let path = Utf8PathBuf::from(&previous);
+    let previous = if path.is_file() {
+        read_interface(path)?
+    } else {
+        let version = Version::parse(&previous).map_err(|error| Error::InvalidVersionFormat {
+            input: previous.clone(),
+            error: error.to_string(),
+        })?;
+        let runtime = tokio::runtime::Runtime::new().expect("Unable to start Tokio async runtime");
+        runtime
+            .block_on(published_interface(&config.name, &version))?
+            .ok_or_else(|| Error::PackageInterfaceUnavailable {
+                package: config.name.clone(),
+                version: version.to_string().into(),
+            })?
+    };
+
+    let diff = ApiDiff::new(&previous, &current_interface()?);
+    println!("Changes since version {}:\n", previous.version());
+    print!("{}", diff.to_pretty_string());
+    if let Ok(version) = Version::parse(previous.version()) {
+        println!(
+            "\nThese changes can be published as version {} or later.",
+            diff.minimum_version(&version)
+        );
+    }
+    Ok(())
+}
+
+/// Compare the API of a package about to be published with the version
+/// published before it, refusing breaking changes that the new version does
+/// not mark as breaking.
+pub fn check_for_unmarked_breaking_changes(
+    config: &PackageConfig,
+    package: &Package,
+) -> Result<()> {
+    let runtime = tokio::runtime::Runtime::new().expect("Unable to start Tokio async runtime");
+    let Some(previous_version) = runtime.block_on(previous_release(config))? else {
+        return Ok(());
+    };
+    let Some(previous) = runtime.block_on(published_interface(&config.name, &previous_version))?
+    else {
+        println!(
+            "\nNo package interface was published for version {previous_version} so API changes cannot be checked."
+        );
+        return Ok(());
+    };
+
+    let diff = ApiDiff::new(&previous, &PackageInterface::from_package(package));
+    println!("\nChanges since version {previous_version}:\n");
+    print!("{}", diff.to_pretty_string());
+
+    if diff.is_unmarked_breaking_change(&previous_version, &config.version) {
+        return Err(Error::UnmarkedBreakingChange {
+            package: config.name.clone(),
+            previous_version: previous_version.to_string().into(),
+            version: config.version.to_string().into(),
+            minimum_version: diff.minimum_version(&previous_version).to_string().into(),
+            changes: diff
+                .changes()
+                .iter()
+                .filter(|change| change.kind == ChangeKind::Breaking)
+                .map(|change| change.description.clone())
+                .collect(),
+        });
+    }
+    Ok(())
+}
+
+fn current_interface() -> Result<PackageInterface> {
+    let mut built = crate::build::main(
+        Options {
+            mode: Mode::Prod,
+            target: None,
+            codegen: Codegen::All,
+            warnings_as_errors: false,
+            root_target_support: TargetSupport::Enforced,
+        },
+        crate::build::download_dependencies()?,
+    )?;
+    built.root_package.attach_doc_and_module_comments();
+    Ok(PackageInterface::from_package(&built.root_package))
+}
+
+fn read_interface(path: Utf8PathBuf) -> Result<PackageInterface> {
+    let json = fs::read(&path)?;
+    serde_json::from_str(&json).map_err(|error| Error::FileIo {
+        action: FileIoAction::Parse,
+        kind: FileKind::File,
+        path,
+        err: Some(error.to_string()),
+    })
+}
+
+/// The latest version published to Hex before the one being published, if
+/// the package has been published before.
+async fn previous_release(config: &PackageConfig) -> Result<Option<Version>> {
+    let request = hexpm::get_package_request(&config.name, None, &hexpm::Config::new());
+    let response = HttpClient::new().send(request).await?;
+    match hexpm::get_package_response(response, HEXPM_PUBLIC_KEY) {
+        Ok(package) => Ok(package
+            .releases
+            .into_iter()
+            .map(|release| release.version)
+            .filter(|version| version < &config.version)
+            .max()),
+        Err(hexpm::ApiError::NotFound) => Ok(None),
+        Err(error) => Err(Error::hex(error)),
+    }
+}
+
+/// The package interface published to HexDocs along with the documentation
+/// of a version. Versions published by older Gleam versions may not have one.
+async fn published_interface(name: &str, version: &Version) -> Result<Option<PackageInterface>> {
+    let url = format!("https://hexdocs.pm/{name}/{version}/package-interface.json");
+    let request = http::Request::get(url)
+        .body(vec![])
+        .expect("Package interface request");
+    let response = HttpClient::new().send(request).await?;
+    if response.status() != http::StatusCode::OK {
+        return Ok(None);
+    }
+    Ok(serde_json::from_slice(response.body()).ok())
+}
@@ -0,0 +1,599 @@
+//! The `browser` runtime, which runs compiled JavaScript in a headless browser
+//! so that code using DOM APIs can be run and tested.
+//!
+//! The JavaScript build directory is served over HTTP from a local static
+//! server, and a locally installed Chromium based browser is started in
+//! headless mode and controlled with the Chrome DevTools protocol. Console
+//! messages and uncaught exceptions are printed to the terminal, and the exit
+//! status is sent back when `main` returns or the code calls `process.exit`.
+//!
+//! The protocol is spoken over a WebSocket, of which only the small part
+//! needed to talk to a local browser is implemented here.
+
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{TcpListener, TcpStream},
+    process::{Child, Command, Stdio},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use gleam_core::{
+    build::{Mode, Target},
+    config::{JavaScriptModuleFormat, PackageConfig},
+    error::Error,
+    paths::ProjectPaths,
+};
+use serde_json::{json, Value};
+
+/// The Chromium based browsers looked for on the `PATH`, in order.
+const BROWSERS: &[&str] = &[
+    "chromium",
+    "chromium-browser",
+    "google-chrome",
+    "google-chrome-stable",
+    "chrome",
+    "microsoft-edge",
+];
+
+/// The name of the function the page calls with its exit status.
+const EXIT_BINDING: &str = "gleamExit";
+
+pub fn run(
+    paths: &ProjectPaths,
+    config: &PackageConfig,
+    package: &str,
+    module: &str,
+    arguments: Vec<String>,
+) -> Result<i32, Error> {
+    if config.javascript.module_format == JavaScriptModuleFormat::CommonJs {
+        return Err(Error::BrowserRuntime {
+            reason: "CommonJS modules can not be loaded by browsers. Remove
+`module_format = \"commonjs\"` from gleam.toml to use the browser runtime."
+                .into(),
+        });
+    }
+
+    let root = paths.build_directory_for_target(Mode::Dev, Target::JavaScript);
+    write_entrypoint(&root.join(package), module, &arguments)?;
+    let port = serve(root).map_err(failure)?;
+    let url = format!("http://127.0.0.1:{port}/{package}/gleam.main.html");
+
+    let executable = find_browser(config)?;
+    let profile = std::env::temp_dir().join(format!("gleam-browser-{}", std::process::id()));
+    let mut browser = Browser::launch(&executable, &config.javascript.browser.arguments, &profile)?;
+    let status = browser.run(&url);
+    browser.close();
+    let _ = std::fs::remove_dir_all(&profile);
+    status
+}
+
+/// Writes the page that runs the main function, along with a module that
+/// imports and calls it.
+fn write_entrypoint(directory: &Utf8Path, module: &str, arguments: &[String]) -> Result<(), Error> {
+    let html = r#"<!doctype html>
+<html>
+  <head>
+    <meta charset="utf-8">
+    <script type="module" src="./gleam.main.browser.mjs"></script>
+  </head>
+  <body></body>
+</html>
+"#;
+    crate::fs::write(&directory.join("gleam.main.html"), html)?;
+
+    // Code written for the server side runtimes exits with `process.exit`,
+    // so a stand in for it is given to the page.
+    let argv = serde_json::to_string(arguments).expect("Arguments JSON");
+    let javascript = format!(
+        r#"const exit = (code = 0) => globalThis.{EXIT_BINDING}(String(code));
+globalThis.process ??= {{ argv: ["browser", "gleam.main.browser.mjs", ...{argv}], exit }};
+
+try {{
+  const {{ main }} = await import("./{module}.mjs");
+  await main();
+  exit(0);
+}} catch (error) {{
+  console.error(error);
+  exit(1);
+}}
+"#
+    );
+    crate::fs::write(&directory.join("gleam.main.browser.mjs"), &javascript)
+}
+
+fn find_browser(config: &PackageConfig) -> Result<Utf8PathBuf, Error> {
+    if let Some(executable) = &config.javascript.browser.executable {
+        return Ok(executable.clone());
+    }
+
+    let path = std::env::var_os("PATH").unwrap_or_default();
+    std::env::split_paths(&path)
+        .flat_map(|directory| BROWSERS.iter().map(move |name| directory.join(name)))
+        .find(|candidate| candidate.is_file())
+        .and_then(|candidate| Utf8PathBuf::from_path_buf(candidate).ok())
+        .ok_or_else(|| Error::ShellProgramNotFound {
+            program: "chromium".into(),
+        })
+}
+
+fn failure(error: impl std::fmt::Display) -> Error {
+    Error::BrowserRuntime {
+        reason: error.to_string(),
+    }
+}
+
+struct Browser {
+    process: Child,
+    /// The port the DevTools protocol is served on.
+    port: u16,
+}
+
+impl Browser {
+    fn launch(
+        executable: &Utf8Path,
+        arguments: &[String],
+        profile: &std::path::Path,
+    ) -> Result<Self, Error> {
+        let mut process = Command::new(executable)
+            .arg("--headless=new")
+            .arg("--remote-debugging-port=0")
+            .arg(format!("--user-data-dir={}", profile.display()))
+            .arg("--no-first-run")
+            .arg("--no-default-browser-check")
+            .args(arguments)
+            .arg("about:blank")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|error| match error.kind() {
+                io::ErrorKind::NotFound => Error::ShellProgramNotFound {
+                    program: executable.to_string(),
+                },
+                other => Error::ShellCommand {
+                    program: executable.to_string(),
+                    err: Some(other),
+                },
+            })?;
+
+        // The browser prints the address of the DevTools protocol once it is
+        // listening, having picked a free port.
+        let mut stderr = BufReader::new(process.stderr.take().expect("Browser stderr"));
+        let mut line = String::new();
+        let port = loop {
+            line.clear();
+            if stderr.read_line(&mut line).map_err(failure)? == 0 {
+                let _ = process.kill();
+                return Err(failure("The browser exited before it could be controlled"));
+            }
+            if let Some(port) = devtools_port(&line) {
+                break port;
+            }
+        };
+
+        // Anything else the browser prints is not of interest, but must still
+        // be read so that it doesn't block.
+        let _ = std::thread::spawn(move || io::copy(&mut stderr, &mut io::sink()));
+
+        Ok(Self { process, port })
+    }
+
+    fn run(&mut self, url: &str) -> Result<i32, Error> {
+        let targets = http_get(self.port, "/json/list").map_err(failure)?;
+        let targets: Value = serde_json::from_str(&targets).map_err(failure)?;
+        let page = targets
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|target| target.get("type").and_then(Value::as_str) == Some("page"))
+            .and_then(|target| target.get("webSocketDebuggerUrl")?.as_str())
+            .ok_or_else(|| failure("The browser has no page to run the code in"))?;
+
+        let mut socket = WebSocket::connect(page).map_err(failure)?;
+        let commands = [
+            json!({ "id": 0, "method": "Runtime.enable" }),
+            json!({
+                "id": 1,
+                "method": "Runtime.addBinding",
+                "params": { "name": EXIT_BINDING },
+            }),
+            json!({ "id": 2, "method": "Page.navigate", "params": { "url": url } }),
+        ];
+        for command in commands {
+            socket.send(&command.to_string()).map_err(failure)?;
+        }
+
+        loop {
+            let Some(message) = socket.receive().map_err(failure)? else {
+                return Err(failure("The browser closed the connection"));
+            };
+            let message: Value = serde_json::from_str(&message).map_err(failure)?;
+            if let Some(error) = message.get("error") {
+                return Err(failure(error.get("message").unwrap_or(error)));
+            }
+            if let Some(status) = handle_event(&message) {
+                return Ok(status);
+            }
+        }
+    }
+
+    fn close(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+/// Prints a message from the page, returning the exit status if the page has
+/// finished.
+fn handle_event(message: &Value) -> Option<i32> {
+    let params = message.get("params");
+    let param = |name: &str| params.and_then(|params| params.get(name));
+    match message.get("method")?.as_str()? {
+        "Runtime.consoleAPICalled" => {
+            let text = param("args")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .map(remote_object_text)
+                .collect::<Vec<_>>()
+                .join(" ");
+            match param("type").and_then(Value::as_str) {
+                Some("error" | "warning" | "assert" | "trace") => eprintln!("{text}"),
+                _ => println!("{text}"),
+            }
+            None
+        }
+
+        "Runtime.exceptionThrown" => {
+            let details = param("exceptionDetails");
+            let text = details
+                .and_then(|details| details.get("exception"))
+                .and_then(|exception| exception.get("description")?.as_str())
+                .or_else(|| details?.get("text")?.as_str())
+                .unwrap_or("Uncaught exception");
+            eprintln!("{text}");
+            Some(1)
+        }
+
+        "Runtime.bindingCalled" if param("name").and_then(Value::as_str) == Some(EXIT_BINDING) => {
+            Some(param("payload")?.as_str()?.parse().unwrap_or(1))
+        }
+
+        "Inspector.targetCrashed" => {
+            eprintln!("The browser page crashed");
+            Some(1)
+        }
+
+        _ => None,
+    }
+}
+
+/// The text a value logged to the console is printed as.
+fn remote_object_text(object: &Value) -> String {
+    let field = |name: &str| object.get(name).unwrap_or(&Value::Null);
+    match (
+        field("value"),
+        field("unserializableValue"),
+        field("description"),
+    ) {
+        (Value::String(value), _, _) => value.clone(),
+        (_, Value::String(value), _) | (_, _, Value::String(value)) => value.clone(),
+        (Value::Null, _, _) => match field("subtype").as_str() {
+            Some(subtype) => subtype.into(),
+            None => field("type").as_str().unwrap_or("undefined").into(),
+        },
+        (value, _, _) => value.to_string(),
+    }
+}
+
+/// The port from the line the browser prints once the DevTools protocol is
+/// listening, such as `DevTools listening on ws://127.0.0.1:9222/devtools/...`.
+fn devtools_port(line: &str) -> Option<u16> {
+    let address = line.trim().strip_prefix("DevTools listening on ws://")?;
+    let (host, _path) = address.split_once('/')?;
+    host.rsplit_once(':')?.1.parse().ok()
+}
+
+fn http_get(port: u16, path: &str) -> io::Result<String> {
+    let mut stream = TcpStream::connect(("127.0.0.1", port))?;
+    write!(
+        stream,
+        "GET {path} HTTP/1.1\r\nHost: 127.0.0.1:{port}\r\nConnection: close\r\n\r\n"
+    )?;
+    let mut response = String::new();
+    let _ = stream.read_to_string(&mut response)?;
+    match response.split_once("\r\n\r\n") {
+        Some((_headers, body)) => Ok(body.into()),
+        None => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Invalid HTTP response",
+        )),
+    }
+}
+
+/// Serves the files in the given directory over HTTP on a free local port,
+/// returning the port.
+fn serve(root: Utf8PathBuf) -> io::Result<u16> {
+    let listener = TcpListener::bind(("127.0.0.1", 0))?;
+    let port = listener.local_addr()?.port();
+    let _ = std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let _ = respond(&root, stream);
+        }
+    });
+    Ok(port)
+}
+
+fn respond(root: &Utf8Path, mut stream: TcpStream) -> io::Result<()> {
+    let mut reader = BufReader::new(&stream);
+    let mut request = String::new();
+    let _ = reader.read_line(&mut request)?;
+    // The headers are not needed, but are read so the client isn't reset
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 2 {
+        header.clear();
+    }
+
+    let file = request
+        .split_whitespace()
+        .nth(1)
+        .and_then(|path| served_file(root, path))
+        .and_then(|file| Some((content_type(&file), std::fs::read(&file).ok()?)));
+    match file {
+        Some((content_type, body)) => {
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            )?;
+            stream.write_all(&body)
+        }
+        None => write!(
+            stream,
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+        ),
+    }
+}
+
+/// The file a request path refers to, if it is within the served directory.
+fn served_file(root: &Utf8Path, path: &str) -> Option<Utf8PathBuf> {
+    let path = path.split(['?', '#']).next()?.strip_prefix('/')?;
+    let mut file = root.to_path_buf();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => (),
+            ".." => return None,
+            segment => file.push(segment),
+        }
+    }
+    Some(file)
+}
+
+fn content_type(path: &Utf8Path) -> &'static str {
+    match path.extension() {
+        Some("js" | "mjs") => "text/javascript",
+        Some("html") => "text/html",
+        Some("css") => "text/css",
+        Some("json") => "application/json",
+        Some("wasm") => "application/wasm",
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("txt") => "text/plain",
+        _ => "application/octet-stream",
+    }
+}
+
+/// A WebSocket client connection, as described in RFC 6455.
+struct WebSocket {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl WebSocket {
+    fn connect(url: &str) -> io::Result<Self> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidInput, "Invalid WebSocket URL");
+        let address = url.strip_prefix("ws://").ok_or_else(invalid)?;
+        let (host, path) = address.split_once('/').ok_or_else(invalid)?;
+
+        let mut writer = TcpStream::connect(host)?;
+        let mut reader = BufReader::new(writer.try_clone()?);
+        // The key is only checked by proxies, which a local connection
+        // doesn't go through, so the example key from the RFC is used.
+        write!(
+            writer,
+            "GET /{path} HTTP/1.1\r\nHost: {host}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n"
+        )?;
+
+        let mut line = String::new();
+        let _ = reader.read_line(&mut line)?;
+        if !line.starts_with("HTTP/1.1 101") {
+            return Err(io::Error::new(
+                io::ErrorKind::ConnectionRefused,
+                format!("WebSocket upgrade refused: {}", line.trim()),
+            ));
+        }
+        while line.len() > 2 {
+            line.clear();
+            let _ = reader.read_line(&mut line)?;
+        }
+
+        Ok(Self { reader, writer })
+    }
+
+    fn send(&mut self, text: &str) -> io::Result<()> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.subsec_nanos())
+            .unwrap_or_default();
+        let frame = encode_frame(OPCODE_TEXT, text.as_bytes(), nanos.to_be_bytes());
+        self.writer.write_all(&frame)
+    }
+
+    /// Receives the next text message, or `None` if the connection has been
+    /// closed.
+    fn receive(&mut self) -> io::Result<Option<String>> {
+        let mut message = vec![];
+        loop {
+            let mut header = [0; 2];
+            if let Err(error) = self.reader.read_exact(&mut header) {
+                return match error.kind() {
+                    io::ErrorKind::UnexpectedEof => Ok(None),
+                    _ => Err(error),
+                };
+            }
+            let fin = header[0] & 0x80 != 0;
+            let opcode = header[0] & 0x0f;
+            let length = match header[1] & 0x7f {
+                126 => {
+                    let mut length = [0; 2];
+                    self.reader.read_exact(&mut length)?;
+                    u64::from(u16::from_be_bytes(length))
+                }
+                127 => {
+                    let mut length = [0; 8];
+                    self.reader.read_exact(&mut length)?;
+                    u64::from_be_bytes(length)
+                }
+                length => u64::from(length),
+            };
+            let mut payload = vec![];
+            let _ = (&mut self.reader).take(length).read_to_end(&mut payload)?;
+
+            match opcode {
+                OPCODE_CLOSE => return Ok(None),
+                OPCODE_PING => {
+                    let frame = encode_frame(OPCODE_PONG, &payload, [0; 4]);
+                    self.writer.write_all(&frame)?;
+                }
+                OPCODE_PONG => (),
+                _ => {
+                    message.extend(payload);
+                    if fin {
+                        return String::from_utf8(message)
+                            .map(Some)
+                            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error));
+                    }
+                }
+            }
+        }
+    }
+}
+
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xa;
+
+/// Encodes a single frame sent by a client, which must always be masked.
+fn encode_frame(opcode: u8, payload: &[u8], mask: [u8; 4]) -> Vec<u8> {
+    let mut frame = vec![0x80 | opcode];
+    match payload.len() {
+        length @ 0..=125 => frame.push(0x80 | length as u8),
+        length @ 126..=0xffff => {
+            frame.push(0x80 | 126);
+            frame.extend((length as u16).to_be_bytes());
+        }
+        length => {
+            frame.push(0x80 | 127);
+            frame.extend((length as u64).to_be_bytes());
+        }
+    }
+    frame.extend(mask);
+    frame.extend(
+        payload
+            .iter()
+            .zip(mask.iter().cycle())
+            .map(|(byte, mask)| byte ^ mask),
+    );
+    frame
+}
+
+#[test]
+fn devtools_port_is_read_from_the_browser_output() {
+    assert_eq!(
+        devtools_port("DevTools listening on ws://127.0.0.1:36123/devtools/browser/b9f1-4d2c\n"),
+        Some(36123)
+    );
+    assert_eq!(
+        devtools_port("[0101/000000.000000:ERROR] Something else"),
+        None
+    );
+}
+
+#[test]
+fn served_files_are_within_the_root() {
+    let root = Utf8Path::new("/build/dev/javascript");
+    assert_eq!(
+        served_file(root, "/app/gleam.main.html?v=1"),
+        Some(Utf8PathBuf::from(
+            "/build/dev/javascript/app/gleam.main.html"
+        ))
+    );
+    assert_eq!(
+        served_file(root, "/prelude.mjs"),
+        Some(Utf8PathBuf::from("/build/dev/javascript/prelude.mjs"))
+    );
+    assert_eq!(served_file(root, "/app/../../secret"), None);
+    assert_eq!(served_file(root, "app/gleam.main.html"), None);
+}
+
+#[test]
+fn client_frames_are_masked() {
+    assert_eq!(
+        encode_frame(OPCODE_TEXT, b"Hi", [1, 2, 3, 4]),
+        vec![0x81, 0x82, 1, 2, 3, 4, b'H' ^ 1, b'i' ^ 2]
+    );
+
+    let frame = encode_frame(OPCODE_TEXT, &[0; 300], [0; 4]);
+    assert!(frame.starts_with(&[0x81, 0x80 | 126, 1, 44]));
+    assert_eq!(frame.len(), 4 + 4 + 300);
+}
+
+#[test]
+fn console_values_are_printed_as_text() {
+    let text = |object: Value| remote_object_text(&object);
+    assert_eq!(text(json!({ "type": "string", "value": "Hello" })), "Hello");
+    assert_eq!(text(json!({ "type": "number", "value": 1.5 })), "1.5");
+    assert_eq!(
+        text(json!({ "type": "bigint", "unserializableValue": "10n" })),
+        "10n"
+    );
+    assert_eq!(
+        text(json!({ "type": "object", "className": "Ok", "description": "Ok" })),
+        "Ok"
+    );
+    assert_eq!(text(json!({ "type": "undefined" })), "undefined");
+    assert_eq!(
+        text(json!({ "type": "object", "subtype": "null", "value": null })),
+        "null"
+    );
+}
+
+#[test]
+fn exit_status_is_read_from_page_events() {
+    let exit = |payload: &str| {
+        json!({
+            "method": "Runtime.bindingCalled",
+            "params": { "name": EXIT_BINDING, "payload": payload },
+        })
+    };
+    assert_eq!(handle_event(&exit("3")), Some(3));
+    assert_eq!(handle_event(&exit("wibble")), Some(1));
+    assert_eq!(
+        handle_event(&json!({
+            "method": "Runtime.bindingCalled",
+            "params": { "name": "somethingElse", "payload": "3" },
+        })),
+        None
+    );
+    assert_eq!(
+        handle_event(&json!({
+            "method": "Runtime.exceptionThrown",
+            "params": { "exceptionDetails": { "text": "Uncaught" } },
+        })),
+        Some(1)
+    );
+    assert_eq!(handle_event(&json!({ "id": 2, "result": {} })), None);
+}
@@ -10,13 +10,19 @@ use gleam_core::{
 use crate::{
     build_lock::BuildLock,
     cli,
-    dependencies::UseManifest,
+    dependencies::{Connectivity, UseManifest},
     fs::{self, get_current_directory, get_project_root, ConsoleWarningEmitter},
 };
 
 pub fn download_dependencies() -> Result<Manifest> {
     let paths = crate::find_project_paths()?;
-    crate::dependencies::download(&paths, cli::Reporter::new(), None, UseManifest::Yes)
+    crate::dependencies::download(
+        &paths,
+        cli::Reporter::new(),
+        None,
+        UseManifest::Yes,
+        Connectivity::Online,
+    )
 }
 
 pub fn main(options: Options, manifest: Manifest) -> Result<Built> {
@@ -39,6 +39,10 @@ impl Telemetry for Reporter {
         print_resolving_versions()
     }
 
+    fn resolving_package_versions_offline(&self) {
+        print_resolving_versions_offline()
+    }
+
     fn waiting_for_build_directory_lock(&self) {
         print_waiting_for_build_directory_lock()
     }
@@ -91,6 +95,26 @@ pub fn print_unretired(package: &str, version: &str) {
     print_colourful_prefix("Unretired", &format!("{package} {version}"))
 }
 
+pub fn print_vendored(count: usize) {
+    let msg = match count {
+        1 => "1 package".into(),
+        _ => format!("{count} packages"),
+    };
+    print_colourful_prefix("Vendored", &msg)
+}
+
+pub fn print_verified(count: usize) {
+    let msg = match count {
+        1 => "1 package".into(),
+        _ => format!("{count} packages"),
+    };
+    print_colourful_prefix("Verified", &msg)
+}
+
+pub fn print_restored(package: &str) {
+    print_colourful_prefix("Restored", package)
+}
+
 pub fn print_publishing_documentation() {
     print_colourful_prefix("Publishing", "documentation");
 }
@@ -107,6 +131,10 @@ fn print_resolving_versions() {
     print_colourful_prefix("Resolving", "versions")
 }
 
+fn print_resolving_versions_offline() {
+    print_colourful_prefix("Resolving", "versions offline")
+}
+
 fn print_compiling(text: &str) {
     print_colourful_prefix("Compiling", text)
 }
@@ -33,6 +33,9 @@ pub fn command(options: CompilePackage) -> Result<()> {
             prelude_location: options
                 .javascript_prelude
                 .ok_or_else(|| Error::JavaScriptPreludeRequired)?,
+            integers: config.javascript.integers,
+            module_format: config.javascript.module_format,
+            check_externals: false,
         },
     };
 
@@ -50,7 +50,7 @@ pub fn find_package_config_for_module(
     Ok((root_config()?, PackageKind::Root))
 }
 
-fn package_root(package: &ManifestPackage, project_paths: &ProjectPaths) -> Utf8PathBuf {
+pub fn package_root(package: &ManifestPackage, project_paths: &ProjectPaths) -> Utf8PathBuf {
     match &package.source {
         ManifestPackageSource::Local { path } => project_paths.root().join(path),
 
@@ -101,6 +101,7 @@ mod tests {
             requirements: vec![],
             source: ManifestPackageSource::Hex {
                 outer_checksum: Base16Checksum(vec![]),
+                repository: None,
             },
         };
         assert_eq!(
@@ -1,5 +1,7 @@
 use std::{
+    cell::RefCell,
     collections::{HashMap, HashSet},
+    rc::Rc,
     time::Instant,
 };
 
@@ -11,15 +13,15 @@ use gleam_core::{
     build::{Mode, Target, Telemetry},
     config::PackageConfig,
     dependency,
-    error::{FileIoAction, FileKind, StandardIoAction},
+    error::{FileIoAction, FileKind, LicenceViolation, StandardIoAction},
     hex::{self, HEXPM_PUBLIC_KEY},
     io::{HttpClient as _, TarUnpacker, WrappedReader},
     manifest::{Base16Checksum, Manifest, ManifestPackage, ManifestPackageSource},
-    paths::ProjectPaths,
+    paths::{PackageCache, ProjectPaths},
     requirement::Requirement,
     Error, Result,
 };
-use hexpm::version::Version;
+use hexpm::{version::Version, Release, ReleaseMeta};
 use itertools::Itertools;
 use same_file::is_same_file;
 use strum::IntoEnumIterator;
@@ -29,6 +31,9 @@ use crate::{
     cli,
     fs::{self, ProjectIO},
     http::HttpClient,
+    package_cache,
+    vendor::{self, VendoredPackages},
+    verify,
 };
 
 pub fn list() -> Result<()> {
@@ -43,6 +48,7 @@ pub fn list() -> Result<()> {
         &config,
         &cli::Reporter::new(),
         UseManifest::Yes,
+        Connectivity::Online,
     )?;
     list_manifest_packages(std::io::stdout(), manifest)
 }
@@ -72,6 +78,7 @@ fn list_manifest_format() {
                 requirements: vec![],
                 source: ManifestPackageSource::Hex {
                     outer_checksum: Base16Checksum(vec![1, 2, 3, 4]),
+                    repository: None,
                 },
             },
             ManifestPackage {
@@ -82,6 +89,7 @@ fn list_manifest_format() {
                 requirements: vec!["zzz".into(), "gleam_stdlib".into()],
                 source: ManifestPackageSource::Hex {
                     outer_checksum: Base16Checksum(vec![3, 22]),
+                    repository: None,
                 },
             },
             ManifestPackage {
@@ -92,6 +100,7 @@ fn list_manifest_format() {
                 requirements: vec![],
                 source: ManifestPackageSource::Hex {
                     outer_checksum: Base16Checksum(vec![3, 22]),
+                    repository: None,
                 },
             },
         ],
@@ -106,18 +115,838 @@ zzz 0.4.0
     )
 }
 
-#[derive(Debug, Clone, Copy)]
+pub fn tree() -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let config = crate::config::root_config()?;
+    let manifest = crate::build::download_dependencies()?;
+    let requirements = package_requirements(&paths, &manifest);
+    DependencyGraph::new(&config.name, &manifest, requirements).print_tree(std::io::stdout())
+}
+
+pub fn why(package: EcoString) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let config = crate::config::root_config()?;
+    let manifest = crate::build::download_dependencies()?;
+    let requirements = package_requirements(&paths, &manifest);
+    DependencyGraph::new(&config.name, &manifest, requirements)
+        .print_paths_to(std::io::stdout(), &package)
+}
+
+pub fn check_licences() -> Result<()> {
+    let config = crate::config::root_config()?;
+    if config.licence_policy.is_empty() {
+        println!("No licence policy is configured in gleam.toml");
+        return Ok(());
+    }
+    // Downloading the dependencies checks them against the licence policy
+    let _ = crate::build::download_dependencies()?;
+    println!("All dependency packages meet the licence policy");
+    Ok(())
+}
+
+/// Check that the licences of every dependency package meet the licence
+/// policy in `gleam.toml`.
+fn check_licence_policy(
+    paths: &ProjectPaths,
+    config: &PackageConfig,
+    manifest: &Manifest,
+) -> Result<()> {
+    let policy = &config.licence_policy;
+    if policy.is_empty() {
+        return Ok(());
+    }
+    let graph = DependencyGraph::new(
+        &config.name,
+        manifest,
+        package_requirements(paths, manifest),
+    );
+    let violations = manifest
+        .packages
+        .iter()
+        .filter_map(|package| {
+            let licences = package_licences(paths, config, package);
+            if policy.permits(&licences) {
+                return None;
+            }
+            Some(LicenceViolation {
+                package: package.name.clone(),
+                version: package.version.to_string().into(),
+                licences,
+                dependency_path: graph.first_path_to(&package.name),
+            })
+        })
+        .collect_vec();
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::LicencePolicyViolation { violations })
+    }
+}
+
+/// The licences a package declares in its `gleam.toml`, or in its Hex
+/// metadata if it is not a Gleam package.
+fn package_licences(
+    paths: &ProjectPaths,
+    config: &PackageConfig,
+    package: &ManifestPackage,
+) -> Vec<EcoString> {
+    let config_path = crate::config::package_root(package, paths).join("gleam.toml");
+    if config_path.is_file() {
+        if let Ok(config) = crate::config::read(config_path) {
+            return config
+                .licences
+                .iter()
+                .map(|licence| licence.as_ref().into())
+                .collect();
+        }
+    }
+    match &package.source {
+        ManifestPackageSource::Hex { repository, .. } => {
+            let repository_url = match repository {
+                Some(name) => match config.hex_repository(name) {
+                    Ok(repository) => Some(&repository.repository_url),
+                    Err(_) => return vec![],
+                },
+                None => None,
+            };
+            package_cache::licences(repository_url, &package.name, &package.version)
+                .unwrap_or_default()
+                .into_iter()
+                .map(EcoString::from)
+                .collect()
+        }
+        ManifestPackageSource::Git { .. } | ManifestPackageSource::Local { .. } => vec![],
+    }
+}
+
+pub fn outdated() -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let config = crate::config::root_config()?;
+    let manifest = crate::build::download_dependencies()?;
+    let runtime = tokio::runtime::Runtime::new().expect("Unable to start Tokio async runtime");
+    let fetched = Rc::new(RefCell::new(FetchedPackages {
+        repositories: manifest_repositories(&manifest),
+        ..Default::default()
+    }));
+    let fetcher = PackageFetcher::boxed(
+        runtime.handle().clone(),
+        hex_repositories(&paths, &config)?,
+        Connectivity::Online,
+        fetched,
+    );
+    let releases = manifest
+        .packages
+        .iter()
+        .filter(|package| package.is_hex())
+        .map(|package| {
+            let releases =
+                dependency::PackageFetcher::get_dependencies(fetcher.as_ref(), &package.name)
+                    .map_err(|error| Error::Hex(error.to_string()))?;
+            Ok((package.name.clone(), releases))
+        })
+        .collect::<Result<_>>()?;
+    print_outdated_packages(std::io::stdout(), &outdated_packages(&manifest, &releases))
+}
+
+/// A package with a newer version than the one locked in the manifest, or
+/// whose locked version has been retired.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct OutdatedPackage {
+    name: EcoString,
+    current: Version,
+    /// The latest version allowed by the requirements on the package.
+    compatible: Option<Version>,
+    latest: Option<Version>,
+    retired: Option<&'static str>,
+}
+
+fn outdated_packages(
+    manifest: &Manifest,
+    releases: &HashMap<EcoString, hexpm::Package>,
+) -> Vec<OutdatedPackage> {
+    // The ranges each package is required in: by the root package, and by the
+    // locked release of each package that depends on it.
+    let mut ranges: HashMap<&str, Vec<hexpm::version::Range>> = HashMap::new();
+    for (name, requirement) in &manifest.requirements {
+        if let Requirement::Hex { version, .. } = requirement {
+            ranges.entry(name).or_default().push(version.clone());
+        }
+    }
+    for package in &manifest.packages {
+        let locked = releases.get(&package.name).and_then(|package_releases| {
+            package_releases
+                .releases
+                .iter()
+                .find(|release| release.version == package.version)
+        });
+        for (name, dependency) in locked.iter().flat_map(|release| &release.requirements) {
+            ranges
+                .entry(name.as_str())
+                .or_default()
+                .push(dependency.requirement.clone());
+        }
+    }
+
+    manifest
+        .packages
+        .iter()
+        .sorted_by_key(|package| &package.name)
+        .filter_map(|package| {
+            let package_releases = &releases.get(&package.name)?.releases;
+            let stable = package_releases
+                .iter()
+                .filter(|release| !release.version.is_pre() || release.version == package.version);
+            let ranges = ranges
+                .get(package.name.as_str())
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let compatible = stable
+                .clone()
+                .map(|release| &release.version)
+                .filter(|version| {
+                    ranges.iter().all(|range| {
+                        range
+                            .to_pubgrub()
+                            .map(|range| range.contains(version))
+                            .unwrap_or(false)
+                    })
+                })
+                .max()
+                .cloned();
+            let latest = stable.map(|release| &release.version).max().cloned();
+            let retired = package_releases
+                .iter()
+                .find(|release| release.version == package.version)
+                .and_then(|release| release.retirement_status.as_ref())
+                .map(|status| status.reason.to_str());
+
+            let newer = |version: &Option<Version>| {
+                version
+                    .as_ref()
+                    .is_some_and(|version| version > &package.version)
+            };
+            if !newer(&compatible) && !newer(&latest) && retired.is_none() {
+                return None;
+            }
+            Some(OutdatedPackage {
+                name: package.name.clone(),
+                current: package.version.clone(),
+                compatible,
+                latest,
+                retired,
+            })
+        })
+        .collect()
+}
+
+fn print_outdated_packages<W: std::io::Write>(
+    mut buffer: W,
+    packages: &[OutdatedPackage],
+) -> Result<()> {
+    let write_error = |error: std::io::Error| Error::StandardIo {
+        action: StandardIoAction::Write,
+        err: Some(error.kind()),
+    };
+    if packages.is_empty() {
+        return writeln!(buffer, "All dependencies are up to date").map_err(write_error);
+    }
+
+    let version = |version: &Option<Version>| {
+        version
+            .as_ref()
+            .map_or_else(|| "-".into(), Version::to_string)
+    };
+    let rows = std::iter::once(
+        ["Package", "Current", "Compatible", "Latest", "Retired"].map(String::from),
+    )
+    .chain(packages.iter().map(|package| {
+        [
+            package.name.to_string(),
+            package.current.to_string(),
+            version(&package.compatible),
+            version(&package.latest),
+            package.retired.unwrap_or("-").into(),
+        ]
+    }))
+    .collect_vec();
+    let mut widths = [0; 5];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = cell.len().max(*width);
+        }
+    }
+    for row in &rows {
+        let line = row
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .join("  ");
+        writeln!(buffer, "{}", line.trim_end()).map_err(write_error)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn outdated_packages_format() {
+    let hex_package = |name: &str, version| ManifestPackage {
+        name: name.into(),
+        version,
+        build_tools: ["gleam".into()].into(),
+        otp_app: None,
+        requirements: vec![],
+        source: ManifestPackageSource::Hex {
+            outer_checksum: Base16Checksum(vec![]),
+            repository: None,
+        },
+    };
+    let release = |version, requirements: &[(&str, &str)]| Release {
+        version,
+        requirements: requirements
+            .iter()
+            .map(|(name, range)| {
+                let dependency = hexpm::Dependency {
+                    requirement: hexpm::version::Range::new(range.to_string()),
+                    optional: false,
+                    app: None,
+                    repository: None,
+                };
+                (name.to_string(), dependency)
+            })
+            .collect(),
+        retirement_status: None,
+        outer_checksum: vec![],
+        meta: (),
+    };
+    let releases = |name: &str, releases| {
+        let package = hexpm::Package {
+            name: name.into(),
+            repository: "hexpm".into(),
+            releases,
+        };
+        (EcoString::from(name), package)
+    };
+
+    let mut retired = release(Version::new(1, 0, 0), &[("transitive", "~> 0.5.0")]);
+    retired.retirement_status = Some(hexpm::RetirementStatus {
+        reason: hexpm::RetirementReason::Security,
+        message: "Leaks secrets".into(),
+    });
+    let manifest = Manifest {
+        requirements: [
+            ("direct".into(), Requirement::hex("~> 1.0")),
+            ("up_to_date".into(), Requirement::hex(">= 1.0.0")),
+        ]
+        .into(),
+        packages: vec![
+            hex_package("direct", Version::new(1, 0, 0)),
+            hex_package("transitive", Version::new(0, 5, 0)),
+            hex_package("up_to_date", Version::new(1, 0, 0)),
+        ],
+    };
+    let releases = [
+        releases(
+            "direct",
+            vec![
+                retired,
+                release(Version::new(1, 2, 0), &[("transitive", ">= 0.5.0")]),
+                release(Version::new(2, 0, 0), &[]),
+                release(Version::parse("3.0.0-rc1").unwrap(), &[]),
+            ],
+        ),
+        releases(
+            "transitive",
+            vec![
+                release(Version::new(0, 5, 0), &[]),
+                release(Version::new(0, 5, 1), &[]),
+                release(Version::new(0, 6, 0), &[]),
+            ],
+        ),
+        releases("up_to_date", vec![release(Version::new(1, 0, 0), &[])]),
+    ]
+    .into();
+    let packages = outdated_packages(&manifest, &releases);
+
+    let mut buffer = vec![];
+    print_outdated_packages(&mut buffer, &packages).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&buffer).unwrap(),
+        r#"Package     Current  Compatible  Latest  Retired
+direct      1.0.0    1.2.0       2.0.0   security
+transitive  0.5.0    0.5.1       0.6.0   -
+"#
+    );
+
+    let mut buffer = vec![];
+    print_outdated_packages(&mut buffer, &[]).unwrap();
+    assert_eq!(
+        std::str::from_utf8(&buffer).unwrap(),
+        "All dependencies are up to date\n"
+    );
+}
+
+/// The requirements of the root package and of each dependency that has a
+/// `gleam.toml`. The manifest only records the names of the packages each
+/// package requires, so the version ranges come from their configs.
+fn package_requirements(
+    paths: &ProjectPaths,
+    manifest: &Manifest,
+) -> HashMap<EcoString, HashMap<EcoString, Requirement>> {
+    manifest
+        .packages
+        .iter()
+        .filter(|package| package.build_tools.contains(&"gleam".into()))
+        .filter_map(|package| {
+            let root = crate::config::package_root(package, paths);
+            let config = crate::config::read(root.join("gleam.toml")).ok()?;
+            Some((package.name.clone(), config.dependencies))
+        })
+        .collect()
+}
+
+/// The graph of requirements between the root package and its dependencies,
+/// as recorded in the manifest.
+struct DependencyGraph<'a> {
+    root: &'a EcoString,
+    root_requirements: &'a HashMap<EcoString, Requirement>,
+    packages: HashMap<&'a str, &'a ManifestPackage>,
+    requirements: HashMap<EcoString, HashMap<EcoString, Requirement>>,
+}
+
+impl<'a> DependencyGraph<'a> {
+    fn new(
+        root: &'a EcoString,
+        manifest: &'a Manifest,
+        requirements: HashMap<EcoString, HashMap<EcoString, Requirement>>,
+    ) -> Self {
+        Self {
+            root,
+            root_requirements: &manifest.requirements,
+            packages: manifest
+                .packages
+                .iter()
+                .map(|package| (package.name.as_str(), package))
+                .collect(),
+            requirements,
+        }
+    }
+
+    fn dependencies(&self, package: &str) -> Vec<&'a EcoString> {
+        if package == self.root {
+            return self.root_requirements.keys().sorted().collect();
+        }
+        match self.packages.get(package) {
+            Some(package) => package.requirements.iter().sorted().collect(),
+            None => vec![],
+        }
+    }
+
+    /// The name and version of a package, followed by the range of versions
+    /// that the package depending on it requires, if known.
+    fn describe(&self, parent: &str, package: &str) -> String {
+        let mut description = match self.packages.get(package) {
+            Some(manifest_package) => format!("{package} {}", manifest_package.version),
+            None => package.to_string(),
+        };
+        let requirement = if parent == self.root {
+            self.root_requirements.get(package)
+        } else {
+            self.requirements
+                .get(parent)
+                .and_then(|requirements| requirements.get(package))
+        };
+        match requirement {
+            Some(Requirement::Hex { version, .. }) => {
+                description.push_str(&format!(" ({version})"))
+            }
+            Some(Requirement::Path { path }) => description.push_str(&format!(" (path: {path})")),
+            Some(Requirement::Git { git }) => description.push_str(&format!(" (git: {git})")),
+            None => (),
+        }
+        description
+    }
+
+    fn print_tree<W: std::io::Write>(&self, mut buffer: W) -> Result<()> {
+        let mut output = format!("{}\n", self.root);
+        let mut shown = HashSet::new();
+        self.tree_lines(&mut output, self.root, "", &mut shown);
+        write!(buffer, "{output}").map_err(|e| Error::StandardIo {
+            action: StandardIoAction::Write,
+            err: Some(e.kind()),
+        })
+    }
+
+    fn tree_lines(
+        &self,
+        output: &mut String,
+        parent: &str,
+        indent: &str,
+        shown: &mut HashSet<&'a EcoString>,
+    ) {
+        let dependencies = self.dependencies(parent);
+        let count = dependencies.len();
+        for (index, package) in dependencies.into_iter().enumerate() {
+            let last = index + 1 == count;
+            let (branch, child_indent) = if last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            };
+            output.push_str(indent);
+            output.push_str(branch);
+            output.push_str(&self.describe(parent, package));
+
+            // Packages required by more than one package have their own
+            // dependencies shown only the first time they appear.
+            if !shown.insert(package) && !self.dependencies(package).is_empty() {
+                output.push_str(" (*)\n");
+                continue;
+            }
+            output.push('\n');
+            self.tree_lines(output, package, &format!("{indent}{child_indent}"), shown);
+        }
+    }
+
+    fn print_paths_to<W: std::io::Write>(&self, mut buffer: W, package: &str) -> Result<()> {
+        let Some(manifest_package) = self.packages.get(package) else {
+            return Err(Error::PackageNotADependency {
+                package: package.into(),
+            });
+        };
+        let mut paths = vec![];
+        self.paths_to(package, &mut vec![self.root.as_str()], &mut paths);
+
+        let mut output = format!(
+            "{} {} is required by:\n",
+            manifest_package.name, manifest_package.version
+        );
+        for path in paths {
+            output.push_str(&format!("\n  {}", self.describe_path(&path)));
+        }
+        writeln!(buffer, "{output}").map_err(|e| Error::StandardIo {
+            action: StandardIoAction::Write,
+            err: Some(e.kind()),
+        })
+    }
+
+    /// The first of the paths from the root package to a dependency package.
+    fn first_path_to(&self, package: &str) -> String {
+        let mut paths = vec![];
+        self.paths_to(package, &mut vec![self.root.as_str()], &mut paths);
+        match paths.first() {
+            Some(path) => self.describe_path(path),
+            None => format!("{} -> {package}", self.root),
+        }
+    }
+
+    fn describe_path(&self, path: &[&str]) -> String {
+        let steps = path
+            .iter()
+            .tuple_windows()
+            .map(|(parent, package)| self.describe(parent, package));
+        std::iter::once(self.root.to_string())
+            .chain(steps)
+            .join(" -> ")
+    }
+
+    fn paths_to(&self, target: &str, path: &mut Vec<&'a str>, paths: &mut Vec<Vec<&'a str>>) {
+        let current = *path.last().expect("Path starts with the root package");
+        for package in self.dependencies(current) {
+            // The manifest shouldn't contain cycles, but we don't want to
+            // loop forever if it does.
+            if path.contains(&package.as_str()) {
+                continue;
+            }
+            path.push(package.as_str());
+            if package == target {
+                paths.push(path.clone());
+            } else {
+                self.paths_to(target, path, paths);
+            }
+            let _ = path.pop();
+        }
+    }
+}
+
+#[cfg(test)]
+fn dependency_graph_manifest() -> Manifest {
+    let package = |name: &str, version: Version, requirements: &[&str]| ManifestPackage {
+        name: name.into(),
+        version,
+        build_tools: ["gleam".into()].into(),
+        otp_app: None,
+        requirements: requirements.iter().map(|name| (*name).into()).collect(),
+        source: ManifestPackageSource::Hex {
+            outer_checksum: Base16Checksum(vec![1, 2, 3, 4]),
+            repository: None,
+        },
+    };
+    Manifest {
+        requirements: [
+            (
+                "gleam_stdlib".into(),
+                Requirement::hex(">= 0.34.0 and < 2.0.0"),
+            ),
+            ("lustre".into(), Requirement::hex(">= 4.0.0 and < 5.0.0")),
+            ("gleeunit".into(), Requirement::hex(">= 1.0.0 and < 2.0.0")),
+        ]
+        .into(),
+        packages: vec![
+            package("gleam_stdlib", Version::new(0, 39, 0), &[]),
+            package(
+                "gleam_json",
+                Version::new(1, 0, 1),
+                &["gleam_stdlib", "thoas"],
+            ),
+            package("gleeunit", Version::new(1, 2, 0), &["gleam_stdlib"]),
+            package(
+                "lustre",
+                Version::new(4, 3, 0),
+                &["gleam_json", "gleam_stdlib"],
+            ),
+            package("thoas", Version::new(1, 2, 1), &[]),
+        ],
+    }
+}
+
+#[cfg(test)]
+fn dependency_graph_requirements() -> HashMap<EcoString, HashMap<EcoString, Requirement>> {
+    [
+        (
+            "gleam_json".into(),
+            [
+                (
+                    "gleam_stdlib".into(),
+                    Requirement::hex(">= 0.32.0 and < 2.0.0"),
+                ),
+                ("thoas".into(), Requirement::hex(">= 1.0.0 and < 2.0.0")),
+            ]
+            .into(),
+        ),
+        (
+            "lustre".into(),
+            [
+                (
+                    "gleam_json".into(),
+                    Requirement::hex(">= 1.0.0 and < 2.0.0"),
+                ),
+                (
+                    "gleam_stdlib".into(),
+                    Requirement::hex(">= 0.36.0 and < 2.0.0"),
+                ),
+            ]
+            .into(),
+        ),
+    ]
+    .into()
+}
+
+#[test]
+fn dependency_tree_format() {
+    let mut buffer = vec![];
+    let root = "app".into();
+    let manifest = dependency_graph_manifest();
+    DependencyGraph::new(&root, &manifest, dependency_graph_requirements())
+        .print_tree(&mut buffer)
+        .unwrap();
+    assert_eq!(
+        std::str::from_utf8(&buffer).unwrap(),
+        "app
+├── gleam_stdlib 0.39.0 (>= 0.34.0 and < 2.0.0)
+├── gleeunit 1.2.0 (>= 1.0.0 and < 2.0.0)
+│   └── gleam_stdlib 0.39.0
+└── lustre 4.3.0 (>= 4.0.0 and < 5.0.0)
+    ├── gleam_json 1.0.1 (>= 1.0.0 and < 2.0.0)
+    │   ├── gleam_stdlib 0.39.0 (>= 0.32.0 and < 2.0.0)
+    │   └── thoas 1.2.1 (>= 1.0.0 and < 2.0.0)
+    └── gleam_stdlib 0.39.0 (>= 0.36.0 and < 2.0.0)
+"
+    )
+}
+
+#[test]
+fn dependency_tree_repeated_dependencies_format() {
+    let mut buffer = vec![];
+    let root = "app".into();
+    let mut manifest = dependency_graph_manifest();
+    let _ = manifest.requirements.insert(
+        "gleam_json".into(),
+        Requirement::hex(">= 1.0.0 and < 2.0.0"),
+    );
+    DependencyGraph::new(&root, &manifest, dependency_graph_requirements())
+        .print_tree(&mut buffer)
+        .unwrap();
+    assert_eq!(
+        std::str::from_utf8(&buffer).unwrap(),
+        "app
+├── gleam_json 1.0.1 (>= 1.0.0 and < 2.0.0)
+│   ├── gleam_stdlib 0.39.0 (>= 0.32.0 and < 2.0.0)
+│   └── thoas 1.2.1 (>= 1.0.0 and < 2.0.0)
+├── gleam_stdlib 0.39.0 (>= 0.34.0 and < 2.0.0)
+├── gleeunit 1.2.0 (>= 1.0.0 and < 2.0.0)
+│   └── gleam_stdlib 0.39.0
+└── lustre 4.3.0 (>= 4.0.0 and < 5.0.0)
+    ├── gleam_json 1.0.1 (>= 1.0.0 and < 2.0.0) (*)
+    └── gleam_stdlib 0.39.0 (>= 0.36.0 and < 2.0.0)
+"
+    )
+}
+
+#[test]
+fn dependency_why_format() {
+    let mut buffer = vec![];
+    let root = "app".into();
+    let manifest = dependency_graph_manifest();
+    DependencyGraph::new(&root, &manifest, dependency_graph_requirements())
+        .print_paths_to(&mut buffer, "gleam_stdlib")
+        .unwrap();
+    assert_eq!(
+        std::str::from_utf8(&buffer).unwrap(),
+        "gleam_stdlib 0.39.0 is required by:
+
+  app -> gleam_stdlib 0.39.0 (>= 0.34.0 and < 2.0.0)
+  app -> gleeunit 1.2.0 (>= 1.0.0 and < 2.0.0) -> gleam_stdlib 0.39.0
+  app -> lustre 4.3.0 (>= 4.0.0 and < 5.0.0) -> gleam_json 1.0.1 (>= 1.0.0 and < 2.0.0) -> gleam_stdlib 0.39.0 (>= 0.32.0 and < 2.0.0)
+  app -> lustre 4.3.0 (>= 4.0.0 and < 5.0.0) -> gleam_stdlib 0.39.0 (>= 0.36.0 and < 2.0.0)
+"
+    )
+}
+
+#[test]
+fn dependency_why_unknown_package() {
+    let root = "app".into();
+    let manifest = dependency_graph_manifest();
+    let result = DependencyGraph::new(&root, &manifest, dependency_graph_requirements())
+        .print_paths_to(vec![], "wibble");
+    assert_eq!(
+        result,
+        Err(Error::PackageNotADependency {
+            package: "wibble".into()
+        })
+    );
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum UseManifest {
     Yes,
     No,
+    /// Use the manifest, but unlock the given packages so that they are
+    /// resolved to their latest versions.
+    Unlocking(HashSet<EcoString>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    Online,
+    /// Resolve versions using only the packages in the global package cache.
+    Offline,
 }
 
-pub fn update() -> Result<()> {
+pub fn update(packages: Vec<String>, recursive: bool) -> Result<()> {
     let paths = crate::find_project_paths()?;
-    _ = download(&paths, cli::Reporter::new(), None, UseManifest::No)?;
+    let use_manifest = if packages.is_empty() || !paths.manifest().exists() {
+        UseManifest::No
+    } else {
+        let manifest = read_manifest_from_disc(&paths)?;
+        UseManifest::Unlocking(packages_to_unlock(&manifest, packages, recursive)?)
+    };
+    _ = download(
+        &paths,
+        cli::Reporter::new(),
+        None,
+        use_manifest,
+        Connectivity::Online,
+    )?;
     Ok(())
 }
 
+/// The packages to unlock to update the given packages, which must be in the
+/// manifest. If `recursive` is true the packages they depend on, directly or
+/// indirectly, are unlocked too.
+fn packages_to_unlock(
+    manifest: &Manifest,
+    packages: Vec<String>,
+    recursive: bool,
+) -> Result<HashSet<EcoString>> {
+    let requirements: HashMap<_, _> = manifest
+        .packages
+        .iter()
+        .map(|package| (package.name.clone(), &package.requirements))
+        .collect();
+    let mut unlocked = HashSet::new();
+    let mut queue = Vec::new();
+    for package in packages {
+        let package = EcoString::from(package);
+        if !requirements.contains_key(&package) {
+            return Err(Error::PackageNotADependency { package });
+        }
+        queue.push(package);
+    }
+    while let Some(package) = queue.pop() {
+        if !unlocked.insert(package.clone()) || !recursive {
+            continue;
+        }
+        if let Some(dependencies) = requirements.get(&package) {
+            queue.extend(dependencies.iter().cloned());
+        }
+    }
+    Ok(unlocked)
+}
+
+#[test]
+fn packages_to_unlock_test() {
+    let package = |name: &str, requirements: &[&str]| ManifestPackage {
+        name: name.into(),
+        version: Version::new(1, 0, 0),
+        build_tools: ["gleam".into()].into(),
+        otp_app: None,
+        requirements: requirements.iter().map(|name| (*name).into()).collect(),
+        source: ManifestPackageSource::Hex {
+            outer_checksum: Base16Checksum(vec![1, 2, 3, 4]),
+            repository: None,
+        },
+    };
+    let manifest = Manifest {
+        requirements: HashMap::new(),
+        packages: vec![
+            package("gleam_json", &["gleam_stdlib", "thoas"]),
+            package("gleam_stdlib", &[]),
+            package("thoas", &[]),
+            package("wibble", &["gleam_stdlib"]),
+        ],
+    };
+    let unlock = |packages: &[&str], recursive| {
+        packages_to_unlock(
+            &manifest,
+            packages.iter().map(|name| name.to_string()).collect(),
+            recursive,
+        )
+        .map(|unlocked| unlocked.into_iter().sorted().collect_vec())
+    };
+
+    assert_eq!(
+        unlock(&["gleam_json"], false),
+        Ok(vec!["gleam_json".into()])
+    );
+    assert_eq!(
+        unlock(&["gleam_json", "wibble"], false),
+        Ok(vec!["gleam_json".into(), "wibble".into()])
+    );
+    assert_eq!(
+        unlock(&["gleam_json"], true),
+        Ok(vec![
+            "gleam_json".into(),
+            "gleam_stdlib".into(),
+            "thoas".into()
+        ])
+    );
+    assert_eq!(
+        unlock(&["wobble"], false),
+        Err(Error::PackageNotADependency {
+            package: "wobble".into()
+        })
+    );
+}
+
 pub fn parse_gleam_add_specifier(package: &str) -> Result<(EcoString, Requirement)> {
     let Some((package, version)) = package.split_once('@') else {
         // Default to the latest version available.
@@ -185,7 +1014,7 @@ fn parse_gleam_add_specifier_default() {
     let expected = ">= 0.0.0";
     let (package, version) = parse_gleam_add_specifier(provided).unwrap();
     match &version {
-        Requirement::Hex { version: v } => {
+        Requirement::Hex { version: v, .. } => {
             assert!(v.to_pubgrub().is_ok(), "failed pubgrub parse: {}", v);
         }
         _ => assert!(false, "failed hexpm version parse: {}", provided),
@@ -200,7 +1029,7 @@ fn parse_gleam_add_specifier_major_only() {
     let expected = ">= 1.0.0 and < 2.0.0";
     let (package, version) = parse_gleam_add_specifier(provided).unwrap();
     match &version {
-        Requirement::Hex { version: v } => {
+        Requirement::Hex { version: v, .. } => {
             assert!(v.to_pubgrub().is_ok(), "failed pubgrub parse: {}", v);
         }
         _ => assert!(false, "failed hexpm version parse: {}", provided),
@@ -215,7 +1044,7 @@ fn parse_gleam_add_specifier_major_and_minor() {
     let expected = ">= 1.2.0 and < 2.0.0";
     let (package, version) = parse_gleam_add_specifier(provided).unwrap();
     match &version {
-        Requirement::Hex { version: v } => {
+        Requirement::Hex { version: v, .. } => {
             assert!(v.to_pubgrub().is_ok(), "failed pubgrub parse: {}", v);
         }
         _ => assert!(false, "failed hexpm version parse: {}", provided),
@@ -230,7 +1059,7 @@ fn parse_gleam_add_specifier_major_minor_and_patch() {
     let expected = "1.2.3";
     let (package, version) = parse_gleam_add_specifier(provided).unwrap();
     match &version {
-        Requirement::Hex { version: v } => {
+        Requirement::Hex { version: v, .. } => {
             assert!(v.to_pubgrub().is_ok(), "failed pubgrub parse: {}", v);
         }
         _ => assert!(false, "failed hexpm version parse: {}", provided),
@@ -247,6 +1076,47 @@ pub fn download<Telem: Telemetry>(
     // manifest which will result in the latest versions of the dependency
     // packages being resolved (not the locked ones).
     use_manifest: UseManifest,
+    connectivity: Connectivity,
+) -> Result<Manifest> {
+    download_packages(
+        paths,
+        telemetry,
+        new_package,
+        use_manifest,
+        connectivity,
+        UseVendored::Yes,
+    )
+}
+
+/// Download the locked dependency packages from Hex, even if the project has
+/// vendored them.
+pub fn download_ignoring_vendored<Telem: Telemetry>(
+    paths: &ProjectPaths,
+    telemetry: Telem,
+) -> Result<Manifest> {
+    download_packages(
+        paths,
+        telemetry,
+        None,
+        UseManifest::Yes,
+        Connectivity::Online,
+        UseVendored::No,
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum UseVendored {
+    Yes,
+    No,
+}
+
+fn download_packages<Telem: Telemetry>(
+    paths: &ProjectPaths,
+    telemetry: Telem,
+    new_package: Option<(Vec<(EcoString, Requirement)>, bool)>,
+    use_manifest: UseManifest,
+    connectivity: Connectivity,
+    use_vendored: UseVendored,
 ) -> Result<Manifest> {
     let span = tracing::info_span!("download_deps");
     let _enter = span.enter();
@@ -288,21 +1158,49 @@ pub fn download<Telem: Telemetry>(
         &config,
         &telemetry,
         use_manifest,
+        connectivity,
     )?;
     let local = LocalPackages::read_from_disc(paths)?;
+    let manifest_changed = manifest_updated || verify::manifest_changed(paths);
 
     // Remove any packages that are no longer required due to gleam.toml changes
     remove_extra_packages(paths, &local, &manifest, &telemetry)?;
 
-    // Download them from Hex to the local cache
-    runtime.block_on(add_missing_packages(
-        paths,
-        fs,
-        &manifest,
-        &local,
-        project_name,
-        &telemetry,
-    ))?;
+    // Use the vendored copies of the packages if there are any, otherwise
+    // download them from Hex to the local cache
+    let vendored = match use_vendored {
+        UseVendored::Yes => VendoredPackages::read_from_disc(paths)?,
+        UseVendored::No => None,
+    };
+    if let Some(vendored) = vendored {
+        vendored.verify(paths, &manifest)?;
+        let missing = local.missing_local_packages(&manifest, &project_name);
+        vendor::copy_to_build_directory(paths, missing.into_iter())?;
+    } else {
+        runtime.block_on(add_missing_packages(
+            paths,
+            fs,
+            &manifest,
+            &local,
+            project_name,
+            hex_repositories(paths, &config)?,
+            &telemetry,
+        ))?;
+
+        // The packages are checked whenever the manifest changes, so that
+        // packages edited in the build directory are not built in their place
+        if manifest_changed {
+            let _ = verify::verify_packages(
+                paths,
+                &config,
+                &manifest,
+                &PackageCache::global(),
+                &runtime,
+            )?;
+        }
+    }
+
+    check_licence_policy(paths, &config, &manifest)?;
 
     if manifest_updated {
         // Record new state of the packages directory
@@ -321,6 +1219,7 @@ async fn add_missing_packages<Telem: Telemetry>(
     manifest: &Manifest,
     local: &LocalPackages,
     project_name: EcoString,
+    repositories: HashMap<EcoString, hex::Repository>,
     telemetry: &Telem,
 ) -> Result<(), Error> {
     let missing_packages = local.missing_local_packages(manifest, &project_name);
@@ -338,7 +1237,15 @@ async fn add_missing_packages<Telem: Telemetry>(
     // If we need to download at-least one package
     if missing_hex_packages.peek().is_some() {
         let http = HttpClient::boxed();
-        let downloader = hex::Downloader::new(fs.clone(), fs, http, Untar::boxed(), paths.clone());
+        let downloader = hex::Downloader::new(
+            fs.clone(),
+            fs,
+            http,
+            Untar::boxed(),
+            repositories,
+            PackageCache::global(),
+            paths.clone(),
+        );
         let start = Instant::now();
         telemetry.downloading_package("packages");
         downloader
@@ -492,6 +1399,7 @@ fn missing_local_packages() {
                 requirements: vec![],
                 source: ManifestPackageSource::Hex {
                     outer_checksum: Base16Checksum(vec![1, 2, 3, 4]),
+                    repository: None,
                 },
             },
             ManifestPackage {
@@ -502,6 +1410,7 @@ fn missing_local_packages() {
                 requirements: vec![],
                 source: ManifestPackageSource::Hex {
                     outer_checksum: Base16Checksum(vec![1, 2, 3, 4, 5]),
+                    repository: None,
                 },
             },
             ManifestPackage {
@@ -512,6 +1421,7 @@ fn missing_local_packages() {
                 requirements: vec![],
                 source: ManifestPackageSource::Hex {
                     outer_checksum: Base16Checksum(vec![1, 2, 3, 4, 5]),
+                    repository: None,
                 },
             },
         ],
@@ -536,6 +1446,7 @@ fn missing_local_packages() {
                 requirements: vec![],
                 source: ManifestPackageSource::Hex {
                     outer_checksum: Base16Checksum(vec![1, 2, 3, 4, 5]),
+                    repository: None,
                 },
             },
             &ManifestPackage {
@@ -546,6 +1457,7 @@ fn missing_local_packages() {
                 requirements: vec![],
                 source: ManifestPackageSource::Hex {
                     outer_checksum: Base16Checksum(vec![1, 2, 3, 4, 5]),
+                    repository: None,
                 },
             },
         ]
@@ -573,6 +1485,7 @@ fn extra_local_packages() {
                 requirements: vec![],
                 source: ManifestPackageSource::Hex {
                     outer_checksum: Base16Checksum(vec![1, 2, 3, 4, 5]),
+                    repository: None,
                 },
             },
             ManifestPackage {
@@ -583,6 +1496,7 @@ fn extra_local_packages() {
                 requirements: vec![],
                 source: ManifestPackageSource::Hex {
                     outer_checksum: Base16Checksum(vec![4, 5]),
+                    repository: None,
                 },
             },
         ],
@@ -604,6 +1518,7 @@ fn get_manifest<Telem: Telemetry>(
     config: &PackageConfig,
     telemetry: &Telem,
     use_manifest: UseManifest,
+    connectivity: Connectivity,
 ) -> Result<(bool, Manifest)> {
     // If there's no manifest (or we have been asked not to use it) then resolve
     // the versions anew
@@ -616,16 +1531,43 @@ fn get_manifest<Telem: Telemetry>(
             tracing::debug!("ignoring_manifest");
             true
         }
-        UseManifest::Yes => false,
+        UseManifest::Yes | UseManifest::Unlocking(_) => false,
     };
 
     if should_resolve {
-        let manifest = resolve_versions(runtime, mode, paths, config, None, telemetry)?;
+        let manifest = resolve_versions(
+            runtime,
+            mode,
+            paths,
+            config,
+            ManifestLocks::default(),
+            telemetry,
+            connectivity,
+        )?;
         return Ok((true, manifest));
     }
 
     let manifest = read_manifest_from_disc(paths)?;
 
+    // The packages being updated are resolved again, with every other package
+    // kept at its locked version.
+    if let UseManifest::Unlocking(unlocked) = &use_manifest {
+        tracing::debug!("unlocking_packages");
+        let manifest = resolve_versions(
+            runtime,
+            mode,
+            paths,
+            config,
+            ManifestLocks {
+                manifest: Some(&manifest),
+                unlocked: Some(unlocked),
+            },
+            telemetry,
+            connectivity,
+        )?;
+        return Ok((true, manifest));
+    }
+
     // If the config has unchanged since the manifest was written then it is up
     // to date so we can return it unmodified.
     if is_same_requirements(
@@ -637,7 +1579,18 @@ fn get_manifest<Telem: Telemetry>(
         Ok((false, manifest))
     } else {
         tracing::debug!("manifest_outdated");
-        let manifest = resolve_versions(runtime, mode, paths, config, Some(&manifest), telemetry)?;
+        let manifest = resolve_versions(
+            runtime,
+            mode,
+            paths,
+            config,
+            ManifestLocks {
+                manifest: Some(&manifest),
+                unlocked: None,
+            },
+            telemetry,
+            connectivity,
+        )?;
         Ok((true, manifest))
     }
 }
@@ -708,7 +1661,7 @@ impl ProvidedPackage {
                 )
             })
             .collect();
-        let release = hexpm::Release {
+        let release = Release {
             version: self.version.clone(),
             requirements,
             retirement_status: None,
@@ -784,17 +1737,74 @@ impl PartialEq for ProvidedPackageSource {
     }
 }
 
+/// The manifest that versions are resolved against, if there is one. The
+/// packages in it are kept at their locked versions, other than any that are
+/// being unlocked.
+#[derive(Debug, Clone, Copy, Default)]
+struct ManifestLocks<'a> {
+    manifest: Option<&'a Manifest>,
+    unlocked: Option<&'a HashSet<EcoString>>,
+}
+
 fn resolve_versions<Telem: Telemetry>(
     runtime: tokio::runtime::Handle,
     mode: Mode,
     project_paths: &ProjectPaths,
     config: &PackageConfig,
-    manifest: Option<&Manifest>,
+    locks: ManifestLocks<'_>,
     telemetry: &Telem,
+    connectivity: Connectivity,
+) -> Result<Manifest, Error> {
+    match connectivity {
+        Connectivity::Online => telemetry.resolving_package_versions(),
+        Connectivity::Offline => telemetry.resolving_package_versions_offline(),
+    }
+    let resolve = |connectivity, fetched: &Rc<RefCell<FetchedPackages>>| {
+        resolve_versions_fetching(
+            runtime.clone(),
+            mode,
+            project_paths,
+            config,
+            locks,
+            connectivity,
+            fetched.clone(),
+        )
+    };
+    let fetched = Rc::default();
+    match resolve(connectivity, &fetched) {
+        // If Hex could not be reached then the packages in the global package
+        // cache may still be enough to resolve the versions.
+        Err(_) if connectivity == Connectivity::Online && fetched.borrow().hex_unreachable => {
+            telemetry.resolving_package_versions_offline();
+            resolve(Connectivity::Offline, &Rc::default())
+        }
+        result => result,
+    }
+}
+
+fn resolve_versions_fetching(
+    runtime: tokio::runtime::Handle,
+    mode: Mode,
+    project_paths: &ProjectPaths,
+    config: &PackageConfig,
+    locks: ManifestLocks<'_>,
+    connectivity: Connectivity,
+    fetched: Rc<RefCell<FetchedPackages>>,
 ) -> Result<Manifest, Error> {
-    telemetry.resolving_package_versions();
     let dependencies = config.dependencies_for(mode)?;
-    let locked = config.locked(manifest)?;
+    let mut locked = config.locked(locks.manifest)?;
+    if let Some(unlocked) = locks.unlocked {
+        locked.retain(|name, _| !unlocked.contains(name));
+    }
+    let repositories = hex_repositories(project_paths, config)?;
+
+    // The repository each package comes from, for those not from hex.pm.
+    // Packages keep the repository recorded in the manifest unless the config
+    // says otherwise.
+    let mut package_repositories = locks
+        .manifest
+        .map(manifest_repositories)
+        .unwrap_or_default();
 
     // Packages which are provided directly instead of downloaded from hex
     let mut provided_packages = HashMap::new();
@@ -804,7 +1814,17 @@ fn resolve_versions<Telem: Telemetry>(
     // Populate the provided_packages and root_requirements maps
     for (name, requirement) in dependencies.into_iter() {
         let version = match requirement {
-            Requirement::Hex { version } => version,
+            Requirement::Hex {
+                version,
+                repository,
+            } => {
+                let _ = package_repositories.remove(&name);
+                if let Some(repository) = repository {
+                    let _ = config.hex_repository(&repository)?;
+                    let _ = package_repositories.insert(name.clone(), repository);
+                }
+                version
+            }
             Requirement::Path { path } => provide_local_package(
                 name.clone(),
                 &path,
@@ -826,20 +1846,43 @@ fn resolve_versions<Telem: Telemetry>(
         .map(|(name, package)| (name.clone(), package.to_hex_package(name)))
         .collect();
 
+    fetched.borrow_mut().repositories = package_repositories;
     let resolved = dependency::resolve_versions(
-        PackageFetcher::boxed(runtime.clone()),
+        PackageFetcher::boxed(
+            runtime.clone(),
+            repositories.clone(),
+            connectivity,
+            fetched.clone(),
+        ),
         provided_hex_packages,
         config.name.clone(),
-        root_requirements.into_iter(),
+        root_requirements.clone().into_iter(),
         &locked,
-    )?;
+    )
+    .map_err(|error| match (connectivity, error) {
+        (Connectivity::Offline, Error::DependencyResolutionFailed(error)) => {
+            Error::OfflineDependencyResolutionFailed {
+                error,
+                unavailable: unavailable_offline(&root_requirements, &fetched.borrow()),
+            }
+        }
+        (_, error) => error,
+    })?;
 
     // Convert the hex packages and local packages into manifest packages
-    let manifest_packages = runtime.block_on(future::try_join_all(
-        resolved
-            .into_iter()
-            .map(|(name, version)| lookup_package(name, version, &provided_packages)),
-    ))?;
+    let fetched = fetched.borrow();
+    let manifest_packages = runtime.block_on(future::try_join_all(resolved.into_iter().map(
+        |(name, version)| {
+            let repository = fetched.repositories.get(name.as_str());
+            let repository = hex_repository(&repositories, repository);
+            let cached = fetched
+                .cached
+                .get(name.as_str())
+                .and_then(|releases| releases.iter().find(|release| release.version == version))
+                .cloned();
+            lookup_package(name, version, repository, cached, &provided_packages)
+        },
+    )))?;
 
     let manifest = Manifest {
         packages: manifest_packages,
@@ -849,6 +1892,58 @@ fn resolve_versions<Telem: Telemetry>(
     Ok(manifest)
 }
 
+/// Describe the requirements that the packages in the global package cache
+/// cannot satisfy: root requirements with no matching cached version, and
+/// packages with no cached versions at all.
+fn unavailable_offline(
+    root_requirements: &HashMap<EcoString, hexpm::version::Range>,
+    fetched: &FetchedPackages,
+) -> Vec<String> {
+    let cached_versions = |name: &str| {
+        fetched
+            .cached
+            .get(name)
+            .map(|releases| {
+                releases
+                    .iter()
+                    .map(|release| &release.version)
+                    .collect_vec()
+            })
+            .unwrap_or_default()
+    };
+
+    let mut unavailable = vec![];
+    for (name, range) in root_requirements.iter().sorted_by_key(|(name, _)| *name) {
+        // Path and git dependencies are not fetched so are never cached.
+        if !fetched.cached.contains_key(name.as_str()) {
+            continue;
+        }
+        let versions = cached_versions(name);
+        let Ok(pubgrub_range) = range.to_pubgrub() else {
+            continue;
+        };
+        if versions
+            .iter()
+            .any(|version| pubgrub_range.contains(version))
+        {
+            continue;
+        }
+        if versions.is_empty() {
+            unavailable.push(format!("{name} {range} (no versions cached)"));
+        } else {
+            let versions = versions.iter().sorted().join(", ");
+            unavailable.push(format!("{name} {range} (cached versions: {versions})"));
+        }
+    }
+
+    for (name, releases) in fetched.cached.iter().sorted_by_key(|(name, _)| *name) {
+        if releases.is_empty() && !root_requirements.contains_key(name) {
+            unavailable.push(format!("{name} (no versions cached)"));
+        }
+    }
+    unavailable
+}
+
 /// Provide a package from a local project
 fn provide_local_package(
     package_name: EcoString,
@@ -943,7 +2038,7 @@ fn provide_package(
     parents.push(package_name);
     for (name, requirement) in config.dependencies.into_iter() {
         let version = match requirement {
-            Requirement::Hex { version } => version,
+            Requirement::Hex { version, .. } => version,
             Requirement::Path { path } => {
                 // Recursively walk local packages
                 provide_local_package(
@@ -1097,18 +2192,99 @@ fn provided_recursive() {
     )
 }
 
+/// The repository each package in the manifest was downloaded from, for
+/// those not from hex.pm.
+fn manifest_repositories(manifest: &Manifest) -> HashMap<EcoString, EcoString> {
+    manifest
+        .packages
+        .iter()
+        .filter_map(|package| match &package.source {
+            ManifestPackageSource::Hex {
+                repository: Some(repository),
+                ..
+            } => Some((package.name.clone(), repository.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The Hex repositories configured in `gleam.toml`, along with the keys they
+/// are signed with and the keys to authenticate with them.
+pub fn hex_repositories(
+    paths: &ProjectPaths,
+    config: &PackageConfig,
+) -> Result<HashMap<EcoString, hex::Repository>> {
+    config
+        .hex_repositories
+        .iter()
+        .map(|(name, repository)| {
+            let public_key = match &repository.public_key {
+                Some(path) => fs::read_bytes(paths.root().join(path))?,
+                None => HEXPM_PUBLIC_KEY.to_vec(),
+            };
+            let api_key = match &repository.auth_key_env {
+                Some(variable) => Some(std::env::var(variable.as_str()).map_err(|_| {
+                    Error::HexRepositoryAuthKeyNotSet {
+                        repository: name.clone(),
+                        variable: variable.clone(),
+                    }
+                })?),
+                None => None,
+            };
+            let repository = hex::Repository {
+                config: repository.hex_config(),
+                api_key,
+                public_key,
+            };
+            Ok((name.clone(), repository))
+        })
+        .collect()
+}
+
+/// Get a Hex repository by name, or hex.pm if no name is given.
+fn hex_repository(
+    repositories: &HashMap<EcoString, hex::Repository>,
+    name: Option<&EcoString>,
+) -> Result<(Option<EcoString>, hex::Repository)> {
+    let Some(name) = name else {
+        let repository = hex::Repository {
+            config: hexpm::Config::new(),
+            api_key: None,
+            public_key: HEXPM_PUBLIC_KEY.to_vec(),
+        };
+        return Ok((None, repository));
+    };
+    match repositories.get(name) {
+        Some(repository) => Ok((Some(name.clone()), repository.clone())),
+        None => Err(Error::UnknownHexRepository { name: name.clone() }),
+    }
+}
+
 /// Determine the information to add to the manifest for a specific package
 async fn lookup_package(
     name: String,
     version: Version,
+    repository: Result<(Option<EcoString>, hex::Repository)>,
+    cached: Option<Release<ReleaseMeta>>,
     provided: &HashMap<EcoString, ProvidedPackage>,
 ) -> Result<ManifestPackage> {
     match provided.get(name.as_str()) {
         Some(provided_package) => Ok(provided_package.to_manifest_package(name.as_str())),
         None => {
-            let config = hexpm::Config::new();
-            let release =
-                hex::get_package_release(&name, &version, &config, &HttpClient::new()).await?;
+            let (repository_name, repository) = repository?;
+            let release = match cached {
+                Some(release) => release,
+                None => {
+                    hex::get_package_release(
+                        &name,
+                        &version,
+                        repository.api_key.as_deref(),
+                        &repository.config,
+                        &HttpClient::new(),
+                    )
+                    .await?
+                }
+            };
             let build_tools = release
                 .meta
                 .build_tools
@@ -1128,24 +2304,69 @@ async fn lookup_package(
                 requirements,
                 source: ManifestPackageSource::Hex {
                     outer_checksum: Base16Checksum(release.outer_checksum),
+                    repository: repository_name,
                 },
             })
         }
     }
 }
 
+/// What the package fetcher learnt while resolving versions. This is shared
+/// with the caller as the fetcher itself is consumed by the resolver.
+#[derive(Debug, Default)]
+struct FetchedPackages {
+    /// The repository each package is fetched from, for those not from
+    /// hex.pm. Packages that other packages require from one of the
+    /// configured repositories are added as they are found.
+    repositories: HashMap<EcoString, EcoString>,
+    /// The releases of each package found in the global package cache when
+    /// resolving offline.
+    cached: HashMap<EcoString, Vec<Release<ReleaseMeta>>>,
+    /// Whether a request to Hex failed, in which case resolving offline may
+    /// still succeed.
+    hex_unreachable: bool,
+}
+
 struct PackageFetcher {
     runtime: tokio::runtime::Handle,
     http: HttpClient,
+    repositories: HashMap<EcoString, hex::Repository>,
+    connectivity: Connectivity,
+    fetched: Rc<RefCell<FetchedPackages>>,
 }
 
 impl PackageFetcher {
-    pub fn boxed(runtime: tokio::runtime::Handle) -> Box<Self> {
+    pub fn boxed(
+        runtime: tokio::runtime::Handle,
+        repositories: HashMap<EcoString, hex::Repository>,
+        connectivity: Connectivity,
+        fetched: Rc<RefCell<FetchedPackages>>,
+    ) -> Box<Self> {
         Box::new(Self {
             runtime,
             http: HttpClient::new(),
+            repositories,
+            connectivity,
+            fetched,
         })
     }
+
+    fn record_package_repositories(&self, package: &hexpm::Package) {
+        let package_repositories = &mut self.fetched.borrow_mut().repositories;
+        let requirements = package
+            .releases
+            .iter()
+            .flat_map(|release| &release.requirements);
+        for (name, requirement) in requirements {
+            if let Some(repository) = &requirement.repository {
+                if self.repositories.contains_key(repository.as_str()) {
+                    let _ = package_repositories
+                        .entry(name.as_str().into())
+                        .or_insert_with(|| repository.as_str().into());
+                }
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -1180,16 +2401,207 @@ impl dependency::PackageFetcher for PackageFetcher {
         package: &str,
     ) -> Result<hexpm::Package, Box<dyn std::error::Error>> {
         tracing::debug!(package = package, "looking_up_hex_package");
-        let config = hexpm::Config::new();
-        let request = hexpm::get_package_request(package, None, &config);
-        let response = self
-            .runtime
-            .block_on(self.http.send(request))
-            .map_err(Box::new)?;
-        hexpm::get_package_response(response, HEXPM_PUBLIC_KEY).map_err(|e| e.into())
+        let repository = self.fetched.borrow().repositories.get(package).cloned();
+        let (repository_name, repository) =
+            hex_repository(&self.repositories, repository.as_ref())?;
+        let package = match self.connectivity {
+            Connectivity::Online => {
+                let request = hexpm::get_package_request(
+                    package,
+                    repository.api_key.as_deref(),
+                    &repository.config,
+                );
+                let response = self
+                    .runtime
+                    .block_on(self.http.send(request))
+                    .map_err(|error| {
+                        self.fetched.borrow_mut().hex_unreachable = true;
+                        Box::new(error)
+                    })?;
+                hexpm::get_package_response(response, &repository.public_key)?
+            }
+            Connectivity::Offline => {
+                let repository_url = repository_name
+                    .as_ref()
+                    .map(|_| &repository.config.repository_base);
+                let releases = package_cache::releases(repository_url, package);
+                let package = cached_package(package, repository_name, &releases);
+                let _ = self
+                    .fetched
+                    .borrow_mut()
+                    .cached
+                    .insert(package.name.as_str().into(), releases);
+                package
+            }
+        };
+        self.record_package_repositories(&package);
+        Ok(package)
+    }
+}
+
+/// A package as Hex would describe it, made from its releases in the global
+/// package cache.
+fn cached_package(
+    name: &str,
+    repository: Option<EcoString>,
+    releases: &[Release<ReleaseMeta>],
+) -> hexpm::Package {
+    hexpm::Package {
+        name: name.into(),
+        repository: repository.map_or_else(|| "hexpm".into(), String::from),
+        releases: releases
+            .iter()
+            .map(|release| Release {
+                version: release.version.clone(),
+                requirements: release.requirements.clone(),
+                retirement_status: release.retirement_status.clone(),
+                outer_checksum: release.outer_checksum.clone(),
+                meta: (),
+            })
+            .collect(),
     }
 }
 
+#[test]
+fn hex_repositories_with_unset_auth_key_env() {
+    let tmp = tempfile::tempdir().unwrap();
+    let paths = ProjectPaths::new(Utf8Path::from_path(tmp.path()).unwrap().to_path_buf());
+    let mut config = PackageConfig::default();
+    let repository = gleam_core::config::HexRepository {
+        api_url: "https://hex.example.com/api/".parse().unwrap(),
+        repository_url: "https://hex.example.com/repo/".parse().unwrap(),
+        auth_key_env: Some("GLEAM_TEST_UNSET_HEX_REPOSITORY_KEY".into()),
+        public_key: None,
+    };
+    let _ = config
+        .hex_repositories
+        .insert("internal".into(), repository);
+    assert_eq!(
+        hex_repositories(&paths, &config).map(|repositories| repositories.len()),
+        Err(Error::HexRepositoryAuthKeyNotSet {
+            repository: "internal".into(),
+            variable: "GLEAM_TEST_UNSET_HEX_REPOSITORY_KEY".into(),
+        })
+    );
+}
+
+#[test]
+fn package_fetcher_records_repositories_of_requirements() {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let repositories = [(
+        "internal".into(),
+        hex::Repository {
+            config: hexpm::Config::new(),
+            api_key: None,
+            public_key: vec![],
+        },
+    )]
+    .into();
+    let fetched = Rc::new(RefCell::new(FetchedPackages {
+        repositories: [("pinned".into(), "internal".into())].into(),
+        ..Default::default()
+    }));
+    let fetcher = PackageFetcher::boxed(
+        runtime.handle().clone(),
+        repositories,
+        Connectivity::Online,
+        fetched.clone(),
+    );
+    let dependency = |repository: Option<&str>| hexpm::Dependency {
+        requirement: hexpm::version::Range::new("~> 1.0".into()),
+        optional: false,
+        app: None,
+        repository: repository.map(String::from),
+    };
+    let package = hexpm::Package {
+        name: "internal_lib".into(),
+        repository: "internal".into(),
+        releases: vec![Release {
+            version: Version::new(1, 0, 0),
+            retirement_status: None,
+            outer_checksum: vec![],
+            meta: (),
+            requirements: [
+                ("private".into(), dependency(Some("internal"))),
+                ("public".into(), dependency(None)),
+                ("elsewhere".into(), dependency(Some("unknown"))),
+                ("pinned".into(), dependency(Some("internal"))),
+            ]
+            .into(),
+        }],
+    };
+
+    fetcher.record_package_repositories(&package);
+
+    assert_eq!(
+        fetched.borrow().repositories,
+        [
+            ("pinned".into(), "internal".into()),
+            ("private".into(), "internal".into()),
+        ]
+        .into()
+    );
+}
+
+#[test]
+fn unavailable_offline_packages() {
+    let release = |version| Release {
+        version,
+        requirements: HashMap::new(),
+        retirement_status: None,
+        outer_checksum: vec![],
+        meta: ReleaseMeta {
+            app: "wibble".into(),
+            build_tools: vec!["gleam".into()],
+        },
+    };
+    let fetched = FetchedPackages {
+        repositories: HashMap::new(),
+        cached: [
+            (
+                "too_old".into(),
+                vec![
+                    release(Version::new(1, 1, 0)),
+                    release(Version::new(1, 0, 0)),
+                ],
+            ),
+            ("satisfied".into(), vec![release(Version::new(2, 0, 0))]),
+            ("uncached".into(), vec![]),
+            ("transitive".into(), vec![]),
+        ]
+        .into(),
+        hex_unreachable: false,
+    };
+    let root_requirements = [
+        (
+            "too_old".into(),
+            hexpm::version::Range::new(">= 2.0.0".into()),
+        ),
+        (
+            "satisfied".into(),
+            hexpm::version::Range::new(">= 2.0.0".into()),
+        ),
+        (
+            "uncached".into(),
+            hexpm::version::Range::new("~> 1.0".into()),
+        ),
+        (
+            "local".into(),
+            hexpm::version::Range::new("== 1.0.0".into()),
+        ),
+    ]
+    .into();
+
+    assert_eq!(
+        unavailable_offline(&root_requirements, &fetched),
+        vec![
+            "too_old >= 2.0.0 (cached versions: 1.0.0, 1.1.0)".to_string(),
+            "uncached ~> 1.0 (no versions cached)".to_string(),
+            "transitive (no versions cached)".to_string(),
+        ]
+    );
+}
+
 #[test]
 fn provided_local_to_hex() {
     let provided_package = ProvidedPackage {
@@ -1213,7 +2625,7 @@ fn provided_local_to_hex() {
     let hex_package = hexpm::Package {
         name: "package".into(),
         repository: "local".into(),
-        releases: vec![hexpm::Release {
+        releases: vec![Release {
             version: Version::new(1, 0, 0),
             retirement_status: None,
             outer_checksum: vec![],
@@ -1272,7 +2684,7 @@ fn provided_git_to_hex() {
     let hex_package = hexpm::Package {
         name: "package".into(),
         repository: "local".into(),
-        releases: vec![hexpm::Release {
+        releases: vec![Release {
             version: Version::new(1, 0, 0),
             retirement_status: None,
             outer_checksum: vec![],
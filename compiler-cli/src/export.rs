@@ -1,7 +1,13 @@
+use std::collections::HashMap;
+
+use crate::fs::ProjectIO;
 use camino::Utf8PathBuf;
 use gleam_core::{
     analyse::TargetSupport,
     build::{Codegen, Mode, Options, Target},
+    error::Error,
+    javascript::ApiResults,
+    sbom::SbomFormat,
     Result,
 };
 
@@ -127,6 +133,132 @@ pub fn typescript_prelude() -> Result<()> {
     Ok(())
 }
 
+/// Generate a single JavaScript module that contains the main function of the
+/// entry module and everything it uses, including the prelude.
+///
+/// Functions, constants, and record constructors that cannot be reached from
+/// the main function are not included.
+pub fn javascript_bundle(entry: Option<String>, output: Option<Utf8PathBuf>) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let config = crate::config::root_config()?;
+    let target = Target::JavaScript;
+    let mode = Mode::Prod;
+    let build = paths.build_directory_for_target(mode, target);
+    let entry = entry.unwrap_or_else(|| config.name.to_string());
+    let out = output.unwrap_or_else(|| paths.javascript_bundle(&entry));
+
+    // Reset the build directory so every module is compiled and their typed
+    // ASTs are available for dead code elimination.
+    crate::fs::delete_directory(&build)?;
+
+    let built = crate::build::main(
+        Options {
+            root_target_support: TargetSupport::Enforced,
+            warnings_as_errors: false,
+            codegen: Codegen::All,
+            mode,
+            target: Some(target),
+        },
+        crate::build::download_dependencies()?,
+    )?;
+
+    // A bundle can only be created for a module with a main function
+    let _ = built.get_main_function(&entry.as_str().into(), target)?;
+
+    let bundle = gleam_core::javascript::bundle(
+        &ProjectIO::new(),
+        &build,
+        built.into_compiled_modules(),
+        &entry,
+        config.javascript.integers,
+    )?;
+    crate::fs::write(&out, &bundle)?;
+
+    crate::cli::print_exported(&entry);
+
+    println!(
+        "
+Your JavaScript bundle has been generated to {out}.
+
+It exports the main function of the {entry} module.
+"
+    );
+
+    Ok(())
+}
+
+/// Generate a wrapper for each of the given modules that gives their public
+/// functions an idiomatic JavaScript API, along with TypeScript declarations.
+pub fn javascript_api(modules: Vec<String>, results: ApiResults) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let config = crate::config::root_config()?;
+    let target = Target::JavaScript;
+    let mode = Mode::Prod;
+    let build = paths.build_directory_for_package(mode, target, &config.name);
+
+    // Reset the package's build directory so every module is compiled and
+    // their typed ASTs are available.
+    crate::fs::delete_directory(&build)?;
+
+    let built = crate::build::main(
+        Options {
+            root_target_support: TargetSupport::Enforced,
+            warnings_as_errors: false,
+            codegen: Codegen::All,
+            mode,
+            target: Some(target),
+        },
+        crate::build::download_dependencies()?,
+    )?;
+
+    let mut written = vec![];
+    for name in &modules {
+        let module = built
+            .root_package
+            .modules
+            .iter()
+            .find(|module| module.name == name.as_str())
+            .ok_or_else(|| Error::ModuleDoesNotExist {
+                module: name.as_str().into(),
+                suggestion: None,
+            })?;
+
+        let api = gleam_core::javascript::api(
+            &module.ast,
+            results,
+            config.javascript.integers,
+            config.javascript.module_format,
+        );
+        let path = build.join(name);
+        let javascript = path.with_extension("api.mjs");
+        let typescript = path.with_extension("api.d.mts");
+        crate::fs::write(&javascript, &api.javascript)?;
+        crate::fs::write(&typescript, &api.typescript)?;
+        written.push(javascript);
+    }
+
+    crate::cli::print_exported(&modules.join(", "));
+
+    let written = written
+        .iter()
+        .map(|path| format!("  {path}"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    println!(
+        "
+Your JavaScript API has been generated to:
+
+{written}
+
+Each module has TypeScript declarations alongside it. Enable
+`typescript_declarations` in the `[javascript]` section of gleam.toml
+to have the declarations of the modules they refer to generated too.
+"
+    );
+
+    Ok(())
+}
+
 pub fn package_interface(path: Utf8PathBuf) -> Result<()> {
     // Build the project
     let mut built = crate::build::main(
@@ -145,3 +277,37 @@ pub fn package_interface(path: Utf8PathBuf) -> Result<()> {
     crate::fs::write_outputs_under(&[out], crate::find_project_paths()?.root())?;
     Ok(())
 }
+
+/// Write a software bill of materials for the project and every package in
+/// its manifest, with the licences each package declares in its `gleam.toml`.
+pub fn sbom(format: SbomFormat, output: Utf8PathBuf) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let config = crate::config::root_config()?;
+    let manifest = crate::build::download_dependencies()?;
+
+    let mut licences = HashMap::new();
+    for package in &manifest.packages {
+        let config_path = crate::config::package_root(package, &paths).join("gleam.toml");
+        // Packages built with rebar3 or mix have no gleam.toml
+        if config_path.is_file() {
+            let package_config = crate::config::read(config_path)?;
+            let _ = licences.insert(package.name.clone(), package_config.licences);
+        }
+    }
+
+    let timestamp = time::OffsetDateTime::now_utc()
+        .replace_nanosecond(0)
+        .expect("SBOM timestamp")
+        .format(&time::format_description::well_known::Rfc3339)
+        .expect("SBOM timestamp");
+    let document = gleam_core::sbom::generate(format, &config, &manifest, &licences, &timestamp);
+    crate::fs::write(&output, &document)?;
+
+    crate::cli::print_exported(&config.name);
+    println!(
+        "
+Your software bill of materials has been generated to {output}.
+"
+    );
+    Ok(())
+}
@@ -22,7 +22,10 @@ use std::{
 
 use camino::{ReadDirUtf8, Utf8Path, Utf8PathBuf};
 
-use crate::{dependencies::UseManifest, lsp::LspLocker};
+use crate::{
+    dependencies::{Connectivity, UseManifest},
+    lsp::LspLocker,
+};
 
 #[cfg(test)]
 mod tests;
@@ -231,7 +234,13 @@ impl MakeLocker for ProjectIO {
 
 impl DownloadDependencies for ProjectIO {
     fn download_dependencies(&self, paths: &ProjectPaths) -> Result<Manifest> {
-        crate::dependencies::download(paths, NullTelemetry, None, UseManifest::Yes)
+        crate::dependencies::download(
+            paths,
+            NullTelemetry,
+            None,
+            UseManifest::Yes,
+            Connectivity::Online,
+        )
     }
 }
 
@@ -363,7 +372,9 @@ fn is_gleam_path(path: &Utf8Path, dir: impl AsRef<Utf8Path>) -> bool {
 }
 
 fn is_gleam_build_dir(e: &ignore::DirEntry) -> bool {
-    if !e.path().is_dir() || !e.path().ends_with("build") {
+    // The vendor directory holds the sources of dependency packages, which
+    // are not part of the project.
+    if !e.path().is_dir() || !(e.path().ends_with("build") || e.path().ends_with("vendor")) {
         return false;
     }
 
@@ -393,7 +404,10 @@ pub fn native_files(dir: &Utf8Path) -> Result<impl Iterator<Item = Utf8PathBuf>
         .map(|e| e.into_path())
         .filter(|path| {
             let extension = path.extension().unwrap_or_default();
-            matches!(extension, "erl" | "hrl" | "ex" | "js" | "mjs" | "ts")
+            matches!(
+                extension,
+                "erl" | "hrl" | "ex" | "js" | "mjs" | "cjs" | "ts"
+            )
         }))
 }
 
@@ -545,19 +559,22 @@ pub fn copy(
         .map(|_| ())
 }
 
-// pub fn rename(path: impl AsRef<Utf8Path> + Debug, to: impl AsRef<Utf8Path> + Debug) -> Result<(), Error> {
-//     tracing::trace!(from=?path, to=?to, "renaming_file");
+pub fn rename(
+    path: impl AsRef<Utf8Path> + Debug,
+    to: impl AsRef<Utf8Path> + Debug,
+) -> Result<(), Error> {
+    tracing::trace!(from=?path, to=?to, "renaming_file");
 
-//     // TODO: include the destination in the error message
-//     std::fs::rename(&path, &to)
-//         .map_err(|err| Error::FileIo {
-//             action: FileIoAction::Rename,
-//             kind: FileKind::File,
-//             path: Utf8PathBuf::from(path.as_ref()),
-//             err: Some(err.to_string()),
-//         })
-//         .map(|_| ())
-// }
+    // TODO: include the destination in the error message
+    std::fs::rename(path.as_ref(), to.as_ref())
+        .map_err(|err| Error::FileIo {
+            action: FileIoAction::Rename,
+            kind: FileKind::File,
+            path: Utf8PathBuf::from(path.as_ref()),
+            err: Some(err.to_string()),
+        })
+        .map(|_| ())
+}
 
 pub fn copy_dir(
     path: impl AsRef<Utf8Path> + Debug,
@@ -49,6 +49,8 @@
 extern crate pretty_assertions;
 
 mod add;
+mod api_diff;
+mod browser;
 mod build;
 mod build_lock;
 mod cli;
@@ -64,14 +66,18 @@ mod hex;
 mod http;
 mod lsp;
 mod new;
+mod package_cache;
 mod panic;
 mod publish;
 mod remove;
+mod repl;
 mod run;
 mod shell;
+mod vendor;
+mod verify;
 
 use config::root_config;
-use dependencies::UseManifest;
+use dependencies::{Connectivity, UseManifest};
 use fs::{get_current_directory, get_project_root};
 pub use gleam_core::error::{Error, Result};
 
@@ -79,7 +85,9 @@ use gleam_core::{
     analyse::TargetSupport,
     build::{Codegen, Mode, Options, Runtime, Target},
     hex::RetirementReason,
+    javascript::ApiResults,
     paths::ProjectPaths,
+    sbom::SbomFormat,
     version::COMPILER_VERSION,
 };
 use hex::ApiKeyCommand as _;
@@ -141,6 +149,13 @@ enum Command {
         yes: bool,
     },
 
+    /// Compare the public API of the project with a previous version
+    ApiDiff {
+        /// A version published to Hex, or the path of a package interface
+        /// exported with `gleam export package-interface`
+        previous: String,
+    },
+
     /// Render HTML documentation
     #[command(subcommand)]
     Docs(Docs),
@@ -150,7 +165,15 @@ enum Command {
     Deps(Dependencies),
 
     /// Update dependency packages to their latest versions
-    Update,
+    Update {
+        /// The names of the packages to update. All packages are updated if
+        /// none are given
+        packages: Vec<String>,
+
+        /// Also update the packages the named packages depend on
+        #[arg(long, requires = "packages")]
+        recursive: bool,
+    },
 
     /// Work with the Hex package manager
     #[command(subcommand)]
@@ -179,6 +202,15 @@ enum Command {
     /// Start an Erlang shell
     Shell,
 
+    /// Start an interactive Gleam REPL
+    Repl {
+        #[arg(short, long, ignore_case = true, help = target_doc())]
+        target: Option<Target>,
+
+        #[arg(long, ignore_case = true, help = runtime_doc())]
+        runtime: Option<Runtime>,
+    },
+
     /// Run the project
     #[command(trailing_var_arg = true)]
     Run {
@@ -263,12 +295,54 @@ pub enum ExportTarget {
     JavascriptPrelude,
     /// The TypeScript prelude module
     TypescriptPrelude,
+    /// A single JavaScript module containing only the code reachable from
+    /// the main function of a module, suitable for deployment to browsers
+    JavascriptBundle {
+        /// The module whose main function is the entrypoint of the bundle
+        #[arg(long)]
+        entry: Option<String>,
+
+        /// The path to write the bundle to
+        #[arg(long = "out")]
+        output: Option<Utf8PathBuf>,
+    },
+    /// A wrapper for each of the given modules giving their public functions
+    /// an idiomatic JavaScript API, along with TypeScript declarations
+    JavascriptApi {
+        /// A module to generate a wrapper for. May be given more than once
+        #[arg(long = "module", required = true)]
+        modules: Vec<String>,
+
+        /// Whether a returned `Result` is given as an object with an `ok`
+        /// field, or its error is thrown
+        #[arg(
+            long,
+            default_value = "union",
+            value_parser = PossibleValuesParser::new(ApiResults::VARIANTS).map(|s| ApiResults::from_str(&s).unwrap())
+        )]
+        results: ApiResults,
+    },
     /// Information on the modules, functions, and types in the project in JSON format
     PackageInterface {
         #[arg(long = "out", required = true)]
         /// The path to write the JSON file to
         output: Utf8PathBuf,
     },
+    /// A software bill of materials listing the project and its dependency
+    /// packages, in CycloneDX or SPDX JSON format
+    Sbom {
+        /// The format of the document
+        #[arg(
+            long,
+            default_value = "cyclonedx",
+            value_parser = PossibleValuesParser::new(SbomFormat::VARIANTS).map(|s| SbomFormat::from_str(&s).unwrap())
+        )]
+        format: SbomFormat,
+
+        #[arg(long = "out", required = true)]
+        /// The path to write the JSON file to
+        output: Utf8PathBuf,
+    },
 }
 
 #[derive(Args, Debug, Clone)]
@@ -331,11 +405,57 @@ enum Dependencies {
     /// List all dependency packages
     List,
 
+    /// Show the tree of requirements between the project and its dependencies
+    ///
+    /// Each package is shown with its resolved version and the range of
+    /// versions required by the package that depends on it. Packages whose
+    /// dependencies have already been shown are marked with (*).
+    Tree,
+
+    /// Show every path from the project to a dependency package
+    Why {
+        /// The name of the dependency package
+        package: String,
+    },
+
+    /// Show dependency packages with newer or retired versions
+    ///
+    /// For each package the locked version is shown along with the latest
+    /// version allowed by the requirements on it, the latest version overall,
+    /// and the reason the locked version was retired, if it was.
+    Outdated,
+
+    /// Check the licences of all dependency packages against the licence
+    /// policy in gleam.toml
+    CheckLicences,
+
+    /// Copy the sources of all Hex dependency packages into `vendor`
+    ///
+    /// Builds use the vendored packages rather than downloading them, and
+    /// fail if the vendored sources no longer match the manifest.
+    Vendor,
+
+    /// Check the dependency packages against the checksums in the manifest,
+    /// restoring any that have been changed in the build directory
+    Verify,
+
     /// Download all dependency packages
-    Download,
+    Download {
+        /// Resolve versions using only the packages in the global package cache
+        #[arg(long)]
+        offline: bool,
+    },
 
     /// Update dependency packages to their latest versions
-    Update,
+    Update {
+        /// The names of the packages to update. All packages are updated if
+        /// none are given
+        packages: Vec<String>,
+
+        /// Also update the packages the named packages depend on
+        #[arg(long, requires = "packages")]
+        recursive: bool,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -457,14 +577,31 @@ fn main() {
 
         Command::Deps(Dependencies::List) => dependencies::list(),
 
-        Command::Deps(Dependencies::Download) => download_dependencies(),
+        Command::Deps(Dependencies::Tree) => dependencies::tree(),
 
-        Command::Deps(Dependencies::Update) => dependencies::update(),
+        Command::Deps(Dependencies::Why { package }) => dependencies::why(package.into()),
+
+        Command::Deps(Dependencies::Outdated) => dependencies::outdated(),
+
+        Command::Deps(Dependencies::CheckLicences) => dependencies::check_licences(),
+
+        Command::Deps(Dependencies::Vendor) => vendor::command(),
+
+        Command::Deps(Dependencies::Verify) => verify::command(),
+
+        Command::Deps(Dependencies::Download { offline }) => download_dependencies(offline),
+
+        Command::Deps(Dependencies::Update {
+            packages,
+            recursive,
+        }) => dependencies::update(packages, recursive),
 
         Command::New(options) => new::create(options, COMPILER_VERSION),
 
         Command::Shell => shell::command(),
 
+        Command::Repl { target, runtime } => repl::command(target, runtime),
+
         Command::Run {
             target,
             arguments,
@@ -482,6 +619,8 @@ fn main() {
 
         Command::Publish { replace, yes } => publish::command(replace, yes),
 
+        Command::ApiDiff { previous } => api_diff::command(previous),
+
         Command::PrintConfig => print_config(),
 
         Command::Hex(Hex::Retire {
@@ -501,7 +640,10 @@ fn main() {
 
         Command::Remove { packages } => remove::command(packages),
 
-        Command::Update => dependencies::update(),
+        Command::Update {
+            packages,
+            recursive,
+        } => dependencies::update(packages, recursive),
 
         Command::Clean => clean(),
 
@@ -511,9 +653,16 @@ fn main() {
         Command::Export(ExportTarget::HexTarball) => export::hex_tarball(),
         Command::Export(ExportTarget::JavascriptPrelude) => export::javascript_prelude(),
         Command::Export(ExportTarget::TypescriptPrelude) => export::typescript_prelude(),
+        Command::Export(ExportTarget::JavascriptBundle { entry, output }) => {
+            export::javascript_bundle(entry, output)
+        }
+        Command::Export(ExportTarget::JavascriptApi { modules, results }) => {
+            export::javascript_api(modules, results)
+        }
         Command::Export(ExportTarget::PackageInterface { output }) => {
             export::package_interface(output)
         }
+        Command::Export(ExportTarget::Sbom { format, output }) => export::sbom(format, output),
     };
 
     match result {
@@ -591,8 +740,19 @@ fn project_paths_at_current_directory_without_toml() -> ProjectPaths {
     ProjectPaths::new(current_dir)
 }
 
-fn download_dependencies() -> Result<()> {
+fn download_dependencies(offline: bool) -> Result<()> {
     let paths = find_project_paths()?;
-    _ = dependencies::download(&paths, cli::Reporter::new(), None, UseManifest::Yes)?;
+    let connectivity = if offline {
+        Connectivity::Offline
+    } else {
+        Connectivity::Online
+    };
+    _ = dependencies::download(
+        &paths,
+        cli::Reporter::new(),
+        None,
+        UseManifest::Yes,
+        connectivity,
+    )?;
     Ok(())
 }
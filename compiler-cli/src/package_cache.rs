@@ -0,0 +1,613 @@
+//! Reading package releases from the global package cache, so that
+//! dependencies can be resolved without access to Hex.
+
+use std::{collections::HashMap, io::Read};
+
+use camino::Utf8PathBuf;
+use gleam_core::{
+    error::{FileIoAction, FileKind},
+    paths::PackageCache,
+    Error, Result,
+};
+use hexpm::{
+    version::{Range, Version},
+    Dependency, Release, ReleaseMeta,
+};
+use sha2::Digest;
+
+use crate::fs;
+
+/// All the versions of a package in the global package cache, read from the
+/// cached tarballs. Packages from one of the Hex repositories configured in
+/// `gleam.toml` are cached separately from those from hex.pm, by the URL of
+/// the repository.
+pub fn releases(repository_url: Option<&http::Uri>, name: &str) -> Vec<Release<ReleaseMeta>> {
+    let directory = PackageCache::global().packages(repository_url);
+    let Ok(entries) = fs::read_dir(&directory) else {
+        return vec![];
+    };
+    let prefix = format!("{name}-");
+    entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let version = entry
+                .file_name()
+                .strip_prefix(&prefix)?
+                .strip_suffix(".tar")?;
+            let version = Version::parse(version).ok()?;
+            match read_release(entry.path().to_path_buf(), version) {
+                Ok(release) => Some(release),
+                Err(error) => {
+                    tracing::warn!(path = %entry.path(), error = ?error, "unreadable_cached_package");
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+fn read_release(path: Utf8PathBuf, version: Version) -> Result<Release<ReleaseMeta>> {
+    let tarball = fs::read_bytes(&path)?;
+    release_from_tarball(&tarball, version).map_err(|error| Error::FileIo {
+        action: FileIoAction::Parse,
+        kind: FileKind::File,
+        path,
+        err: Some(error),
+    })
+}
+
+/// The licences declared in the metadata of a package in the global package
+/// cache, or `None` if the package is not in the cache.
+pub fn licences(
+    repository_url: Option<&http::Uri>,
+    name: &str,
+    version: &Version,
+) -> Option<Vec<String>> {
+    let path = PackageCache::global().package_tarball(repository_url, name, &version.to_string());
+    let tarball = fs::read_bytes(path).ok()?;
+    let metadata = read_metadata(&tarball).ok()?;
+    Some(licences_from_metadata(&metadata))
+}
+
+fn licences_from_metadata(metadata: &Term) -> Vec<String> {
+    match metadata.get("licenses") {
+        Some(Term::List(licences)) => licences
+            .iter()
+            .filter_map(Term::as_str)
+            .map(String::from)
+            .collect(),
+        _ => vec![],
+    }
+}
+
+/// Read the metadata of a release from its Hex package tarball. The outer
+/// checksum is that of the whole tarball, as it is for Hex.
+fn release_from_tarball(tarball: &[u8], version: Version) -> Result<Release<ReleaseMeta>, String> {
+    let metadata = read_metadata(tarball)?;
+
+    let app = metadata
+        .get("app")
+        .and_then(Term::as_str)
+        .ok_or("The package metadata has no app name")?;
+    let build_tools = match metadata.get("build_tools") {
+        Some(Term::List(tools)) => tools
+            .iter()
+            .filter_map(Term::as_str)
+            .map(String::from)
+            .collect(),
+        _ => vec![],
+    };
+    let requirements = match metadata.get("requirements") {
+        Some(requirements) => parse_requirements(requirements)?,
+        None => HashMap::new(),
+    };
+
+    Ok(Release {
+        version,
+        requirements,
+        retirement_status: None,
+        outer_checksum: sha2::Sha256::digest(tarball).to_vec(),
+        meta: ReleaseMeta {
+            app: app.into(),
+            build_tools,
+        },
+    })
+}
+
+/// Read the `metadata.config` file of a Hex package tarball.
+fn read_metadata(tarball: &[u8]) -> Result<Term, String> {
+    let mut archive = tar::Archive::new(tarball);
+    let mut metadata = None;
+    for entry in archive.entries().map_err(|error| error.to_string())? {
+        let mut entry = entry.map_err(|error| error.to_string())?;
+        let path = entry.path().map_err(|error| error.to_string())?;
+        if path.as_os_str() == "metadata.config" {
+            let mut contents = String::new();
+            let _ = entry
+                .read_to_string(&mut contents)
+                .map_err(|error| error.to_string())?;
+            metadata = Some(contents);
+            break;
+        }
+    }
+    let metadata = metadata.ok_or("The package has no metadata.config")?;
+    parse_metadata(&metadata)
+}
+
+/// Read the source files of a package from the `contents.tar.gz` file of its
+/// Hex package tarball, along with their paths relative to the package root.
+pub fn read_contents(tarball: &[u8]) -> Result<Vec<(Utf8PathBuf, Vec<u8>)>, String> {
+    let mut archive = tar::Archive::new(tarball);
+    for entry in archive.entries().map_err(|error| error.to_string())? {
+        let entry = entry.map_err(|error| error.to_string())?;
+        let path = entry.path().map_err(|error| error.to_string())?;
+        if path.as_os_str() != "contents.tar.gz" {
+            continue;
+        }
+        let mut contents = tar::Archive::new(flate2::read::GzDecoder::new(entry));
+        let mut files = vec![];
+        for file in contents.entries().map_err(|error| error.to_string())? {
+            let mut file = file.map_err(|error| error.to_string())?;
+            if !file.header().entry_type().is_file() {
+                continue;
+            }
+            let path = file.path().map_err(|error| error.to_string())?;
+            let path = Utf8PathBuf::from_path_buf(path.into_owned())
+                .map_err(|path| format!("Non Utf-8 path {}", path.display()))?;
+            let mut bytes = vec![];
+            let _ = file
+                .read_to_end(&mut bytes)
+                .map_err(|error| error.to_string())?;
+            files.push((path, bytes));
+        }
+        return Ok(files);
+    }
+    Err("The package has no contents.tar.gz".into())
+}
+
+/// Requirements are written as a list of `{Name, Properties}` tuples by
+/// Gleam and rebar3, as a list of property lists including the name by Mix,
+/// and as a map of names to properties by older tools.
+fn parse_requirements(requirements: &Term) -> Result<HashMap<String, Dependency>, String> {
+    let requirements: Vec<(&str, &Term)> = match requirements {
+        Term::List(items) => items
+            .iter()
+            .map(|item| match item {
+                Term::Tuple(pair) => match pair.as_slice() {
+                    [Term::Binary(name), properties] => Ok((name.as_str(), properties)),
+                    _ => Err("Invalid requirement in the package metadata"),
+                },
+                properties => properties
+                    .get("name")
+                    .and_then(Term::as_str)
+                    .map(|name| (name, properties))
+                    .ok_or("Requirement without a name in the package metadata"),
+            })
+            .collect::<Result<_, _>>()?,
+        Term::Map(entries) => entries
+            .iter()
+            .map(|(name, properties)| match name {
+                Term::Binary(name) => Ok((name.as_str(), properties)),
+                _ => Err("Invalid requirement in the package metadata"),
+            })
+            .collect::<Result<_, _>>()?,
+        _ => return Err("Invalid requirements in the package metadata".into()),
+    };
+
+    requirements
+        .into_iter()
+        .map(|(name, properties)| {
+            let requirement = properties
+                .get("requirement")
+                .and_then(Term::as_str)
+                .ok_or_else(|| format!("Requirement {name} has no version range"))?;
+            let dependency = Dependency {
+                requirement: Range::new(requirement.into()),
+                optional: properties.get("optional") == Some(&Term::Atom("true".into())),
+                app: properties
+                    .get("app")
+                    .and_then(Term::as_str)
+                    .map(String::from),
+                repository: properties
+                    .get("repository")
+                    .and_then(Term::as_str)
+                    .map(String::from),
+            };
+            Ok((name.to_string(), dependency))
+        })
+        .collect()
+}
+
+/// The subset of Erlang terms found in Hex package metadata.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Term {
+    Binary(String),
+    Atom(String),
+    Integer(i64),
+    List(Vec<Self>),
+    Tuple(Vec<Self>),
+    Map(Vec<(Self, Self)>),
+}
+
+impl Term {
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::Binary(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    /// Look up a value by its key in a property list or a map.
+    fn get(&self, key: &str) -> Option<&Self> {
+        match self {
+            Self::List(items) => items.iter().find_map(|item| match item {
+                Self::Tuple(pair) => match pair.as_slice() {
+                    [name, value] if name.as_str() == Some(key) => Some(value),
+                    _ => None,
+                },
+                _ => None,
+            }),
+            Self::Map(entries) => entries
+                .iter()
+                .find(|(name, _)| name.as_str() == Some(key))
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+}
+
+/// Parse the `{Key, Value}.` terms of a `metadata.config` file into a
+/// property list.
+fn parse_metadata(source: &str) -> Result<Term, String> {
+    let mut parser = TermParser {
+        chars: source.chars().collect(),
+        position: 0,
+    };
+    let mut terms = vec![];
+    while parser.skip_whitespace().is_some() {
+        terms.push(parser.term()?);
+        parser.expect('.')?;
+    }
+    Ok(Term::List(terms))
+}
+
+struct TermParser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl TermParser {
+    /// Skip whitespace and comments, returning the next character.
+    fn skip_whitespace(&mut self) -> Option<char> {
+        loop {
+            match self.chars.get(self.position)? {
+                '%' => {
+                    while !matches!(self.chars.get(self.position), Some('\n') | None) {
+                        self.position += 1;
+                    }
+                }
+                char if char.is_whitespace() => self.position += 1,
+                char => return Some(*char),
+            }
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.skip_whitespace() {
+            Some(char) if char == expected => {
+                self.position += 1;
+                Ok(())
+            }
+            Some(char) => Err(format!("Expected `{expected}` but found `{char}`")),
+            None => Err(format!("Expected `{expected}` but the file ended")),
+        }
+    }
+
+    fn starts_with(&self, prefix: &str) -> bool {
+        prefix
+            .chars()
+            .enumerate()
+            .all(|(index, char)| self.chars.get(self.position + index) == Some(&char))
+    }
+
+    fn term(&mut self) -> Result<Term, String> {
+        match self.skip_whitespace() {
+            Some('{') => {
+                self.position += 1;
+                Ok(Term::Tuple(self.sequence('}')?))
+            }
+            Some('[') => {
+                self.position += 1;
+                Ok(Term::List(self.sequence(']')?))
+            }
+            Some('#') => {
+                self.expect('#')?;
+                self.expect('{')?;
+                self.map()
+            }
+            Some('<') => self.binary(),
+            Some('"') => Ok(Term::Binary(self.string('"')?)),
+            Some('\'') => Ok(Term::Atom(self.string('\'')?)),
+            Some(char) if char.is_ascii_digit() || char == '-' => self.integer(),
+            Some(char) if char.is_ascii_lowercase() => Ok(Term::Atom(self.atom())),
+            Some(char) => Err(format!("Unexpected `{char}`")),
+            None => Err("Unexpected end of file".into()),
+        }
+    }
+
+    fn sequence(&mut self, close: char) -> Result<Vec<Term>, String> {
+        let mut terms = vec![];
+        if self.skip_whitespace() == Some(close) {
+            self.position += 1;
+            return Ok(terms);
+        }
+        loop {
+            terms.push(self.term()?);
+            match self.skip_whitespace() {
+                Some(',') => self.position += 1,
+                _ => {
+                    self.expect(close)?;
+                    return Ok(terms);
+                }
+            }
+        }
+    }
+
+    fn map(&mut self) -> Result<Term, String> {
+        let mut entries = vec![];
+        if self.skip_whitespace() == Some('}') {
+            self.position += 1;
+            return Ok(Term::Map(entries));
+        }
+        loop {
+            let key = self.term()?;
+            self.expect('=')?;
+            self.expect('>')?;
+            entries.push((key, self.term()?));
+            match self.skip_whitespace() {
+                Some(',') => self.position += 1,
+                _ => {
+                    self.expect('}')?;
+                    return Ok(Term::Map(entries));
+                }
+            }
+        }
+    }
+
+    fn binary(&mut self) -> Result<Term, String> {
+        self.expect('<')?;
+        self.expect('<')?;
+        if self.skip_whitespace() == Some('>') {
+            self.expect('>')?;
+            self.expect('>')?;
+            return Ok(Term::Binary(String::new()));
+        }
+        let _ = self.skip_whitespace();
+        let string = self.string('"')?;
+        let _ = self.skip_whitespace();
+        if self.starts_with("/utf8") {
+            self.position += "/utf8".len();
+        }
+        self.expect('>')?;
+        self.expect('>')?;
+        Ok(Term::Binary(string))
+    }
+
+    fn string(&mut self, quote: char) -> Result<String, String> {
+        self.expect(quote)?;
+        let mut string = String::new();
+        loop {
+            let char = *self.chars.get(self.position).ok_or("Unterminated string")?;
+            self.position += 1;
+            match char {
+                '\\' => {
+                    let escaped = *self.chars.get(self.position).ok_or("Unterminated string")?;
+                    self.position += 1;
+                    string.push(match escaped {
+                        'n' => '\n',
+                        'r' => '\r',
+                        't' => '\t',
+                        other => other,
+                    });
+                }
+                char if char == quote => return Ok(string),
+                char => string.push(char),
+            }
+        }
+    }
+
+    /// Take the next character, and those after it that match the predicate.
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> String {
+        let mut taken = String::new();
+        while let Some(char) = self.chars.get(self.position) {
+            if !taken.is_empty() && !predicate(*char) {
+                break;
+            }
+            taken.push(*char);
+            self.position += 1;
+        }
+        taken
+    }
+
+    fn integer(&mut self) -> Result<Term, String> {
+        let digits = self.take_while(|char| char.is_ascii_digit());
+        digits
+            .parse()
+            .map(Term::Integer)
+            .map_err(|_| format!("Invalid integer `{digits}`"))
+    }
+
+    fn atom(&mut self) -> String {
+        self.take_while(|char| char.is_ascii_alphanumeric() || char == '_' || char == '@')
+    }
+}
+
+#[cfg(test)]
+fn tarball(metadata: &str) -> Vec<u8> {
+    tarball_with_contents(metadata, &[])
+}
+
+#[cfg(test)]
+fn tarball_with_contents(metadata: &str, contents: &[u8]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(vec![]);
+    for (path, contents) in [
+        ("VERSION", "3".as_bytes()),
+        ("metadata.config", metadata.as_bytes()),
+        ("contents.tar.gz", contents),
+    ] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, path, contents).unwrap();
+    }
+    builder.into_inner().unwrap()
+}
+
+/// A package tarball holding the given source files.
+#[cfg(test)]
+pub fn tarball_with_files(files: &[(&str, &str)]) -> Vec<u8> {
+    let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+        vec![],
+        flate2::Compression::default(),
+    ));
+    for (path, contents) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder
+            .append_data(&mut header, path, contents.as_bytes())
+            .unwrap();
+    }
+    let contents = builder.into_inner().unwrap().finish().unwrap();
+    tarball_with_contents(r#"{<<"app">>,<<"myapp">>}."#, &contents)
+}
+
+#[cfg(test)]
+fn requirement(range: &str, repository: Option<&str>) -> Dependency {
+    Dependency {
+        requirement: Range::new(range.into()),
+        optional: false,
+        app: Some("wibble".into()),
+        repository: repository.map(String::from),
+    }
+}
+
+#[test]
+fn release_from_gleam_tarball() {
+    let metadata = r#"{<<"name">>, <<"myapp">>}.
+{<<"app">>, <<"myapp">>}.
+{<<"version">>, <<"1.2.3">>}.
+{<<"description">>, <<"description goes here 🌈"/utf8>>}.
+{<<"licenses">>, [<<"MIT">>]}.
+{<<"build_tools">>, [<<"gleam">>]}.
+{<<"requirements">>, [
+  {<<"wibble">>, [
+    {<<"app">>, <<"wibble">>},
+    {<<"optional">>, false},
+    {<<"requirement">>, <<"~> 1.2.3 or >= 5.0.0">>},
+    {<<"repository">>, <<"internal">>}
+  ]}
+]}.
+"#;
+    let tarball = tarball(metadata);
+    let release = release_from_tarball(&tarball, Version::new(1, 2, 3)).unwrap();
+    assert_eq!(
+        release,
+        Release {
+            version: Version::new(1, 2, 3),
+            requirements: [(
+                "wibble".into(),
+                requirement("~> 1.2.3 or >= 5.0.0", Some("internal"))
+            )]
+            .into(),
+            retirement_status: None,
+            outer_checksum: sha2::Sha256::digest(&tarball).to_vec(),
+            meta: ReleaseMeta {
+                app: "myapp".into(),
+                build_tools: vec!["gleam".into()],
+            },
+        }
+    );
+}
+
+#[test]
+fn licences_from_tarball() {
+    let metadata = r#"{<<"app">>,<<"myapp">>}.
+{<<"licenses">>,[<<"Apache-2.0">>,<<"MIT">>]}.
+"#;
+    let metadata = read_metadata(&tarball(metadata)).unwrap();
+    assert_eq!(
+        licences_from_metadata(&metadata),
+        vec!["Apache-2.0".to_string(), "MIT".to_string()]
+    );
+    let metadata = read_metadata(&tarball(r#"{<<"app">>,<<"myapp">>}."#)).unwrap();
+    assert_eq!(licences_from_metadata(&metadata), Vec::<String>::new());
+}
+
+#[test]
+fn contents_from_tarball() {
+    let tarball = tarball_with_files(&[
+        ("gleam.toml", "name = \"myapp\""),
+        ("src/myapp.gleam", "pub fn main() { 1 }"),
+    ]);
+    assert_eq!(
+        read_contents(&tarball).unwrap(),
+        vec![
+            ("gleam.toml".into(), b"name = \"myapp\"".to_vec()),
+            ("src/myapp.gleam".into(), b"pub fn main() { 1 }".to_vec()),
+        ]
+    );
+}
+
+#[test]
+fn release_from_mix_tarball() {
+    let metadata = r#"{<<"app">>,<<"myapp">>}.
+{<<"build_tools">>,[<<"mix">>]}.
+{<<"requirements">>,
+ [[{<<"app">>,<<"wibble">>},
+   {<<"name">>,<<"wibble">>},
+   {<<"optional">>,false},
+   {<<"repository">>,<<"hexpm">>},
+   {<<"requirement">>,<<"~> 1.0">>}]]}.
+"#;
+    let release = release_from_tarball(&tarball(metadata), Version::new(0, 1, 0)).unwrap();
+    assert_eq!(
+        release.requirements,
+        [("wibble".into(), requirement("~> 1.0", Some("hexpm")))].into()
+    );
+    assert_eq!(release.meta.build_tools, vec!["mix".to_string()]);
+}
+
+#[test]
+fn release_from_tarball_with_requirements_map() {
+    let metadata = r#"% An older metadata format
+{<<"app">>,<<"myapp">>}.
+{<<"build_tools">>,[<<"rebar3">>]}.
+{<<"requirements">>,
+ #{<<"wibble">> => #{<<"app">> => <<"wibble">>,<<"optional">> => false,
+                    <<"requirement">> => <<">= 0.0.0">>}}}.
+"#;
+    let release = release_from_tarball(&tarball(metadata), Version::new(0, 1, 0)).unwrap();
+    assert_eq!(
+        release.requirements,
+        [("wibble".into(), requirement(">= 0.0.0", None))].into()
+    );
+}
+
+#[test]
+fn release_from_tarball_without_metadata() {
+    let mut builder = tar::Builder::new(vec![]);
+    let mut header = tar::Header::new_gnu();
+    header.set_size(0);
+    header.set_cksum();
+    builder
+        .append_data(&mut header, "VERSION", &[][..])
+        .unwrap();
+    let tarball = builder.into_inner().unwrap();
+    assert_eq!(
+        release_from_tarball(&tarball, Version::new(0, 1, 0)),
+        Err("The package has no metadata.config".into())
+    );
+}
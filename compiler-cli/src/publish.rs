@@ -15,7 +15,7 @@ use itertools::Itertools;
 use sha2::Digest;
 use std::{io::Write, path::PathBuf, time::Instant};
 
-use crate::{build, cli, docs, fs, hex::ApiKeyCommand, http::HttpClient};
+use crate::{api_diff, build, cli, docs, fs, hex::ApiKeyCommand, http::HttpClient};
 
 pub fn command(replace: bool, yes: bool) -> Result<()> {
     let command = PublishCommand::setup(replace, yes)?;
@@ -63,6 +63,8 @@ impl PublishCommand {
             DocContext::HexPublish,
         )?)?;
 
+        api_diff::check_for_unmarked_breaking_changes(&config, &compile_result)?;
+
         // Ask user if this is correct
         if !generated_files_added.is_empty() {
             println!("\nGenerated files:");
@@ -345,10 +347,21 @@ fn metadata_config<'a>(
         .dependencies
         .iter()
         .map(|(name, requirement)| match requirement {
-            Requirement::Hex { version } => Ok(ReleaseRequirement {
+            Requirement::Hex {
+                version,
+                repository: None,
+            } => Ok(ReleaseRequirement {
                 name,
                 requirement: version,
             }),
+            // Hex has no way to tell which repository a local name refers to
+            Requirement::Hex {
+                repository: Some(repository),
+                ..
+            } => Err(Error::PublishHexRepositoryDependency {
+                package: name.to_string(),
+                repository: repository.clone(),
+            }),
             _ => Err(Error::PublishNonHexDependencies {
                 package: name.to_string(),
             }),
@@ -691,6 +704,26 @@ fn prevent_publish_git_dependency() {
     );
 }
 
+#[test]
+fn prevent_publish_hex_repository_dependency() {
+    let mut config = PackageConfig::default();
+    config.dependencies = [(
+        "provided".into(),
+        Requirement::Hex {
+            version: Range::new("~> 1.0".into()),
+            repository: Some("internal".into()),
+        },
+    )]
+    .into();
+    assert_eq!(
+        metadata_config(&config, &[], &[]),
+        Err(Error::PublishHexRepositoryDependency {
+            package: "provided".into(),
+            repository: "internal".into(),
+        })
+    );
+}
+
 pub fn get_hostname() -> String {
     hostname::get()
         .expect("Looking up hostname")
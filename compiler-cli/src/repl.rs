@@ -0,0 +1,303 @@
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    sync::Arc,
+};
+
+use camino::Utf8PathBuf;
+use gleam_core::{
+    analyse::TargetSupport,
+    build::{Codegen, Mode, Options, ProjectCompiler, Runtime, Target},
+    error::Error,
+    io::{CommandExecutor, Stdio as ExecStdio},
+    paths::{self, ProjectPaths},
+    repl::{self, Input, PendingModule, Repl},
+    Result,
+};
+
+use crate::{
+    build_lock::BuildLock,
+    cli,
+    fs::{self, ConsoleWarningEmitter, ProjectIO},
+};
+
+pub fn command(target: Option<Target>, runtime: Option<Runtime>) -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let manifest = crate::build::download_dependencies()?;
+    let config = crate::config::root_config()?;
+    let target = target.unwrap_or(config.target);
+    if let (Target::Erlang, Some(runtime)) = (target, runtime) {
+        return Err(Error::InvalidRuntime {
+            target,
+            invalid_runtime: runtime,
+        });
+    }
+    let runtime = runtime.unwrap_or(config.javascript.runtime);
+    let module_format = config.javascript.module_format;
+    // The modules compiled for each input are written to a directory of their
+    // own, which is cleared of those from any earlier session.
+    let out = paths.build_directory_for_package(Mode::Dev, target, repl::PACKAGE);
+    fs::delete_directory(&out)?;
+    fs::mkdir(&out)?;
+
+    let telemetry = cli::Reporter::new();
+    let lock = BuildLock::new_target(&paths, Mode::Dev, target)?;
+    let mut compiler = ProjectCompiler::new(
+        config,
+        Options {
+            root_target_support: TargetSupport::Enforced,
+            warnings_as_errors: false,
+            codegen: Codegen::All,
+            mode: Mode::Dev,
+            target: Some(target),
+        },
+        manifest.packages,
+        Box::new(cli::Reporter::new()),
+        Arc::new(ConsoleWarningEmitter),
+        paths.clone(),
+        ProjectIO::new(),
+    );
+    {
+        let _guard = lock.lock(&telemetry)?;
+        let _ = compiler.compile_project()?;
+    }
+
+    let mut runtime = match target {
+        Target::Erlang => Evaluator::erlang(&paths)?,
+        Target::JavaScript => Evaluator::javascript(&out, runtime)?,
+    };
+
+    cli::print_running("Gleam REPL");
+    println!("Enter Gleam expressions, imports and definitions. Type :quit to exit.");
+
+    let mut repl = Repl::new();
+    let mut input = String::new();
+    let stdin = io::stdin();
+    loop {
+        print!("{}", if input.is_empty() { "> " } else { "... " });
+        io::stdout().flush().map_err(|error| Error::StandardIo {
+            action: gleam_core::error::StandardIoAction::Write,
+            err: Some(error.kind()),
+        })?;
+
+        let mut line = String::new();
+        if stdin
+            .lock()
+            .read_line(&mut line)
+            .map_err(|error| Error::StandardIo {
+                action: gleam_core::error::StandardIoAction::Read,
+                err: Some(error.kind()),
+            })?
+            == 0
+        {
+            println!();
+            return Ok(());
+        }
+        if input.is_empty() && line.trim() == ":quit" {
+            return Ok(());
+        }
+        input.push_str(&line);
+        if input.trim().is_empty() {
+            input.clear();
+            continue;
+        }
+
+        let pending = match repl.read(&input) {
+            Ok(Input::Incomplete) => continue,
+            Ok(Input::Module(pending)) => pending,
+            Err(error) => {
+                print_error(error);
+                input.clear();
+                continue;
+            }
+        };
+        input.clear();
+
+        let result = {
+            let _guard = lock.lock(&telemetry)?;
+            compiler.compile_repl_module(pending.name.clone(), pending.code.clone())
+        };
+        let module = match result {
+            Ok(module) => module,
+            Err(error) => {
+                print_error(error);
+                continue;
+            }
+        };
+
+        let path = match target {
+            Target::Erlang => out
+                .join(paths::ARTEFACT_DIRECTORY_NAME)
+                .join(module.compiled_erlang_path()),
+            Target::JavaScript => {
+                out.join(format!("{}.{}", module.name, module_format.extension()))
+            }
+        };
+        match runtime.run(&pending, &path)? {
+            Outcome::Loaded => {
+                let _ = repl.accept(pending, &module);
+            }
+            Outcome::Value(value) => match repl.accept(pending, &module) {
+                Some(type_) => println!("{value} : {type_}"),
+                None => println!("{value}"),
+            },
+            Outcome::Failed => (),
+        }
+    }
+}
+
+fn print_error(error: Error) {
+    let stderr = cli::stderr_buffer_writer();
+    let mut buffer = stderr.buffer();
+    error.pretty(&mut buffer);
+    stderr.print(&buffer).expect("Error writing");
+}
+
+enum Outcome {
+    Loaded,
+    Value(String),
+    Failed,
+}
+
+/// A runtime process that evaluates the modules compiled for each input,
+/// keeping the values of variables between them.
+struct Evaluator {
+    program: String,
+    process: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Evaluator {
+    fn erlang(paths: &ProjectPaths) -> Result<Self> {
+        // The runtime is compiled to the REPL's directory in the build
+        // directory, so it is added to the code path along with all the
+        // packages.
+        let packages = paths.build_directory_for_target(Mode::Dev, Target::Erlang);
+        let ebin = packages.join(repl::PACKAGE).join("ebin");
+        fs::mkdir(&ebin)?;
+        let source = ebin.join("gleam@@repl.erl");
+        fs::write(&source, repl::ERLANG_RUNTIME)?;
+        let args = ["-o".into(), ebin.to_string(), source.to_string()];
+        let status = ProjectIO::new().exec("erlc", &args, &[], None, ExecStdio::Inherit)?;
+        if status != 0 {
+            return Err(Error::ShellCommand {
+                program: "erlc".into(),
+                err: None,
+            });
+        }
+
+        let mut command = Command::new("erl");
+        for entry in fs::read_dir(packages)?.filter_map(Result::ok) {
+            let _ = command.arg("-pa").arg(entry.path().join("ebin"));
+        }
+        let _ = command
+            .arg("-noshell")
+            .arg("-eval")
+            .arg("'gleam@@repl':main().");
+        Self::spawn("erl", command)
+    }
+
+    fn javascript(out: &Utf8PathBuf, runtime: Runtime) -> Result<Self> {
+        // The runtime is written next to the modules compiled for each input,
+        // as they import it from there.
+        let path = out.join("gleam@@repl.mjs");
+        fs::write(&path, repl::JAVASCRIPT_RUNTIME)?;
+        let (program, mut command) = match runtime {
+            Runtime::NodeJs => ("node", Command::new("node")),
+            Runtime::Bun => ("bun", Command::new("bun")),
+            Runtime::Deno => {
+                let mut command = Command::new("deno");
+                let _ = command.arg("run").arg("--allow-all");
+                ("deno", command)
+            }
+            Runtime::Browser => {
+                return Err(Error::BrowserRuntime {
+                    reason: "The REPL can not be run in the browser.".into(),
+                })
+            }
+        };
+        let _ = command.arg(path.as_str());
+        Self::spawn(program, command)
+    }
+
+    fn spawn(program: &str, mut command: Command) -> Result<Self> {
+        tracing::info!("Running OS process {:?}", command);
+        let mut process = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|error| match error.kind() {
+                io::ErrorKind::NotFound => Error::ShellProgramNotFound {
+                    program: program.into(),
+                },
+                other => Error::ShellCommand {
+                    program: program.into(),
+                    err: Some(other),
+                },
+            })?;
+        let stdin = process.stdin.take().expect("Runtime stdin");
+        let stdout = BufReader::new(process.stdout.take().expect("Runtime stdout"));
+        Ok(Self {
+            program: program.into(),
+            process,
+            stdin,
+            stdout,
+        })
+    }
+
+    /// Load the module compiled for some input, evaluating it if it holds
+    /// statements. Anything the input prints is passed through until the
+    /// runtime replies with the outcome.
+    fn run(&mut self, pending: &PendingModule, path: &Utf8PathBuf) -> Result<Outcome> {
+        let command = if pending.is_statements() {
+            "eval"
+        } else {
+            "load"
+        };
+        writeln!(self.stdin, "{command} {path}").map_err(|error| self.error(error))?;
+
+        loop {
+            let mut line = vec![];
+            let read = self
+                .stdout
+                .read_until(b'\n', &mut line)
+                .map_err(|error| self.error(error))?;
+            if read == 0 {
+                let _ = self.process.wait();
+                return Err(Error::ShellCommand {
+                    program: self.program.clone(),
+                    err: None,
+                });
+            }
+            let line = String::from_utf8_lossy(&line);
+            let Some((output, reply)) = line.split_once('\0') else {
+                print!("{line}");
+                continue;
+            };
+            if !output.is_empty() {
+                println!("{output}");
+            }
+            let reply = reply.trim_end_matches('\n');
+            return Ok(match reply.split_once(' ') {
+                Some(("value", value)) => Outcome::Value(value.into()),
+                _ if reply == "ok" => Outcome::Loaded,
+                _ => Outcome::Failed,
+            });
+        }
+    }
+
+    fn error(&self, error: io::Error) -> Error {
+        Error::ShellCommand {
+            program: self.program.clone(),
+            err: Some(error.kind()),
+        }
+    }
+}
+
+impl Drop for Evaluator {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
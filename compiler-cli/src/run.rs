@@ -1,16 +1,17 @@
-use std::sync::OnceLock;
+use std::{collections::HashMap, sync::OnceLock};
 
 use camino::Utf8PathBuf;
 use ecow::EcoString;
 use gleam_core::{
     analyse::TargetSupport,
     build::{Built, Codegen, Mode, Options, Runtime, Target},
-    config::{DenoFlag, PackageConfig},
+    config::{JavaScriptModuleFormat, NodeConfig, PackageConfig, PermissionFlag},
     error::Error,
     io::{CommandExecutor, Stdio},
     paths::ProjectPaths,
     type_::ModuleFunction,
 };
+use itertools::Itertools;
 
 use crate::{config::PackageKind, fs::ProjectIO};
 
@@ -105,10 +106,27 @@ pub fn command(
                 &module,
                 arguments,
             ),
-            Runtime::NodeJs => {
-                run_javascript_node(&paths, &main_function.package, &module, arguments)
-            }
-            Runtime::Bun => run_javascript_bun(&paths, &main_function.package, &module, arguments),
+            Runtime::NodeJs => run_javascript_node(
+                &paths,
+                &root_config,
+                &main_function.package,
+                &module,
+                arguments,
+            ),
+            Runtime::Bun => run_javascript_bun(
+                &paths,
+                &root_config,
+                &main_function.package,
+                &module,
+                arguments,
+            ),
+            Runtime::Browser => crate::browser::run(
+                &paths,
+                &root_config,
+                &main_function.package,
+                &module,
+                arguments,
+            ),
         },
     }?;
 
@@ -151,12 +169,15 @@ fn run_erlang(
 
 fn run_javascript_bun(
     paths: &ProjectPaths,
+    config: &PackageConfig,
     package: &str,
     module: &str,
     arguments: Vec<String>,
 ) -> Result<i32, Error> {
+    let bun = &config.javascript.bun;
     let mut args = vec!["run".to_string()];
-    let entry = write_javascript_entrypoint(paths, package, module)?;
+    args.extend(bun.arguments.iter().cloned());
+    let entry = write_javascript_entrypoint(paths, config, package, module)?;
 
     args.push(entry.to_string());
 
@@ -164,17 +185,19 @@ fn run_javascript_bun(
         args.push(arg);
     }
 
-    ProjectIO::new().exec("bun", &args, &[], None, Stdio::Inherit)
+    let environment = environment_variables(&bun.environment);
+    ProjectIO::new().exec("bun", &args, &environment, None, Stdio::Inherit)
 }
 
 fn run_javascript_node(
     paths: &ProjectPaths,
+    config: &PackageConfig,
     package: &str,
     module: &str,
     arguments: Vec<String>,
 ) -> Result<i32, Error> {
-    let mut args = vec![];
-    let entry = write_javascript_entrypoint(paths, package, module)?;
+    let mut args = node_arguments(paths, &config.javascript.node)?;
+    let entry = write_javascript_entrypoint(paths, config, package, module)?;
 
     args.push(entry.to_string());
 
@@ -182,23 +205,106 @@ fn run_javascript_node(
         args.push(argument);
     }
 
-    ProjectIO::new().exec("node", &args, &[], None, Stdio::Inherit)
+    let environment = environment_variables(&config.javascript.node.environment);
+    ProjectIO::new().exec("node", &args, &environment, None, Stdio::Inherit)
+}
+
+/// The arguments given to Node before the module being run.
+fn node_arguments(paths: &ProjectPaths, node: &NodeConfig) -> Result<Vec<String>, Error> {
+    let mut args = node.arguments.clone();
+
+    // Enable experimental features
+    for feature in &node.experimental {
+        args.push(format!("--experimental-{feature}"));
+    }
+
+    // Permissions are only granted when the permission model is enabled, so
+    // they are an error without it rather than being silently ignored.
+    if !node.permission {
+        let settings = [
+            ("allow_fs_read", node.allow_fs_read.is_granted()),
+            ("allow_fs_write", node.allow_fs_write.is_granted()),
+            ("allow_child_process", node.allow_child_process),
+            ("allow_worker", node.allow_worker),
+        ];
+        let settings: Vec<EcoString> = settings
+            .into_iter()
+            .filter(|(_, set)| *set)
+            .map(|(setting, _)| setting.into())
+            .collect();
+        if !settings.is_empty() {
+            return Err(Error::NodePermissionsWithoutPermissionModel { settings });
+        }
+    }
+
+    // Set node permissions
+    if node.permission {
+        args.push("--permission".into());
+
+        // The generated modules must be readable for the program to be run at
+        // all.
+        let build = paths.build_directory_for_target(Mode::Dev, Target::JavaScript);
+        args.push(format!("--allow-fs-read={build}"));
+
+        add_node_flag(&mut args, "--allow-fs-read", &node.allow_fs_read);
+        add_node_flag(&mut args, "--allow-fs-write", &node.allow_fs_write);
+
+        if node.allow_child_process {
+            args.push("--allow-child-process".into())
+        }
+
+        if node.allow_worker {
+            args.push("--allow-worker".into())
+        }
+    }
+
+    Ok(args)
+}
+
+fn add_node_flag(args: &mut Vec<String>, flag: &str, flags: &PermissionFlag) {
+    match flags {
+        PermissionFlag::AllowAll => args.push(format!("{flag}=*")),
+        PermissionFlag::Allow(allow) => {
+            for path in allow {
+                args.push(format!("{flag}={path}"));
+            }
+        }
+    }
+}
+
+/// The environment variables set for a runtime, sorted so they are set in
+/// the same order each time.
+fn environment_variables(environment: &HashMap<String, String>) -> Vec<(&str, String)> {
+    environment
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.clone()))
+        .sorted()
+        .collect()
 }
 
 fn write_javascript_entrypoint(
     paths: &ProjectPaths,
+    config: &PackageConfig,
     package: &str,
     module: &str,
 ) -> Result<Utf8PathBuf, Error> {
+    let module_format = config.javascript.module_format;
+    let extension = module_format.extension();
     let path = paths
         .build_directory_for_package(Mode::Dev, Target::JavaScript, package)
         .to_path_buf()
-        .join("gleam.main.mjs");
-    let module = format!(
-        r#"import {{ main }} from "./{module}.mjs";
+        .join(format!("gleam.main.{extension}"));
+    let module = match module_format {
+        JavaScriptModuleFormat::Esm => format!(
+            r#"import {{ main }} from "./{module}.{extension}";
 main();
 "#,
-    );
+        ),
+        JavaScriptModuleFormat::CommonJs => format!(
+            r#"require("./{module}.{extension}").main();
+"#,
+        ),
+    };
     crate::fs::write(&path, &module)?;
     Ok(path)
 }
@@ -269,7 +375,7 @@ fn run_javascript_deno(
         );
     }
 
-    let entrypoint = write_javascript_entrypoint(paths, package, module)?;
+    let entrypoint = write_javascript_entrypoint(paths, config, package, module)?;
     args.push(entrypoint.to_string());
 
     for argument in arguments.into_iter() {
@@ -279,10 +385,10 @@ fn run_javascript_deno(
     ProjectIO::new().exec("deno", &args, &[], None, Stdio::Inherit)
 }
 
-fn add_deno_flag(args: &mut Vec<String>, flag: &str, flags: &DenoFlag) {
+fn add_deno_flag(args: &mut Vec<String>, flag: &str, flags: &PermissionFlag) {
     match flags {
-        DenoFlag::AllowAll => args.push(flag.to_owned()),
-        DenoFlag::Allow(allow) => {
+        PermissionFlag::AllowAll => args.push(flag.to_owned()),
+        PermissionFlag::Allow(allow) => {
             if !allow.is_empty() {
                 args.push(format!("{}={}", flag.to_owned(), allow.join(",")));
             }
@@ -356,3 +462,59 @@ fn valid_module_names() {
         assert!(is_gleam_module(mod_name));
     }
 }
+
+#[test]
+fn node_arguments_with_permissions() {
+    let paths = ProjectPaths::new(Utf8PathBuf::from("/app"));
+    let node = NodeConfig {
+        arguments: vec!["--enable-source-maps".into()],
+        experimental: vec!["vm-modules".into()],
+        permission: true,
+        allow_fs_read: PermissionFlag::Allow(vec!["/data".into(), "/config".into()]),
+        allow_fs_write: PermissionFlag::AllowAll,
+        allow_worker: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        node_arguments(&paths, &node).unwrap(),
+        vec![
+            "--enable-source-maps",
+            "--experimental-vm-modules",
+            "--permission",
+            "--allow-fs-read=/app/build/dev/javascript",
+            "--allow-fs-read=/data",
+            "--allow-fs-read=/config",
+            "--allow-fs-write=*",
+            "--allow-worker",
+        ]
+    );
+}
+
+#[test]
+fn node_arguments_without_permissions() {
+    let paths = ProjectPaths::new(Utf8PathBuf::from("/app"));
+    let node = NodeConfig {
+        arguments: vec!["--enable-source-maps".into()],
+        ..Default::default()
+    };
+    assert_eq!(
+        node_arguments(&paths, &node).unwrap(),
+        vec!["--enable-source-maps"]
+    );
+}
+
+#[test]
+fn node_permissions_without_permission_model() {
+    let paths = ProjectPaths::new(Utf8PathBuf::from("/app"));
+    let node = NodeConfig {
+        allow_fs_read: PermissionFlag::AllowAll,
+        allow_child_process: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        node_arguments(&paths, &node),
+        Err(Error::NodePermissionsWithoutPermissionModel {
+            settings: vec!["allow_fs_read".into(), "allow_child_process".into()],
+        })
+    );
+}
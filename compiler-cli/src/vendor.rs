@@ -0,0 +1,360 @@
+//! Vendoring the Hex dependency packages of a project into its `vendor`
+//! directory, so that they can be built without downloading them.
+
+use std::collections::BTreeMap;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use ecow::EcoString;
+use gleam_core::{
+    error::{FileIoAction, FileKind, VendoredPackageProblem},
+    hex,
+    manifest::{Base16Checksum, Manifest, ManifestPackage, ManifestPackageSource},
+    paths::{PackageCache, ProjectPaths},
+    Error, Result,
+};
+use hexpm::version::Version;
+use sha2::Digest;
+
+use crate::{
+    build_lock::BuildLock,
+    cli,
+    dependencies::{self, Untar},
+    fs::{self, ProjectIO},
+    http::HttpClient,
+    package_cache,
+};
+
+/// The packages in the `vendor` directory, as recorded in
+/// `vendor/packages.toml`.
+#[derive(Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct VendoredPackages {
+    packages: BTreeMap<EcoString, VendoredPackage>,
+}
+
+#[derive(Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+struct VendoredPackage {
+    version: Version,
+    /// The checksum of the Hex tarball the package was taken from.
+    outer_checksum: Base16Checksum,
+}
+
+impl VendoredPackages {
+    /// Read the vendored packages, if the project has any.
+    pub fn read_from_disc(paths: &ProjectPaths) -> Result<Option<Self>> {
+        let path = paths.vendor_packages_toml();
+        if !path.exists() {
+            return Ok(None);
+        }
+        let toml = fs::read(&path)?;
+        toml::from_str(&toml).map(Some).map_err(|e| Error::FileIo {
+            action: FileIoAction::Parse,
+            kind: FileKind::File,
+            path: path.clone(),
+            err: Some(e.to_string()),
+        })
+    }
+
+    fn write_to_disc(&self, paths: &ProjectPaths) -> Result<()> {
+        let path = paths.vendor_packages_toml();
+        let toml = toml::to_string(&self).expect("vendor packages.toml serialization");
+        fs::write(&path, &toml)
+    }
+
+    /// Check that every Hex package in the manifest has been vendored from
+    /// the locked release, and that its sources are those of the vendored
+    /// tarball with the checksum recorded in the manifest.
+    pub fn verify(&self, paths: &ProjectPaths, manifest: &Manifest) -> Result<()> {
+        for package in manifest.packages.iter() {
+            let ManifestPackageSource::Hex { outer_checksum, .. } = &package.source else {
+                continue;
+            };
+            let mismatch = |problem| Error::VendoredPackageMismatch {
+                package: package.name.clone(),
+                problem,
+            };
+            let vendored = self
+                .packages
+                .get(&package.name)
+                .ok_or_else(|| mismatch(VendoredPackageProblem::NotVendored))?;
+            if vendored.version != package.version {
+                return Err(mismatch(VendoredPackageProblem::WrongVersion {
+                    vendored: vendored.version.to_string().into(),
+                    locked: package.version.to_string().into(),
+                }));
+            }
+            let tarball_path =
+                paths.vendor_package_tarball(&package.name, &package.version.to_string());
+            if &vendored.outer_checksum != outer_checksum {
+                return Err(mismatch(VendoredPackageProblem::WrongChecksum));
+            }
+            if !tarball_path.is_file() {
+                return Err(mismatch(VendoredPackageProblem::NotVendored));
+            }
+            let tarball = fs::read_bytes(&tarball_path)?;
+            if sha2::Sha256::digest(&tarball).as_slice() != outer_checksum.0.as_slice() {
+                return Err(mismatch(VendoredPackageProblem::WrongChecksum));
+            }
+            let directory = paths.vendor_package(&package.name);
+            if !directory.is_dir() || sources_changed(&directory, &tarball_path, &tarball)? {
+                return Err(mismatch(VendoredPackageProblem::SourcesChanged));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Copy the vendored sources of the given Hex packages into the build
+/// packages directory.
+pub fn copy_to_build_directory<'a>(
+    paths: &ProjectPaths,
+    packages: impl Iterator<Item = &'a ManifestPackage>,
+) -> Result<()> {
+    for package in packages.filter(|package| package.is_hex()) {
+        tracing::debug!(package=%package.name, "copying_vendored_package");
+        let destination = paths.build_packages_package(&package.name);
+        if destination.exists() {
+            fs::delete_directory(&destination)?;
+        }
+        fs::mkdir(&destination)?;
+        fs::copy_dir(paths.vendor_package(&package.name), &destination)?;
+    }
+    Ok(())
+}
+
+/// Copy the sources of every Hex dependency package into the `vendor`
+/// directory, along with the tarballs they were taken from, and record them
+/// in `vendor/packages.toml`.
+pub fn command() -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let config = crate::config::root_config()?;
+
+    // The packages are downloaded rather than taken from the vendor directory,
+    // which may not match the manifest any more.
+    let manifest = dependencies::download_ignoring_vendored(&paths, cli::Reporter::new())?;
+
+    let telemetry = cli::Reporter::new();
+    let lock = BuildLock::new_packages(&paths)?;
+    let _guard = lock.lock(&telemetry)?;
+
+    let fs = ProjectIO::boxed();
+    let downloader = hex::Downloader::new(
+        fs.clone(),
+        fs,
+        HttpClient::boxed(),
+        Untar::boxed(),
+        dependencies::hex_repositories(&paths, &config)?,
+        PackageCache::global(),
+        paths.clone(),
+    );
+    let runtime = tokio::runtime::Runtime::new().expect("Unable to start Tokio async runtime");
+
+    // The packages are vendored in the build directory first, and only
+    // replace the vendor directory once they have all been vendored, so that
+    // the packages already vendored are kept if vendoring fails.
+    let staging = ProjectPaths::new(paths.build_directory().join("vendoring"));
+    fs::delete_directory(staging.root())?;
+
+    let mut vendored = VendoredPackages::default();
+    for package in manifest.packages.iter() {
+        let ManifestPackageSource::Hex {
+            outer_checksum,
+            repository,
+        } = &package.source
+        else {
+            continue;
+        };
+
+        // The package is extracted from its Hex tarball again, as the copy in
+        // the build directory could have been edited.
+        let build_directory = paths.build_packages_package(&package.name);
+        fs::delete_directory(&build_directory)?;
+        let _ = runtime.block_on(downloader.ensure_package_in_build_directory(package))?;
+
+        let version = package.version.to_string();
+        let tarball_path = downloader.package_tarball_path(
+            repository.as_deref(),
+            &package.name,
+            &package.version,
+        )?;
+        let tarball = fs::read_bytes(&tarball_path)?;
+        if sha2::Sha256::digest(&tarball).as_slice() != outer_checksum.0.as_slice() {
+            return Err(Error::CachedPackageChecksumMismatch {
+                package: package.name.clone(),
+                version: version.into(),
+                path: tarball_path,
+            });
+        }
+        fs::write_bytes(
+            &staging.vendor_package_tarball(&package.name, &version),
+            &tarball,
+        )?;
+
+        let directory = staging.vendor_package(&package.name);
+        fs::mkdir(&directory)?;
+        fs::copy_dir(&build_directory, &directory)?;
+        let _ = vendored.packages.insert(
+            package.name.clone(),
+            VendoredPackage {
+                version: package.version.clone(),
+                outer_checksum: outer_checksum.clone(),
+            },
+        );
+    }
+    fs::mkdir(staging.vendor_directory())?;
+    vendored.write_to_disc(&staging)?;
+
+    fs::delete_directory(&paths.vendor_directory())?;
+    fs::rename(staging.vendor_directory(), paths.vendor_directory())?;
+    fs::delete_directory(staging.root())?;
+
+    cli::print_vendored(vendored.packages.len());
+    Ok(())
+}
+
+/// Whether the files in a vendored package's directory differ from the
+/// sources in its tarball.
+fn sources_changed(directory: &Utf8Path, tarball_path: &Utf8Path, tarball: &[u8]) -> Result<bool> {
+    let contents = package_cache::read_contents(tarball).map_err(|error| Error::FileIo {
+        action: FileIoAction::Parse,
+        kind: FileKind::File,
+        path: tarball_path.to_path_buf(),
+        err: Some(error),
+    })?;
+    let expected: BTreeMap<Utf8PathBuf, Vec<u8>> = contents
+        .into_iter()
+        .map(|(path, bytes)| (directory.join(path), bytes))
+        .collect();
+
+    let mut found = 0;
+    for entry in walkdir::WalkDir::new(directory).follow_links(true) {
+        let entry = entry.map_err(|e| Error::FileIo {
+            action: FileIoAction::Read,
+            kind: FileKind::Directory,
+            path: directory.to_path_buf(),
+            err: Some(e.to_string()),
+        })?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let path = Utf8Path::from_path(entry.path()).expect("Non Utf-8 Path");
+        match expected.get(path) {
+            Some(bytes) if &fs::read_bytes(path)? == bytes => found += 1,
+            _ => {
+                tracing::debug!(path=%path, "vendored_source_changed");
+                return Ok(true);
+            }
+        }
+    }
+    Ok(found != expected.len())
+}
+
+#[cfg(test)]
+fn vendored_project(
+    files: &[(&str, &str)],
+) -> (tempfile::TempDir, ProjectPaths, VendoredPackages, Manifest) {
+    let tarball = package_cache::tarball_with_files(files);
+    let outer_checksum = Base16Checksum(sha2::Sha256::digest(&tarball).to_vec());
+    let tmp = tempfile::tempdir().unwrap();
+    let paths = ProjectPaths::new(Utf8Path::from_path(tmp.path()).unwrap().to_path_buf());
+    fs::write_bytes(&paths.vendor_package_tarball("wibble", "1.0.0"), &tarball).unwrap();
+    for (path, contents) in files {
+        fs::write(&paths.vendor_package("wibble").join(path), contents).unwrap();
+    }
+    let version = Version::new(1, 0, 0);
+    let vendored = VendoredPackages {
+        packages: [(
+            "wibble".into(),
+            VendoredPackage {
+                version: version.clone(),
+                outer_checksum: outer_checksum.clone(),
+            },
+        )]
+        .into(),
+    };
+    let manifest = Manifest {
+        requirements: Default::default(),
+        packages: vec![ManifestPackage {
+            name: "wibble".into(),
+            version,
+            build_tools: vec!["gleam".into()],
+            otp_app: None,
+            requirements: vec![],
+            source: ManifestPackageSource::Hex {
+                outer_checksum,
+                repository: None,
+            },
+        }],
+    };
+    (tmp, paths, vendored, manifest)
+}
+
+#[cfg(test)]
+const VENDORED_FILES: [(&str, &str); 2] = [
+    ("gleam.toml", "name = \"wibble\""),
+    ("src/wibble.gleam", "pub fn wibble() { 1 }"),
+];
+
+#[cfg(test)]
+fn vendored_mismatch(problem: VendoredPackageProblem) -> Result<()> {
+    Err(Error::VendoredPackageMismatch {
+        package: "wibble".into(),
+        problem,
+    })
+}
+
+#[test]
+fn verify_vendored_sources() {
+    let (_tmp, paths, vendored, manifest) = vendored_project(&VENDORED_FILES);
+    assert_eq!(vendored.verify(&paths, &manifest), Ok(()));
+}
+
+#[test]
+fn verify_vendored_sources_with_edited_file() {
+    let (_tmp, paths, vendored, manifest) = vendored_project(&VENDORED_FILES);
+    let path = paths.vendor_package("wibble").join("src/wibble.gleam");
+    fs::write(&path, "pub fn wibble() { 2 }").unwrap();
+    assert_eq!(
+        vendored.verify(&paths, &manifest),
+        vendored_mismatch(VendoredPackageProblem::SourcesChanged)
+    );
+}
+
+#[test]
+fn verify_vendored_sources_with_added_file() {
+    let (_tmp, paths, vendored, manifest) = vendored_project(&VENDORED_FILES);
+    let path = paths.vendor_package("wibble").join("src/wobble.gleam");
+    fs::write(&path, "").unwrap();
+    assert_eq!(
+        vendored.verify(&paths, &manifest),
+        vendored_mismatch(VendoredPackageProblem::SourcesChanged)
+    );
+}
+
+#[test]
+fn verify_vendored_sources_with_missing_file() {
+    let (_tmp, paths, vendored, manifest) = vendored_project(&VENDORED_FILES);
+    let path = paths.vendor_package("wibble").join("src/wibble.gleam");
+    fs::delete_file(&path).unwrap();
+    assert_eq!(
+        vendored.verify(&paths, &manifest),
+        vendored_mismatch(VendoredPackageProblem::SourcesChanged)
+    );
+}
+
+#[test]
+fn verify_vendored_sources_with_replaced_tarball() {
+    // Editing both the sources and the tarball does not go unnoticed, as the
+    // tarball is checked against the manifest.
+    let (_tmp, paths, vendored, manifest) = vendored_project(&VENDORED_FILES);
+    let files = [
+        ("gleam.toml", "name = \"wibble\""),
+        ("src/wibble.gleam", ""),
+    ];
+    let tarball = package_cache::tarball_with_files(&files);
+    fs::write_bytes(&paths.vendor_package_tarball("wibble", "1.0.0"), &tarball).unwrap();
+    fs::write(&paths.vendor_package("wibble").join("src/wibble.gleam"), "").unwrap();
+    assert_eq!(
+        vendored.verify(&paths, &manifest),
+        vendored_mismatch(VendoredPackageProblem::WrongChecksum)
+    );
+}
@@ -0,0 +1,315 @@
+//! Checking the Hex dependency packages in the global package cache and the
+//! build directory against the checksums recorded in the manifest, so that
+//! corrupted or locally edited copies of packages are not built.
+
+use std::collections::HashSet;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use gleam_core::{
+    config::PackageConfig,
+    error::{FileIoAction, FileKind},
+    hex,
+    io::FileSystemReader,
+    manifest::{Manifest, ManifestPackage, ManifestPackageSource},
+    paths::{PackageCache, ProjectPaths},
+    Error, Result,
+};
+use sha2::Digest;
+
+use crate::{
+    build_lock::BuildLock,
+    cli,
+    dependencies::{self, Untar},
+    fs::{self, ProjectIO},
+    http::HttpClient,
+    package_cache,
+    vendor::VendoredPackages,
+};
+
+/// Verify the dependency packages of the project, restoring the sources of
+/// any that have been changed in the build directory.
+pub fn command() -> Result<()> {
+    let paths = crate::find_project_paths()?;
+    let config = crate::config::root_config()?;
+    let manifest = crate::build::download_dependencies()?;
+
+    // Vendored packages have already been checked against the vendor
+    // directory while the dependencies were downloaded.
+    if VendoredPackages::read_from_disc(&paths)?.is_some() {
+        cli::print_verified(manifest.packages.iter().filter(|p| p.is_hex()).count());
+        return Ok(());
+    }
+
+    let telemetry = cli::Reporter::new();
+    let lock = BuildLock::new_packages(&paths)?;
+    let _guard = lock.lock(&telemetry)?;
+
+    let runtime = tokio::runtime::Runtime::new().expect("Unable to start Tokio async runtime");
+    let count = verify_packages(
+        &paths,
+        &config,
+        &manifest,
+        &PackageCache::global(),
+        &runtime,
+    )?;
+    cli::print_verified(count);
+    Ok(())
+}
+
+/// Whether the manifest has changed since the packages in the build
+/// directory were last recorded.
+pub fn manifest_changed(paths: &ProjectPaths) -> bool {
+    let io = ProjectIO::new();
+    match (
+        io.modification_time(&paths.manifest()),
+        io.modification_time(&paths.build_packages_toml()),
+    ) {
+        (Ok(manifest), Ok(packages)) => manifest > packages,
+        _ => true,
+    }
+}
+
+/// Check the tarball of each Hex package in the package cache against the
+/// outer checksum in the manifest, and its sources in the build directory against those in the
+/// tarball. Packages whose sources have changed are extracted again, while a
+/// tarball that does not match its checksum is an error.
+///
+/// Returns the number of packages checked.
+pub fn verify_packages(
+    paths: &ProjectPaths,
+    config: &PackageConfig,
+    manifest: &Manifest,
+    package_cache: &PackageCache,
+    runtime: &tokio::runtime::Runtime,
+) -> Result<usize> {
+    let fs = ProjectIO::boxed();
+    let downloader = hex::Downloader::new(
+        fs.clone(),
+        fs,
+        HttpClient::boxed(),
+        Untar::boxed(),
+        dependencies::hex_repositories(paths, config)?,
+        package_cache.clone(),
+        paths.clone(),
+    );
+
+    let mut count = 0;
+    for package in manifest.packages.iter() {
+        let ManifestPackageSource::Hex {
+            outer_checksum,
+            repository,
+        } = &package.source
+        else {
+            continue;
+        };
+        tracing::debug!(package=%package.name, "verifying_package");
+
+        // A package missing from the cache is downloaded again, which checks
+        // it against the checksum as it is downloaded.
+        let _ = runtime.block_on(downloader.ensure_package_downloaded(package))?;
+        let path = downloader.package_tarball_path(
+            repository.as_deref(),
+            &package.name,
+            &package.version,
+        )?;
+        let tarball = fs::read_bytes(&path)?;
+        if sha2::Sha256::digest(&tarball).as_slice() != outer_checksum.0.as_slice() {
+            return Err(Error::CachedPackageChecksumMismatch {
+                package: package.name.clone(),
+                version: package.version.to_string().into(),
+                path,
+            });
+        }
+
+        let directory = paths.build_packages_package(&package.name);
+        if directory.is_dir() && sources_changed(package, &path, &tarball, &directory)? {
+            cli::print_restored(&package.name);
+            fs::delete_directory(&directory)?;
+        }
+        let _ = downloader.extract_package_from_cache(
+            repository.as_deref(),
+            &package.name,
+            &package.version,
+        )?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+/// Whether the sources of a package in the build directory differ from those
+/// in its tarball. Files added to packages built by Gleam count as changes,
+/// while other build tools may write their own files there.
+fn sources_changed(
+    package: &ManifestPackage,
+    tarball_path: &Utf8Path,
+    tarball: &[u8],
+    directory: &Utf8Path,
+) -> Result<bool> {
+    let contents = package_cache::read_contents(tarball).map_err(|error| Error::FileIo {
+        action: FileIoAction::Parse,
+        kind: FileKind::File,
+        path: tarball_path.to_path_buf(),
+        err: Some(error),
+    })?;
+    for (path, bytes) in contents.iter() {
+        let path = directory.join(path);
+        if !path.is_file() || &fs::read_bytes(&path)? != bytes {
+            tracing::debug!(path=%path, "package_source_changed");
+            return Ok(true);
+        }
+    }
+
+    if !package.build_tools.iter().any(|tool| tool == "gleam") {
+        return Ok(false);
+    }
+    let expected: HashSet<Utf8PathBuf> = contents
+        .into_iter()
+        .map(|(path, _)| directory.join(path))
+        .collect();
+    for entry in walkdir::WalkDir::new(directory).follow_links(true) {
+        let entry = entry.map_err(|e| Error::FileIo {
+            action: FileIoAction::Read,
+            kind: FileKind::Directory,
+            path: directory.to_path_buf(),
+            err: Some(e.to_string()),
+        })?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let path = Utf8Path::from_path(entry.path()).expect("Non Utf-8 Path");
+        if !expected.contains(path) {
+            tracing::debug!(path=%path, "package_source_added");
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+fn hex_package(name: &str, build_tool: &str, tarball: &[u8]) -> ManifestPackage {
+    ManifestPackage {
+        name: name.into(),
+        version: hexpm::version::Version::new(1, 0, 0),
+        build_tools: vec![build_tool.into()],
+        otp_app: None,
+        requirements: vec![],
+        source: ManifestPackageSource::Hex {
+            outer_checksum: gleam_core::manifest::Base16Checksum(
+                sha2::Sha256::digest(tarball).to_vec(),
+            ),
+            repository: None,
+        },
+    }
+}
+
+/// Extract the sources of a package tarball to a temporary directory, so
+/// they can be checked against the tarball after being changed.
+#[cfg(test)]
+fn extracted_sources(build_tool: &str) -> (tempfile::TempDir, ManifestPackage, Vec<u8>) {
+    let files = [
+        ("gleam.toml", "name = \"wibble\""),
+        ("src/wibble.gleam", "pub fn main() { 1 }"),
+    ];
+    let tarball = package_cache::tarball_with_files(&files);
+    let tmp = tempfile::tempdir().unwrap();
+    let directory = Utf8Path::from_path(tmp.path()).expect("Non Utf-8 Path");
+    for (path, contents) in files {
+        fs::write(&directory.join(path), contents).unwrap();
+    }
+    let package = hex_package("wibble", build_tool, &tarball);
+    (tmp, package, tarball)
+}
+
+#[cfg(test)]
+fn sources_changed_in(tmp: &tempfile::TempDir, package: &ManifestPackage, tarball: &[u8]) -> bool {
+    let directory = Utf8Path::from_path(tmp.path()).expect("Non Utf-8 Path");
+    sources_changed(
+        package,
+        Utf8Path::new("wibble-1.0.0.tar"),
+        tarball,
+        directory,
+    )
+    .unwrap()
+}
+
+#[test]
+fn sources_unchanged() {
+    let (tmp, package, tarball) = extracted_sources("gleam");
+    assert!(!sources_changed_in(&tmp, &package, &tarball));
+}
+
+#[test]
+fn sources_changed_with_edited_file() {
+    let (tmp, package, tarball) = extracted_sources("gleam");
+    let path = Utf8Path::from_path(tmp.path())
+        .unwrap()
+        .join("src/wibble.gleam");
+    fs::write(&path, "pub fn main() { 2 }").unwrap();
+    assert!(sources_changed_in(&tmp, &package, &tarball));
+}
+
+#[test]
+fn sources_changed_with_missing_file() {
+    let (tmp, package, tarball) = extracted_sources("gleam");
+    let path = Utf8Path::from_path(tmp.path())
+        .unwrap()
+        .join("src/wibble.gleam");
+    fs::delete_file(&path).unwrap();
+    assert!(sources_changed_in(&tmp, &package, &tarball));
+}
+
+#[test]
+fn sources_changed_with_file_added_to_gleam_package() {
+    let (tmp, package, tarball) = extracted_sources("gleam");
+    let path = Utf8Path::from_path(tmp.path())
+        .unwrap()
+        .join("src/wobble.gleam");
+    fs::write(&path, "pub fn main() { 3 }").unwrap();
+    assert!(sources_changed_in(&tmp, &package, &tarball));
+}
+
+#[test]
+fn sources_unchanged_with_file_added_to_other_package() {
+    let (tmp, package, tarball) = extracted_sources("rebar3");
+    let path = Utf8Path::from_path(tmp.path())
+        .unwrap()
+        .join("ebin/wibble.beam");
+    fs::write(&path, "").unwrap();
+    assert!(!sources_changed_in(&tmp, &package, &tarball));
+}
+
+#[test]
+fn verify_packages_with_cached_package_checksum_mismatch() {
+    let tarball = package_cache::tarball_with_files(&[("gleam.toml", "name = \"wibble\"")]);
+    let mut package = hex_package("wibble", "gleam", &tarball);
+    package.source = ManifestPackageSource::Hex {
+        outer_checksum: gleam_core::manifest::Base16Checksum(vec![1, 2, 3, 4]),
+        repository: None,
+    };
+    let cache_tmp = tempfile::tempdir().unwrap();
+    let cache = PackageCache::new(Utf8Path::from_path(cache_tmp.path()).unwrap().to_path_buf());
+    let path = cache.package_tarball(None, "wibble", "1.0.0");
+    fs::write_bytes(&path, &tarball).unwrap();
+
+    let tmp = tempfile::tempdir().unwrap();
+    let paths = ProjectPaths::new(Utf8Path::from_path(tmp.path()).unwrap().to_path_buf());
+    let manifest = Manifest {
+        requirements: Default::default(),
+        packages: vec![package],
+    };
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    assert_eq!(
+        verify_packages(
+            &paths,
+            &PackageConfig::default(),
+            &manifest,
+            &cache,
+            &runtime
+        ),
+        Err(Error::CachedPackageChecksumMismatch {
+            package: "wibble".into(),
+            version: "1.0.0".into(),
+            path,
+        })
+    );
+}
@@ -969,6 +969,18 @@ impl<A, B, C, E> Definition<A, B, C, E> {
         }
     }
 
+    /// The end of the definition, including the body of a function, the
+    /// constructors of a custom type and the value of a constant, which its
+    /// location doesn't cover.
+    pub fn end_position(&self) -> u32 {
+        match self {
+            Definition::Function(Function { end_position, .. })
+            | Definition::CustomType(CustomType { end_position, .. }) => *end_position,
+            Definition::ModuleConstant(ModuleConstant { value, .. }) => value.location().end,
+            Definition::TypeAlias(_) | Definition::Import(_) => self.location().end,
+        }
+    }
+
     /// Returns `true` if the definition is [`Import`].
     ///
     /// [`Import`]: Definition::Import
@@ -23,7 +23,7 @@ use crate::ast::{
 use crate::type_::Type;
 use crate::{
     ast::{Definition, SrcSpan, TypedModule},
-    config::{self, PackageConfig},
+    config::{self, JavaScriptIntegers, JavaScriptModuleFormat, PackageConfig},
     erlang,
     error::{Error, FileIoAction, FileKind},
     io::OutputFile,
@@ -116,6 +116,9 @@ pub enum Runtime {
     #[strum(serialize = "bun")]
     #[serde(rename = "bun")]
     Bun,
+    #[strum(serialize = "browser")]
+    #[serde(rename = "browser")]
+    Browser,
 }
 
 impl Default for Runtime {
@@ -129,6 +132,9 @@ pub enum TargetCodegenConfiguration {
     JavaScript {
         emit_typescript_definitions: bool,
         prelude_location: Utf8PathBuf,
+        integers: JavaScriptIntegers,
+        module_format: JavaScriptModuleFormat,
+        check_externals: bool,
     },
     Erlang {
         app_file: Option<ErlangAppCodegenConfiguration>,
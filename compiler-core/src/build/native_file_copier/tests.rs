@@ -78,6 +78,25 @@ fn mjavascript_files_are_copied_from_src() {
     );
 }
 
+#[test]
+fn cjavascript_files_are_copied_from_src() {
+    let fs = InMemoryFileSystem::new();
+    fs.write(&Utf8Path::new("/src/wibble.cjs"), "1").unwrap();
+
+    let copier = NativeFileCopier::new(fs.clone(), root(), root_out());
+    let copied = copier.run().unwrap();
+
+    assert!(!copied.any_elixir);
+    assert!(copied.to_compile.is_empty());
+    assert_eq!(
+        HashMap::from([
+            (Utf8PathBuf::from("/src/wibble.cjs"), "1".into()),
+            (Utf8PathBuf::from("/out/wibble.cjs"), "1".into())
+        ]),
+        fs.into_contents(),
+    );
+}
+
 #[test]
 fn mjavascript_files_are_copied_from_test() {
     let fs = InMemoryFileSystem::new();
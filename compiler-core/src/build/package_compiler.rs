@@ -13,6 +13,7 @@ use crate::{
     config::PackageConfig,
     dep_tree, error,
     io::{CommandExecutor, FileSystemReader, FileSystemWriter, Stdio},
+    javascript,
     metadata::ModuleEncoder,
     parse::extra::ModuleExtra,
     paths, type_,
@@ -322,11 +323,23 @@ where
             TargetCodegenConfiguration::JavaScript {
                 emit_typescript_definitions,
                 prelude_location,
-            } => self.perform_javascript_codegen(
-                modules,
-                *emit_typescript_definitions,
-                prelude_location,
-            ),
+                integers,
+                module_format,
+                check_externals,
+            } => {
+                let options = javascript::CodegenOptions {
+                    target_support: self.target_support,
+                    typescript: if *emit_typescript_definitions {
+                        TypeScriptDeclarations::Emit
+                    } else {
+                        TypeScriptDeclarations::None
+                    },
+                    integers: *integers,
+                    module_format: *module_format,
+                    check_externals: *check_externals,
+                };
+                self.perform_javascript_codegen(modules, prelude_location, options)
+            }
             TargetCodegenConfiguration::Erlang { app_file } => {
                 self.perform_erlang_codegen(modules, app_file.as_ref())
             }
@@ -383,18 +396,12 @@ where
     fn perform_javascript_codegen(
         &mut self,
         modules: &[Module],
-        typescript: bool,
         prelude_location: &Utf8Path,
+        options: javascript::CodegenOptions,
     ) -> Result<(), Error> {
         let mut written = HashSet::new();
-        let typescript = if typescript {
-            TypeScriptDeclarations::Emit
-        } else {
-            TypeScriptDeclarations::None
-        };
 
-        JavaScript::new(&self.out, typescript, prelude_location, self.target_support)
-            .render(&self.io, modules)?;
+        JavaScript::new(&self.out, prelude_location, options).render(&self.io, modules)?;
 
         if self.copy_native_files {
             self.copy_project_native_files(&self.out, &mut written)?;
@@ -9,13 +9,16 @@ use crate::{
     dep_tree,
     error::{FileIoAction, FileKind},
     io::{CommandExecutor, FileSystemReader, FileSystemWriter, Stdio},
+    javascript,
+    line_numbers::LineNumbers,
     manifest::{ManifestPackage, ManifestPackageSource},
     metadata,
     paths::{self, ProjectPaths},
+    repl,
     type_::{self, ModuleFunction},
     uid::UniqueIdGenerator,
     version::COMPILER_VERSION,
-    warning::{self, WarningEmitter, WarningEmitterIO},
+    warning::{self, TypeWarningEmitter, WarningEmitter, WarningEmitterIO},
     Error, Result, Warning,
 };
 use ecow::EcoString;
@@ -25,7 +28,7 @@ use std::{
     fmt::Write,
     io::BufReader,
     sync::Arc,
-    time::Instant,
+    time::{Instant, SystemTime},
 };
 
 use super::{
@@ -77,6 +80,15 @@ impl Built {
             }),
         }
     }
+
+    /// All the modules that were compiled in this build, from both the root
+    /// package and its dependencies. Modules that were loaded from the build
+    /// cache rather than compiled are not included.
+    pub fn into_compiled_modules(self) -> Vec<Module> {
+        let mut modules = self.root_package.modules;
+        modules.extend(self.compiled_dependency_modules);
+        modules
+    }
 }
 
 #[derive(Debug)]
@@ -154,6 +166,19 @@ where
     /// Compiles all packages in the project and returns the compiled
     /// information from the root package
     pub fn compile(mut self) -> Result<Built> {
+        let (root_package, compiled_dependency_modules) = self.compile_project()?;
+        Ok(Built {
+            root_package,
+            module_interfaces: self.importable_modules,
+            compiled_dependency_modules,
+        })
+    }
+
+    /// Compiles all packages in the project, returning the root package and
+    /// the dependency modules that were compiled. Unlike `compile` the
+    /// compiler is kept so more modules can be compiled against the project
+    /// afterwards.
+    pub fn compile_project(&mut self) -> Result<(Package, Vec<Module>)> {
         // We make sure the stale module tracker is empty before we start, to
         // avoid mistakenly thinking a module is stale due to outdated state
         // from a previous build. A ProjectCompiler instance is re-used by the
@@ -163,6 +188,7 @@ where
         // Each package may specify a Gleam version that it supports, so we
         // verify that this version is appropriate.
         self.check_gleam_version()?;
+        self.check_codegen_settings()?;
 
         // The JavaScript target requires a prelude module to be written.
         self.write_prelude()?;
@@ -183,11 +209,97 @@ where
             });
         }
 
-        Ok(Built {
-            root_package,
-            module_interfaces: self.importable_modules,
-            compiled_dependency_modules,
-        })
+        Ok((root_package, compiled_dependency_modules))
+    }
+
+    /// Compiles a module of the REPL against the modules of the project,
+    /// writing its code to the REPL's own directory in the build directory
+    /// rather than to that of the root package. Once compiled the module can
+    /// be imported by any module compiled after it.
+    ///
+    /// This is used by the REPL, which compiles a module for each input it is
+    /// given. Warnings are not emitted for these modules.
+    ///
+    pub fn compile_repl_module(&mut self, name: EcoString, code: EcoString) -> Result<Module> {
+        let path = Utf8PathBuf::from(format!("{name}.gleam"));
+        let warnings = WarningEmitter::null();
+        let parsed =
+            crate::parse::parse_module(path.clone(), &code, &warnings).map_err(|error| {
+                Error::Parse {
+                    path: path.clone(),
+                    src: code.clone(),
+                    error,
+                }
+            })?;
+        let mut ast = parsed.module;
+        ast.name = name.clone();
+
+        // The module belongs to a package of its own so that its code imports
+        // the modules of the root package from the root package's directory.
+        let config = PackageConfig {
+            name: repl::PACKAGE.into(),
+            ..self.config.clone()
+        };
+        let direct_dependencies = self.config.dependencies_for(self.mode())?;
+        let analysis = crate::analyse::ModuleAnalyzerConstructor {
+            target: self.target(),
+            ids: &self.ids,
+            origin: Origin::Src,
+            importable_modules: &self.importable_modules,
+            warnings: &TypeWarningEmitter::new(path.clone(), code.clone(), warnings),
+            direct_dependencies: &direct_dependencies,
+            target_support: TargetSupport::Enforced,
+            package_config: &config,
+        }
+        .infer_module(ast, LineNumbers::new(&code), path.clone());
+        let ast = match analysis {
+            Outcome::Ok(ast) => ast,
+            Outcome::PartialFailure(_, errors) | Outcome::TotalFailure(errors) => {
+                return Err(Error::Type {
+                    path,
+                    src: code,
+                    errors,
+                })
+            }
+        };
+        let _ = self
+            .importable_modules
+            .insert(name.clone(), ast.type_info.clone());
+
+        let module = Module {
+            name,
+            code,
+            mtime: SystemTime::now(),
+            input_path: path,
+            origin: Origin::Src,
+            ast,
+            extra: parsed.extra,
+            dependencies: vec![],
+        };
+
+        let out = self
+            .paths
+            .build_directory_for_package(self.mode(), self.target(), repl::PACKAGE);
+        let modules = std::slice::from_ref(&module);
+        match self.target() {
+            Target::Erlang => {
+                let build = out.join(paths::ARTEFACT_DIRECTORY_NAME);
+                codegen::Erlang::new(&build, &out.join("include"))
+                    .render(self.io.clone(), modules)?
+            }
+            Target::JavaScript => {
+                let options = javascript::CodegenOptions {
+                    target_support: TargetSupport::Enforced,
+                    typescript: codegen::TypeScriptDeclarations::None,
+                    integers: self.config.javascript.integers,
+                    module_format: self.config.javascript.module_format,
+                    check_externals: self.check_externals(),
+                };
+                codegen::JavaScript::new(&out, &self.prelude_location(), options)
+                    .render(&self.io, modules)?
+            }
+        }
+        Ok(module)
     }
 
     pub fn compile_root_package(&mut self) -> Outcome<Package, Error> {
@@ -228,6 +340,50 @@ where
             })
     }
 
+    /// Checks that the code generation settings found in the build directory
+    /// match those of the project. Code is only generated for modules as they
+    /// are compiled, not when they are loaded from the cache, so if the
+    /// settings have changed we clear the build directory rather than mix code
+    /// generated with different settings.
+    pub fn check_codegen_settings(&self) -> Result<(), Error> {
+        let settings_path = self
+            .paths
+            .build_codegen_settings(self.mode(), self.target());
+        let settings = self.codegen_settings();
+        if self.io.is_file(&settings_path) && self.io.read(&settings_path)? == settings {
+            return Ok(());
+        }
+
+        tracing::info!("removing_build_state_from_different_codegen_settings");
+        let build_path = self
+            .paths
+            .build_directory_for_target(self.mode(), self.target());
+        self.io.delete_directory(&build_path)?;
+        self.io.mkdir(&build_path)?;
+        let version_path = self.paths.build_gleam_version(self.mode(), self.target());
+        self.io.write(&version_path, COMPILER_VERSION)?;
+        self.io.write(&settings_path, &settings)
+    }
+
+    /// The settings that change the code generated for every module.
+    fn codegen_settings(&self) -> String {
+        match self.target() {
+            Target::Erlang => String::new(),
+            Target::JavaScript => format!(
+                "integers: {:?}\nmodule_format: {:?}\ncheck_externals: {}\n",
+                self.config.javascript.integers,
+                self.config.javascript.module_format,
+                self.check_externals(),
+            ),
+        }
+    }
+
+    /// Externals are only checked in development, for the dependencies as
+    /// well as the root package.
+    fn check_externals(&self) -> bool {
+        self.config.javascript.check_externals && self.mode() == Mode::Dev
+    }
+
     pub fn compile_dependencies(&mut self) -> Result<Vec<Module>, Error> {
         let sequence = order_packages(&self.packages)?;
         let mut modules = vec![];
@@ -250,15 +406,18 @@ where
             .paths
             .build_directory_for_target(self.mode(), self.target());
 
+        let module_format = self.config.javascript.module_format;
+
         // Write the JavaScript prelude
-        let path = build.join("prelude.mjs");
+        let path = build.join(format!("prelude.{}", module_format.extension()));
         if !self.io.is_file(&path) {
-            self.io.write(&path, crate::javascript::PRELUDE)?;
+            self.io
+                .write(&path, &crate::javascript::prelude(module_format))?;
         }
 
         // Write the TypeScript prelude, if asked for
         if self.config.javascript.typescript_declarations {
-            let path = build.join("prelude.d.mts");
+            let path = build.join(format!("prelude.{}", module_format.declaration_extension()));
             if !self.io.is_file(&path) {
                 self.io.write(&path, crate::javascript::PRELUDE_TS_DEF)?;
             }
@@ -267,6 +426,13 @@ where
         Ok(())
     }
 
+    /// The location of the JavaScript prelude, relative to each package's
+    /// output directory.
+    fn prelude_location(&self) -> Utf8PathBuf {
+        let extension = self.config.javascript.module_format.extension();
+        Utf8PathBuf::from(format!("../prelude.{extension}"))
+    }
+
     fn load_cache_or_compile_package(&mut self, name: &str) -> Result<Vec<Module>, Error> {
         // TODO: We could remove this clone if we split out the compilation of
         // packages into their own classes and then only mutate self after we no
@@ -534,7 +700,13 @@ where
             Target::JavaScript => super::TargetCodegenConfiguration::JavaScript {
                 emit_typescript_definitions: self.config.javascript.typescript_declarations,
                 // This path is relative to each package output directory
-                prelude_location: Utf8PathBuf::from("../prelude.mjs"),
+                prelude_location: self.prelude_location(),
+                // The whole project shares a representation for `Int`s, as
+                // numbers and `BigInt`s can't be mixed in JavaScript.
+                integers: self.config.javascript.integers,
+                // And a module format, as the modules import each other.
+                module_format: self.config.javascript.module_format,
+                check_externals: self.check_externals(),
             },
         };
 
@@ -1,6 +1,22 @@
-use crate::{manifest::ManifestPackage, Error};
+use std::sync::Arc;
 
-use super::project_compiler::{usable_build_tools, BuildTool};
+use camino::Utf8PathBuf;
+
+use crate::{
+    analyse::TargetSupport,
+    config::{JavaScriptConfig, JavaScriptIntegers, JavaScriptModuleFormat, PackageConfig},
+    io::{memory::InMemoryFileSystem, FileSystemReader, FileSystemWriter},
+    manifest::ManifestPackage,
+    paths::ProjectPaths,
+    warning::NullWarningEmitterIO,
+    Error,
+};
+
+use super::{
+    project_compiler::{usable_build_tools, BuildTool},
+    telemetry::NullTelemetry,
+    Codegen, Mode, Options, ProjectCompiler, Target,
+};
 
 #[test]
 fn usable_build_tool_unknown() {
@@ -55,3 +71,76 @@ fn usable_build_tool_mix_then_rebar3() {
         Ok(vec![BuildTool::Mix, BuildTool::Rebar3])
     )
 }
+
+fn javascript_project_compiler(
+    javascript: JavaScriptConfig,
+    io: &InMemoryFileSystem,
+) -> ProjectCompiler<InMemoryFileSystem> {
+    let config = PackageConfig {
+        javascript,
+        ..Default::default()
+    };
+    let options = Options {
+        root_target_support: TargetSupport::Enforced,
+        warnings_as_errors: false,
+        codegen: Codegen::All,
+        mode: Mode::Dev,
+        target: Some(Target::JavaScript),
+    };
+    ProjectCompiler::new(
+        config,
+        options,
+        vec![],
+        Box::new(NullTelemetry),
+        Arc::new(NullWarningEmitterIO),
+        ProjectPaths::new(Utf8PathBuf::from("/app")),
+        io.clone(),
+    )
+}
+
+#[test]
+fn build_directory_is_cleared_when_codegen_settings_change() {
+    let io = InMemoryFileSystem::new();
+    let module = Utf8PathBuf::from("/app/build/dev/javascript/app/app.mjs");
+
+    let compiler = javascript_project_compiler(JavaScriptConfig::default(), &io);
+    compiler.check_gleam_version().unwrap();
+    compiler.check_codegen_settings().unwrap();
+    io.write(&module, "").unwrap();
+
+    // The build is kept while the settings are the same
+    compiler.check_codegen_settings().unwrap();
+    assert!(io.is_file(&module));
+
+    let javascript = JavaScriptConfig {
+        integers: JavaScriptIntegers::BigInt,
+        ..Default::default()
+    };
+    let compiler = javascript_project_compiler(javascript, &io);
+    compiler.check_gleam_version().unwrap();
+    compiler.check_codegen_settings().unwrap();
+    assert!(!io.is_file(&module));
+    assert!(io.is_file(&Utf8PathBuf::from(
+        "/app/build/dev/javascript/gleam_version"
+    )));
+}
+
+#[test]
+fn build_directory_is_cleared_when_module_format_changes() {
+    let io = InMemoryFileSystem::new();
+    let module = Utf8PathBuf::from("/app/build/dev/javascript/app/app.mjs");
+
+    let compiler = javascript_project_compiler(JavaScriptConfig::default(), &io);
+    compiler.check_gleam_version().unwrap();
+    compiler.check_codegen_settings().unwrap();
+    io.write(&module, "").unwrap();
+
+    let javascript = JavaScriptConfig {
+        module_format: JavaScriptModuleFormat::CommonJs,
+        ..Default::default()
+    };
+    let compiler = javascript_project_compiler(javascript, &io);
+    compiler.check_gleam_version().unwrap();
+    compiler.check_codegen_settings().unwrap();
+    assert!(!io.is_file(&module));
+}
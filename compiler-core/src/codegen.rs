@@ -1,7 +1,6 @@
 use crate::{
-    analyse::TargetSupport,
     build::{ErlangAppCodegenConfiguration, Module},
-    config::PackageConfig,
+    config::{JavaScriptModuleFormat, PackageConfig},
     erlang,
     io::FileSystemWriter,
     javascript,
@@ -159,29 +158,26 @@ pub enum TypeScriptDeclarations {
 pub struct JavaScript<'a> {
     output_directory: &'a Utf8Path,
     prelude_location: &'a Utf8Path,
-    typescript: TypeScriptDeclarations,
-    target_support: TargetSupport,
+    options: javascript::CodegenOptions,
 }
 
 impl<'a> JavaScript<'a> {
     pub fn new(
         output_directory: &'a Utf8Path,
-        typescript: TypeScriptDeclarations,
         prelude_location: &'a Utf8Path,
-        target_support: TargetSupport,
+        options: javascript::CodegenOptions,
     ) -> Self {
         Self {
             prelude_location,
             output_directory,
-            target_support,
-            typescript,
+            options,
         }
     }
 
     pub fn render(&self, writer: &impl FileSystemWriter, modules: &[Module]) -> Result<()> {
         for module in modules {
             let js_name = module.name.clone();
-            if self.typescript == TypeScriptDeclarations::Emit {
+            if self.options.typescript == TypeScriptDeclarations::Emit {
                 self.ts_declaration(writer, module, &js_name)?;
             }
             self.js_module(writer, module, &js_name)?
@@ -191,8 +187,16 @@ impl<'a> JavaScript<'a> {
     }
 
     fn write_prelude(&self, writer: &impl FileSystemWriter) -> Result<()> {
-        let rexport = format!("export * from \"{}\";\n", self.prelude_location);
-        let prelude_path = &self.output_directory.join("gleam.mjs");
+        let rexport = match self.options.module_format {
+            JavaScriptModuleFormat::Esm => {
+                format!("export * from \"{}\";\n", self.prelude_location)
+            }
+            JavaScriptModuleFormat::CommonJs => {
+                format!("module.exports = require(\"{}\");\n", self.prelude_location)
+            }
+        };
+        let extension = self.options.module_format.extension();
+        let prelude_path = &self.output_directory.join(format!("gleam.{extension}"));
 
         // This check skips unnecessary `gleam.mjs` writes which confuse
         // watchers and HMR build tools
@@ -200,9 +204,13 @@ impl<'a> JavaScript<'a> {
             writer.write(prelude_path, &rexport)?;
         }
 
-        if self.typescript == TypeScriptDeclarations::Emit {
-            let rexport = rexport.replace(".mjs", ".d.mts");
-            let prelude_declaration_path = &self.output_directory.join("gleam.d.mts");
+        if self.options.typescript == TypeScriptDeclarations::Emit {
+            let declaration_extension = self.options.module_format.declaration_extension();
+            let declaration_location = self.prelude_location.with_extension(declaration_extension);
+            let rexport = format!("export * from \"{declaration_location}\";\n");
+            let prelude_declaration_path = &self
+                .output_directory
+                .join(format!("gleam.{declaration_extension}"));
 
             // Type decleration may trigger badly configured watchers
             if !writer.exists(prelude_declaration_path) {
@@ -219,9 +227,18 @@ impl<'a> JavaScript<'a> {
         module: &Module,
         js_name: &str,
     ) -> Result<()> {
-        let name = format!("{js_name}.d.mts");
+        let name = format!(
+            "{js_name}.{}",
+            self.options.module_format.declaration_extension()
+        );
         let path = self.output_directory.join(name);
-        let output = javascript::ts_declaration(&module.ast, &module.input_path, &module.code);
+        let output = javascript::ts_declaration(
+            &module.ast,
+            &module.input_path,
+            &module.code,
+            self.options.integers,
+            self.options.module_format,
+        );
         tracing::debug!(name = ?js_name, "Generated TS declaration");
         writer.write(&path, &output?)
     }
@@ -232,7 +249,7 @@ impl<'a> JavaScript<'a> {
         module: &Module,
         js_name: &str,
     ) -> Result<()> {
-        let name = format!("{js_name}.mjs");
+        let name = format!("{js_name}.{}", self.options.module_format.extension());
         let path = self.output_directory.join(name);
         let line_numbers = LineNumbers::new(&module.code);
         let output = javascript::module(
@@ -240,8 +257,7 @@ impl<'a> JavaScript<'a> {
             &line_numbers,
             &module.input_path,
             &module.code,
-            self.target_support,
-            self.typescript,
+            self.options,
         );
         tracing::debug!(name = ?js_name, "Generated js module");
         writer.write(&path, &output?)
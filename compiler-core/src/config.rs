@@ -97,6 +97,10 @@ pub struct PackageConfig {
     pub target: Target,
     #[serde(default)]
     pub internal_modules: Option<Vec<Glob>>,
+    #[serde(default, deserialize_with = "repository_name::deserialize_keys")]
+    pub hex_repositories: HashMap<EcoString, HexRepository>,
+    #[serde(default, alias = "license_policy")]
+    pub licence_policy: LicencePolicy,
 }
 
 impl PackageConfig {
@@ -135,6 +139,13 @@ impl PackageConfig {
         Ok(config)
     }
 
+    /// Get one of the Hex repositories configured in `gleam.toml`.
+    pub fn hex_repository(&self, name: &str) -> Result<&HexRepository> {
+        self.hex_repositories
+            .get(name)
+            .ok_or_else(|| Error::UnknownHexRepository { name: name.into() })
+    }
+
     /// Get the locked packages for the current config and a given (optional)
     /// manifest of previously locked packages.
     ///
@@ -592,6 +603,7 @@ fn manifest_package(
         requirements: requirements.iter().map(|e| (*e).into()).collect(),
         source: crate::manifest::ManifestPackageSource::Hex {
             outer_checksum: Base16Checksum(vec![]),
+            repository: None,
         },
     }
 }
@@ -617,6 +629,8 @@ impl Default for PackageConfig {
             licences: Default::default(),
             links: Default::default(),
             internal_modules: Default::default(),
+            hex_repositories: Default::default(),
+            licence_policy: Default::default(),
             target: Target::Erlang,
         }
     }
@@ -638,28 +652,101 @@ pub struct JavaScriptConfig {
     pub runtime: Runtime,
     #[serde(default, rename = "deno")]
     pub deno: DenoConfig,
+    #[serde(default, rename = "node")]
+    pub node: NodeConfig,
+    #[serde(default, rename = "bun")]
+    pub bun: BunConfig,
+    #[serde(default, rename = "browser")]
+    pub browser: BrowserConfig,
+    #[serde(default)]
+    pub integers: JavaScriptIntegers,
+    #[serde(default)]
+    pub module_format: JavaScriptModuleFormat,
+    /// Whether values returned by external functions are checked against
+    /// their declared types when compiling in development mode.
+    #[serde(default)]
+    pub check_externals: bool,
+}
+
+/// How the `Int` type is represented in the generated JavaScript.
+#[derive(Deserialize, Debug, PartialEq, Eq, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum JavaScriptIntegers {
+    /// JavaScript numbers, which are only exact up to 2^53.
+    #[default]
+    Number,
+    /// JavaScript `BigInt`s, which are exact at any size, as on Erlang.
+    BigInt,
+}
+
+/// The kind of module the generated JavaScript is written as.
+///
+/// CommonJS modules import their dependencies with `require`, so a top level
+/// definition named `require`, `module`, or `exports` will shadow the bindings
+/// Node gives each module.
+#[derive(Deserialize, Debug, PartialEq, Eq, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum JavaScriptModuleFormat {
+    /// ECMAScript modules, written as `.mjs` files.
+    #[default]
+    Esm,
+    /// CommonJS modules, written as `.cjs` files.
+    CommonJs,
+}
+
+impl JavaScriptModuleFormat {
+    /// The extension of the generated JavaScript modules.
+    pub fn extension(self) -> &'static str {
+        match self {
+            JavaScriptModuleFormat::Esm => "mjs",
+            JavaScriptModuleFormat::CommonJs => "cjs",
+        }
+    }
+
+    /// The extension of the generated TypeScript declarations.
+    pub fn declaration_extension(self) -> &'static str {
+        match self {
+            JavaScriptModuleFormat::Esm => "d.mts",
+            JavaScriptModuleFormat::CommonJs => "d.cts",
+        }
+    }
 }
 
+/// A permission given to a JavaScript runtime, such as Deno's `--allow-read`
+/// or Node's `--allow-fs-read`, either for everything or for the listed
+/// values.
 #[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
-pub enum DenoFlag {
+pub enum PermissionFlag {
     AllowAll,
     Allow(Vec<String>),
 }
 
-impl Default for DenoFlag {
+impl Default for PermissionFlag {
     fn default() -> Self {
         Self::Allow(Vec::new())
     }
 }
 
-fn bool_or_seq_string_to_deno_flag<'de, D>(deserializer: D) -> Result<DenoFlag, D::Error>
+impl PermissionFlag {
+    /// Whether anything is allowed by this permission.
+    pub fn is_granted(&self) -> bool {
+        match self {
+            Self::AllowAll => true,
+            Self::Allow(allow) => !allow.is_empty(),
+        }
+    }
+}
+
+fn bool_or_seq_string_to_permission_flag<'de, D>(
+    deserializer: D,
+) -> Result<PermissionFlag, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
     struct StringOrVec(PhantomData<Vec<String>>);
 
     impl<'de> serde::de::Visitor<'de> for StringOrVec {
-        type Value = DenoFlag;
+        type Value = PermissionFlag;
 
         fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
             formatter.write_str("bool or list of strings")
@@ -670,9 +757,9 @@ where
             E: serde::de::Error,
         {
             if value {
-                Ok(DenoFlag::AllowAll)
+                Ok(PermissionFlag::AllowAll)
             } else {
-                Ok(DenoFlag::default())
+                Ok(PermissionFlag::default())
             }
         }
 
@@ -684,7 +771,7 @@ where
                 Deserialize::deserialize(serde::de::value::SeqAccessDeserializer::new(visitor))
                     .unwrap_or_default();
 
-            Ok(DenoFlag::Allow(allow))
+            Ok(PermissionFlag::Allow(allow))
         }
     }
 
@@ -693,22 +780,22 @@ where
 
 #[derive(Deserialize, Debug, PartialEq, Eq, Default, Clone)]
 pub struct DenoConfig {
-    #[serde(default, deserialize_with = "bool_or_seq_string_to_deno_flag")]
-    pub allow_env: DenoFlag,
+    #[serde(default, deserialize_with = "bool_or_seq_string_to_permission_flag")]
+    pub allow_env: PermissionFlag,
     #[serde(default)]
     pub allow_sys: bool,
     #[serde(default)]
     pub allow_hrtime: bool,
-    #[serde(default, deserialize_with = "bool_or_seq_string_to_deno_flag")]
-    pub allow_net: DenoFlag,
+    #[serde(default, deserialize_with = "bool_or_seq_string_to_permission_flag")]
+    pub allow_net: PermissionFlag,
     #[serde(default)]
     pub allow_ffi: bool,
-    #[serde(default, deserialize_with = "bool_or_seq_string_to_deno_flag")]
-    pub allow_read: DenoFlag,
-    #[serde(default, deserialize_with = "bool_or_seq_string_to_deno_flag")]
-    pub allow_run: DenoFlag,
-    #[serde(default, deserialize_with = "bool_or_seq_string_to_deno_flag")]
-    pub allow_write: DenoFlag,
+    #[serde(default, deserialize_with = "bool_or_seq_string_to_permission_flag")]
+    pub allow_read: PermissionFlag,
+    #[serde(default, deserialize_with = "bool_or_seq_string_to_permission_flag")]
+    pub allow_run: PermissionFlag,
+    #[serde(default, deserialize_with = "bool_or_seq_string_to_permission_flag")]
+    pub allow_write: PermissionFlag,
     #[serde(default)]
     pub allow_all: bool,
     #[serde(default)]
@@ -717,6 +804,59 @@ pub struct DenoConfig {
     pub location: Option<Uri>,
 }
 
+/// How Node.js is run by the `nodejs` runtime.
+#[derive(Deserialize, Debug, PartialEq, Eq, Default, Clone)]
+pub struct NodeConfig {
+    /// Extra command line arguments for Node, such as `--enable-source-maps`,
+    /// given before the module being run.
+    #[serde(default)]
+    pub arguments: Vec<String>,
+    /// Environment variables to set for the program.
+    #[serde(default)]
+    pub environment: HashMap<String, String>,
+    /// Experimental features to enable, each given as an `--experimental-`
+    /// flag. For example `vm-modules` enables `--experimental-vm-modules`.
+    #[serde(default)]
+    pub experimental: Vec<String>,
+    /// Whether the program is run with Node's permission model, which denies
+    /// it access to the file system, child processes, and worker threads
+    /// unless they are allowed below.
+    #[serde(default)]
+    pub permission: bool,
+    #[serde(default, deserialize_with = "bool_or_seq_string_to_permission_flag")]
+    pub allow_fs_read: PermissionFlag,
+    #[serde(default, deserialize_with = "bool_or_seq_string_to_permission_flag")]
+    pub allow_fs_write: PermissionFlag,
+    #[serde(default)]
+    pub allow_child_process: bool,
+    #[serde(default)]
+    pub allow_worker: bool,
+}
+
+/// How Bun is run by the `bun` runtime.
+#[derive(Deserialize, Debug, PartialEq, Eq, Default, Clone)]
+pub struct BunConfig {
+    /// Extra command line arguments for Bun, such as `--smol`, given before
+    /// the module being run.
+    #[serde(default)]
+    pub arguments: Vec<String>,
+    /// Environment variables to set for the program.
+    #[serde(default)]
+    pub environment: HashMap<String, String>,
+}
+
+/// The headless browser used by the `browser` runtime.
+#[derive(Deserialize, Debug, PartialEq, Eq, Default, Clone)]
+pub struct BrowserConfig {
+    /// The browser to run. When not given a Chromium based browser is looked
+    /// for on the `PATH`.
+    #[serde(default)]
+    pub executable: Option<Utf8PathBuf>,
+    /// Extra command line arguments for the browser, such as `--no-sandbox`.
+    #[serde(default)]
+    pub arguments: Vec<String>,
+}
+
 #[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum Repository {
@@ -780,6 +920,96 @@ impl Default for Repository {
     }
 }
 
+/// A Hex repository other than hex.pm that dependencies can be downloaded
+/// from, such as a Hex organisation or a self-hosted mirror.
+///
+/// ```toml
+/// [hex_repositories.internal]
+/// api_url = "https://hex.example.com/api/"
+/// repository_url = "https://hex.example.com/repo/"
+/// auth_key_env = "INTERNAL_HEX_KEY"
+/// public_key = "internal_hex.pem"
+/// ```
+///
+#[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct HexRepository {
+    #[serde(deserialize_with = "uri_serde::deserialize_base")]
+    pub api_url: Uri,
+    #[serde(deserialize_with = "uri_serde::deserialize_base")]
+    pub repository_url: Uri,
+    /// The environment variable holding the key to authenticate with, if the
+    /// repository is private.
+    #[serde(default)]
+    pub auth_key_env: Option<EcoString>,
+    /// The path to the public key that the repository signs its package
+    /// registry with, relative to the project root. The hex.pm key is used if
+    /// none is given.
+    #[serde(default)]
+    pub public_key: Option<Utf8PathBuf>,
+}
+
+impl HexRepository {
+    pub fn hex_config(&self) -> hexpm::Config {
+        hexpm::Config {
+            api_base: self.api_url.clone(),
+            repository_base: self.repository_url.clone(),
+        }
+    }
+}
+
+/// The licences that dependency packages may and may not be used under.
+///
+/// ```toml
+/// [licence_policy]
+/// allow = ["Apache-2.0", "MIT"]
+/// deny = ["GPL-3.0-only"]
+/// ```
+///
+/// A package that declares several licences may be used under any one of
+/// them, so it meets the policy if at least one of its licences is allowed
+/// and not denied. When the allow list is empty every licence that is not
+/// denied is allowed.
+#[derive(Deserialize, Default, Debug, PartialEq, Eq, Clone)]
+pub struct LicencePolicy {
+    #[serde(default)]
+    pub allow: Vec<SpdxLicense>,
+    #[serde(default)]
+    pub deny: Vec<SpdxLicense>,
+}
+
+impl LicencePolicy {
+    pub fn is_empty(&self) -> bool {
+        self.allow.is_empty() && self.deny.is_empty()
+    }
+
+    /// Whether a package declaring the given licences meets the policy.
+    /// Packages that declare no licences never do.
+    pub fn permits<L: AsRef<str>>(&self, licences: &[L]) -> bool {
+        let listed = |list: &[SpdxLicense], licence: &str| {
+            list.iter().any(|listed| listed.as_ref() == licence)
+        };
+        licences.iter().any(|licence| {
+            let licence = licence.as_ref();
+            !listed(&self.deny, licence) && (self.allow.is_empty() || listed(&self.allow, licence))
+        })
+    }
+}
+
+#[test]
+fn licence_policy() {
+    let policy: LicencePolicy =
+        toml::from_str("allow = [\"Apache-2.0\", \"MIT\"]\ndeny = [\"MIT\"]").unwrap();
+    assert!(policy.permits(&["Apache-2.0"]));
+    assert!(policy.permits(&["GPL-3.0-only", "Apache-2.0"]));
+    assert!(!policy.permits(&["MIT"]));
+    assert!(!policy.permits(&["GPL-3.0-only"]));
+    assert!(!policy.permits::<&str>(&[]));
+
+    let policy: LicencePolicy = toml::from_str("deny = [\"GPL-3.0-only\"]").unwrap();
+    assert!(policy.permits(&["MIT"]));
+    assert!(!policy.permits(&["GPL-3.0-only"]));
+}
+
 #[derive(Deserialize, Default, Debug, PartialEq, Eq, Clone)]
 pub struct Docs {
     #[serde(default)]
@@ -819,6 +1049,23 @@ mod uri_serde {
         Ok(uri)
     }
 
+    /// Request paths are appended to base URLs, such as those of a Hex
+    /// repository, so they are given a trailing slash if they lack one.
+    pub fn deserialize_base<'de, D>(deserializer: D) -> Result<http::Uri, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let uri = deserialize(deserializer)?.to_string();
+        if uri.ends_with('/') {
+            return uri
+                .parse()
+                .map_err(|err: InvalidUri| D::Error::custom(err.to_string()));
+        }
+        format!("{uri}/")
+            .parse()
+            .map_err(|err: InvalidUri| D::Error::custom(err.to_string()))
+    }
+
     pub fn deserialize_option<'de, D>(deserializer: D) -> Result<Option<http::Uri>, D::Error>
     where
         D: Deserializer<'de>,
@@ -885,6 +1132,54 @@ mod package_name {
     }
 }
 
+/// Hex repository names are used as the names of directories in the global
+/// package cache, so they can't be allowed to hold paths.
+pub(crate) mod repository_name {
+    use ecow::EcoString;
+    use regex::Regex;
+    use serde::{Deserialize, Deserializer};
+    use std::{collections::HashMap, sync::OnceLock};
+
+    static REPOSITORY_NAME_PATTERN: OnceLock<Regex> = OnceLock::new();
+
+    const ERROR: &str =
+        "Hex repository names may only contain lowercase letters, numbers, underscores, and hyphens";
+
+    pub fn check<E: serde::de::Error>(name: &str) -> Result<(), E> {
+        if REPOSITORY_NAME_PATTERN
+            .get_or_init(|| Regex::new("^[a-z0-9_-]+$").expect("Repository name regex"))
+            .is_match(name)
+        {
+            Ok(())
+        } else {
+            Err(E::custom(format!("{ERROR}, got `{name}`")))
+        }
+    }
+
+    pub fn deserialize_keys<'de, D, V>(deserializer: D) -> Result<HashMap<EcoString, V>, D::Error>
+    where
+        D: Deserializer<'de>,
+        V: Deserialize<'de>,
+    {
+        let map: HashMap<EcoString, V> = HashMap::deserialize(deserializer)?;
+        for name in map.keys() {
+            check(name)?;
+        }
+        Ok(map)
+    }
+
+    pub fn deserialize_option<'de, D>(deserializer: D) -> Result<Option<EcoString>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let name: Option<EcoString> = Option::deserialize(deserializer)?;
+        if let Some(name) = &name {
+            check(name)?;
+        }
+        Ok(name)
+    }
+}
+
 #[test]
 fn name_with_dash() {
     let input = r#"
@@ -910,3 +1205,182 @@ name = "1"
         "Package names may only container lowercase letters, numbers, and underscores for key `name` at line 1 column 1"
     )
 }
+
+#[test]
+fn hex_repositories() {
+    let input = r#"
+name = "app"
+
+[dependencies]
+internal_lib = { version = "~> 1.0", repository = "internal" }
+
+[hex_repositories.internal]
+api_url = "https://hex.example.com/api"
+repository_url = "https://hex.example.com/repo/"
+auth_key_env = "INTERNAL_HEX_KEY"
+"#;
+    let config = toml::from_str::<PackageConfig>(input).unwrap();
+    let repository = config.hex_repository("internal").unwrap();
+    assert_eq!(repository.auth_key_env, Some("INTERNAL_HEX_KEY".into()));
+    assert_eq!(repository.public_key, None);
+
+    let hex_config = repository.hex_config();
+    assert_eq!(hex_config.api_base, "https://hex.example.com/api/");
+    assert_eq!(hex_config.repository_base, "https://hex.example.com/repo/");
+
+    assert_eq!(
+        config.hex_repository("other"),
+        Err(Error::UnknownHexRepository {
+            name: "other".into()
+        })
+    );
+}
+
+#[test]
+fn hex_repository_name_with_path() {
+    let input = r#"
+name = "app"
+
+[hex_repositories."../other"]
+api_url = "https://hex.example.com/api"
+repository_url = "https://hex.example.com/repo"
+"#;
+    assert_eq!(
+        toml::from_str::<PackageConfig>(input)
+            .unwrap_err()
+            .to_string(),
+        "Hex repository names may only contain lowercase letters, numbers, underscores, and hyphens, got `../other` for key `hex_repositories` at line 4 column 1"
+    )
+}
+
+#[test]
+fn dependency_repository_name_with_path() {
+    let input = r#"
+name = "app"
+
+[dependencies]
+internal_lib = { version = "~> 1.0", repository = "/absolute" }
+"#;
+    assert!(toml::from_str::<PackageConfig>(input)
+        .unwrap_err()
+        .to_string()
+        .contains("Hex repository names may only contain lowercase letters"))
+}
+
+#[test]
+fn javascript_integers() {
+    let input = r#"
+name = "app"
+
+[javascript]
+integers = "bigint"
+"#;
+    let config = toml::from_str::<PackageConfig>(input).unwrap();
+    assert_eq!(config.javascript.integers, JavaScriptIntegers::BigInt);
+
+    let config = toml::from_str::<PackageConfig>("name = \"app\"").unwrap();
+    assert_eq!(config.javascript.integers, JavaScriptIntegers::Number);
+}
+
+#[test]
+fn javascript_module_format() {
+    let input = r#"
+name = "app"
+
+[javascript]
+module_format = "commonjs"
+"#;
+    let config = toml::from_str::<PackageConfig>(input).unwrap();
+    assert_eq!(
+        config.javascript.module_format,
+        JavaScriptModuleFormat::CommonJs
+    );
+
+    let config = toml::from_str::<PackageConfig>("name = \"app\"").unwrap();
+    assert_eq!(config.javascript.module_format, JavaScriptModuleFormat::Esm);
+}
+
+#[test]
+fn javascript_browser() {
+    let input = r#"
+name = "app"
+
+[javascript]
+runtime = "browser"
+
+[javascript.browser]
+executable = "/usr/bin/chromium"
+arguments = ["--no-sandbox"]
+"#;
+    let config = toml::from_str::<PackageConfig>(input).unwrap();
+    assert_eq!(config.javascript.runtime, Runtime::Browser);
+    assert_eq!(
+        config.javascript.browser,
+        BrowserConfig {
+            executable: Some("/usr/bin/chromium".into()),
+            arguments: vec!["--no-sandbox".into()],
+        }
+    );
+}
+
+#[test]
+fn javascript_check_externals() {
+    let input = r#"
+name = "app"
+
+[javascript]
+check_externals = true
+"#;
+    let config = toml::from_str::<PackageConfig>(input).unwrap();
+    assert!(config.javascript.check_externals);
+    assert!(!PackageConfig::default().javascript.check_externals);
+}
+
+#[test]
+fn javascript_node() {
+    let input = r#"
+name = "app"
+
+[javascript.node]
+arguments = ["--enable-source-maps"]
+environment = { NODE_ENV = "development" }
+experimental = ["vm-modules"]
+permission = true
+allow_fs_read = ["./priv"]
+allow_fs_write = true
+allow_worker = true
+"#;
+    let config = toml::from_str::<PackageConfig>(input).unwrap();
+    assert_eq!(
+        config.javascript.node,
+        NodeConfig {
+            arguments: vec!["--enable-source-maps".into()],
+            environment: [("NODE_ENV".into(), "development".into())].into(),
+            experimental: vec!["vm-modules".into()],
+            permission: true,
+            allow_fs_read: PermissionFlag::Allow(vec!["./priv".into()]),
+            allow_fs_write: PermissionFlag::AllowAll,
+            allow_child_process: false,
+            allow_worker: true,
+        }
+    );
+}
+
+#[test]
+fn javascript_bun() {
+    let input = r#"
+name = "app"
+
+[javascript.bun]
+arguments = ["--smol"]
+environment = { NODE_ENV = "development" }
+"#;
+    let config = toml::from_str::<PackageConfig>(input).unwrap();
+    assert_eq!(
+        config.javascript.bun,
+        BunConfig {
+            arguments: vec!["--smol".into()],
+            environment: [("NODE_ENV".into(), "development".into())].into(),
+        }
+    );
+}
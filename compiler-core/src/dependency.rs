@@ -1,4 +1,9 @@
-use std::{borrow::Borrow, cell::RefCell, collections::HashMap, error::Error as StdError};
+use std::{
+    borrow::Borrow,
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    error::Error as StdError,
+};
 
 use crate::{Error, Result};
 
@@ -7,10 +12,13 @@ use hexpm::{
     version::{Range, Version},
     Dependency, Release,
 };
+use itertools::Itertools;
 use pubgrub::{
     error::PubGrubError,
+    report::{DerivationTree, External},
     solver::{choose_package_with_fewest_versions, Dependencies},
     type_aliases::Map,
+    version::Version as _,
 };
 
 pub type PackageVersions = HashMap<String, Version>;
@@ -53,15 +61,23 @@ where
         }],
     };
 
-    let packages = pubgrub::solver::resolve(
-        &DependencyProvider::new(package_fetcher, provided_packages, root, locked, exact_deps),
-        root_name.as_str().into(),
-        root_version,
-    )
-    .map_err(Error::dependency_resolution_failed)?
-    .into_iter()
-    .filter(|(name, _)| name.as_str() != root_name.as_str())
-    .collect();
+    let provider =
+        DependencyProvider::new(package_fetcher, provided_packages, root, locked, exact_deps);
+    let packages = pubgrub::solver::resolve(&provider, root_name.as_str().into(), root_version)
+        .map_err(|error| match error {
+            PubGrubError::NoSolution(derivation_tree) => {
+                Error::DependencyResolutionFailed(explain_no_solution(
+                    derivation_tree,
+                    &root_name,
+                    locked,
+                    &provider.packages.borrow(),
+                ))
+            }
+            error => Error::dependency_resolution_failed(error),
+        })?
+        .into_iter()
+        .filter(|(name, _)| name.as_str() != root_name.as_str())
+        .collect();
 
     Ok(packages)
 }
@@ -147,6 +163,269 @@ but it is locked to {version}, which is incompatible.",
     Ok(requirements)
 }
 
+/// A requirement on a package that took part in a resolution failure.
+#[derive(Debug, PartialEq)]
+struct ConflictingRequirement<'a> {
+    dependent: &'a str,
+    /// The versions of the dependent package that have this requirement.
+    dependent_versions: &'a PubgrubRange,
+    range: &'a PubgrubRange,
+}
+
+/// The incompatibilities at the root of a resolution failure, gathered from
+/// pubgrub's derivation tree.
+#[derive(Debug, Default)]
+struct Conflicts<'a> {
+    requirements: BTreeMap<&'a str, Vec<ConflictingRequirement<'a>>>,
+    no_versions: Vec<(&'a str, &'a PubgrubRange)>,
+    unavailable: Vec<(&'a str, &'a PubgrubRange)>,
+}
+
+impl<'a> Conflicts<'a> {
+    fn collect(&mut self, derivation_tree: &'a DerivationTree<PackageName, Version>) {
+        match derivation_tree {
+            DerivationTree::External(External::NotRoot(..)) => (),
+            DerivationTree::External(External::NoVersions(package, range)) => {
+                if !self.no_versions.contains(&(package, range)) {
+                    self.no_versions.push((package, range));
+                }
+            }
+            DerivationTree::External(External::UnavailableDependencies(package, range)) => {
+                if !self.unavailable.contains(&(package, range)) {
+                    self.unavailable.push((package, range));
+                }
+            }
+            DerivationTree::External(External::FromDependencyOf(
+                dependent,
+                dependent_versions,
+                package,
+                range,
+            )) => {
+                let requirement = ConflictingRequirement {
+                    dependent,
+                    dependent_versions,
+                    range,
+                };
+                let requirements = self.requirements.entry(package).or_default();
+                if !requirements.contains(&requirement) {
+                    requirements.push(requirement);
+                }
+            }
+            DerivationTree::Derived(derived) => {
+                self.collect(&derived.cause1);
+                self.collect(&derived.cause2);
+            }
+        }
+    }
+}
+
+/// Explain why no versions of the dependency packages satisfy all the
+/// requirements on them: which requirements conflict, which versions were
+/// considered, and what changes could resolve the conflict.
+fn explain_no_solution(
+    mut derivation_tree: DerivationTree<PackageName, Version>,
+    root_name: &str,
+    locked: &HashMap<EcoString, Version>,
+    packages: &HashMap<EcoString, hexpm::Package>,
+) -> String {
+    derivation_tree.collapse_no_versions();
+    let mut conflicts = Conflicts::default();
+    conflicts.collect(&derivation_tree);
+
+    let is_lock = |package: &str, requirement: &ConflictingRequirement<'_>| {
+        requirement.dependent == root_name
+            && locked
+                .get(package)
+                .is_some_and(|version| requirement.range == &PubgrubRange::exact(version.clone()))
+    };
+    let describe_requirement = |package: &str, requirement: &ConflictingRequirement<'_>| {
+        let range = describe_range(requirement.range);
+        if is_lock(package, requirement) {
+            format!("manifest.toml locks {package} to {range}")
+        } else if requirement.dependent == root_name {
+            format!("{root_name} requires {package} {range}")
+        } else if requirement.dependent_versions == &PubgrubRange::any() {
+            format!(
+                "every version of {} requires {package} {range}",
+                requirement.dependent
+            )
+        } else {
+            format!(
+                "{} {} requires {package} {range}",
+                requirement.dependent,
+                describe_range(requirement.dependent_versions)
+            )
+        }
+    };
+    let available_versions = |package: &str| {
+        let versions = packages
+            .get(package)
+            .map(|package| {
+                package
+                    .releases
+                    .iter()
+                    .map(|release| &release.version)
+                    .sorted()
+                    .rev()
+                    .map(Version::to_string)
+                    .collect_vec()
+            })
+            .unwrap_or_default();
+        match versions.len() {
+            0 => format!("No versions of {package} were found."),
+            count if count <= 5 => format!(
+                "The versions of {package} considered were {}.",
+                versions.join(", ")
+            ),
+            count => format!(
+                "The versions of {package} considered were {} and {} older versions.",
+                versions.iter().take(5).join(", "),
+                count - 5
+            ),
+        }
+    };
+    // The newest version of a package that every one of the given
+    // requirements accepts.
+    let newest_accepted = |package: &str, requirements: &[&ConflictingRequirement<'_>]| {
+        packages.get(package).and_then(|package| {
+            package
+                .releases
+                .iter()
+                .map(|release| &release.version)
+                .filter(|version| {
+                    requirements
+                        .iter()
+                        .all(|requirement| requirement.range.contains(version))
+                })
+                .max()
+        })
+    };
+
+    let mut sections = vec![];
+    let mut suggestions = vec![];
+    for (package, requirements) in &conflicts.requirements {
+        // A lone requirement is only a problem when no version matches it.
+        let unmatched = requirements
+            .iter()
+            .find(|requirement| newest_accepted(package, &[requirement]).is_none());
+        let mut section = if requirements.len() < 2 {
+            let Some(requirement) = unmatched else {
+                continue;
+            };
+            format!(
+                "No version of {package} matches {}.",
+                describe_range(requirement.range)
+            )
+        } else {
+            format!("These requirements on {package} conflict:")
+        };
+        if requirements.len() >= 2 || requirements.iter().any(|r| r.dependent != root_name) {
+            for requirement in requirements {
+                section.push_str(&format!(
+                    "\n  - {}",
+                    describe_requirement(package, requirement)
+                ));
+            }
+        }
+        section.push_str(&format!("\n{}", available_versions(package)));
+        sections.push(section);
+
+        let (from_root, from_others): (Vec<_>, Vec<_>) = requirements
+            .iter()
+            .partition(|requirement| requirement.dependent == root_name);
+        if let Some(lock) = from_root.iter().find(|r| is_lock(package, r)) {
+            suggestions.push(format!(
+                "Run `gleam update {package}` to unlock {package}, which is locked to {}.",
+                describe_range(lock.range)
+            ));
+        } else if !from_root.is_empty() {
+            if let Some(version) = newest_accepted(package, &from_others) {
+                let dependents = from_others.iter().map(|r| r.dependent).unique().join(", ");
+                let accepted_by = if from_others.is_empty() {
+                    String::new()
+                } else {
+                    format!(", which {dependents} accepts")
+                };
+                suggestions.push(format!(
+                    "Run `gleam add {package}@{}.{}` to require {package} {version}{accepted_by}.",
+                    version.major, version.minor
+                ));
+            }
+        }
+    }
+    for (package, range) in &conflicts.no_versions {
+        if !conflicts.requirements.contains_key(package) {
+            sections.push(format!(
+                "No version of {package} matches {}.\n{}",
+                describe_range(range),
+                available_versions(package)
+            ));
+        }
+    }
+    for (package, range) in &conflicts.unavailable {
+        sections.push(format!(
+            "The dependencies of {package} {} could not be found. Those versions \
+may have been retired.",
+            describe_range(range)
+        ));
+    }
+
+    let mut explanation = "Unable to find versions of the dependency packages that satisfy \
+all of the version requirements on them."
+        .to_string();
+    for section in sections {
+        explanation.push_str(&format!("\n\n{section}"));
+    }
+    if !suggestions.is_empty() {
+        explanation.push_str("\n\nTo resolve the conflict:");
+        for suggestion in suggestions {
+            explanation.push_str(&format!("\n  - {suggestion}"));
+        }
+    }
+    explanation
+}
+
+/// Describe a range of versions using the same operators as version
+/// requirements in `gleam.toml`.
+fn describe_range(range: &PubgrubRange) -> String {
+    if range == &PubgrubRange::any() {
+        return "any version".into();
+    }
+    if range == &PubgrubRange::none() {
+        return "no version".into();
+    }
+    range_intervals(range)
+        .into_iter()
+        .map(|(start, end)| match end {
+            None => format!(">= {start}"),
+            Some(end) if start == Version::lowest() => format!("< {end}"),
+            Some(end) if end == start.bump() => start.to_string(),
+            Some(end) => format!(">= {start} and < {end}"),
+        })
+        .join(" or ")
+}
+
+/// The intervals that make up a range, each with an inclusive start and an
+/// exclusive end, if it has one. pubgrub doesn't expose the intervals of a
+/// range, so they are found one after another from the versions that are the
+/// lowest in the range and in its complement.
+fn range_intervals(range: &PubgrubRange) -> Vec<(Version, Option<Version>)> {
+    let complement = range.negate();
+    let mut intervals = vec![];
+    let mut remaining = range.clone();
+    while let Some(start) = remaining.lowest_version() {
+        let end = complement
+            .intersection(&PubgrubRange::higher_than(start.clone()))
+            .lowest_version();
+        remaining = match &end {
+            Some(end) => remaining.intersection(&PubgrubRange::higher_than(end.clone())),
+            None => PubgrubRange::none(),
+        };
+        intervals.push((start, end));
+    }
+    intervals
+}
+
 pub trait PackageFetcher {
     fn get_dependencies(&self, package: &str) -> Result<hexpm::Package, Box<dyn StdError>>;
 }
@@ -407,6 +686,29 @@ mod tests {
                 ],
             },
         );
+        let _ = deps.insert(
+            "gleam_json".into(),
+            hexpm::Package {
+                name: "gleam_json".into(),
+                repository: "hexpm".into(),
+                releases: vec![Release {
+                    version: Version::try_from("1.0.0").unwrap(),
+                    requirements: [(
+                        "gleam_stdlib".into(),
+                        Dependency {
+                            app: None,
+                            optional: false,
+                            repository: None,
+                            requirement: Range::new(">= 0.2.0 and < 0.3.0".into()),
+                        },
+                    )]
+                    .into(),
+                    retirement_status: None,
+                    outer_checksum: vec![1, 2, 3],
+                    meta: (),
+                }],
+            },
+        );
         let _ = deps.insert(
             "package_with_retired".into(),
             hexpm::Package {
@@ -662,6 +964,117 @@ mod tests {
         }
     }
 
+    #[test]
+    fn resolution_conflicting_requirements() {
+        let err = resolve_versions(
+            make_remote(),
+            HashMap::new(),
+            "app".into(),
+            vec![
+                ("gleam_stdlib".into(), Range::new("~> 0.1.0".into())),
+                ("gleam_json".into(), Range::new("~> 1.0".into())),
+            ]
+            .into_iter(),
+            &vec![].into_iter().collect(),
+        )
+        .unwrap_err();
+
+        match err {
+            Error::DependencyResolutionFailed(msg) => assert_eq!(
+                msg,
+                "Unable to find versions of the dependency packages that satisfy all of the version requirements on them.
+
+These requirements on gleam_stdlib conflict:
+  - gleam_json >= 1.0.0 and < 2.0.0 requires gleam_stdlib >= 0.2.0 and < 0.3.0
+  - app requires gleam_stdlib >= 0.1.0 and < 0.2.0
+The versions of gleam_stdlib considered were 0.3.0, 0.2.2, 0.2.0, 0.1.0.
+
+To resolve the conflict:
+  - Run `gleam add gleam_stdlib@0.2` to require gleam_stdlib 0.2.2, which gleam_json accepts."
+            ),
+            _ => panic!("wrong error: {}", err),
+        }
+    }
+
+    #[test]
+    fn resolution_conflicting_with_locked_version() {
+        let err = resolve_versions(
+            make_remote(),
+            HashMap::new(),
+            "app".into(),
+            vec![
+                ("gleam_stdlib".into(), Range::new(">= 0.1.0".into())),
+                ("gleam_json".into(), Range::new("~> 1.0".into())),
+            ]
+            .into_iter(),
+            &vec![("gleam_stdlib".into(), Version::new(0, 3, 0))]
+                .into_iter()
+                .collect(),
+        )
+        .unwrap_err();
+
+        match err {
+            Error::DependencyResolutionFailed(msg) => assert_eq!(
+                msg,
+                "Unable to find versions of the dependency packages that satisfy all of the version requirements on them.
+
+These requirements on gleam_stdlib conflict:
+  - gleam_json >= 1.0.0 and < 2.0.0 requires gleam_stdlib >= 0.2.0 and < 0.3.0
+  - manifest.toml locks gleam_stdlib to 0.3.0
+The versions of gleam_stdlib considered were 0.3.0, 0.2.2, 0.2.0, 0.1.0.
+
+To resolve the conflict:
+  - Run `gleam update gleam_stdlib` to unlock gleam_stdlib, which is locked to 0.3.0."
+            ),
+            _ => panic!("wrong error: {}", err),
+        }
+    }
+
+    #[test]
+    fn resolution_no_matching_version_explanation() {
+        let err = resolve_versions(
+            make_remote(),
+            HashMap::new(),
+            "app".into(),
+            vec![("gleam_stdlib".into(), Range::new("~> 99.0".into()))].into_iter(),
+            &vec![].into_iter().collect(),
+        )
+        .unwrap_err();
+
+        match err {
+            Error::DependencyResolutionFailed(msg) => assert_eq!(
+                msg,
+                "Unable to find versions of the dependency packages that satisfy all of the version requirements on them.
+
+No version of gleam_stdlib matches >= 99.0.0 and < 100.0.0.
+The versions of gleam_stdlib considered were 0.3.0, 0.2.2, 0.2.0, 0.1.0.
+
+To resolve the conflict:
+  - Run `gleam add gleam_stdlib@0.3` to require gleam_stdlib 0.3.0."
+            ),
+            _ => panic!("wrong error: {}", err),
+        }
+    }
+
+    #[test]
+    fn describe_range_test() {
+        let range = |requirement: &str| Range::new(requirement.into()).to_pubgrub().unwrap();
+        assert_eq!(describe_range(&range(">= 0.0.0")), "any version");
+        assert_eq!(describe_range(&range("~> 1.2")), ">= 1.2.0 and < 2.0.0");
+        assert_eq!(describe_range(&range(">= 1.2.0")), ">= 1.2.0");
+        assert_eq!(describe_range(&range("< 1.2.0")), "< 1.2.0");
+        assert_eq!(describe_range(&range("== 1.2.0")), "1.2.0");
+        assert_eq!(
+            describe_range(&range("< 1.0.0 or >= 2.0.0")),
+            "< 1.0.0 or >= 2.0.0"
+        );
+        assert_eq!(
+            describe_range(&range("< 1.0.0 or >= 2.0.0 and < 3.0.0 or == 4.0.0")),
+            "< 1.0.0 or >= 2.0.0 and < 3.0.0 or 4.0.0"
+        );
+        assert_eq!(describe_range(&PubgrubRange::none()), "no version");
+    }
+
     #[test]
     fn resolution_with_exact_dep() {
         let result = resolve_versions(
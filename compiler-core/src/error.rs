@@ -143,6 +143,9 @@ pub enum Error {
         err: Option<std::io::ErrorKind>,
     },
 
+    #[error("the browser runtime failed: {reason}")]
+    BrowserRuntime { reason: String },
+
     #[error("{name} is not a valid project name")]
     InvalidProjectName {
         name: String,
@@ -180,6 +183,12 @@ file_names.iter().map(|x| x.as_str()).join(", "))]
     #[error("Packages not exist: {}", packages.iter().join(", "))]
     RemovedPackagesNotExist { packages: Vec<String> },
 
+    #[error("Package {package} is not a dependency")]
+    PackageNotADependency { package: EcoString },
+
+    #[error("Unknown Hex repository {name}")]
+    UnknownHexRepository { name: EcoString },
+
     #[error("unable to find project root")]
     UnableToFindProjectRoot { path: String },
 
@@ -199,12 +208,18 @@ file_names.iter().map(|x| x.as_str()).join(", "))]
         error: javascript::Error,
     },
 
+    #[error("javascript bundling failed")]
+    JavaScriptBundle { error: javascript::BundleError },
+
     #[error("Invalid runtime for {target} target: {invalid_runtime}")]
     InvalidRuntime {
         target: Target,
         invalid_runtime: Runtime,
     },
 
+    #[error("Node permissions {settings:?} are set without the permission model")]
+    NodePermissionsWithoutPermissionModel { settings: Vec<EcoString> },
+
     #[error("package downloading failed: {error}")]
     DownloadPackageError {
         package_name: String,
@@ -224,6 +239,43 @@ file_names.iter().map(|x| x.as_str()).join(", "))]
     #[error("Dependency tree resolution failed: {0}")]
     DependencyResolutionFailed(String),
 
+    #[error("No package interface was published for {package} v{version}")]
+    PackageInterfaceUnavailable {
+        package: EcoString,
+        version: EcoString,
+    },
+
+    #[error("Breaking changes to {package} need a new major version")]
+    UnmarkedBreakingChange {
+        package: EcoString,
+        previous_version: EcoString,
+        version: EcoString,
+        minimum_version: EcoString,
+        changes: Vec<EcoString>,
+    },
+
+    #[error("Dependency tree resolution failed offline: {error}")]
+    OfflineDependencyResolutionFailed {
+        error: String,
+        unavailable: Vec<String>,
+    },
+
+    #[error("The vendored package {package} does not match the manifest")]
+    VendoredPackageMismatch {
+        package: EcoString,
+        problem: VendoredPackageProblem,
+    },
+
+    #[error("The cached package {package} {version} does not match the manifest")]
+    CachedPackageChecksumMismatch {
+        package: EcoString,
+        version: EcoString,
+        path: Utf8PathBuf,
+    },
+
+    #[error("Dependency packages do not meet the licence policy")]
+    LicencePolicyViolation { violations: Vec<LicenceViolation> },
+
     #[error("The package {0} is listed in dependencies and dev-dependencies")]
     DuplicateDependency(EcoString),
 
@@ -250,6 +302,18 @@ file_names.iter().map(|x| x.as_str()).join(", "))]
     #[error("Dependency {package:?} has not been published to Hex")]
     PublishNonHexDependencies { package: String },
 
+    #[error("Dependency {package:?} is from the Hex repository {repository}")]
+    PublishHexRepositoryDependency {
+        package: String,
+        repository: EcoString,
+    },
+
+    #[error("The environment variable {variable} for the Hex repository {repository} is not set")]
+    HexRepositoryAuthKeyNotSet {
+        repository: EcoString,
+        variable: EcoString,
+    },
+
     #[error("The package {package} uses unsupported build tools {build_tools:?}")]
     UnsupportedBuildTool {
         package: String,
@@ -428,6 +492,29 @@ impl From<capnp::NotInSchema> for Error {
     }
 }
 
+/// A dependency package whose licences do not meet the licence policy of
+/// the project.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LicenceViolation {
+    pub package: EcoString,
+    pub version: EcoString,
+    /// The licences the package declares, if any.
+    pub licences: Vec<EcoString>,
+    /// How the root package depends on this package.
+    pub dependency_path: String,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum VendoredPackageProblem {
+    NotVendored,
+    WrongVersion {
+        vendored: EcoString,
+        locked: EcoString,
+    },
+    WrongChecksum,
+    SourcesChanged,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum InvalidProjectNameReason {
     Format,
@@ -461,7 +548,7 @@ pub enum FileIoAction {
     Read,
     Parse,
     Delete,
-    // Rename,
+    Rename,
     Create,
     WriteTo,
     Canonicalise,
@@ -479,7 +566,7 @@ impl FileIoAction {
             FileIoAction::Read => "read",
             FileIoAction::Parse => "parse",
             FileIoAction::Delete => "delete",
-            // FileIoAction::Rename => "rename",
+            FileIoAction::Rename => "rename",
             FileIoAction::Create => "create",
             FileIoAction::WriteTo => "write to",
             FileIoAction::FindParent => "find the parent of",
@@ -874,6 +961,30 @@ be removed.
                 }
             ],
 
+            Error::PackageNotADependency { package } => vec![Diagnostic {
+                title: "Package not found".into(),
+                text: format!(
+                    "The package `{package}` is not a dependency of your package,
+either directly or through another package."
+                ),
+                level: Level::Error,
+                hint: None,
+                location: None,
+            }],
+
+            Error::UnknownHexRepository { name } => vec![Diagnostic {
+                title: "Unknown Hex repository".into(),
+                text: format!(
+                    "A package is to be downloaded from the Hex repository `{name}`,
+but no repository with that name has been configured."
+                ),
+                level: Level::Error,
+                hint: Some(format!(
+                    "Add the repository to your gleam.toml as `[hex_repositories.{name}]`."
+                )),
+                location: None,
+            }],
+
             Error::CannotPublishTodo { unfinished } => vec![Diagnostic {
                 title: "Cannot publish unfinished code".into(),
                 text: format!(
@@ -954,6 +1065,12 @@ https://gleam.run/getting-started/installing/",
                         "
 Documentation for installing rebar3 can be viewed here:
 https://gleam.run/getting-started/installing/",
+                    ),
+                    "chromium" => text.push_str(
+                        "
+The browser runtime needs a Chromium based browser such as Chromium or
+Google Chrome. If one is installed somewhere other than the PATH then
+set `executable` in the `[javascript.browser]` section of gleam.toml.",
                     ),
                     _ => (),
                 }
@@ -979,6 +1096,21 @@ You can also install rebar3 via homebrew using \"brew install rebar3\"",
                 }]
             }
 
+            Error::BrowserRuntime { reason } => {
+                let text = format!(
+                    "There was a problem when running the code in the browser:
+
+    {reason}"
+                );
+                vec![Diagnostic {
+                    title: "Browser runtime failure".into(),
+                    text,
+                    hint: None,
+                    level: Level::Error,
+                    location: None,
+                }]
+            }
+
             Error::ShellCommand {
                 program: command,
                 err: None,
@@ -3176,8 +3308,67 @@ Fix the warnings and try again."
                         extra_labels: vec![],
                     }),
                 }],
+                javascript::Error::EsModuleExternal { module, location } => vec![Diagnostic {
+                    title: "ES module external in CommonJS project".into(),
+                    text: wrap_format!(
+                        "This function is implemented in the ES module `{module}`, \
+which cannot be required by the CommonJS modules generated for this project.
+
+ES module externals are not supported in CommonJS projects, including those \
+in dependency packages. Projects depending on `gleam_stdlib`, or on any other \
+package with `.mjs` externals, cannot use the CommonJS module format."
+                    ),
+                    hint: Some(
+                        "Implement the function in a `.cjs` module, or use the default ES \
+module format for this project."
+                            .into(),
+                    ),
+                    level: Level::Error,
+                    location: Some(Location {
+                        label: Label {
+                            text: None,
+                            span: *location,
+                        },
+                        path: path.clone(),
+                        src: src.clone(),
+                        extra_labels: vec![],
+                    }),
+                }],
             },
 
+            Error::JavaScriptBundle { error } => {
+                let text = match error {
+                    javascript::BundleError::UnsupportedSyntax { path, line, syntax } => {
+                        wrap_format!(
+                            "The JavaScript module {path} uses {syntax} on line {line}, \
+which cannot be included in a bundle."
+                        )
+                    }
+                    javascript::BundleError::ImportCycle { modules } => {
+                        let mut text =
+                            "These JavaScript modules import each other and re-export \
+everything from one another:\n"
+                                .into();
+                        write_cycle(&mut text, modules);
+                        text.push_str(
+                            "`export * from` cannot be bundled when it is part of an import cycle.",
+                        );
+                        text
+                    }
+                    javascript::BundleError::UnknownModule { path, importer } => wrap_format!(
+                        "The JavaScript module {importer} imports {path}, \
+but there is no such module in the build directory."
+                    ),
+                };
+                vec![Diagnostic {
+                    title: "Failed to bundle JavaScript".into(),
+                    text,
+                    hint: None,
+                    location: None,
+                    level: Level::Error,
+                }]
+            }
+
             Error::DownloadPackageError {
                 package_name,
                 package_version,
@@ -3246,10 +3437,9 @@ The error from the parser was:
                 let text = format!(
                     "An error occurred while determining what dependency packages and
 versions should be downloaded.
-The error from the version resolver library was:
 
 {}",
-                    wrap(error)
+                    error.lines().map(wrap).join("\n")
                 );
                 vec![Diagnostic {
                     title: "Dependency resolution failed".into(),
@@ -3260,6 +3450,155 @@ The error from the version resolver library was:
                 }]
             }
 
+            Error::PackageInterfaceUnavailable { package, version } => vec![Diagnostic {
+                title: "Package interface unavailable".into(),
+                text: format!(
+                    "The documentation of {package} v{version} on HexDocs does not include
+a package interface, so its API cannot be compared."
+                ),
+                hint: Some(
+                    "Export the interface of that version with `gleam export package-interface`
+and compare with the exported file instead."
+                        .into(),
+                ),
+                location: None,
+                level: Level::Error,
+            }],
+
+            Error::UnmarkedBreakingChange {
+                package,
+                previous_version,
+                version,
+                minimum_version,
+                changes,
+            } => {
+                let changes = changes
+                    .iter()
+                    .map(|change| format!("  - {change}"))
+                    .join("\n");
+                vec![Diagnostic {
+                    title: "Breaking change without a major version".into(),
+                    text: format!(
+                        "The public API of {package} has these breaking changes since
+version {previous_version}, but version {version} does not mark them as breaking:
+
+{changes}"
+                    ),
+                    hint: Some(format!(
+                        "Publish these changes as version {minimum_version} or later."
+                    )),
+                    location: None,
+                    level: Level::Error,
+                }]
+            }
+
+            Error::OfflineDependencyResolutionFailed { error, unavailable } => {
+                let unavailable = unavailable
+                    .iter()
+                    .map(|package| format!("  - {package}"))
+                    .join("\n");
+                let text = format!(
+                    "Versions were resolved using only the packages in the global
+package cache, and these requirements could not be satisfied by them:
+
+{unavailable}
+
+The error from the version resolver library was:
+
+{}",
+                    wrap(error)
+                );
+                vec![Diagnostic {
+                    title: "Dependency resolution failed offline".into(),
+                    text,
+                    hint: Some(
+                        "Connect to the internet so the missing packages can be downloaded."
+                            .into(),
+                    ),
+                    location: None,
+                    level: Level::Error,
+                }]
+            }
+
+            Error::LicencePolicyViolation { violations } => {
+                let violations = violations
+                    .iter()
+                    .map(|violation| {
+                        let licences = if violation.licences.is_empty() {
+                            "which declares no licence".into()
+                        } else {
+                            format!("licensed under {}", violation.licences.join(", "))
+                        };
+                        format!(
+                            "  - {} {}, {licences}\n    {}",
+                            violation.package, violation.version, violation.dependency_path
+                        )
+                    })
+                    .join("\n");
+                vec![Diagnostic {
+                    title: "Licence policy violation".into(),
+                    text: format!(
+                        "These dependency packages do not meet the licence policy in gleam.toml:
+
+{violations}"
+                    ),
+                    hint: Some(
+                        "Replace them with packages under allowed licences, or change the
+`[licence_policy]` section of gleam.toml."
+                            .into(),
+                    ),
+                    location: None,
+                    level: Level::Error,
+                }]
+            }
+
+            Error::VendoredPackageMismatch { package, problem } => {
+                let text = match problem {
+                    VendoredPackageProblem::NotVendored => format!(
+                        "The package {package} is in the manifest but it has not been vendored."
+                    ),
+                    VendoredPackageProblem::WrongVersion { vendored, locked } => format!(
+                        "The manifest locks {package} to v{locked} but v{vendored} has been vendored."
+                    ),
+                    VendoredPackageProblem::WrongChecksum => format!(
+                        "The vendored package {package} was taken from a different release than
+the one recorded in the manifest."
+                    ),
+                    VendoredPackageProblem::SourcesChanged => format!(
+                        "The sources in vendor/{package} have been changed since the package
+was vendored."
+                    ),
+                };
+                vec![Diagnostic {
+                    title: "Vendored package mismatch".into(),
+                    text,
+                    hint: Some(
+                        "Run `gleam deps vendor` to vendor the dependency packages again.".into(),
+                    ),
+                    location: None,
+                    level: Level::Error,
+                }]
+            }
+
+            Error::CachedPackageChecksumMismatch {
+                package,
+                version,
+                path,
+            } => vec![Diagnostic {
+                title: "Cached package checksum mismatch".into(),
+                text: format!(
+                    "The tarball of {package} v{version} in the package cache does not match
+the checksum recorded in the manifest. It may have been corrupted or edited.
+
+    {path}"
+                ),
+                hint: Some(
+                    "Delete the tarball so that the package is downloaded again.".into(),
+                ),
+                location: None,
+                level: Level::Error,
+            }],
+
             Error::GitDependencyUnsupported => vec![Diagnostic {
                 title: "Git dependencies are not currently supported".into(),
                 text: "Please remove all git dependencies from the gleam.toml file".into(),
@@ -3361,6 +3700,39 @@ because dependency `{package}` is not a Hex dependency.",
                 level: Level::Error,
             }],
 
+            Error::PublishHexRepositoryDependency {
+                package,
+                repository,
+            } => vec![Diagnostic {
+                title: "Unpublished dependencies".into(),
+                text: wrap_format!(
+                    "The package cannot be published to Hex \
+because dependency `{package}` is from the Hex repository `{repository}` \
+rather than hex.pm.",
+                ),
+                hint: None,
+                location: None,
+                level: Level::Error,
+            }],
+
+            Error::HexRepositoryAuthKeyNotSet {
+                repository,
+                variable,
+            } => vec![Diagnostic {
+                title: "Missing Hex repository key".into(),
+                text: wrap_format!(
+                    "The Hex repository `{repository}` is configured to \
+authenticate with the key in the environment variable `{variable}`, \
+but that variable is not set.",
+                ),
+                hint: Some(format!(
+                    "Set `{variable}` to your key for the repository, or remove \
+`auth_key_env` from `[hex_repositories.{repository}]` in your gleam.toml."
+                )),
+                location: None,
+                level: Level::Error,
+            }],
+
             Error::UnsupportedBuildTool {
                 package,
                 build_tools,
@@ -3443,6 +3815,24 @@ but you are using v{gleam_version}.",
                 }]
             }
 
+            Error::NodePermissionsWithoutPermissionModel { settings } => {
+                let settings = settings.iter().map(|setting| format!("`{setting}`")).join(", ");
+                vec![Diagnostic {
+                    title: "Node permissions without the permission model".into(),
+                    text: wrap_format!(
+                        "The {settings} settings in the `[javascript.node]` section of \
+gleam.toml only have an effect when Node's permission model is enabled."
+                    ),
+                    hint: Some(
+                        "Add `permission = true` to the `[javascript.node]` section, or remove \
+these settings."
+                            .into(),
+                    ),
+                    location: None,
+                    level: Level::Error,
+                }]
+            }
+
             Error::JavaScriptPreludeRequired => vec![Diagnostic {
                 title: "JavaScript prelude required".into(),
                 text: "The --javascript-prelude flag must be given when compiling to JavaScript."
@@ -5,15 +5,11 @@
 //! <https://github.com/yorickpeterse/pattern-matching-in-rust>. Thank you Yorick!
 //!
 //! Note that while this produces a decision tree, this tree is not suitable for
-//! use in code generation yet as it is incomplete. The tree is not correctly
-//! formed for:
-//! - Bit strings
-//! - String prefixes
+//! use in code generation as it is only built to check the exhaustiveness and
+//! reachability of patterns.
 //!
-//! These were not implemented as they are more complex and I've not worked out
-//! a good way to do them yet. The tricky bit is that unlike the others they are
-//! not an exact match and they can overlap with other patterns. Take this
-//! example:
+//! Unlike the others, string prefix patterns are not an exact match and they
+//! can overlap with other patterns. Take this example:
 //!
 //! ```text
 //! case x {
@@ -24,8 +20,17 @@
 //! }
 //! ```
 //!
-//! The decision tree needs to take into account that the first pattern is a
-//! super-pattern of the second, and the second is a super-pattern of the third.
+//! The first pattern is a super-pattern of the second, and the second is a
+//! super-pattern of the third. To handle this the cases of a switch on a
+//! string are ordered from the most specific test to the least specific, with
+//! the first case to match being taken, and each row is added to the cases of
+//! all the tests that its pattern includes.
+//!
+//! Bit arrays are checked as if they were lists of bits, each bit being a
+//! `Bool`. Segments with a literal value become the bits of that value, and
+//! other fixed size segments become that many discarded bits. Patterns with
+//! segments that can't be broken down like this, such as floats or segments
+//! with a size taken from a variable, are only taken to match themselves.
 //!
 
 mod missing_patterns;
@@ -37,14 +42,14 @@ use self::pattern::{Constructor, Pattern, PatternId};
 use crate::{
     ast::AssignName,
     type_::{
-        collapse_links, error::UnknownTypeConstructorError, is_prelude_module, Environment, Type,
-        TypeValueConstructor, TypeValueConstructorField, TypeVar,
+        self, collapse_links, error::UnknownTypeConstructorError, is_prelude_module, Environment,
+        Type, TypeValueConstructor, TypeValueConstructorField, TypeVar,
     },
 };
 use ecow::EcoString;
 use id_arena::Arena;
 use itertools::Itertools;
-use std::{cell::RefCell, collections::HashMap, sync::Arc};
+use std::{cell::RefCell, cmp::Reverse, collections::HashMap, sync::Arc};
 
 pub use self::pattern::PatternArena;
 
@@ -185,7 +190,8 @@ pub enum Decision {
     /// 3. A fallback decision to take, in case none of the cases matched.
     Switch(Variable, Vec<Case>, Option<Box<Decision>>),
 
-    /// Checks if a list is empty or non-empty.
+    /// Checks if a list is empty or non-empty. This is also used for bit
+    /// arrays, which are checked as if they were lists of bits.
     List {
         /// The variable to test.
         variable: Variable,
@@ -283,6 +289,9 @@ impl<'a> Compiler<'a> {
             | Pattern::Variable { .. }
             | Pattern::EmptyList
             | Pattern::BitArray { .. }
+            | Pattern::EmptyBitArray
+            | Pattern::BitArrayBit { .. }
+            | Pattern::BitArrayBytes { .. }
             | Pattern::Constructor { .. }
             | Pattern::StringPrefix { .. } => vec![(id, row)],
         }
@@ -316,7 +325,7 @@ impl<'a> Compiler<'a> {
         match self.branch_mode(&rows) {
             BranchMode::Infinite { variable } => {
                 let (cases, fallback) = self.compile_infinite_cases(rows, variable.clone());
-                Decision::Switch(variable, cases, Some(fallback))
+                Decision::Switch(variable, cases, fallback)
             }
 
             BranchMode::Tuple { variable, types } => {
@@ -331,6 +340,8 @@ impl<'a> Compiler<'a> {
                 element_type,
             } => self.compile_list_cases(rows, variable, element_type),
 
+            BranchMode::BitArray { variable } => self.compile_bit_array_cases(rows, variable),
+
             BranchMode::NamedType {
                 variable,
                 constructors,
@@ -361,31 +372,25 @@ impl<'a> Compiler<'a> {
 
     /// String, ints and floats have an infinite number of constructors, so we
     /// specialise the compilation of their patterns with this function.
+    ///
+    /// String prefix patterns can overlap with other patterns, so the tests
+    /// are sorted from the most specific to the least specific, and each row
+    /// is added to the case of every test its pattern includes. The first case
+    /// whose test matches is the one that is taken.
     fn compile_infinite_cases(
         &mut self,
         rows: Vec<Row>,
         branch_var: Variable,
-    ) -> (Vec<Case>, Box<Decision>) {
-        let mut raw_cases: Vec<(Constructor, Vec<Variable>, Vec<Row>)> = Vec::new();
-        let mut fallback_rows = Vec::new();
-        let mut tested: HashMap<TestKey, usize> = HashMap::new();
-
-        #[derive(Debug, PartialEq, Eq, Hash)]
-        enum TestKey {
-            Prefix(EcoString),
-            Exact(EcoString),
-        }
+    ) -> (Vec<Case>, Option<Box<Decision>>) {
+        let mut tests: Vec<(InfiniteTest, Constructor)> = Vec::new();
+        let mut test_rows: Vec<(Option<InfiniteTest>, Row)> = Vec::with_capacity(rows.len());
 
-        let branch_variable_id = branch_var.id;
         for mut row in rows {
-            let col = match row.remove_column(branch_variable_id) {
-                // This row does not match on the branch variable, so we push it
-                // into the fallback rows to be tested later.
+            let col = match row.remove_column(branch_var.id) {
+                // This row does not match on the branch variable, so it is
+                // tested whatever the value is.
                 None => {
-                    fallback_rows.push(row.clone());
-                    for (_, _, rows) in &mut raw_cases {
-                        rows.push(row.clone());
-                    }
+                    test_rows.push((None, row));
                     continue;
                 }
                 // This row does match on the branch variable.
@@ -393,23 +398,19 @@ impl<'a> Compiler<'a> {
             };
 
             for (pattern, mut row) in self.flatten_or(col.pattern, row) {
-                let (key, constructor) = match self.pattern(pattern) {
+                let (test, constructor) = match self.pattern(pattern) {
                     Pattern::Int { value } => (
-                        TestKey::Exact(value.clone()),
+                        InfiniteTest::Exact(value.clone()),
                         Constructor::Int(value.clone()),
                     ),
 
                     Pattern::Float { value } => (
-                        TestKey::Exact(value.clone()),
+                        InfiniteTest::Exact(value.clone()),
                         Constructor::Float(value.clone()),
                     ),
 
-                    Pattern::BitArray { value } => {
-                        (TestKey::Exact(value.clone()), Constructor::BitArray)
-                    }
-
                     Pattern::String { value } => (
-                        TestKey::Exact(value.clone()),
+                        InfiniteTest::Exact(value.clone()),
                         Constructor::String(value.clone()),
                     ),
 
@@ -424,7 +425,7 @@ impl<'a> Compiler<'a> {
                                 row.columns.push(Column::new(branch_var.clone(), pattern));
                             }
                         };
-                        (TestKey::Prefix(prefix.clone()), Constructor::StringPrefix)
+                        (InfiniteTest::Prefix(prefix), Constructor::StringPrefix)
                     }
 
                     pattern @ (Pattern::Constructor { .. }
@@ -434,33 +435,55 @@ impl<'a> Compiler<'a> {
                     | Pattern::Discard
                     | Pattern::EmptyList
                     | Pattern::List { .. }
+                    | Pattern::BitArray { .. }
+                    | Pattern::EmptyBitArray
+                    | Pattern::BitArrayBit { .. }
+                    | Pattern::BitArrayBytes { .. }
                     | Pattern::Or { .. }) => panic!("Unexpected pattern {:?}", pattern),
                 };
 
-                match tested.get(&key) {
-                    // This value has already been tested, so this is a redundant test.
-                    // Add the row to the previous test rather than duplicating it.
-                    Some(index) => {
-                        let case = raw_cases.get_mut(*index).expect("Case must exist");
-                        case.2.push(row);
-                    }
-                    // This is the first time testing the tag, so we add a case for it.
-                    None => {
-                        _ = tested.insert(key, raw_cases.len());
-                        let mut rows = fallback_rows.clone();
-                        rows.push(row);
-                        raw_cases.push((constructor, Vec::new(), rows));
-                    }
+                // This is the first time testing the value, so we add a case
+                // for it.
+                if !tests.iter().any(|(tested, _)| tested == &test) {
+                    tests.push((test.clone(), constructor));
                 }
+                test_rows.push((Some(test), row));
             }
         }
 
-        let cases = raw_cases
+        tests.sort_by_key(|(test, _)| test.generality());
+
+        // A prefix of "" matches every string, so there is no fallback.
+        let fallback = if tests
+            .iter()
+            .any(|(test, _)| test == &InfiniteTest::Prefix("".into()))
+        {
+            None
+        } else {
+            let rows = test_rows
+                .iter()
+                .filter(|(test, _)| test.is_none())
+                .map(|(_, row)| row.clone())
+                .collect();
+            Some(Box::new(self.compile_rows(rows)))
+        };
+
+        let cases = tests
             .into_iter()
-            .map(|(cons, vars, rows)| Case::new(cons, vars, self.compile_rows(rows)))
+            .map(|(test, constructor)| {
+                let rows = test_rows
+                    .iter()
+                    .filter(|(row_test, _)| match row_test {
+                        None => true,
+                        Some(row_test) => row_test.includes(&test),
+                    })
+                    .map(|(_, row)| row.clone())
+                    .collect();
+                Case::new(constructor, Vec::new(), self.compile_rows(rows))
+            })
             .collect();
 
-        (cases, Box::new(self.compile_rows(fallback_rows)))
+        (cases, fallback)
     }
 
     /// Compiles the cases and sub cases for the constructor located at the
@@ -530,6 +553,9 @@ impl<'a> Compiler<'a> {
                     | Pattern::Discard
                     | Pattern::Variable { .. }
                     | Pattern::BitArray { .. }
+                    | Pattern::EmptyBitArray
+                    | Pattern::BitArrayBit { .. }
+                    | Pattern::BitArrayBytes { .. }
                     | Pattern::EmptyList
                     | Pattern::StringPrefix { .. }) => panic!("Unexpected pattern {:?}", pattern),
                 };
@@ -602,6 +628,9 @@ impl<'a> Compiler<'a> {
                     | Pattern::Assign { .. }
                     | Pattern::Variable { .. }
                     | Pattern::BitArray { .. }
+                    | Pattern::EmptyBitArray
+                    | Pattern::BitArrayBit { .. }
+                    | Pattern::BitArrayBytes { .. }
                     | Pattern::Constructor { .. }
                     | Pattern::StringPrefix { .. }) => {
                         panic!("Unexpected non-list pattern {:?}", pattern)
@@ -621,6 +650,173 @@ impl<'a> Compiler<'a> {
         }
     }
 
+    /// Compiles the cases for a bit array, which is matched on as if it were a
+    /// list of bits.
+    ///
+    /// Patterns that can't be checked bit by bit are tested first, each in a
+    /// case of its own. The remaining patterns then either test whether there
+    /// is another bit, or once there are only `bytes` segments left, how many
+    /// bits are left over after the last whole byte.
+    fn compile_bit_array_cases(&mut self, rows: Vec<Row>, branch_var: Variable) -> Decision {
+        let mut pattern_rows: Vec<(Option<PatternId>, Row)> = Vec::with_capacity(rows.len());
+        for mut row in rows {
+            match row.remove_column(branch_var.id) {
+                None => pattern_rows.push((None, row)),
+                Some(column) => pattern_rows.extend(
+                    self.flatten_or(column.pattern, row)
+                        .into_iter()
+                        .map(|(pattern, row)| (Some(pattern), row)),
+                ),
+            }
+        }
+
+        let mut opaque_values: Vec<EcoString> = Vec::new();
+        let mut has_bits = false;
+        for (pattern, _) in &pattern_rows {
+            match pattern.map(|pattern| self.pattern(pattern)) {
+                Some(Pattern::BitArray { value }) if !opaque_values.contains(value) => {
+                    opaque_values.push(value.clone())
+                }
+                Some(Pattern::EmptyBitArray | Pattern::BitArrayBit { .. }) => has_bits = true,
+                _ => (),
+            }
+        }
+
+        // Each pattern that can't be checked bit by bit gets a case of its own.
+        // Within that case any other such pattern is taken not to match, and
+        // the fallback is only taken when none of them match.
+        if !opaque_values.is_empty() {
+            let mut case_rows = Vec::with_capacity(opaque_values.len());
+            for value in &opaque_values {
+                let mut rows = Vec::new();
+                for (pattern, row) in &pattern_rows {
+                    match pattern.map(|pattern| (pattern, self.pattern(pattern))) {
+                        None => rows.push(row.clone()),
+                        Some((_, Pattern::BitArray { value: other })) if other == value => {
+                            rows.push(row.clone())
+                        }
+                        Some((_, Pattern::BitArray { .. })) => (),
+                        Some((pattern, _)) => {
+                            let mut row = row.clone();
+                            row.columns.push(Column::new(branch_var.clone(), pattern));
+                            rows.push(row);
+                        }
+                    }
+                }
+                case_rows.push(rows);
+            }
+
+            let mut fallback_rows = Vec::new();
+            for (pattern, mut row) in pattern_rows {
+                match pattern {
+                    None => fallback_rows.push(row),
+                    Some(pattern) => {
+                        if let Pattern::BitArray { .. } = self.pattern(pattern) {
+                            continue;
+                        }
+                        row.columns.push(Column::new(branch_var.clone(), pattern));
+                        fallback_rows.push(row);
+                    }
+                }
+            }
+
+            let cases = case_rows
+                .into_iter()
+                .map(|rows| Case::new(Constructor::BitArray, Vec::new(), self.compile_rows(rows)))
+                .collect();
+            let fallback = self.compile_rows(fallback_rows);
+            return Decision::Switch(branch_var, cases, Some(Box::new(fallback)));
+        }
+
+        // Once only `bytes` segments are left there are no more bits to test,
+        // only how many bits there are after the last whole byte.
+        if !has_bits {
+            let cases = (0..8)
+                .map(|remainder| {
+                    let rows = pattern_rows
+                        .iter()
+                        .filter(|(pattern, _)| match pattern.map(|p| self.pattern(p)) {
+                            Some(Pattern::BitArrayBytes { remainder: other }) => {
+                                *other == remainder
+                            }
+                            _ => true,
+                        })
+                        .map(|(_, row)| row.clone())
+                        .collect();
+                    Case::new(
+                        Constructor::BitArrayRemainder(remainder),
+                        Vec::new(),
+                        self.compile_rows(rows),
+                    )
+                })
+                .collect();
+            return Decision::Switch(branch_var, cases, None);
+        }
+
+        let mut empty_rows = vec![];
+        let mut non_empty_rows = vec![];
+        let bit_var = self.new_variable(type_::bool());
+        let rest_var = self.new_variable(branch_var.type_.clone());
+
+        for (pattern, mut row) in pattern_rows {
+            let Some(pattern) = pattern else {
+                empty_rows.push(row.clone());
+                non_empty_rows.push(row);
+                continue;
+            };
+
+            match self.pattern(pattern) {
+                Pattern::EmptyBitArray => empty_rows.push(row),
+
+                // The bit is added last so that it is branched on before the
+                // rest of the bit array.
+                Pattern::BitArrayBit { bit, rest } => {
+                    row.columns.push(Column::new(rest_var.clone(), *rest));
+                    row.columns.push(Column::new(bit_var.clone(), *bit));
+                    non_empty_rows.push(row);
+                }
+
+                Pattern::BitArrayBytes { remainder } => {
+                    let remainder = *remainder;
+                    if remainder == 0 {
+                        empty_rows.push(row.clone());
+                    }
+                    let rest = self.patterns.alloc(Pattern::BitArrayBytes {
+                        remainder: (remainder + 7) % 8,
+                    });
+                    row.columns.push(Column::new(rest_var.clone(), rest));
+                    non_empty_rows.push(row);
+                }
+
+                pattern @ (Pattern::Or { .. }
+                | Pattern::Int { .. }
+                | Pattern::Float { .. }
+                | Pattern::Tuple { .. }
+                | Pattern::String { .. }
+                | Pattern::Discard
+                | Pattern::Assign { .. }
+                | Pattern::Variable { .. }
+                | Pattern::BitArray { .. }
+                | Pattern::EmptyList
+                | Pattern::List { .. }
+                | Pattern::Constructor { .. }
+                | Pattern::StringPrefix { .. }) => {
+                    panic!("Unexpected non-bit array pattern {:?}", pattern)
+                }
+            }
+        }
+
+        Decision::List {
+            variable: branch_var,
+            empty: Box::new(self.compile_rows(empty_rows)),
+            non_empty: Box::new(NonEmptyListDecision {
+                first: bit_var,
+                rest: rest_var,
+                decision: self.compile_rows(non_empty_rows),
+            }),
+        }
+    }
+
     /// Moves variable-only patterns/tests into the right-hand side/body of a
     /// case.
     ///
@@ -675,6 +871,9 @@ impl<'a> Compiler<'a> {
                 | Pattern::String { .. }
                 | Pattern::EmptyList
                 | Pattern::BitArray { .. }
+                | Pattern::EmptyBitArray
+                | Pattern::BitArrayBit { .. }
+                | Pattern::BitArrayBytes { .. }
                 | Pattern::Constructor { .. }
                 | Pattern::StringPrefix { .. } => {
                     next = iterator.next();
@@ -723,14 +922,15 @@ impl<'a> Compiler<'a> {
 
             Type::Named { module, name, .. }
                 if is_prelude_module(module)
-                    && (name == "Int"
-                        || name == "Float"
-                        || name == "String"
-                        || name == "BitArray") =>
+                    && (name == "Int" || name == "Float" || name == "String") =>
             {
                 BranchMode::Infinite { variable }
             }
 
+            Type::Named { module, name, .. } if is_prelude_module(module) && name == "BitArray" => {
+                BranchMode::BitArray { variable }
+            }
+
             Type::Named {
                 module, name, args, ..
             } if is_prelude_module(module) && name == "List" => BranchMode::List {
@@ -805,6 +1005,36 @@ impl<'a> Compiler<'a> {
     }
 }
 
+/// A test made by a case of a switch on a value with an infinite number of
+/// constructors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum InfiniteTest {
+    Exact(EcoString),
+    Prefix(EcoString),
+}
+
+impl InfiniteTest {
+    /// Whether every value that passes the `other` test also passes this one.
+    fn includes(&self, other: &InfiniteTest) -> bool {
+        match (self, other) {
+            (InfiniteTest::Exact(value), InfiniteTest::Exact(other)) => value == other,
+            (InfiniteTest::Exact(_), InfiniteTest::Prefix(_)) => false,
+            (
+                InfiniteTest::Prefix(prefix),
+                InfiniteTest::Exact(other) | InfiniteTest::Prefix(other),
+            ) => other.starts_with(prefix.as_str()),
+        }
+    }
+
+    /// Sorting by this puts each test before any test that includes it.
+    fn generality(&self) -> (bool, Reverse<usize>) {
+        match self {
+            InfiniteTest::Exact(_) => (false, Reverse(0)),
+            InfiniteTest::Prefix(prefix) => (true, Reverse(prefix.len())),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 enum BranchMode {
     Infinite {
@@ -818,6 +1048,9 @@ enum BranchMode {
         variable: Variable,
         element_type: Arc<Type>,
     },
+    BitArray {
+        variable: Variable,
+    },
     NamedType {
         variable: Variable,
         /// The constructors for this type. For example, `Result` has `Ok` and `Error`.
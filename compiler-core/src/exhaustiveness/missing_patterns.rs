@@ -38,6 +38,14 @@ enum Term {
         first: Variable,
         rest: Variable,
     },
+    EmptyBitArray {
+        variable: Variable,
+    },
+    BitArray {
+        variable: Variable,
+        bit: Variable,
+        rest: Variable,
+    },
 }
 
 impl Term {
@@ -47,6 +55,8 @@ impl Term {
             Term::Infinite { variable } => variable,
             Term::EmptyList { variable } => variable,
             Term::List { variable, .. } => variable,
+            Term::EmptyBitArray { variable } => variable,
+            Term::BitArray { variable, .. } => variable,
         }
     }
 
@@ -80,12 +90,74 @@ impl Term {
             Term::EmptyList { .. } => "[]".into(),
 
             Term::List { .. } => format!("[{}]", self.list_pattern_string(terms, mapping)).into(),
+
+            Term::EmptyBitArray { .. } | Term::BitArray { .. } => {
+                format!("<<{}>>", self.bit_array_pattern_string(terms, mapping)).into()
+            }
+        }
+    }
+
+    /// The segments of a bit array pattern. Bits with a known value are
+    /// grouped into bytes where possible, and bits with any value are grouped
+    /// into discarded segments.
+    fn bit_array_pattern_string(&self, terms: &[Term], mapping: &HashMap<usize, usize>) -> String {
+        let term_for = |variable: &Variable| {
+            mapping
+                .get(&variable.id)
+                .map(|&idx| terms.get(idx).expect("Term must exist"))
+        };
+
+        let mut bits = vec![];
+        let mut any_rest = false;
+        let mut term = Some(self);
+        loop {
+            match term {
+                Some(Term::BitArray { bit, rest, .. }) => {
+                    // Bits are checked as `Bool`s, with `True` for a set bit.
+                    bits.push(match term_for(bit) {
+                        Some(Term::Variant { name, .. }) => Some(name == "True"),
+                        _ => None,
+                    });
+                    term = term_for(rest);
+                }
+                Some(Term::EmptyBitArray { .. }) => break,
+                _ => {
+                    any_rest = true;
+                    break;
+                }
+            }
+        }
+
+        let mut segments = vec![];
+        for (known, group) in &bits.into_iter().chunk_by(|bit| bit.is_some()) {
+            let group = group.collect_vec();
+            if !known {
+                segments.push(format!("_:size({})", group.len()));
+                continue;
+            }
+            for byte in group.chunks(8) {
+                let value = byte.iter().fold(0_u8, |value, bit| {
+                    (value << 1) | u8::from(*bit == Some(true))
+                });
+                if byte.len() == 8 {
+                    segments.push(value.to_string());
+                } else {
+                    segments.push(format!("{value}:size({})", byte.len()));
+                }
+            }
+        }
+        if any_rest {
+            segments.push("_:bits".into());
         }
+        segments.join(", ")
     }
 
     fn list_pattern_string(&self, terms: &[Term], mapping: &HashMap<usize, usize>) -> EcoString {
         match self {
-            Term::Infinite { .. } | Term::Variant { .. } => "_".into(),
+            Term::Infinite { .. }
+            | Term::Variant { .. }
+            | Term::EmptyBitArray { .. }
+            | Term::BitArray { .. } => "_".into(),
 
             Term::EmptyList { .. } => "".into(),
 
@@ -167,6 +239,7 @@ fn add_missing_patterns(
                     | Constructor::Float(_)
                     | Constructor::String(_)
                     | Constructor::BitArray
+                    | Constructor::BitArrayRemainder(_)
                     | Constructor::StringPrefix => {
                         terms.push(Term::Infinite {
                             variable: variable.clone(),
@@ -210,6 +283,26 @@ fn add_missing_patterns(
             }
         }
 
+        Decision::List {
+            variable,
+            empty,
+            non_empty,
+        } if variable.type_.is_bit_array() => {
+            terms.push(Term::EmptyBitArray {
+                variable: variable.clone(),
+            });
+            add_missing_patterns(empty, terms, missing, environment);
+            _ = terms.pop();
+
+            terms.push(Term::BitArray {
+                variable: variable.clone(),
+                bit: non_empty.first.clone(),
+                rest: non_empty.rest.clone(),
+            });
+            add_missing_patterns(&non_empty.decision, terms, missing, environment);
+            _ = terms.pop();
+        }
+
         Decision::List {
             variable,
             empty,
@@ -1,6 +1,6 @@
 use crate::{
-    ast::{AssignName, TypedPattern},
-    type_::Type,
+    ast::{AssignName, BitArrayOption, TypedPattern, TypedPatternBitArraySegment},
+    type_::{self, Type},
 };
 use ecow::EcoString;
 use id_arena::{Arena, Id};
@@ -51,7 +51,26 @@ pub enum Pattern {
         rest: PatternId,
     },
     EmptyList,
-    // TODO: Compile the matching within the bit strings
+    /// A bit array with no more bits in it.
+    ///
+    /// Bit arrays are matched on as if they were lists of bits, so that
+    /// patterns with differently sized segments can be compared.
+    EmptyBitArray,
+    /// A bit array with at least one more bit. The `bit` pattern matches on a
+    /// `Bool`, with `True` standing for a set bit.
+    BitArrayBit {
+        bit: PatternId,
+        rest: PatternId,
+    },
+    /// Any number of remaining bits, so long as dividing that number by 8
+    /// leaves `remainder` over. This is what a `bytes` segment without a size
+    /// matches.
+    BitArrayBytes {
+        remainder: u8,
+    },
+    /// A bit array pattern with segments that cannot be checked bit by bit,
+    /// such as floats or sizes taken from variables. Each of these is given
+    /// a unique value, so it is only ever taken to match itself.
     BitArray {
         value: EcoString,
     },
@@ -65,9 +84,8 @@ pub enum Constructor {
     Tuple(Vec<Arc<Type>>),
     String(EcoString),
     Variant { type_: Arc<Type>, index: u16 },
-    // TODO: Generate a decision tree for this
     BitArray,
-    // TODO: Generate a decision tree for this
+    BitArrayRemainder(u8),
     StringPrefix,
 }
 
@@ -80,6 +98,7 @@ impl Constructor {
             | Constructor::Tuple(_)
             | Constructor::String(_)
             | Constructor::BitArray
+            | Constructor::BitArrayRemainder(_)
             | Constructor::StringPrefix => 0,
 
             Constructor::Variant { index, .. } => *index,
@@ -170,15 +189,14 @@ impl PatternArena {
                 })
             }
 
-            TypedPattern::BitArray { location, .. } => {
-                // TODO: in future support bit strings fully and check the
-                // exhaustiveness of their segment patterns.
-                // For now we use the location to give each bit string a pattern
-                // a unique value.
-                self.insert(Pattern::BitArray {
+            TypedPattern::BitArray { location, segments } => match segments_bits(segments) {
+                Some((bits, tail)) => self.register_bits(bits, tail),
+                // Segments that can't be broken down into bits are given a
+                // unique value using their location.
+                None => self.insert(Pattern::BitArray {
                     value: format!("{}:{}", location.start, location.end).into(),
-                })
-            }
+                }),
+            },
 
             TypedPattern::StringPrefix {
                 left_side_string,
@@ -196,6 +214,28 @@ impl PatternArena {
         }
     }
 
+    fn register_bits(&mut self, bits: Vec<Option<bool>>, tail: BitsTail) -> PatternId {
+        let mut pattern = match tail {
+            BitsTail::Empty => self.insert(Pattern::EmptyBitArray),
+            BitsTail::Bits => self.insert(Pattern::Discard),
+            BitsTail::Bytes => self.insert(Pattern::BitArrayBytes { remainder: 0 }),
+        };
+        for bit in bits.into_iter().rev() {
+            let bit = match bit {
+                None => self.insert(Pattern::Discard),
+                Some(value) => self.insert(Pattern::Constructor {
+                    constructor: Constructor::Variant {
+                        type_: type_::bool(),
+                        index: if value { 0 } else { 1 },
+                    },
+                    arguments: vec![],
+                }),
+            };
+            pattern = self.insert(Pattern::BitArrayBit { bit, rest: pattern });
+        }
+        pattern
+    }
+
     pub fn insert(&mut self, pattern: Pattern) -> PatternId {
         self.arena.alloc(pattern)
     }
@@ -204,3 +244,186 @@ impl PatternArena {
         self.arena
     }
 }
+
+/// Bit array patterns longer than this many bits are not checked bit by bit,
+/// to keep the size of the decision tree down.
+const MAXIMUM_CHECKED_BITS: usize = 512;
+
+/// What a bit array pattern matches after its fixed size segments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BitsTail {
+    /// Nothing, the bit array must end.
+    Empty,
+    /// Any number of bits, as matched by a `bits` segment without a size.
+    Bits,
+    /// Any number of whole bytes, as matched by a `bytes` segment without a
+    /// size.
+    Bytes,
+}
+
+/// Breaks the segments of a bit array pattern down into the bits they match,
+/// with `None` for a bit that can have any value.
+///
+/// Returns `None` if any of the segments can't be checked this way, such as
+/// floats, UTF-8 code points and segments with a size taken from a variable.
+///
+fn segments_bits(
+    segments: &[TypedPatternBitArraySegment],
+) -> Option<(Vec<Option<bool>>, BitsTail)> {
+    let mut bits = vec![];
+    let mut tail = BitsTail::Empty;
+
+    for (index, segment) in segments.iter().enumerate() {
+        if tail != BitsTail::Empty {
+            return None;
+        }
+
+        let mut kind = SegmentKind::Int;
+        let mut size = None;
+        let mut unit = None;
+        let mut little_endian = false;
+        let mut signed = false;
+        for option in &segment.options {
+            match option {
+                BitArrayOption::Int { .. } => kind = SegmentKind::Int,
+                BitArrayOption::Bits { .. } => kind = SegmentKind::Bits,
+                BitArrayOption::Bytes { .. } => kind = SegmentKind::Bytes,
+                BitArrayOption::Utf8 { .. } => kind = SegmentKind::Utf8,
+                BitArrayOption::Size { value, .. } => match value.as_ref() {
+                    TypedPattern::Int { value, .. } => size = Some(parse_int(value)?),
+                    _ => return None,
+                },
+                BitArrayOption::Unit { value, .. } => unit = Some(*value as i128),
+                BitArrayOption::Signed { .. } => signed = true,
+                BitArrayOption::Unsigned { .. } | BitArrayOption::Big { .. } => (),
+                BitArrayOption::Little { .. } => little_endian = true,
+                BitArrayOption::Float { .. }
+                | BitArrayOption::Utf16 { .. }
+                | BitArrayOption::Utf32 { .. }
+                | BitArrayOption::Utf8Codepoint { .. }
+                | BitArrayOption::Utf16Codepoint { .. }
+                | BitArrayOption::Utf32Codepoint { .. }
+                | BitArrayOption::Native { .. } => return None,
+            }
+        }
+        if let TypedPattern::String { .. } = segment.value.as_ref() {
+            kind = SegmentKind::Utf8;
+        }
+
+        let is_last = index + 1 == segments.len();
+        match kind {
+            SegmentKind::Utf8 => {
+                let TypedPattern::String { value, .. } = segment.value.as_ref() else {
+                    return None;
+                };
+                // Escape sequences are kept as written in the source, so only
+                // strings without any are checked.
+                if value.contains('\\') {
+                    return None;
+                }
+                for byte in value.as_bytes() {
+                    bits.extend((0..8).rev().map(|shift| Some((byte >> shift) & 1 == 1)));
+                }
+            }
+
+            SegmentKind::Bits | SegmentKind::Bytes => {
+                if !matches!(
+                    segment.value.as_ref(),
+                    TypedPattern::Variable { .. } | TypedPattern::Discard { .. }
+                ) {
+                    return None;
+                }
+                let unit = unit.unwrap_or(if kind == SegmentKind::Bytes { 8 } else { 1 });
+                match size {
+                    Some(size) => {
+                        let size = usize::try_from(size.checked_mul(unit)?).ok()?;
+                        bits.resize(bits.len() + size.min(MAXIMUM_CHECKED_BITS + 1), None);
+                    }
+                    None if !is_last => return None,
+                    None if unit == 1 => tail = BitsTail::Bits,
+                    None if unit == 8 => tail = BitsTail::Bytes,
+                    None => return None,
+                }
+            }
+
+            SegmentKind::Int => {
+                let size = size.unwrap_or(8).checked_mul(unit.unwrap_or(1))?;
+                let size = usize::try_from(size).ok()?;
+                match segment.value.as_ref() {
+                    TypedPattern::Variable { .. } | TypedPattern::Discard { .. } => {
+                        bits.resize(bits.len() + size.min(MAXIMUM_CHECKED_BITS + 1), None);
+                    }
+                    TypedPattern::Int { value, .. } => {
+                        bits.extend(int_bits(parse_int(value)?, size, signed, little_endian)?);
+                    }
+                    _ => return None,
+                }
+            }
+        }
+
+        if bits.len() > MAXIMUM_CHECKED_BITS {
+            return None;
+        }
+    }
+
+    Some((bits, tail))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SegmentKind {
+    Int,
+    Bits,
+    Bytes,
+    Utf8,
+}
+
+/// The bits of an int segment with a literal value, most significant first.
+/// Returns `None` if the value can never be matched by a segment of the given
+/// size.
+fn int_bits(
+    value: i128,
+    size: usize,
+    signed: bool,
+    little_endian: bool,
+) -> Option<Vec<Option<bool>>> {
+    if size > 126 || (little_endian && !size.is_multiple_of(8)) {
+        return None;
+    }
+    let fits = if signed {
+        size > 0 && value >= -(1 << (size - 1)) && value < 1 << (size - 1)
+    } else {
+        value >= 0 && value < 1 << size
+    };
+    if !fits {
+        return None;
+    }
+
+    let bits: Vec<_> = (0..size)
+        .rev()
+        .map(|shift| Some((value >> shift) & 1 == 1))
+        .collect();
+    if !little_endian {
+        return Some(bits);
+    }
+    Some(bits.rchunks(8).flatten().copied().collect())
+}
+
+/// Parses an int literal as written in Gleam source code.
+fn parse_int(value: &str) -> Option<i128> {
+    let (negative, value) = match value.strip_prefix('-') {
+        Some(value) => (true, value),
+        None => (false, value),
+    };
+    let value = value.replace('_', "").to_lowercase();
+    let (radix, digits) = if let Some(digits) = value.strip_prefix("0x") {
+        (16, digits)
+    } else if let Some(digits) = value.strip_prefix("0o") {
+        (8, digits)
+    } else if let Some(digits) = value.strip_prefix("0b") {
+        (2, digits)
+    } else {
+        (10, value.as_str())
+    };
+    let value = i128::from_str_radix(digits, radix).ok()?;
+    Some(if negative { -value } else { value })
+}
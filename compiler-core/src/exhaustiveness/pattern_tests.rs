@@ -1,5 +1,5 @@
 use crate::{
-    ast::{SrcSpan, TypedPattern},
+    ast::{BitArrayOption, BitArraySegment, SrcSpan, TypedPattern},
     type_,
 };
 
@@ -54,13 +54,35 @@ fn register_string() {
 }
 
 #[test]
-fn register_bit_string() {
+fn register_empty_bit_array() {
     let mut patterns = PatternArena::new();
     let input = TypedPattern::BitArray {
         location: SrcSpan::new(123, 456),
         segments: vec![],
     };
     let id = patterns.register(&input);
+    assert_eq!(patterns.get(id), Some(&Pattern::EmptyBitArray))
+}
+
+#[test]
+fn register_bit_array_with_float_segment() {
+    let mut patterns = PatternArena::new();
+    let input = TypedPattern::BitArray {
+        location: SrcSpan::new(123, 456),
+        segments: vec![BitArraySegment {
+            location: SrcSpan::default(),
+            value: Box::new(TypedPattern::Discard {
+                name: "_".into(),
+                location: SrcSpan::default(),
+                type_: type_::float(),
+            }),
+            options: vec![BitArrayOption::Float {
+                location: SrcSpan::default(),
+            }],
+            type_: type_::float(),
+        }],
+    };
+    let id = patterns.register(&input);
     assert_eq!(
         patterns.get(id),
         Some(&Pattern::BitArray {
@@ -69,6 +91,43 @@ fn register_bit_string() {
     )
 }
 
+#[test]
+fn register_bit_array_with_int_segment() {
+    let mut patterns = PatternArena::new();
+    let input = TypedPattern::BitArray {
+        location: SrcSpan::new(123, 456),
+        segments: vec![BitArraySegment {
+            location: SrcSpan::default(),
+            value: Box::new(TypedPattern::Int {
+                value: "0b10".into(),
+                location: SrcSpan::default(),
+            }),
+            options: vec![BitArrayOption::Size {
+                location: SrcSpan::default(),
+                value: Box::new(TypedPattern::Int {
+                    value: "2".into(),
+                    location: SrcSpan::default(),
+                }),
+                short_form: true,
+            }],
+            type_: type_::int(),
+        }],
+    };
+    let mut id = patterns.register(&input);
+
+    let mut bits = vec![];
+    while let Some(Pattern::BitArrayBit { bit, rest }) = patterns.get(id) {
+        let Some(Pattern::Constructor { constructor, .. }) = patterns.get(*bit) else {
+            panic!()
+        };
+        bits.push(constructor.index());
+        id = *rest;
+    }
+    // `True` has the index 0, and `False` the index 1.
+    assert_eq!(bits, vec![0, 1]);
+    assert_eq!(patterns.get(id), Some(&Pattern::EmptyBitArray));
+}
+
 #[test]
 fn register_variable() {
     let mut patterns = PatternArena::new();
@@ -1,5 +1,8 @@
-use camino::Utf8Path;
+use std::collections::HashMap;
+
+use camino::{Utf8Path, Utf8PathBuf};
 use debug_ignore::DebugIgnore;
+use ecow::EcoString;
 use flate2::read::GzDecoder;
 use futures::future;
 use hexpm::{version::Version, ApiError};
@@ -8,7 +11,7 @@ use tar::Archive;
 use crate::{
     io::{FileSystemReader, FileSystemWriter, HttpClient, TarUnpacker},
     manifest::{ManifestPackage, ManifestPackageSource},
-    paths::{self, ProjectPaths},
+    paths::{PackageCache, ProjectPaths},
     Error, Result,
 };
 
@@ -126,6 +129,15 @@ pub async fn remove_api_key<Http: HttpClient>(
     hexpm::remove_api_key_response(response).map_err(Error::hex)
 }
 
+/// A Hex repository other than hex.pm, as configured in `gleam.toml`, with
+/// the key to authenticate with and the key its registry is signed with.
+#[derive(Debug, Clone)]
+pub struct Repository {
+    pub config: hexpm::Config,
+    pub api_key: Option<String>,
+    pub public_key: Vec<u8>,
+}
+
 #[derive(Debug)]
 pub struct Downloader {
     fs_reader: DebugIgnore<Box<dyn FileSystemReader>>,
@@ -133,6 +145,8 @@ pub struct Downloader {
     http: DebugIgnore<Box<dyn HttpClient>>,
     untar: DebugIgnore<Box<dyn TarUnpacker>>,
     hex_config: hexpm::Config,
+    repositories: HashMap<EcoString, Repository>,
+    package_cache: PackageCache,
     paths: ProjectPaths,
 }
 
@@ -142,6 +156,8 @@ impl Downloader {
         fs_writer: Box<dyn FileSystemWriter>,
         http: Box<dyn HttpClient>,
         untar: Box<dyn TarUnpacker>,
+        repositories: HashMap<EcoString, Repository>,
+        package_cache: PackageCache,
         paths: ProjectPaths,
     ) -> Self {
         Self {
@@ -150,6 +166,8 @@ impl Downloader {
             http: DebugIgnore(http),
             untar: DebugIgnore(untar),
             hex_config: hexpm::Config::new(),
+            repositories,
+            package_cache,
             paths,
         }
     }
@@ -158,17 +176,27 @@ impl Downloader {
         &self,
         package: &ManifestPackage,
     ) -> Result<bool, Error> {
-        let outer_checksum = if let ManifestPackageSource::Hex { outer_checksum } = &package.source
-        {
-            outer_checksum
-        } else {
+        let ManifestPackageSource::Hex {
+            outer_checksum,
+            repository,
+        } = &package.source
+        else {
             panic!("Attempt to download non-hex package from hex")
         };
 
-        let tarball_path = paths::global_package_cache_package_tarball(
-            &package.name,
-            &package.version.to_string(),
-        );
+        let (config, api_key) = match repository {
+            None => (&self.hex_config, None),
+            Some(name) => {
+                let repository = self
+                    .repositories
+                    .get(name)
+                    .ok_or_else(|| Error::UnknownHexRepository { name: name.clone() })?;
+                (&repository.config, repository.api_key.as_deref())
+            }
+        };
+
+        let tarball_path =
+            self.package_tarball_path(repository.as_deref(), &package.name, &package.version)?;
         if self.fs_reader.is_file(&tarball_path) {
             tracing::info!(
                 package = package.name.as_str(),
@@ -186,8 +214,8 @@ impl Downloader {
         let request = hexpm::get_package_tarball_request(
             &package.name,
             &package.version.to_string(),
-            None,
-            &self.hex_config,
+            api_key,
+            config,
         );
         let response = self.http.send(request).await?;
 
@@ -208,11 +236,20 @@ impl Downloader {
         package: &ManifestPackage,
     ) -> Result<bool> {
         let _ = self.ensure_package_downloaded(package).await?;
-        self.extract_package_from_cache(&package.name, &package.version)
+        let repository = match &package.source {
+            ManifestPackageSource::Hex { repository, .. } => repository.as_deref(),
+            _ => None,
+        };
+        self.extract_package_from_cache(repository, &package.name, &package.version)
     }
 
     // It would be really nice if this was async but the library is sync
-    pub fn extract_package_from_cache(&self, name: &str, version: &Version) -> Result<bool> {
+    pub fn extract_package_from_cache(
+        &self,
+        repository: Option<&str>,
+        name: &str,
+        version: &Version,
+    ) -> Result<bool> {
         let contents_path = Utf8Path::new("contents.tar.gz");
         let destination = self.paths.build_packages_package(name);
 
@@ -223,7 +260,7 @@ impl Downloader {
         }
 
         tracing::info!(package = name, "writing_package_to_target");
-        let tarball = paths::global_package_cache_package_tarball(name, &version.to_string());
+        let tarball = self.package_tarball_path(repository, name, version)?;
         let reader = self.fs_reader.reader(&tarball)?;
         let mut archive = Archive::new(reader);
 
@@ -273,6 +310,31 @@ impl Downloader {
         }
         Ok(())
     }
+
+    /// The path of a package's tarball in the package cache, where packages
+    /// from other Hex repositories are kept by repository URL.
+    pub fn package_tarball_path(
+        &self,
+        repository: Option<&str>,
+        name: &str,
+        version: &Version,
+    ) -> Result<Utf8PathBuf> {
+        let version = version.to_string();
+        let Some(repository_name) = repository else {
+            return Ok(self.package_cache.package_tarball(None, name, &version));
+        };
+        let repository =
+            self.repositories
+                .get(repository_name)
+                .ok_or_else(|| Error::UnknownHexRepository {
+                    name: repository_name.into(),
+                })?;
+        Ok(self.package_cache.package_tarball(
+            Some(&repository.config.repository_base),
+            name,
+            &version,
+        ))
+    }
 }
 
 pub async fn publish_documentation<Http: HttpClient>(
@@ -293,6 +355,7 @@ pub async fn publish_documentation<Http: HttpClient>(
 pub async fn get_package_release<Http: HttpClient>(
     name: &str,
     version: &Version,
+    api_key: Option<&str>,
     config: &hexpm::Config,
     http: &Http,
 ) -> Result<hexpm::Release<hexpm::ReleaseMeta>> {
@@ -302,7 +365,7 @@ pub async fn get_package_release<Http: HttpClient>(
         version = version.as_str(),
         "looking_up_package_release"
     );
-    let request = hexpm::get_package_release_request(name, &version, None, config);
+    let request = hexpm::get_package_release_request(name, &version, api_key, config);
     let response = http.send(request).await?;
     hexpm::get_package_release_response(response).map_err(Error::hex)
 }
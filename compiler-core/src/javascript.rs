@@ -1,6 +1,9 @@
+mod api;
+mod bundle;
 mod endianness;
 mod expression;
 mod import;
+mod mutual_recursion;
 mod pattern;
 #[cfg(test)]
 mod tests;
@@ -9,7 +12,8 @@ mod typescript;
 use crate::analyse::TargetSupport;
 use crate::build::Target;
 use crate::codegen::TypeScriptDeclarations;
-use crate::type_::PRELUDE_MODULE_NAME;
+use crate::config::{JavaScriptIntegers, JavaScriptModuleFormat};
+use crate::type_::{collapse_links, pretty::Printer, Deprecation, Type, PRELUDE_MODULE_NAME};
 use crate::{
     ast::{CustomType, Function, Import, ModuleConstant, TypeAlias, *},
     docvec,
@@ -20,22 +24,77 @@ use camino::Utf8Path;
 use ecow::EcoString;
 use expression::Context;
 use itertools::Itertools;
+use std::{collections::HashMap, sync::Arc};
 
 use self::import::{Imports, Member};
+use self::mutual_recursion::MutualRecursion;
+
+pub use self::api::{api, ApiModule, ApiResults};
+pub use self::bundle::{bundle, Error as BundleError};
 
 const INDENT: isize = 2;
 
 pub const PRELUDE: &str = include_str!("../templates/prelude.mjs");
 pub const PRELUDE_TS_DEF: &str = include_str!("../templates/prelude.d.mts");
 
+/// The prelude as a module of the given format. The CommonJS prelude is
+/// derived from the ES module one, which only exports classes and functions
+/// from the start of a line.
+pub fn prelude(module_format: JavaScriptModuleFormat) -> String {
+    if module_format == JavaScriptModuleFormat::Esm {
+        return PRELUDE.into();
+    }
+
+    let mut names = vec![];
+    let mut code = String::from("\"use strict\";\n\n");
+    for line in PRELUDE.lines() {
+        let line = match line.strip_prefix("export ") {
+            Some(definition) => {
+                let name = definition
+                    .split_whitespace()
+                    .nth(1)
+                    .expect("Exported prelude definition must be named")
+                    .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$'))
+                    .next()
+                    .unwrap_or_default();
+                names.push(name);
+                definition
+            }
+            None => line,
+        };
+        code.push_str(line);
+        code.push('\n');
+    }
+
+    code.push_str("\nmodule.exports = {\n");
+    for name in names {
+        code.push_str(&format!("  {name},\n"));
+    }
+    code.push_str("};\n");
+    code
+}
+
 pub type Output<'a> = Result<Document<'a>, Error>;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum JavaScriptCodegenTarget {
     JavaScript,
+    CommonJs,
     TypeScriptDeclarations,
 }
 
+/// The options that change the JavaScript generated for a module.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodegenOptions {
+    pub target_support: TargetSupport,
+    pub typescript: TypeScriptDeclarations,
+    pub integers: JavaScriptIntegers,
+    pub module_format: JavaScriptModuleFormat,
+    /// Whether external functions are wrapped to check the values they
+    /// return against their declared return types.
+    pub check_externals: bool,
+}
+
 #[derive(Debug)]
 pub struct Generator<'a> {
     line_numbers: &'a LineNumbers,
@@ -43,18 +102,33 @@ pub struct Generator<'a> {
     tracker: UsageTracker,
     module_scope: im::HashMap<EcoString, usize>,
     current_module_name_segments_count: usize,
-    target_support: TargetSupport,
-    typescript: TypeScriptDeclarations,
+    options: CodegenOptions,
+    /// The names a CommonJS module assigns to `module.exports`.
+    commonjs_exports: Vec<String>,
+    /// The groups of mutually tail recursive functions, which are compiled
+    /// into loops, and the group each of their functions belongs to.
+    mutual_recursion: Vec<MutualRecursion<'a>>,
+    mutual_recursion_groups: HashMap<EcoString, usize>,
 }
 
 impl<'a> Generator<'a> {
     pub fn new(
         line_numbers: &'a LineNumbers,
         module: &'a TypedModule,
-        target_support: TargetSupport,
-        typescript: TypeScriptDeclarations,
+        options: CodegenOptions,
     ) -> Self {
         let current_module_name_segments_count = module.name.split('/').count();
+        let mutual_recursion = mutual_recursion::find(module);
+        let mutual_recursion_groups = mutual_recursion
+            .iter()
+            .enumerate()
+            .flat_map(|(group, recursion)| {
+                recursion
+                    .names
+                    .iter()
+                    .map(move |name| (name.clone(), group))
+            })
+            .collect();
 
         Self {
             current_module_name_segments_count,
@@ -62,13 +136,15 @@ impl<'a> Generator<'a> {
             module,
             tracker: UsageTracker::default(),
             module_scope: Default::default(),
-            target_support,
-            typescript,
+            options,
+            commonjs_exports: vec![],
+            mutual_recursion,
+            mutual_recursion_groups,
         }
     }
 
     fn type_reference(&self) -> Document<'a> {
-        if self.typescript == TypeScriptDeclarations::None {
+        if self.options.typescript == TypeScriptDeclarations::None {
             return Document::Str("");
         }
 
@@ -83,14 +159,21 @@ impl<'a> Generator<'a> {
 
         let name = Document::Str(module);
 
-        docvec!["/// <reference types=\"./", name, ".d.mts\" />", line()]
+        docvec![
+            "/// <reference types=\"./",
+            name,
+            ".",
+            self.options.module_format.declaration_extension(),
+            "\" />",
+            line()
+        ]
     }
 
     pub fn compile(&mut self) -> Output<'a> {
         let type_reference = self.type_reference();
 
         // Determine what JavaScript imports we need to generate
-        let mut imports = self.collect_imports();
+        let mut imports = self.collect_imports()?;
 
         // Determine what names are defined in the module scope so we know to
         // rename any variables that are defined within functions using the same
@@ -171,8 +254,36 @@ impl<'a> Generator<'a> {
             self.register_prelude_usage(&mut imports, "sizedFloat", None);
         };
 
+        if self.tracker.utf16_string_bit_array_segment_used {
+            self.register_prelude_usage(&mut imports, "stringToUtf16", None);
+        };
+
+        if self.tracker.utf16_codepoint_bit_array_segment_used {
+            self.register_prelude_usage(&mut imports, "codepointToUtf16", None);
+        };
+
+        if self.tracker.utf32_string_bit_array_segment_used {
+            self.register_prelude_usage(&mut imports, "stringToUtf32", None);
+        };
+
+        if self.tracker.utf32_codepoint_bit_array_segment_used {
+            self.register_prelude_usage(&mut imports, "codepointToUtf32", None);
+        };
+
+        if self.tracker.sized_bits_segment_used {
+            self.register_prelude_usage(&mut imports, "sizedBits", None);
+        };
+
+        if self.tracker.check_external_used {
+            self.register_prelude_usage(&mut imports, "checkExternal", None);
+        };
+
         // Put it all together
 
+        if self.options.module_format == JavaScriptModuleFormat::CommonJs {
+            return Ok(self.commonjs_module(type_reference, imports, statements));
+        }
+
         if imports.is_empty() && statements.is_empty() {
             Ok(docvec![type_reference, "export {}", line()])
         } else if imports.is_empty() {
@@ -194,6 +305,65 @@ impl<'a> Generator<'a> {
         }
     }
 
+    /// Unlike ES modules, CommonJS modules are not strict by default so they
+    /// opt in, and they export their public definitions together at the end.
+    fn commonjs_module(
+        &mut self,
+        type_reference: Document<'a>,
+        imports: Imports<'a>,
+        mut statements: Vec<Document<'a>>,
+    ) -> Document<'a> {
+        let imports = if imports.is_empty() {
+            nil()
+        } else {
+            docvec![imports.into_doc(JavaScriptCodegenTarget::CommonJs), line()]
+        };
+        if !statements.is_empty() {
+            statements.push(lines(2));
+        }
+
+        let names = std::mem::take(&mut self.commonjs_exports);
+        let exports = if names.is_empty() {
+            "module.exports = {};".to_doc()
+        } else {
+            let names = join(names.into_iter().map(Document::String), break_(",", ", "));
+            docvec![
+                "module.exports = {",
+                docvec![break_("", " "), names].nest(INDENT),
+                break_(",", " "),
+                "};"
+            ]
+            .group()
+        };
+
+        docvec![
+            type_reference,
+            "\"use strict\";",
+            lines(2),
+            imports,
+            statements,
+            exports,
+            line()
+        ]
+    }
+
+    /// The start of a top level definition. ES modules export their public
+    /// definitions inline, while CommonJS modules export them at the end.
+    fn definition_head(&self, exported: bool, keyword: &'static str) -> Document<'a> {
+        if exported && self.options.module_format == JavaScriptModuleFormat::Esm {
+            docvec!["export ", keyword]
+        } else {
+            keyword.to_doc()
+        }
+    }
+
+    fn register_commonjs_export(&mut self, exported: bool, name: &str) {
+        if exported && self.options.module_format == JavaScriptModuleFormat::CommonJs {
+            self.commonjs_exports
+                .push(maybe_escape_identifier_string(name));
+        }
+    }
+
     fn register_prelude_usage(
         &self,
         imports: &mut Imports<'a>,
@@ -219,17 +389,26 @@ impl<'a> Generator<'a> {
             Definition::CustomType(CustomType { .. }) => None,
 
             Definition::ModuleConstant(ModuleConstant {
+                documentation,
                 publicity,
                 name,
                 value,
+                deprecation,
                 ..
-            }) => Some(self.module_constant(*publicity, name, value)),
+            }) => {
+                let jsdoc = jsdoc(documentation.as_ref().map(|(_, doc)| doc), deprecation);
+                Some(
+                    self.module_constant(*publicity, name, value)
+                        .map(|constant| jsdoc.append(constant)),
+                )
+            }
 
             Definition::Function(function) => {
                 // If there's an external JavaScript implementation then it will be imported,
-                // so we don't need to generate a function definition.
+                // so we don't need to generate a function definition unless
+                // the values it returns are checked.
                 if function.external_javascript.is_some() {
-                    return None;
+                    return self.checked_external_function(function);
                 }
 
                 // If the function does not support JavaScript then we don't need to generate
@@ -238,7 +417,11 @@ impl<'a> Generator<'a> {
                     return None;
                 }
 
-                self.module_function(function)
+                let name = function.name.as_ref().map(|(_, name)| name);
+                match name.and_then(|name| self.mutual_recursion_groups.get(name)) {
+                    Some(&group) => self.mutually_recursive_function(function, group),
+                    None => self.module_function(function),
+                }
             }
         }
     }
@@ -248,6 +431,7 @@ impl<'a> Generator<'a> {
         constructors: &'a [TypedRecordConstructor],
         publicity: Publicity,
         opaque: bool,
+        deprecation: &'a Deprecation,
     ) -> Vec<Output<'a>> {
         // If there's no constructors then there's nothing to do here.
         if constructors.is_empty() {
@@ -257,7 +441,15 @@ impl<'a> Generator<'a> {
         self.tracker.custom_type_used = true;
         constructors
             .iter()
-            .map(|constructor| Ok(self.record_definition(constructor, publicity, opaque)))
+            .map(|constructor| {
+                let documentation = constructor.documentation.as_ref().map(|(_, doc)| doc);
+                let definition = self.record_definition(constructor, publicity, opaque);
+                self.register_commonjs_export(
+                    !(publicity.is_private() || opaque),
+                    &constructor.name,
+                );
+                Ok(jsdoc(documentation, deprecation).append(definition))
+            })
             .collect()
     }
 
@@ -274,11 +466,7 @@ impl<'a> Generator<'a> {
                 .unwrap_or_else(|| Document::String(format!("x{i}")))
         }
 
-        let head = if publicity.is_private() || opaque {
-            "class "
-        } else {
-            "export class "
-        };
+        let head = self.definition_head(!(publicity.is_private() || opaque), "class ");
         let head = docvec![head, &constructor.name, " extends $CustomType {"];
 
         if constructor.arguments.is_empty() {
@@ -324,8 +512,9 @@ impl<'a> Generator<'a> {
                     publicity,
                     constructors,
                     opaque,
+                    deprecation,
                     ..
-                }) => self.custom_type_definition(constructors, *publicity, *opaque),
+                }) => self.custom_type_definition(constructors, *publicity, *opaque, deprecation),
 
                 Definition::Function(Function { .. })
                 | Definition::TypeAlias(TypeAlias { .. })
@@ -335,7 +524,7 @@ impl<'a> Generator<'a> {
             .collect()
     }
 
-    fn collect_imports(&mut self) -> Imports<'a> {
+    fn collect_imports(&mut self) -> Result<Imports<'a>, Error> {
         let mut imports = Imports::new();
 
         for statement in &self.module.definitions {
@@ -350,19 +539,42 @@ impl<'a> Generator<'a> {
                     self.register_import(&mut imports, package, module, as_name, unqualified);
                 }
 
-                Definition::Function(Function {
-                    name: Some((_, name)),
-                    publicity,
-                    external_javascript: Some((module, function)),
-                    ..
-                }) => {
-                    self.register_external_function(
-                        &mut imports,
-                        *publicity,
-                        name,
-                        module,
-                        function,
-                    );
+                Definition::Function(
+                    function @ Function {
+                        name: Some((_, name)),
+                        publicity,
+                        external_javascript: Some((module, external_function)),
+                        ..
+                    },
+                ) => {
+                    // A CommonJS module cannot require an ES module, which
+                    // may itself import the ES module version of the prelude.
+                    if self.options.module_format == JavaScriptModuleFormat::CommonJs
+                        && module.ends_with(".mjs")
+                    {
+                        return Err(Error::EsModuleExternal {
+                            module: module.clone(),
+                            location: function.location,
+                        });
+                    }
+
+                    // A checked external is imported under another name, as
+                    // its checking wrapper is defined with its name.
+                    if self.external_return_type(function).is_some() {
+                        let member = Member {
+                            name: external_function.to_doc(),
+                            alias: Some(unchecked_external_name(name)),
+                        };
+                        imports.register_module(module.to_string(), [], [member]);
+                    } else {
+                        self.register_external_function(
+                            &mut imports,
+                            *publicity,
+                            name,
+                            module,
+                            external_function,
+                        );
+                    }
                 }
 
                 Definition::Function(Function { .. })
@@ -372,25 +584,26 @@ impl<'a> Generator<'a> {
             }
         }
 
-        imports
+        Ok(imports)
     }
 
     fn import_path(&self, package: &'a str, module: &'a str) -> String {
         // TODO: strip shared prefixed between current module and imported
         // module to avoid descending and climbing back out again
+        let extension = self.options.module_format.extension();
         if package == self.module.type_info.package || package.is_empty() {
             // Same package
             match self.current_module_name_segments_count {
-                1 => format!("./{module}.mjs"),
+                1 => format!("./{module}.{extension}"),
                 _ => {
                     let prefix = "../".repeat(self.current_module_name_segments_count - 1);
-                    format!("{prefix}{module}.mjs")
+                    format!("{prefix}{module}.{extension}")
                 }
             }
         } else {
             // Different package
             let prefix = "../".repeat(self.current_module_name_segments_count);
-            format!("{prefix}{package}/{module}.mjs")
+            format!("{prefix}{package}/{module}.{extension}")
         }
     }
 
@@ -450,7 +663,12 @@ impl<'a> Generator<'a> {
             },
         };
         if publicity.is_importable() {
-            imports.register_export(maybe_escape_identifier_string(name))
+            match self.options.module_format {
+                JavaScriptModuleFormat::Esm => {
+                    imports.register_export(maybe_escape_identifier_string(name))
+                }
+                JavaScriptModuleFormat::CommonJs => self.register_commonjs_export(true, name),
+            }
         }
         imports.register_module(module.to_string(), [], [member]);
     }
@@ -461,15 +679,16 @@ impl<'a> Generator<'a> {
         name: &'a str,
         value: &'a TypedConstant,
     ) -> Output<'a> {
-        let head = if publicity.is_private() {
-            "const "
-        } else {
-            "export const "
-        };
+        let head = self.definition_head(!publicity.is_private(), "const ");
 
-        let document =
-            expression::constant_expression(Context::Constant, &mut self.tracker, value)?;
+        let document = expression::constant_expression(
+            Context::Constant,
+            &mut self.tracker,
+            self.options.integers,
+            value,
+        )?;
 
+        self.register_commonjs_export(!publicity.is_private(), name);
         Ok(docvec![
             head,
             maybe_escape_identifier_doc(name),
@@ -493,6 +712,7 @@ impl<'a> Generator<'a> {
             .iter()
             .map(|arg| arg.names.get_variable_name())
             .collect();
+        let head = self.definition_head(!function.publicity.is_private(), "function ");
         let mut generator = expression::Generator::new(
             self.module.name.clone(),
             self.line_numbers,
@@ -500,12 +720,8 @@ impl<'a> Generator<'a> {
             argument_names,
             &mut self.tracker,
             self.module_scope.clone(),
+            self.options.integers,
         );
-        let head = if function.publicity.is_private() {
-            "function "
-        } else {
-            "export function "
-        };
 
         let body = match generator.function_body(&function.body, function.arguments.as_slice()) {
             // No error, let's continue!
@@ -514,7 +730,7 @@ impl<'a> Generator<'a> {
             // There is an error coming from some expression that is not supported on JavaScript
             // and the target support is not enforced. In this case we do not error, instead
             // returning nothing which will cause no function to be generated.
-            Err(error) if error.is_unsupported() && !self.target_support.is_enforced() => {
+            Err(error) if error.is_unsupported() && !self.options.target_support.is_enforced() => {
                 return None
             }
 
@@ -522,7 +738,9 @@ impl<'a> Generator<'a> {
             Err(error) => return Some(Err(error)),
         };
 
+        let documentation = function.documentation.as_ref().map(|(_, doc)| doc);
         let document = docvec![
+            jsdoc(documentation, &function.deprecation),
             head,
             maybe_escape_identifier_doc(name.as_str()),
             fun_args(function.arguments.as_slice(), generator.tail_recursion_used),
@@ -531,9 +749,266 @@ impl<'a> Generator<'a> {
             line(),
             "}",
         ];
+        self.register_commonjs_export(!function.publicity.is_private(), name);
         Some(Ok(document))
     }
 
+    /// An external function whose returned values are checked, which calls
+    /// the imported implementation and throws an error if the value it returns
+    /// does not have the shape of the declared return type.
+    fn checked_external_function(&mut self, function: &'a TypedFunction) -> Option<Output<'a>> {
+        let type_ = self.external_return_type(function)?;
+        let (_, name) = function.name.as_ref()?;
+        let (module, external_function) = function.external_javascript.as_ref()?;
+        self.tracker.check_external_used = true;
+
+        let message = format!(
+            "The external function `{external_function}` from `{module}` returned a value \
+that does not match its return type `{}`",
+            Printer::new().pretty_print(&function.return_type, 0)
+        );
+        // Rendered now, as the message doesn't live as long as the document
+        let message = Document::String(expression::string(&message).to_pretty_string(80));
+        let arguments = fun_args(function.arguments.as_slice(), false);
+        let call = docvec![unchecked_external_name(name), arguments.clone()];
+        let check = docvec![
+            "return checkExternal",
+            wrap_args([
+                call,
+                type_,
+                expression::string(&self.module.name),
+                self.line_numbers
+                    .line_number(function.location.start)
+                    .to_doc(),
+                expression::string(name),
+                message,
+            ]),
+            ";"
+        ];
+
+        let documentation = function.documentation.as_ref().map(|(_, doc)| doc);
+        let head = self.definition_head(function.publicity.is_importable(), "function ");
+        self.register_commonjs_export(function.publicity.is_importable(), name);
+        Some(Ok(docvec![
+            jsdoc(documentation, &function.deprecation),
+            head,
+            maybe_escape_identifier_doc(name),
+            arguments,
+            " {",
+            docvec![line(), check].nest(INDENT).group(),
+            line(),
+            "}",
+        ]))
+    }
+
+    /// The shape the value returned by an external function is checked to
+    /// have, if external functions are checked and there is anything to check.
+    fn external_return_type(&self, function: &TypedFunction) -> Option<Document<'a>> {
+        if !self.options.check_externals || function.external_javascript.is_none() {
+            return None;
+        }
+        self.runtime_type(&function.return_type)
+    }
+
+    /// The shape of the values of a type, as understood by the prelude's
+    /// `checkExternal` function. Type variables and types whose values can't
+    /// be told apart, such as external types, have no shape to check.
+    fn runtime_type(&self, type_: &Arc<Type>) -> Option<Document<'a>> {
+        let shape = |kind: &str, elements: Vec<Option<Document<'a>>>| {
+            let elements = elements
+                .into_iter()
+                .map(|element| element.unwrap_or_else(|| "null".to_doc()));
+            let elements = std::iter::once(Document::String(format!("\"{kind}\""))).chain(elements);
+            docvec![
+                "[",
+                docvec![break_("", ""), join(elements, break_(",", ", "))].nest(INDENT),
+                break_(",", ""),
+                "]"
+            ]
+            .group()
+        };
+
+        match collapse_links(type_.clone()).as_ref() {
+            Type::Named {
+                module, name, args, ..
+            } if module == PRELUDE_MODULE_NAME => match name.as_str() {
+                "Int" => Some(match self.options.integers {
+                    JavaScriptIntegers::Number => "\"Int\"".to_doc(),
+                    JavaScriptIntegers::BigInt => "\"BigInt\"".to_doc(),
+                }),
+                "List" | "Result" => Some(shape(
+                    name,
+                    args.iter().map(|arg| self.runtime_type(arg)).collect(),
+                )),
+                name => Some(Document::String(format!("\"{name}\""))),
+            },
+
+            Type::Named { module, name, .. } if *module == self.module.name => {
+                let constructors =
+                    self.module
+                        .definitions
+                        .iter()
+                        .find_map(|definition| match definition {
+                            Definition::CustomType(custom_type) if custom_type.name == *name => {
+                                Some(&custom_type.constructors)
+                            }
+                            _ => None,
+                        })?;
+                if constructors.is_empty() {
+                    return None;
+                }
+                let constructors = constructors
+                    .iter()
+                    .map(|constructor| Some(constructor.name.clone().to_doc()));
+                Some(shape(
+                    "CustomType",
+                    std::iter::once(Some(Document::String(format!("\"{name}\""))))
+                        .chain(constructors)
+                        .collect(),
+                ))
+            }
+
+            Type::Named { .. } | Type::Var { .. } => None,
+
+            Type::Fn { .. } => Some("\"Function\"".to_doc()),
+
+            Type::Tuple { elems } => Some(shape(
+                "Tuple",
+                elems.iter().map(|elem| self.runtime_type(elem)).collect(),
+            )),
+        }
+    }
+
+    /// A function that is mutually tail recursive with others, which starts
+    /// the loop they are compiled into. The loop itself is defined along with
+    /// the first of them.
+    fn mutually_recursive_function(
+        &mut self,
+        function: &'a TypedFunction,
+        group: usize,
+    ) -> Option<Output<'a>> {
+        let recursion = self
+            .mutual_recursion
+            .get(group)
+            .expect("Mutual recursion group must exist");
+        if recursion.separate {
+            return self.module_function(function);
+        }
+        let index = recursion
+            .functions
+            .iter()
+            .position(|f| std::ptr::eq(*f, function))
+            .expect("Function must be in its mutual recursion group");
+        let loop_name = recursion.name.clone();
+
+        let loop_function = if index == 0 {
+            match self.mutual_recursion_loop(group) {
+                Ok(document) => docvec![document, lines(2)],
+
+                // Compile the functions separately so only the one using the
+                // unsupported feature is left out, as with any other function.
+                Err(error)
+                    if error.is_unsupported() && !self.options.target_support.is_enforced() =>
+                {
+                    if let Some(recursion) = self.mutual_recursion.get_mut(group) {
+                        recursion.separate = true;
+                    }
+                    return self.module_function(function);
+                }
+
+                Err(error) => return Some(Err(error)),
+            }
+        } else {
+            nil()
+        };
+
+        let (_, name) = function
+            .name
+            .as_ref()
+            .expect("A module's function must be named");
+        let arguments = fun_arg_names(function.arguments.as_slice(), false);
+        let documentation = function.documentation.as_ref().map(|(_, doc)| doc);
+        let call = wrap_args(std::iter::once(index.to_doc()).chain(arguments.clone()));
+        Some(Ok(docvec![
+            loop_function,
+            jsdoc(documentation, &function.deprecation),
+            "function ",
+            maybe_escape_identifier_doc(name.as_str()),
+            wrap_args(arguments),
+            " {",
+            docvec![line(), "return ", loop_name, call, ";"].nest(INDENT),
+            line(),
+            "}",
+        ]))
+    }
+
+    /// The function containing the loop a group of mutually tail recursive
+    /// functions are compiled into. The function to run is chosen by its index.
+    fn mutual_recursion_loop(&mut self, group: usize) -> Output<'a> {
+        let recursion = self
+            .mutual_recursion
+            .get(group)
+            .expect("Mutual recursion group must exist");
+        let count = recursion.functions.len();
+        let mut bodies = Vec::with_capacity(count);
+        for (index, function) in recursion.functions.iter().enumerate() {
+            let (_, name) = function
+                .name
+                .as_ref()
+                .expect("A module's function must be named");
+            let argument_names = function
+                .arguments
+                .iter()
+                .map(|arg| arg.names.get_variable_name())
+                .collect();
+            let mut generator = expression::Generator::new(
+                self.module.name.clone(),
+                self.line_numbers,
+                name.clone(),
+                argument_names,
+                &mut self.tracker,
+                self.module_scope.clone(),
+                self.options.integers,
+            );
+            generator.mutual_recursion = Some((&recursion.names, index));
+            let body = generator
+                .mutually_recursive_function_body(&function.body, function.arguments.as_slice())?;
+
+            let head = if index == 0 {
+                docvec!["if (loop$function === ", index, ") {"]
+            } else if index + 1 < count {
+                docvec![" else if (loop$function === ", index, ") {"]
+            } else {
+                " else {".to_doc()
+            };
+            bodies.push(docvec![
+                head,
+                docvec![line(), body].nest(INDENT),
+                line(),
+                "}"
+            ]);
+        }
+
+        let arguments = std::iter::once("loop$function".to_doc())
+            .chain((0..recursion.arity()).map(|i| docvec!["loop$", i]));
+        Ok(docvec![
+            "function ",
+            recursion.name.clone(),
+            wrap_args(arguments),
+            " {",
+            docvec![
+                line(),
+                "while (true) {",
+                docvec![line(), bodies].nest(INDENT),
+                line(),
+                "}"
+            ]
+            .nest(INDENT),
+            line(),
+            "}",
+        ])
+    }
+
     fn register_module_definitions_in_scope(&mut self) {
         for statement in self.module.definitions.iter() {
             match statement {
@@ -566,10 +1041,9 @@ pub fn module(
     line_numbers: &LineNumbers,
     path: &Utf8Path,
     src: &EcoString,
-    target_support: TargetSupport,
-    typescript: TypeScriptDeclarations,
+    options: CodegenOptions,
 ) -> Result<String, crate::Error> {
-    let document = Generator::new(line_numbers, module, target_support, typescript)
+    let document = Generator::new(line_numbers, module, options)
         .compile()
         .map_err(|error| crate::Error::JavaScript {
             path: path.to_path_buf(),
@@ -583,8 +1057,10 @@ pub fn ts_declaration(
     module: &TypedModule,
     path: &Utf8Path,
     src: &EcoString,
+    integers: JavaScriptIntegers,
+    module_format: JavaScriptModuleFormat,
 ) -> Result<String, crate::Error> {
-    let document = typescript::TypeScriptGenerator::new(module)
+    let document = typescript::TypeScriptGenerator::new(module, integers, module_format)
         .compile()
         .map_err(|error| crate::Error::JavaScript {
             path: path.to_path_buf(),
@@ -596,7 +1072,14 @@ pub fn ts_declaration(
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Error {
-    Unsupported { feature: String, location: SrcSpan },
+    Unsupported {
+        feature: String,
+        location: SrcSpan,
+    },
+    EsModuleExternal {
+        module: EcoString,
+        location: SrcSpan,
+    },
 }
 
 impl Error {
@@ -609,21 +1092,83 @@ impl Error {
     }
 }
 
+/// The name an external function is imported as when it is wrapped to check
+/// the values it returns.
+fn unchecked_external_name(name: &str) -> Document<'static> {
+    Document::String(format!("{name}$unchecked"))
+}
+
 fn fun_args(args: &'_ [TypedArg], tail_recursion_used: bool) -> Document<'_> {
+    wrap_args(fun_arg_names(args, tail_recursion_used))
+}
+
+fn fun_arg_names(args: &'_ [TypedArg], tail_recursion_used: bool) -> Vec<Document<'_>> {
     let mut discards = 0;
-    wrap_args(args.iter().map(|a| match a.get_variable_name() {
-        None => {
-            let doc = if discards == 0 {
-                "_".to_doc()
-            } else {
-                Document::String(format!("_{discards}"))
-            };
-            discards += 1;
-            doc
+    args.iter()
+        .map(|a| match a.get_variable_name() {
+            None => {
+                let doc = if discards == 0 {
+                    "_".to_doc()
+                } else {
+                    Document::String(format!("_{discards}"))
+                };
+                discards += 1;
+                doc
+            }
+            Some(name) if tail_recursion_used => Document::String(format!("loop${name}")),
+            Some(name) => maybe_escape_identifier_doc(name),
+        })
+        .collect()
+}
+
+/// A JSDoc comment holding the documentation of a definition and whether it is
+/// deprecated, followed by a new line. This is nothing if there is neither.
+fn jsdoc<'a>(documentation: Option<&'a EcoString>, deprecation: &'a Deprecation) -> Document<'a> {
+    let mut lines: Vec<&str> = documentation
+        .into_iter()
+        .flat_map(|documentation| documentation.lines())
+        .map(|line| line.strip_prefix(' ').unwrap_or(line))
+        .collect();
+
+    // Drop any blank lines from the end so the comment closes neatly.
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        let _ = lines.pop();
+    }
+
+    let deprecated = match deprecation {
+        Deprecation::Deprecated { message } => Some(message),
+        Deprecation::NotDeprecated => None,
+    };
+
+    if lines.is_empty() && deprecated.is_none() {
+        return nil();
+    }
+
+    let line_doc = |text: &str| {
+        if text.trim().is_empty() {
+            " *".to_doc()
+        } else {
+            // The comment would be ended early by `*/` in the documentation.
+            Document::String(format!(" * {}", text.replace("*/", "*\\/")))
         }
-        Some(name) if tail_recursion_used => Document::String(format!("loop${name}")),
-        Some(name) => maybe_escape_identifier_doc(name),
-    }))
+    };
+
+    let mut docs = vec!["/**".to_doc(), line()];
+    for text in &lines {
+        docs.push(line_doc(text));
+        docs.push(line());
+    }
+    if let Some(message) = deprecated {
+        if !lines.is_empty() {
+            docs.push(" *".to_doc());
+            docs.push(line());
+        }
+        docs.push(line_doc(&format!("@deprecated {message}")));
+        docs.push(line());
+    }
+    docs.push(" */".to_doc());
+    docs.push(line());
+    docs.to_doc()
 }
 
 fn wrap_args<'a, I>(args: I) -> Document<'a>
@@ -779,6 +1324,12 @@ pub(crate) struct UsageTracker {
     pub string_bit_array_segment_used: bool,
     pub codepoint_bit_array_segment_used: bool,
     pub float_bit_array_segment_used: bool,
+    pub utf16_string_bit_array_segment_used: bool,
+    pub utf16_codepoint_bit_array_segment_used: bool,
+    pub utf32_string_bit_array_segment_used: bool,
+    pub utf32_codepoint_bit_array_segment_used: bool,
+    pub sized_bits_segment_used: bool,
+    pub check_external_used: bool,
 }
 
 fn bool(bool: bool) -> Document<'static> {
@@ -0,0 +1,758 @@
+//! Generation of a wrapper module giving the public functions of a Gleam module
+//! an idiomatic JavaScript API, along with its TypeScript declarations.
+//!
+//! Values are converted where they cross the boundary between JavaScript and
+//! Gleam, so that users of the wrapper never need to work with the prelude
+//! classes directly:
+//!
+//! - Gleam lists are JavaScript arrays.
+//! - `Option`s from the standard library are either the wrapped value or
+//!   `undefined`.
+//! - `Result`s are objects with an `ok` field, in the style of a discriminated
+//!   union. A `Result` returned by a function can instead be unwrapped, with an
+//!   `Error` being thrown as a `GleamError`.
+//! - Tuples are arrays, with each of their elements converted.
+//!
+//! All other values, such as custom types and functions, are passed through
+//! unchanged.
+//!
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+
+use ecow::EcoString;
+use strum::{Display, EnumString, VariantNames};
+
+use crate::{
+    ast::{Definition, Function, TypedArg, TypedFunction, TypedModule},
+    build::Target,
+    config::{JavaScriptIntegers, JavaScriptModuleFormat},
+    docvec,
+    pretty::{join, line, lines, nil, Document, Documentable},
+    type_::{collapse_links, is_prelude_module, Type, PRELUDE_MODULE_NAME},
+};
+
+use super::{
+    import::{Imports, Member},
+    jsdoc, maybe_escape_identifier_doc,
+    typescript::{collect_generic_usages, id_to_type_var, ts_safe_type_name, TypeScriptGenerator},
+    wrap_args, JavaScriptCodegenTarget, INDENT,
+};
+
+/// How a `Result` returned by a wrapped function is given to JavaScript.
+#[derive(Debug, Display, EnumString, VariantNames, Clone, Copy, PartialEq, Eq)]
+#[strum(serialize_all = "lowercase")]
+pub enum ApiResults {
+    /// An object with an `ok` field and either a `value` or an `error`.
+    Union,
+    /// The `Ok` value is returned and the `Error` value is thrown.
+    Throw,
+}
+
+/// The generated wrapper module, which is to be written alongside the compiled
+/// module it wraps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiModule {
+    pub javascript: String,
+    pub typescript: String,
+}
+
+/// The alias the wrapped module is imported with.
+const MODULE_ALIAS: &str = "$module";
+
+/// The variable the value returned by a wrapped function is held in.
+const RESULT_VARIABLE: &str = "$result";
+
+const OPTION_PACKAGE: &str = "gleam_stdlib";
+const OPTION_MODULE: &str = "gleam/option";
+
+pub fn api(
+    module: &TypedModule,
+    results: ApiResults,
+    integers: JavaScriptIntegers,
+    module_format: JavaScriptModuleFormat,
+) -> ApiModule {
+    let mut generator = Generator {
+        module,
+        results,
+        module_format,
+        typescript: TypeScriptGenerator::new(module, integers, module_format),
+        prelude_members: BTreeSet::new(),
+        option_used: false,
+        gleam_error_used: false,
+        type_modules: BTreeMap::new(),
+        own_types: BTreeSet::new(),
+    };
+    generator.generate()
+}
+
+/// The parts of a type that are converted at the boundary.
+enum Shape {
+    List(Arc<Type>),
+    Option(Arc<Type>),
+    Result(Arc<Type>, Arc<Type>),
+    Tuple(Vec<Arc<Type>>),
+    Other,
+}
+
+fn shape(type_: &Arc<Type>) -> Shape {
+    let type_ = collapse_links(type_.clone());
+    match type_.as_ref() {
+        Type::Named {
+            module, name, args, ..
+        } if is_prelude_module(module) => match (name.as_str(), args.as_slice()) {
+            ("List", [element]) => Shape::List(element.clone()),
+            ("Result", [value, error]) => Shape::Result(value.clone(), error.clone()),
+            _ => Shape::Other,
+        },
+
+        Type::Named {
+            package,
+            module,
+            name,
+            args,
+            ..
+        } if is_option(package, module, name) => match args.as_slice() {
+            [value] => Shape::Option(value.clone()),
+            _ => Shape::Other,
+        },
+
+        Type::Tuple { elems } => Shape::Tuple(elems.clone()),
+
+        Type::Named { .. } | Type::Fn { .. } | Type::Var { .. } => Shape::Other,
+    }
+}
+
+fn is_option(package: &str, module: &str, name: &str) -> bool {
+    package == OPTION_PACKAGE && module == OPTION_MODULE && name == "Option"
+}
+
+/// The value wrapped by an option is converted unless it is itself an option,
+/// which is left as a Gleam value as otherwise `None` and `Some(None)` would
+/// both be `undefined`.
+fn is_option_type(type_: &Arc<Type>) -> bool {
+    match collapse_links(type_.clone()).as_ref() {
+        Type::Named {
+            package,
+            module,
+            name,
+            ..
+        } => is_option(package, module, name),
+        Type::Fn { .. } | Type::Var { .. } | Type::Tuple { .. } => false,
+    }
+}
+
+struct Generator<'a> {
+    module: &'a TypedModule,
+    results: ApiResults,
+    /// The format of the wrapped modules. The wrapper itself is always an ES
+    /// module.
+    module_format: JavaScriptModuleFormat,
+    typescript: TypeScriptGenerator<'a>,
+    prelude_members: BTreeSet<&'static str>,
+    option_used: bool,
+    gleam_error_used: bool,
+    /// Modules other than the wrapped one that define types used in the
+    /// declarations, along with their packages.
+    type_modules: BTreeMap<EcoString, EcoString>,
+    /// The names of the types from the wrapped module used in the
+    /// declarations.
+    own_types: BTreeSet<String>,
+}
+
+impl<'a> Generator<'a> {
+    fn generate(&mut self) -> ApiModule {
+        let functions: Vec<_> = self
+            .module
+            .definitions
+            .iter()
+            .filter_map(|definition| match definition {
+                Definition::Function(function @ Function { name: Some(_), .. })
+                    if function.publicity.is_importable()
+                        && (function.external_javascript.is_some()
+                            || function.implementations.supports(Target::JavaScript)) =>
+                {
+                    Some(function)
+                }
+                _ => None,
+            })
+            .collect();
+
+        let javascript: Vec<_> = functions
+            .iter()
+            .map(|function| self.function(function))
+            .collect();
+        let typescript: Vec<_> = functions
+            .iter()
+            .map(|function| self.function_declaration(function))
+            .collect();
+
+        ApiModule {
+            javascript: self.javascript_module(javascript),
+            typescript: self.typescript_module(typescript),
+        }
+    }
+
+    fn javascript_module(&self, functions: Vec<Document<'a>>) -> String {
+        let mut imports = Imports::new();
+        imports.register_module(
+            format!(
+                "./{}.{}",
+                self.module_file_name(),
+                self.module_format.extension()
+            ),
+            [MODULE_ALIAS.to_string()],
+            [],
+        );
+        if !self.prelude_members.is_empty() {
+            let prelude = self.import_path(&self.module.type_info.package, PRELUDE_MODULE_NAME);
+            let members = self.prelude_members.iter().map(|name| Member {
+                name: name.to_doc(),
+                alias: None,
+            });
+            imports.register_module(
+                format!("{prelude}.{}", self.module_format.extension()),
+                [],
+                members,
+            );
+        }
+        if self.option_used {
+            let option = self.import_path(OPTION_PACKAGE, OPTION_MODULE);
+            imports.register_module(
+                format!("{option}.{}", self.module_format.extension()),
+                ["$option".to_string()],
+                [],
+            );
+        }
+
+        let mut definitions = vec![];
+        if self.gleam_error_used {
+            definitions.push(docvec![
+                "export class GleamError extends globalThis.Error {",
+                docvec![
+                    line(),
+                    "constructor(error) {",
+                    docvec![
+                        line(),
+                        "super(\"The Gleam function returned an error\");",
+                        line(),
+                        "this.error = error;",
+                    ]
+                    .nest(INDENT),
+                    line(),
+                    "}",
+                ]
+                .nest(INDENT),
+                line(),
+                "}",
+            ]);
+        }
+        definitions.extend(functions);
+
+        module_document(imports, JavaScriptCodegenTarget::JavaScript, definitions)
+            .to_pretty_string(80)
+    }
+
+    fn typescript_module(&self, functions: Vec<Document<'a>>) -> String {
+        let mut imports = Imports::new();
+        let package = &self.module.type_info.package;
+        if !self.own_types.is_empty() {
+            let members = self.own_types.iter().map(|name| Member {
+                name: Document::String(name.clone()),
+                alias: None,
+            });
+            imports.register_module(
+                format!(
+                    "./{}.{}",
+                    self.module_file_name(),
+                    self.module_format.declaration_extension()
+                ),
+                [],
+                members,
+            );
+        }
+        if self.typescript.prelude_used() {
+            let prelude = self.import_path(package, PRELUDE_MODULE_NAME);
+            imports.register_module(
+                format!("{prelude}.{}", self.module_format.declaration_extension()),
+                ["_".to_string()],
+                [],
+            );
+        }
+        for (module, package) in &self.type_modules {
+            let path = self.import_path(package, module);
+            let alias = module.split('/').next_back().unwrap_or(module);
+            imports.register_module(
+                format!("{path}.{}", self.module_format.declaration_extension()),
+                [format!("${alias}")],
+                [],
+            );
+        }
+
+        let mut definitions = vec![];
+        if self.gleam_error_used {
+            definitions.push(docvec![
+                "export class GleamError extends globalThis.Error {",
+                docvec![line(), "error: unknown;"].nest(INDENT),
+                line(),
+                "}",
+            ]);
+        }
+        definitions.extend(functions);
+
+        module_document(
+            imports,
+            JavaScriptCodegenTarget::TypeScriptDeclarations,
+            definitions,
+        )
+        .to_pretty_string(80)
+    }
+
+    /// The name of the wrapped module's file, without an extension. The
+    /// wrapper is written to the same directory.
+    fn module_file_name(&self) -> &'a str {
+        let name = &self.module.name;
+        name.split('/').next_back().unwrap_or(name)
+    }
+
+    /// The path of a module relative to the wrapper, without an extension.
+    fn import_path(&self, package: &str, module: &str) -> String {
+        let depth = self.module.name.split('/').count();
+        if package == self.module.type_info.package || package.is_empty() {
+            match depth {
+                1 => format!("./{module}"),
+                _ => format!("{}{module}", "../".repeat(depth - 1)),
+            }
+        } else {
+            format!("{}{package}/{module}", "../".repeat(depth))
+        }
+    }
+
+    fn function(&mut self, function: &'a TypedFunction) -> Document<'a> {
+        let (_, name) = function
+            .name
+            .as_ref()
+            .expect("A module's function must be named");
+
+        let arguments = function.arguments.iter().enumerate().map(|(i, argument)| {
+            let name = argument_name(i, argument);
+            match self.convert_to_gleam(&argument.type_, name.clone(), 1) {
+                Some(converted) => converted,
+                None => name,
+            }
+        });
+        let call = docvec![
+            MODULE_ALIAS,
+            ".",
+            maybe_escape_identifier_doc(name),
+            wrap_args(arguments.collect::<Vec<_>>()),
+        ];
+
+        let body = match (shape(&function.return_type), self.results) {
+            (Shape::Result(value, error), ApiResults::Throw) => {
+                self.gleam_error_used = true;
+                let unwrapped = docvec![RESULT_VARIABLE, "[0]"];
+                let value = self
+                    .convert_from_gleam(&value, unwrapped.clone(), 1)
+                    .unwrap_or_else(|| unwrapped.clone());
+                let error = self
+                    .convert_from_gleam(&error, unwrapped.clone(), 1)
+                    .unwrap_or(unwrapped);
+                docvec![
+                    "let ",
+                    RESULT_VARIABLE,
+                    " = ",
+                    call,
+                    ";",
+                    line(),
+                    "if (!",
+                    RESULT_VARIABLE,
+                    ".isOk()) {",
+                    docvec![line(), "throw new GleamError(", error, ");"].nest(INDENT),
+                    line(),
+                    "}",
+                    line(),
+                    "return ",
+                    value,
+                    ";",
+                ]
+            }
+
+            _ => {
+                match self.convert_from_gleam(&function.return_type, RESULT_VARIABLE.to_doc(), 1) {
+                    Some(converted) => docvec![
+                        "let ",
+                        RESULT_VARIABLE,
+                        " = ",
+                        call,
+                        ";",
+                        line(),
+                        "return ",
+                        converted,
+                        ";",
+                    ],
+                    None => docvec!["return ", call, ";"],
+                }
+            }
+        };
+
+        let documentation = function.documentation.as_ref().map(|(_, doc)| doc);
+        docvec![
+            jsdoc(documentation, &function.deprecation),
+            "export function ",
+            maybe_escape_identifier_doc(name),
+            wrap_args(
+                function
+                    .arguments
+                    .iter()
+                    .enumerate()
+                    .map(|(i, argument)| argument_name(i, argument))
+            ),
+            " {",
+            docvec![line(), body].nest(INDENT),
+            line(),
+            "}",
+        ]
+    }
+
+    /// Converts a JavaScript value to the Gleam representation of the given
+    /// type, or returns `None` if it needs no conversion.
+    fn convert_to_gleam(
+        &mut self,
+        type_: &Arc<Type>,
+        value: Document<'a>,
+        depth: usize,
+    ) -> Option<Document<'a>> {
+        match shape(type_) {
+            Shape::List(element) => {
+                let _ = self.prelude_members.insert("toList");
+                let variable = element_variable(depth);
+                Some(
+                    match self.convert_to_gleam(&element, variable.clone(), depth + 1) {
+                        Some(converted) => {
+                            docvec!["toList(", value, ".map((", variable, ") => ", converted, "))"]
+                        }
+                        None => docvec!["toList(", value, ")"],
+                    },
+                )
+            }
+
+            Shape::Option(inner) => {
+                self.option_used = true;
+                let converted = if is_option_type(&inner) {
+                    value.clone()
+                } else {
+                    self.convert_to_gleam(&inner, value.clone(), depth)
+                        .unwrap_or_else(|| value.clone())
+                };
+                Some(docvec![
+                    "(",
+                    value,
+                    " === undefined ? new $option.None() : new $option.Some(",
+                    converted,
+                    "))"
+                ])
+            }
+
+            Shape::Result(ok, error) => {
+                let _ = self.prelude_members.insert("Ok");
+                let _ = self.prelude_members.insert("Error");
+                let ok_value = docvec![value.clone(), ".value"];
+                let ok_value = self
+                    .convert_to_gleam(&ok, ok_value.clone(), depth)
+                    .unwrap_or(ok_value);
+                let error_value = docvec![value.clone(), ".error"];
+                let error_value = self
+                    .convert_to_gleam(&error, error_value.clone(), depth)
+                    .unwrap_or(error_value);
+                Some(docvec![
+                    "(",
+                    value,
+                    ".ok ? new Ok(",
+                    ok_value,
+                    ") : new Error(",
+                    error_value,
+                    "))"
+                ])
+            }
+
+            Shape::Tuple(elements) => {
+                self.convert_tuple(&elements, value, |generator, type_, value| {
+                    generator.convert_to_gleam(type_, value, depth)
+                })
+            }
+
+            Shape::Other => None,
+        }
+    }
+
+    /// Converts a Gleam value of the given type to its JavaScript
+    /// representation, or returns `None` if it needs no conversion.
+    fn convert_from_gleam(
+        &mut self,
+        type_: &Arc<Type>,
+        value: Document<'a>,
+        depth: usize,
+    ) -> Option<Document<'a>> {
+        match shape(type_) {
+            Shape::List(element) => {
+                let variable = element_variable(depth);
+                Some(
+                    match self.convert_from_gleam(&element, variable.clone(), depth + 1) {
+                        Some(converted) => {
+                            docvec![value, ".toArray().map((", variable, ") => ", converted, ")"]
+                        }
+                        None => docvec![value, ".toArray()"],
+                    },
+                )
+            }
+
+            Shape::Option(inner) => {
+                self.option_used = true;
+                let wrapped = docvec![value.clone(), "[0]"];
+                let converted = if is_option_type(&inner) {
+                    wrapped
+                } else {
+                    self.convert_from_gleam(&inner, wrapped.clone(), depth)
+                        .unwrap_or(wrapped)
+                };
+                Some(docvec![
+                    "(",
+                    value,
+                    " instanceof $option.Some ? ",
+                    converted,
+                    " : undefined)"
+                ])
+            }
+
+            Shape::Result(ok, error) => {
+                let wrapped = docvec![value.clone(), "[0]"];
+                let ok_value = self
+                    .convert_from_gleam(&ok, wrapped.clone(), depth)
+                    .unwrap_or_else(|| wrapped.clone());
+                let error_value = self
+                    .convert_from_gleam(&error, wrapped.clone(), depth)
+                    .unwrap_or(wrapped);
+                Some(docvec![
+                    "(",
+                    value,
+                    ".isOk() ? { ok: true, value: ",
+                    ok_value,
+                    " } : { ok: false, error: ",
+                    error_value,
+                    " })"
+                ])
+            }
+
+            Shape::Tuple(elements) => {
+                self.convert_tuple(&elements, value, |generator, type_, value| {
+                    generator.convert_from_gleam(type_, value, depth)
+                })
+            }
+
+            Shape::Other => None,
+        }
+    }
+
+    /// Converts each element of a tuple, or returns `None` if none of them
+    /// need converting.
+    fn convert_tuple(
+        &mut self,
+        elements: &[Arc<Type>],
+        value: Document<'a>,
+        mut convert: impl FnMut(&mut Self, &Arc<Type>, Document<'a>) -> Option<Document<'a>>,
+    ) -> Option<Document<'a>> {
+        let mut any_converted = false;
+        let elements: Vec<_> = elements
+            .iter()
+            .enumerate()
+            .map(|(i, type_)| {
+                let element = docvec![value.clone(), "[", i, "]"];
+                match convert(self, type_, element.clone()) {
+                    Some(converted) => {
+                        any_converted = true;
+                        converted
+                    }
+                    None => element,
+                }
+            })
+            .collect();
+
+        if any_converted {
+            Some(docvec!["[", join(elements, ", ".to_doc()), "]"])
+        } else {
+            None
+        }
+    }
+
+    fn function_declaration(&mut self, function: &'a TypedFunction) -> Document<'a> {
+        let (_, name) = function
+            .name
+            .as_ref()
+            .expect("A module's function must be named");
+
+        let generic_usages = collect_generic_usages(
+            HashMap::new(),
+            std::iter::once(&function.return_type)
+                .chain(function.arguments.iter().map(|a| &a.type_)),
+        );
+        let mut generic_ids: Vec<_> = generic_usages
+            .iter()
+            .filter(|(_id, use_count)| **use_count > 1)
+            .map(|(id, _use_count)| *id)
+            .collect();
+        generic_ids.sort();
+        let generics = if generic_ids.is_empty() {
+            nil()
+        } else {
+            docvec![
+                "<",
+                join(generic_ids.into_iter().map(id_to_type_var), ", ".to_doc()),
+                ">"
+            ]
+        };
+
+        let arguments: Vec<_> = function
+            .arguments
+            .iter()
+            .enumerate()
+            .map(|(i, argument)| {
+                docvec![
+                    argument_name(i, argument),
+                    ": ",
+                    self.api_type(&argument.type_, &generic_usages)
+                ]
+            })
+            .collect();
+
+        let return_type = match (shape(&function.return_type), self.results) {
+            (Shape::Result(value, _), ApiResults::Throw) => self.api_type(&value, &generic_usages),
+            _ => self.api_type(&function.return_type, &generic_usages),
+        };
+
+        let documentation = function.documentation.as_ref().map(|(_, doc)| doc);
+        docvec![
+            jsdoc(documentation, &function.deprecation),
+            "export function ",
+            maybe_escape_identifier_doc(name),
+            generics,
+            wrap_args(arguments),
+            ": ",
+            return_type,
+            ";",
+        ]
+    }
+
+    /// The TypeScript type of a value of the given Gleam type once it has been
+    /// converted at the boundary.
+    fn api_type(&mut self, type_: &Arc<Type>, generic_usages: &HashMap<u64, u64>) -> Document<'a> {
+        match shape(type_) {
+            Shape::List(element) => {
+                docvec!["Array<", self.api_type(&element, generic_usages), ">"]
+            }
+
+            Shape::Option(inner) if is_option_type(&inner) => {
+                docvec![self.gleam_type(&inner, generic_usages), " | undefined"]
+            }
+
+            Shape::Option(inner) => {
+                docvec![self.api_type(&inner, generic_usages), " | undefined"]
+            }
+
+            Shape::Result(ok, error) => docvec![
+                "{ ok: true; value: ",
+                self.api_type(&ok, generic_usages),
+                " } | { ok: false; error: ",
+                self.api_type(&error, generic_usages),
+                " }",
+            ],
+
+            Shape::Tuple(elements) => {
+                let elements: Vec<_> = elements
+                    .iter()
+                    .map(|element| self.api_type(element, generic_usages))
+                    .collect();
+                docvec!["[", join(elements, ", ".to_doc()), "]"]
+            }
+
+            Shape::Other => self.gleam_type(type_, generic_usages),
+        }
+    }
+
+    /// The TypeScript type of a value that is passed through unconverted.
+    fn gleam_type(
+        &mut self,
+        type_: &Arc<Type>,
+        generic_usages: &HashMap<u64, u64>,
+    ) -> Document<'a> {
+        self.register_type_modules(type_);
+        self.typescript
+            .print_type_with_generic_usages(type_, generic_usages)
+    }
+
+    /// Records the modules that define the custom types used in a type, so
+    /// they can be imported by the declarations.
+    fn register_type_modules(&mut self, type_: &Arc<Type>) {
+        let type_ = collapse_links(type_.clone());
+        match type_.as_ref() {
+            Type::Named {
+                package,
+                module,
+                name,
+                args,
+                ..
+            } => {
+                if module == &self.module.name {
+                    let _ = self
+                        .own_types
+                        .insert(format!("{}$", ts_safe_type_name(name.to_string())));
+                } else if !is_prelude_module(module) {
+                    let _ = self.type_modules.insert(module.clone(), package.clone());
+                }
+                for arg in args {
+                    self.register_type_modules(arg);
+                }
+            }
+
+            Type::Fn { args, retrn } => {
+                for arg in args {
+                    self.register_type_modules(arg);
+                }
+                self.register_type_modules(retrn);
+            }
+
+            Type::Tuple { elems } => {
+                for elem in elems {
+                    self.register_type_modules(elem);
+                }
+            }
+
+            Type::Var { .. } => (),
+        }
+    }
+}
+
+fn module_document<'a>(
+    imports: Imports<'a>,
+    target: JavaScriptCodegenTarget,
+    definitions: Vec<Document<'a>>,
+) -> Document<'a> {
+    let definitions = join(definitions, lines(2));
+    if imports.is_empty() {
+        docvec![definitions, line()]
+    } else {
+        docvec![imports.into_doc(target), line(), definitions, line()]
+    }
+}
+
+fn argument_name(index: usize, argument: &TypedArg) -> Document<'_> {
+    match argument.get_variable_name() {
+        Some(name) => maybe_escape_identifier_doc(name),
+        None => Document::String(format!("x{index}")),
+    }
+}
+
+/// The variable bound to each element of a list while it is converted.
+fn element_variable(depth: usize) -> Document<'static> {
+    Document::String(format!("$x{depth}"))
+}
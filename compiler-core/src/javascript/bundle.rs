@@ -0,0 +1,1289 @@
+//! Bundling of a project's compiled JavaScript into a single ES module.
+//!
+//! Starting from the `main` function of an entry module the typed AST is
+//! walked to find every function, constant and record constructor that could
+//! be used at runtime. FFI modules used by any of these are scanned for the
+//! Gleam definitions they import in turn. Everything else is dropped before
+//! code generation.
+//!
+//! Each remaining module (along with the prelude and any FFI modules) is then
+//! wrapped in a function scope so that their top level names cannot clash, and
+//! the ES module import and export statements are rewritten to refer to the
+//! other wrapped modules.
+//!
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::{HashMap, HashSet};
+
+use camino::Utf8Path;
+use ecow::EcoString;
+use itertools::Itertools;
+
+use crate::{
+    analyse::TargetSupport,
+    ast::{
+        visit::{self, Visit},
+        BitArrayOption, ClauseGuard, Constant, CustomType, Definition, Function, Import,
+        ModuleConstant, SrcSpan, TypedClause, TypedClauseGuard, TypedConstant,
+    },
+    build::Module,
+    codegen::TypeScriptDeclarations,
+    config::{JavaScriptIntegers, JavaScriptModuleFormat},
+    io::FileSystemReader,
+    line_numbers::LineNumbers,
+    type_::{ModuleValueConstructor, ValueConstructor, ValueConstructorVariant},
+};
+
+/// The path used for the prelude module within the bundle.
+const PRELUDE_PATH: &str = "prelude.mjs";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// A module uses an ES module feature that the bundler does not know how
+    /// to rewrite.
+    UnsupportedSyntax {
+        path: EcoString,
+        line: usize,
+        syntax: &'static str,
+    },
+
+    /// A module re-exports everything from a module that imports it, so its
+    /// exports cannot be known when it is evaluated.
+    ImportCycle { modules: Vec<EcoString> },
+
+    /// A module imports a relative path that is not part of the build.
+    UnknownModule {
+        path: EcoString,
+        importer: EcoString,
+    },
+}
+
+/// Compile the given modules into a single ES module which exports the
+/// `main` function of the entry module.
+///
+/// The modules must have been freshly compiled for the JavaScript target so
+/// that their typed ASTs are available, and `build_directory` must be the
+/// JavaScript build directory they were compiled into, as FFI modules are
+/// read from there.
+///
+pub fn bundle<IO: FileSystemReader>(
+    io: &IO,
+    build_directory: &Utf8Path,
+    modules: Vec<Module>,
+    entry: &str,
+    integers: JavaScriptIntegers,
+) -> crate::Result<String> {
+    let modules: HashMap<EcoString, Module> = modules
+        .into_iter()
+        .map(|module| (module.name.clone(), module))
+        .collect();
+    let entry_path: EcoString = match modules.get(entry) {
+        Some(module) => module_path(module).into(),
+        None => {
+            return Err(crate::Error::ModuleDoesNotExist {
+                module: entry.into(),
+                suggestion: None,
+            })
+        }
+    };
+    let gleam_paths = modules.values().map(module_path).collect();
+
+    let mut reachability = Reachability::new(io, build_directory, &modules);
+    reachability.push(entry.into(), "main".into());
+    reachability.run()?;
+    let Reachability {
+        reachable, natives, ..
+    } = reachability;
+
+    // Generate the JavaScript for each module that has anything left in it
+    // once unreachable definitions have been removed.
+    let options = super::CodegenOptions {
+        target_support: TargetSupport::Enforced,
+        typescript: TypeScriptDeclarations::None,
+        integers,
+        module_format: JavaScriptModuleFormat::Esm,
+        check_externals: false,
+    };
+    let mut sources: HashMap<EcoString, String> = HashMap::new();
+    for (name, mut module) in modules {
+        let Some(keep) = reachable.get(&name) else {
+            continue;
+        };
+        remove_unreachable_definitions(&mut module, keep);
+        let line_numbers = LineNumbers::new(&module.code);
+        let code = super::module(
+            &module.ast,
+            &line_numbers,
+            &module.input_path,
+            &module.code,
+            options,
+        )?;
+        let _ = sources.insert(module_path(&module).into(), code);
+    }
+
+    for path in natives {
+        let code = io.read(&build_directory.join(path.as_str()))?;
+        let _ = sources.insert(path, code);
+    }
+    let _ = sources.insert(PRELUDE_PATH.into(), super::PRELUDE.into());
+
+    let mut bundled = BundledModules {
+        modules: HashMap::new(),
+        gleam_paths,
+    };
+    for (path, code) in &sources {
+        let chunks = scan(path, code).map_err(bundle_error)?;
+        let _ = bundled.modules.insert(path.clone(), chunks);
+    }
+    bundled.render(&entry_path).map_err(bundle_error)
+}
+
+fn bundle_error(error: Error) -> crate::Error {
+    crate::Error::JavaScriptBundle { error }
+}
+
+/// The path of the compiled JavaScript for a module, relative to the
+/// JavaScript build directory.
+fn module_path(module: &Module) -> String {
+    format!("{}/{}.mjs", module.ast.type_info.package, module.name)
+}
+
+fn remove_unreachable_definitions(module: &mut Module, keep: &HashSet<EcoString>) {
+    module
+        .ast
+        .definitions
+        .retain_mut(|definition| match definition {
+            Definition::Function(Function { name, .. }) => {
+                name.as_ref().is_some_and(|(_, name)| keep.contains(name))
+            }
+
+            Definition::ModuleConstant(ModuleConstant { name, .. }) => keep.contains(name),
+
+            Definition::CustomType(CustomType { constructors, .. }) => {
+                constructors.retain(|constructor| keep.contains(&constructor.name));
+                true
+            }
+
+            Definition::TypeAlias(_) | Definition::Import(_) => true,
+        });
+}
+
+/// The module level names of a Gleam module, and the definitions they refer
+/// to.
+#[derive(Debug, Default)]
+struct ModuleScope {
+    /// Functions, constants, record constructors and unqualified imports,
+    /// mapped to the module and name of their definition.
+    values: HashMap<EcoString, (EcoString, EcoString)>,
+    /// The names used to refer to imported modules, mapped to the full module
+    /// name.
+    aliases: HashMap<EcoString, EcoString>,
+    /// The names of all the public values defined in the module.
+    public: Vec<EcoString>,
+}
+
+impl ModuleScope {
+    fn new(module: &Module) -> Self {
+        let mut scope = Self::default();
+        let module_name = &module.name;
+        let define = |scope: &mut Self, name: &EcoString, public: bool| {
+            let _ = scope
+                .values
+                .insert(name.clone(), (module_name.clone(), name.clone()));
+            if public {
+                scope.public.push(name.clone());
+            }
+        };
+
+        for definition in &module.ast.definitions {
+            match definition {
+                Definition::Function(Function {
+                    name: Some((_, name)),
+                    publicity,
+                    ..
+                }) => define(&mut scope, name, publicity.is_importable()),
+
+                Definition::ModuleConstant(ModuleConstant {
+                    name, publicity, ..
+                }) => define(&mut scope, name, publicity.is_importable()),
+
+                Definition::CustomType(CustomType {
+                    constructors,
+                    publicity,
+                    opaque,
+                    ..
+                }) => {
+                    for constructor in constructors {
+                        let public = publicity.is_importable() && !opaque;
+                        define(&mut scope, &constructor.name, public);
+                    }
+                }
+
+                Definition::Import(import @ Import { module, .. }) => {
+                    if let Some(alias) = import.used_name() {
+                        let _ = scope.aliases.insert(alias, module.clone());
+                    }
+                    for value in &import.unqualified_values {
+                        let _ = scope.values.insert(
+                            value.used_name().clone(),
+                            (module.clone(), value.name.clone()),
+                        );
+                    }
+                }
+
+                Definition::Function(Function { name: None, .. }) | Definition::TypeAlias(_) => {}
+            }
+        }
+
+        scope
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Item {
+    /// A function, constant or record constructor in a Gleam module.
+    Value(EcoString, EcoString),
+    /// A hand written JavaScript module, identified by its path.
+    Native(EcoString),
+}
+
+/// Walks the definitions reachable from the entry point, recording which are
+/// used in each Gleam module and which FFI modules are needed.
+struct Reachability<'a, IO> {
+    io: &'a IO,
+    build_directory: &'a Utf8Path,
+    modules: &'a HashMap<EcoString, Module>,
+    scopes: HashMap<EcoString, ModuleScope>,
+    /// The JavaScript path of each Gleam module, mapped to its name.
+    gleam_paths: HashMap<String, EcoString>,
+    queue: Vec<Item>,
+    seen: HashSet<Item>,
+    reachable: HashMap<EcoString, HashSet<EcoString>>,
+    natives: HashSet<EcoString>,
+}
+
+impl<'a, IO: FileSystemReader> Reachability<'a, IO> {
+    fn new(
+        io: &'a IO,
+        build_directory: &'a Utf8Path,
+        modules: &'a HashMap<EcoString, Module>,
+    ) -> Self {
+        Self {
+            io,
+            build_directory,
+            modules,
+            scopes: modules
+                .iter()
+                .map(|(name, module)| (name.clone(), ModuleScope::new(module)))
+                .collect(),
+            gleam_paths: modules
+                .iter()
+                .map(|(name, module)| (module_path(module), name.clone()))
+                .collect(),
+            queue: vec![],
+            seen: HashSet::new(),
+            reachable: HashMap::new(),
+            natives: HashSet::new(),
+        }
+    }
+
+    fn push(&mut self, module: EcoString, name: EcoString) {
+        self.push_item(Item::Value(module, name));
+    }
+
+    fn push_item(&mut self, item: Item) {
+        if self.seen.insert(item.clone()) {
+            self.queue.push(item);
+        }
+    }
+
+    fn run(&mut self) -> crate::Result<()> {
+        while let Some(item) = self.queue.pop() {
+            match item {
+                Item::Value(module, name) => self.value(module, name),
+                Item::Native(path) => self.native(path)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn value(&mut self, module_name: EcoString, name: EcoString) {
+        let Some(module) = self.modules.get(&module_name) else {
+            // Not a Gleam module compiled in this build, so there is nothing
+            // to follow.
+            return;
+        };
+        let _ = self
+            .reachable
+            .entry(module_name.clone())
+            .or_default()
+            .insert(name.clone());
+
+        let scope = self.scopes.get(&module_name).expect("Module scope");
+        let mut references = References {
+            scope,
+            found: vec![],
+        };
+        let mut native = None;
+
+        for definition in &module.ast.definitions {
+            match definition {
+                Definition::Function(
+                    function @ Function {
+                        name: Some((_, n)), ..
+                    },
+                ) if *n == name => match &function.external_javascript {
+                    Some((path, _)) => native = resolve_import(&module_path(module), path),
+                    None => references.visit_typed_function(function),
+                },
+
+                Definition::ModuleConstant(constant) if constant.name == name => {
+                    references.constant(&constant.value)
+                }
+
+                Definition::Function(_)
+                | Definition::ModuleConstant(_)
+                | Definition::CustomType(_)
+                | Definition::TypeAlias(_)
+                | Definition::Import(_) => (),
+            }
+        }
+
+        for (module, name) in references.found {
+            self.push(module, name);
+        }
+        if let Some(path) = native {
+            self.push_item(Item::Native(path.into()));
+        }
+    }
+
+    /// Scan an FFI module for any Gleam definitions or other FFI modules that
+    /// it imports.
+    fn native(&mut self, path: EcoString) -> crate::Result<()> {
+        if path == PRELUDE_PATH {
+            return Ok(());
+        }
+        let code = self.io.read(&self.build_directory.join(path.as_str()))?;
+        let chunks = scan(&path, &code).map_err(bundle_error)?;
+        let _ = self.natives.insert(path.clone());
+
+        let imports = chunks.iter().filter_map(|chunk| match chunk {
+            Chunk::Import(import) => Some((
+                &import.specifier,
+                import.members.as_slice(),
+                import.namespace.is_some(),
+            )),
+            Chunk::Export(Export::Names {
+                from: Some(from),
+                names,
+            }) => Some((from, names.as_slice(), false)),
+            Chunk::Export(Export::All { from, .. }) => Some((from, [].as_slice(), true)),
+            Chunk::Code(_) | Chunk::Export(_) => None,
+        });
+        let mut found = vec![];
+        for (specifier, members, everything) in imports {
+            let Some(target) = resolve_import(&path, specifier) else {
+                continue;
+            };
+            let Some(module) = self.gleam_paths.get(&target).cloned() else {
+                found.push(Item::Native(prelude_or(target).into()));
+                continue;
+            };
+            let scope = self.scopes.get(&module).expect("Module scope");
+            let names: Vec<EcoString> = if everything {
+                scope.public.clone()
+            } else {
+                members
+                    .iter()
+                    .map(|(imported, _)| unescape_identifier(scope, imported))
+                    .collect()
+            };
+            found.extend(
+                names
+                    .into_iter()
+                    .map(|name| Item::Value(module.clone(), name)),
+            );
+        }
+
+        for item in found {
+            self.push_item(item);
+        }
+        Ok(())
+    }
+}
+
+/// Gleam names that are not valid JavaScript identifiers are escaped with a
+/// trailing `$` by the code generator.
+fn unescape_identifier(scope: &ModuleScope, name: &str) -> EcoString {
+    match name.strip_suffix('$') {
+        Some(unescaped) if scope.values.contains_key(unescaped) => unescaped.into(),
+        _ => name.into(),
+    }
+}
+
+/// The `gleam.mjs` module in each package only re-exports the prelude, so it
+/// is replaced with the prelude itself.
+fn prelude_or(path: String) -> String {
+    match path.split_once('/') {
+        Some((_, "gleam.mjs")) => PRELUDE_PATH.into(),
+        _ => path,
+    }
+}
+
+/// Collects the module level definitions referenced by an expression.
+struct References<'a> {
+    scope: &'a ModuleScope,
+    found: Vec<(EcoString, EcoString)>,
+}
+
+impl References<'_> {
+    fn unqualified(&mut self, name: &EcoString) {
+        if let Some(value) = self.scope.values.get(name) {
+            self.found.push(value.clone());
+        }
+    }
+
+    fn qualified(&mut self, alias: &EcoString, name: &EcoString) {
+        if let Some(module) = self.scope.aliases.get(alias) {
+            self.found.push((module.clone(), name.clone()));
+        }
+    }
+
+    fn constant(&mut self, constant: &TypedConstant) {
+        match constant {
+            Constant::Int { .. }
+            | Constant::Float { .. }
+            | Constant::String { .. }
+            | Constant::Invalid { .. } => (),
+
+            Constant::Tuple { elements, .. } | Constant::List { elements, .. } => {
+                elements.iter().for_each(|element| self.constant(element))
+            }
+
+            Constant::Record {
+                module, name, args, ..
+            } => {
+                match module {
+                    Some(alias) => self.qualified(alias, name),
+                    None => self.unqualified(name),
+                }
+                args.iter().for_each(|arg| self.constant(&arg.value))
+            }
+
+            Constant::BitArray { segments, .. } => {
+                for segment in segments {
+                    self.constant(&segment.value);
+                    for option in &segment.options {
+                        if let BitArrayOption::Size { value, .. } = option {
+                            self.constant(value);
+                        }
+                    }
+                }
+            }
+
+            Constant::Var {
+                module: Some(alias),
+                name,
+                ..
+            } => self.qualified(alias, name),
+
+            Constant::Var {
+                module: None, name, ..
+            } => self.unqualified(name),
+
+            Constant::StringConcatenation { left, right, .. } => {
+                self.constant(left);
+                self.constant(right);
+            }
+        }
+    }
+
+    fn guard(&mut self, guard: &TypedClauseGuard) {
+        match guard {
+            ClauseGuard::Equals { left, right, .. }
+            | ClauseGuard::NotEquals { left, right, .. }
+            | ClauseGuard::GtInt { left, right, .. }
+            | ClauseGuard::GtEqInt { left, right, .. }
+            | ClauseGuard::LtInt { left, right, .. }
+            | ClauseGuard::LtEqInt { left, right, .. }
+            | ClauseGuard::GtFloat { left, right, .. }
+            | ClauseGuard::GtEqFloat { left, right, .. }
+            | ClauseGuard::LtFloat { left, right, .. }
+            | ClauseGuard::LtEqFloat { left, right, .. }
+            | ClauseGuard::AddInt { left, right, .. }
+            | ClauseGuard::AddFloat { left, right, .. }
+            | ClauseGuard::SubInt { left, right, .. }
+            | ClauseGuard::SubFloat { left, right, .. }
+            | ClauseGuard::MultInt { left, right, .. }
+            | ClauseGuard::MultFloat { left, right, .. }
+            | ClauseGuard::DivInt { left, right, .. }
+            | ClauseGuard::DivFloat { left, right, .. }
+            | ClauseGuard::RemainderInt { left, right, .. }
+            | ClauseGuard::Or { left, right, .. }
+            | ClauseGuard::And { left, right, .. } => {
+                self.guard(left);
+                self.guard(right);
+            }
+
+            ClauseGuard::Not { expression, .. } => self.guard(expression),
+            ClauseGuard::TupleIndex { tuple, .. } => self.guard(tuple),
+            ClauseGuard::FieldAccess { container, .. } => self.guard(container),
+
+            // Guard variables do not record what they refer to, so this may
+            // be a variable bound by the pattern shadowing a module level
+            // name. Keeping the definition in that case is harmless.
+            ClauseGuard::Var { name, .. } => self.unqualified(name),
+
+            ClauseGuard::ModuleSelect {
+                module_name, label, ..
+            } => self.found.push((module_name.clone(), label.clone())),
+
+            ClauseGuard::Constant(constant) => self.constant(constant),
+        }
+    }
+}
+
+impl<'ast> Visit<'ast> for References<'_> {
+    fn visit_typed_expr_var(
+        &mut self,
+        location: &'ast SrcSpan,
+        constructor: &'ast ValueConstructor,
+        name: &'ast EcoString,
+    ) {
+        match &constructor.variant {
+            ValueConstructorVariant::Record { module, name, .. } => {
+                self.found.push((module.clone(), name.clone()))
+            }
+            ValueConstructorVariant::ModuleFn { .. }
+            | ValueConstructorVariant::ModuleConstant { .. } => self.unqualified(name),
+            ValueConstructorVariant::LocalConstant { literal } => self.constant(literal),
+            ValueConstructorVariant::LocalVariable { .. } => (),
+        }
+        visit::visit_typed_expr_var(self, location, constructor, name);
+    }
+
+    fn visit_typed_expr_module_select(
+        &mut self,
+        location: &'ast SrcSpan,
+        typ: &'ast std::sync::Arc<crate::type_::Type>,
+        label: &'ast EcoString,
+        module_name: &'ast EcoString,
+        module_alias: &'ast EcoString,
+        constructor: &'ast ModuleValueConstructor,
+    ) {
+        self.found.push((module_name.clone(), label.clone()));
+        visit::visit_typed_expr_module_select(
+            self,
+            location,
+            typ,
+            label,
+            module_name,
+            module_alias,
+            constructor,
+        );
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn visit_typed_pattern_constructor(
+        &mut self,
+        location: &'ast SrcSpan,
+        name: &'ast EcoString,
+        arguments: &'ast Vec<crate::ast::CallArg<crate::ast::TypedPattern>>,
+        module: &'ast Option<EcoString>,
+        constructor: &'ast crate::analyse::Inferred<crate::type_::PatternConstructor>,
+        spread: &'ast Option<SrcSpan>,
+        type_: &'ast std::sync::Arc<crate::type_::Type>,
+    ) {
+        if let crate::analyse::Inferred::Known(constructor) = constructor {
+            self.found
+                .push((constructor.module.clone(), constructor.name.clone()));
+        }
+        visit::visit_typed_pattern_constructor(
+            self,
+            location,
+            name,
+            arguments,
+            module,
+            constructor,
+            spread,
+            type_,
+        );
+    }
+
+    fn visit_typed_clause(&mut self, clause: &'ast TypedClause) {
+        if let Some(guard) = &clause.guard {
+            self.guard(guard);
+        }
+        visit::visit_typed_clause(self, clause);
+    }
+}
+
+/// Resolve an import specifier against the path of the importing module.
+/// Returns `None` for specifiers that are not relative paths, such as those
+/// for npm packages or runtime built-in modules.
+fn resolve_import(importer: &str, specifier: &str) -> Option<String> {
+    if !specifier.starts_with("./") && !specifier.starts_with("../") {
+        return None;
+    }
+
+    let mut segments: Vec<&str> = importer.split('/').collect();
+    let _ = segments.pop();
+    for segment in specifier.split('/') {
+        match segment {
+            "." | "" => (),
+            ".." => {
+                let _ = segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    Some(segments.join("/"))
+}
+
+/// A piece of an ES module, as split up by the `scan` function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Chunk<'a> {
+    /// Code that is included in the bundle as-is.
+    Code(&'a str),
+    Import(EsImport),
+    Export(Export),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct EsImport {
+    specifier: String,
+    line: usize,
+    default: Option<String>,
+    namespace: Option<String>,
+    /// Pairs of the imported name and the local name.
+    members: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Export {
+    /// An exported function, class or variable declaration. The declaration
+    /// itself is kept as code, only the `export` keyword is removed.
+    Declaration { name: String },
+    /// `export { a, b as c }`, optionally re-exporting from another module.
+    /// Pairs of the local name and the exported name.
+    Names {
+        names: Vec<(String, String)>,
+        from: Option<String>,
+    },
+    /// `export * from "..."` or `export * as name from "..."`.
+    All { name: Option<String>, from: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScanState {
+    Code,
+    LineComment,
+    BlockComment,
+    String(u8),
+    Template,
+}
+
+/// Split an ES module into the import and export statements that need
+/// rewriting and the code in between them.
+///
+/// This is not a full JavaScript parser. Only statements that start at the
+/// beginning of a line are considered, and comments, strings and template
+/// literals are skipped over so that they do not get mistaken for
+/// statements. This is always the case for the code that Gleam generates and
+/// is how FFI modules are conventionally written.
+///
+fn scan<'a>(path: &str, src: &'a str) -> Result<Vec<Chunk<'a>>, Error> {
+    let bytes = src.as_bytes();
+    let mut chunks = vec![];
+    let mut state = ScanState::Code;
+    let mut code_start = 0;
+    let mut i = 0;
+
+    while let Some(&byte) = bytes.get(i) {
+        let line_start = i == 0 || bytes.get(i - 1) == Some(&b'\n');
+        if state == ScanState::Code && line_start {
+            let statement = if starts_with_keyword(src, i, "import") {
+                scan_import(path, src, i)?.map(|(import, end)| (Chunk::Import(import), end))
+            } else if starts_with_keyword(src, i, "export") {
+                Some(scan_export(path, src, i)?)
+            } else {
+                None
+            };
+
+            if let Some((chunk, end)) = statement {
+                if code_start < i {
+                    chunks.push(Chunk::Code(&src[code_start..i]));
+                }
+                chunks.push(chunk);
+                i = end;
+                code_start = end;
+                continue;
+            }
+        }
+
+        let next = bytes.get(i + 1).copied();
+        state = match (state, byte) {
+            (ScanState::Code, b'/') if next == Some(b'/') => ScanState::LineComment,
+            (ScanState::Code, b'/') if next == Some(b'*') => {
+                i += 1;
+                ScanState::BlockComment
+            }
+            (ScanState::Code, b'"' | b'\'') => ScanState::String(byte),
+            (ScanState::Code, b'`') => ScanState::Template,
+            (ScanState::LineComment, b'\n') => ScanState::Code,
+            (ScanState::BlockComment, b'*') if next == Some(b'/') => {
+                i += 1;
+                ScanState::Code
+            }
+            (ScanState::String(_) | ScanState::Template, b'\\') => {
+                i += 1;
+                state
+            }
+            (ScanState::String(quote), _) if byte == quote || byte == b'\n' => ScanState::Code,
+            (ScanState::Template, b'`') => ScanState::Code,
+            (state, _) => state,
+        };
+        i += 1;
+    }
+
+    if code_start < bytes.len() {
+        chunks.push(Chunk::Code(&src[code_start..]));
+    }
+    Ok(chunks)
+}
+
+fn starts_with_keyword(src: &str, position: usize, keyword: &str) -> bool {
+    src[position..].starts_with(keyword)
+        && !src[position + keyword.len()..]
+            .chars()
+            .next()
+            .is_some_and(is_identifier_char)
+}
+
+fn is_identifier_char(char: char) -> bool {
+    char.is_alphanumeric() || char == '_' || char == '$'
+}
+
+fn line_of(src: &str, position: usize) -> usize {
+    src[..position].matches('\n').count() + 1
+}
+
+fn skip_whitespace(src: &str, mut position: usize) -> usize {
+    while src[position..].starts_with(char::is_whitespace) {
+        position += src[position..].chars().next().map_or(1, char::len_utf8);
+    }
+    position
+}
+
+/// Read a quoted module specifier, returning it and the position after the
+/// statement's terminating semicolon, if there is one.
+fn scan_specifier(src: &str, position: usize) -> Option<(String, usize)> {
+    let quote = src[position..].chars().next()?;
+    if quote != '"' && quote != '\'' {
+        return None;
+    }
+    let length = src[position + 1..].find(quote)?;
+    let specifier = src[position + 1..position + 1 + length].to_string();
+    let mut end = position + length + 2;
+    let after = skip_inline_whitespace(src, end);
+    if src[after..].starts_with(';') {
+        end = after + 1;
+    }
+    Some((specifier, end))
+}
+
+fn skip_inline_whitespace(src: &str, mut position: usize) -> usize {
+    while src[position..].starts_with([' ', '\t']) {
+        position += 1;
+    }
+    position
+}
+
+/// Scan an import statement. Returns `None` if this is a dynamic `import()`
+/// expression or an `import.meta` rather than a statement.
+fn scan_import(path: &str, src: &str, start: usize) -> Result<Option<(EsImport, usize)>, Error> {
+    let line = line_of(src, start);
+    let unsupported = |syntax| Error::UnsupportedSyntax {
+        path: path.into(),
+        line,
+        syntax,
+    };
+    let position = skip_whitespace(src, start + "import".len());
+    if src[position..].starts_with(['(', '.']) {
+        return Ok(None);
+    }
+
+    // A side effect only import, `import "./module.mjs";`
+    if let Some((specifier, end)) = scan_specifier(src, position) {
+        let import = EsImport {
+            specifier,
+            line,
+            default: None,
+            namespace: None,
+            members: vec![],
+        };
+        return Ok(Some((import, end)));
+    }
+
+    let quote = src[position..]
+        .find(['"', '\''])
+        .ok_or_else(|| unsupported("this import statement"))?;
+    let clause = src[position..position + quote].trim_end();
+    let clause = clause
+        .strip_suffix("from")
+        .ok_or_else(|| unsupported("this import statement"))?
+        .trim();
+    let (specifier, end) = scan_specifier(src, position + quote)
+        .ok_or_else(|| unsupported("this import statement"))?;
+
+    let (default, rest) = match clause.split_once(',') {
+        _ if clause.starts_with(['{', '*']) => (None, clause),
+        Some((default, rest)) => (Some(default.trim().to_string()), rest.trim()),
+        None => (Some(clause.to_string()), ""),
+    };
+
+    let mut import = EsImport {
+        specifier,
+        line,
+        default,
+        namespace: None,
+        members: vec![],
+    };
+    if let Some(namespace) = rest.strip_prefix('*') {
+        let name = namespace
+            .trim()
+            .strip_prefix("as")
+            .ok_or_else(|| unsupported("this import statement"))?;
+        import.namespace = Some(name.trim().to_string());
+    } else if !rest.is_empty() {
+        import.members = scan_names(rest).ok_or_else(|| unsupported("this import statement"))?;
+    }
+    Ok(Some((import, end)))
+}
+
+/// Parse a `{ a, b as c }` list of names into pairs of the name before and
+/// after the `as`.
+fn scan_names(names: &str) -> Option<Vec<(String, String)>> {
+    let names = names.strip_prefix('{')?.strip_suffix('}')?;
+    names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            let (name, alias) = match name.split_once(" as ") {
+                Some((name, alias)) => (name.trim(), alias.trim()),
+                None => (name, name),
+            };
+            if name.chars().all(is_identifier_char) && alias.chars().all(is_identifier_char) {
+                Some((name.to_string(), alias.to_string()))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+fn scan_export(path: &str, src: &str, start: usize) -> Result<(Chunk<'static>, usize), Error> {
+    let line = line_of(src, start);
+    let unsupported = |syntax| Error::UnsupportedSyntax {
+        path: path.into(),
+        line,
+        syntax,
+    };
+    let position = skip_whitespace(src, start + "export".len());
+    let rest = &src[position..];
+
+    if rest.starts_with('{') {
+        let close = rest
+            .find('}')
+            .ok_or_else(|| unsupported("this export statement"))?;
+        let names =
+            scan_names(&rest[..=close]).ok_or_else(|| unsupported("this export statement"))?;
+        let after = skip_whitespace(src, position + close + 1);
+        if starts_with_keyword(src, after, "from") {
+            let specifier_start = skip_whitespace(src, after + "from".len());
+            let (from, end) = scan_specifier(src, specifier_start)
+                .ok_or_else(|| unsupported("this export statement"))?;
+            let export = Export::Names {
+                names,
+                from: Some(from),
+            };
+            return Ok((Chunk::Export(export), end));
+        }
+        let mut end = skip_inline_whitespace(src, position + close + 1);
+        if src[end..].starts_with(';') {
+            end += 1;
+        }
+        return Ok((Chunk::Export(Export::Names { names, from: None }), end));
+    }
+
+    if let Some(all) = rest.strip_prefix('*') {
+        let all = all.trim_start();
+        let (name, all) = match all.strip_prefix("as") {
+            Some(named) => {
+                let named = named.trim_start();
+                let length = named
+                    .find(|char| !is_identifier_char(char))
+                    .unwrap_or(named.len());
+                (
+                    Some(named[..length].to_string()),
+                    named[length..].trim_start(),
+                )
+            }
+            None => (None, all),
+        };
+        let specifier = all
+            .strip_prefix("from")
+            .map(str::trim_start)
+            .ok_or_else(|| unsupported("this export statement"))?;
+        let specifier_start = src.len() - specifier.len();
+        let (from, end) = scan_specifier(src, specifier_start)
+            .ok_or_else(|| unsupported("this export statement"))?;
+        return Ok((Chunk::Export(Export::All { name, from }), end));
+    }
+
+    if starts_with_keyword(src, position, "default") {
+        return Err(unsupported("default exports"));
+    }
+
+    let mut declaration = position;
+    if starts_with_keyword(src, declaration, "async") {
+        declaration = skip_whitespace(src, declaration + "async".len());
+    }
+    let keyword = ["function", "class", "const", "let", "var"]
+        .into_iter()
+        .find(|keyword| starts_with_keyword(src, declaration, keyword))
+        .ok_or_else(|| unsupported("this export statement"))?;
+    let mut name_start = skip_whitespace(src, declaration + keyword.len());
+    if keyword == "function" && src[name_start..].starts_with('*') {
+        name_start = skip_whitespace(src, name_start + 1);
+    }
+    let length = src[name_start..]
+        .find(|char| !is_identifier_char(char))
+        .unwrap_or(src.len() - name_start);
+    if length == 0 {
+        return Err(unsupported("exported destructuring declarations"));
+    }
+    let name = src[name_start..name_start + length].to_string();
+
+    // The declaration itself is kept, so scanning resumes at its keyword.
+    Ok((Chunk::Export(Export::Declaration { name }), position))
+}
+
+/// The scanned modules to be bundled, keyed by path.
+#[derive(Debug)]
+struct BundledModules<'a> {
+    modules: HashMap<EcoString, Vec<Chunk<'a>>>,
+    /// The paths of all the compiled Gleam modules, including those that are
+    /// not in the bundle.
+    gleam_paths: HashSet<String>,
+}
+
+/// Where an import statement within the bundle points to.
+enum ImportTarget {
+    /// Another module in the bundle, by the name of its namespace variable.
+    Bundled(String),
+    /// A module outside of the bundle, such as one from npm. These are
+    /// imported at the top of the bundle.
+    External(String),
+    /// A Gleam module that has nothing reachable in it, so the import can be
+    /// removed.
+    Removed,
+}
+
+impl BundledModules<'_> {
+    fn target(&self, importer: &str, specifier: &str) -> Result<ImportTarget, Error> {
+        let Some(path) = resolve_import(importer, specifier) else {
+            return Ok(ImportTarget::External(namespace_variable(specifier)));
+        };
+        let path = prelude_or(path);
+        if self.modules.contains_key(path.as_str()) {
+            Ok(ImportTarget::Bundled(namespace_variable(&path)))
+        } else if self.gleam_paths.contains(&path) {
+            // Gleam modules are only included if something in them is
+            // reachable, so an import of a missing one is simply unused.
+            Ok(ImportTarget::Removed)
+        } else {
+            Err(Error::UnknownModule {
+                path: path.into(),
+                importer: importer.into(),
+            })
+        }
+    }
+
+    fn dependencies(&self, path: &str) -> Result<Vec<EcoString>, Error> {
+        let mut dependencies = vec![];
+        for chunk in self.modules.get(path).expect("Bundled module") {
+            let specifier = match chunk {
+                Chunk::Import(import) => &import.specifier,
+                Chunk::Export(
+                    Export::Names {
+                        from: Some(from), ..
+                    }
+                    | Export::All { from, .. },
+                ) => from,
+                Chunk::Code(_) | Chunk::Export(_) => continue,
+            };
+            if let ImportTarget::Bundled(_) = self.target(path, specifier)? {
+                let target = prelude_or(resolve_import(path, specifier).expect("Relative path"));
+                dependencies.push(target.into());
+            }
+        }
+        Ok(dependencies)
+    }
+
+    /// Order the modules so that each comes after all the modules it imports,
+    /// as far as import cycles allow. Like an ES module loader this is a
+    /// depth first post-order, so a module is evaluated before any module
+    /// that is still waiting on it.
+    fn evaluation_order(&self, entry: &EcoString) -> Result<Vec<EcoString>, Error> {
+        fn visit(
+            modules: &BundledModules<'_>,
+            path: &EcoString,
+            seen: &mut HashSet<EcoString>,
+            order: &mut Vec<EcoString>,
+        ) -> Result<(), Error> {
+            if !seen.insert(path.clone()) {
+                return Ok(());
+            }
+            for dependency in modules.dependencies(path)? {
+                visit(modules, &dependency, seen, order)?;
+            }
+            order.push(path.clone());
+            Ok(())
+        }
+
+        let mut order = vec![];
+        visit(self, entry, &mut HashSet::new(), &mut order)?;
+        Ok(order)
+    }
+
+    fn render(&self, entry: &EcoString) -> Result<String, Error> {
+        let order = self.evaluation_order(entry)?;
+        let positions: HashMap<String, usize> = order
+            .iter()
+            .enumerate()
+            .map(|(position, path)| (namespace_variable(path), position))
+            .collect();
+
+        let mut externals: Vec<(String, String)> = vec![];
+        // Binders to call once the last module they are waiting on has been
+        // evaluated, by the position of that module.
+        let mut pending_binders: HashMap<usize, Vec<String>> = HashMap::new();
+        let mut body = String::new();
+
+        for (position, path) in order.iter().enumerate() {
+            let variable = namespace_variable(path);
+            let mut module = String::new();
+            let mut exports = vec![];
+            let mut deferred = vec![];
+            let mut waiting_on = None;
+
+            for chunk in self.modules.get(path).expect("Bundled module") {
+                match chunk {
+                    Chunk::Code(code) => module.push_str(code),
+
+                    Chunk::Import(import) => {
+                        let namespace = match self.target(path, &import.specifier)? {
+                            ImportTarget::Removed => continue,
+                            ImportTarget::Bundled(namespace) => {
+                                if import.default.is_some() {
+                                    return Err(Error::UnsupportedSyntax {
+                                        path: path.clone(),
+                                        line: import.line,
+                                        syntax: "default imports of bundled modules",
+                                    });
+                                }
+                                namespace
+                            }
+                            ImportTarget::External(namespace) => {
+                                externals.push((namespace.clone(), import.specifier.clone()));
+                                namespace
+                            }
+                        };
+
+                        // A module that imports one that has not been
+                        // evaluated yet is part of an import cycle. Its
+                        // bindings are declared now and assigned once the
+                        // imported module has been evaluated, which works so
+                        // long as they are only used inside functions, the
+                        // same as it would for ES modules.
+                        match positions.get(&namespace) {
+                            Some(&target) if target >= position => {
+                                module.push_str(&deferred_declarations(import));
+                                deferred.push(import_bindings(import, &namespace, ""));
+                                waiting_on = waiting_on.max(Some(target));
+                            }
+                            _ => module.push_str(&import_bindings(import, &namespace, "const ")),
+                        }
+                    }
+
+                    Chunk::Export(Export::Declaration { name }) => {
+                        exports.push(name.clone());
+                    }
+
+                    Chunk::Export(Export::Names { names, from: None }) => {
+                        exports.extend(names.iter().map(|(local, exported)| {
+                            if local == exported {
+                                local.clone()
+                            } else {
+                                format!("{exported}: {local}")
+                            }
+                        }));
+                    }
+
+                    // Re-exports are getters so that they can refer to modules
+                    // which are evaluated later in the bundle.
+                    Chunk::Export(Export::Names {
+                        names,
+                        from: Some(from),
+                    }) => {
+                        let namespace = self.export_target(path, from, &mut externals)?;
+                        exports.extend(names.iter().map(|(name, exported)| {
+                            format!("get {exported}() {{ return {namespace}.{name}; }}")
+                        }));
+                    }
+
+                    Chunk::Export(Export::All {
+                        name: Some(name),
+                        from,
+                    }) => {
+                        let namespace = self.export_target(path, from, &mut externals)?;
+                        exports.push(format!("get {name}() {{ return {namespace}; }}"));
+                    }
+
+                    Chunk::Export(Export::All { name: None, from }) => {
+                        let namespace = self.export_target(path, from, &mut externals)?;
+                        if positions.get(&namespace).is_some_and(|&t| t >= position) {
+                            return Err(Error::ImportCycle {
+                                modules: vec![
+                                    path.clone(),
+                                    resolve_import(path, from)
+                                        .map(prelude_or)
+                                        .unwrap_or_default()
+                                        .into(),
+                                ],
+                            });
+                        }
+                        exports.push(format!("...{namespace}"));
+                    }
+                }
+            }
+
+            if !module.ends_with('\n') {
+                module.push('\n');
+            }
+            let exports = format!("{{ {} }}", exports.join(", "));
+            match waiting_on {
+                None => body.push_str(&format!(
+                    "// {path}\nconst {variable} = (() => {{\n{module}return {exports};\n}})();\n\n"
+                )),
+                Some(target) => {
+                    let binder = format!("{variable}$bind");
+                    body.push_str(&format!(
+                        "// {path}\nconst [{variable}, {binder}] = (() => {{\n{module}\
+return [{exports}, () => {{\n{}\n}}];\n}})();\n\n",
+                        deferred.join("\n")
+                    ));
+                    pending_binders.entry(target).or_default().push(binder);
+                }
+            }
+
+            for binder in pending_binders.remove(&position).unwrap_or_default() {
+                body.push_str(&format!("{binder}();\n\n"));
+            }
+        }
+
+        let mut bundle = String::new();
+        for (namespace, specifier) in externals.into_iter().unique() {
+            bundle.push_str(&format!("import * as {namespace} from \"{specifier}\";\n"));
+        }
+        if !bundle.is_empty() {
+            bundle.push('\n');
+        }
+        bundle.push_str(&body);
+        bundle.push_str(&format!(
+            "export const main = {}.main;\n",
+            namespace_variable(entry)
+        ));
+        Ok(bundle)
+    }
+
+    fn export_target(
+        &self,
+        path: &str,
+        from: &str,
+        externals: &mut Vec<(String, String)>,
+    ) -> Result<String, Error> {
+        Ok(match self.target(path, from)? {
+            ImportTarget::Bundled(namespace) => namespace,
+            ImportTarget::External(namespace) => {
+                externals.push((namespace.clone(), from.to_string()));
+                namespace
+            }
+            ImportTarget::Removed => "{}".into(),
+        })
+    }
+}
+
+fn import_bindings(import: &EsImport, namespace: &str, declaration: &str) -> String {
+    let mut bindings = vec![];
+    if let Some(default) = &import.default {
+        bindings.push(format!("{declaration}{default} = {namespace}.default;"));
+    }
+    if let Some(alias) = &import.namespace {
+        bindings.push(format!("{declaration}{alias} = {namespace};"));
+    }
+    if !import.members.is_empty() {
+        let members = import
+            .members
+            .iter()
+            .map(|(name, alias)| {
+                if name == alias {
+                    name.clone()
+                } else {
+                    format!("{name}: {alias}")
+                }
+            })
+            .join(", ");
+        // An assignment to a destructuring pattern must be parenthesised.
+        if declaration.is_empty() {
+            bindings.push(format!("({{ {members} }} = {namespace});"));
+        } else {
+            bindings.push(format!("{declaration}{{ {members} }} = {namespace};"));
+        }
+    }
+    bindings.join("\n")
+}
+
+/// Declare the bindings of an import whose values are assigned later.
+fn deferred_declarations(import: &EsImport) -> String {
+    let names = import
+        .default
+        .iter()
+        .chain(import.namespace.iter())
+        .chain(import.members.iter().map(|(_, alias)| alias))
+        .join(", ");
+    format!("let {names};")
+}
+
+/// The name of the variable holding the exports of a bundled module.
+fn namespace_variable(path: &str) -> String {
+    let path = path
+        .strip_suffix(".mjs")
+        .or_else(|| path.strip_suffix(".js"))
+        .unwrap_or(path);
+    let name: String = path
+        .chars()
+        .map(|char| {
+            if char.is_ascii_alphanumeric() {
+                char
+            } else {
+                '$'
+            }
+        })
+        .collect();
+    format!("$bundle${name}")
+}
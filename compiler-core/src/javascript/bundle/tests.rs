@@ -0,0 +1,194 @@
+use super::*;
+
+fn names(names: &[(&str, &str)]) -> Vec<(String, String)> {
+    names
+        .iter()
+        .map(|(name, alias)| (name.to_string(), alias.to_string()))
+        .collect()
+}
+
+#[test]
+fn export_async_function() {
+    let src = "export async function go() {}\n";
+    assert_eq!(
+        scan("ffi.mjs", src).unwrap(),
+        vec![
+            Chunk::Export(Export::Declaration { name: "go".into() }),
+            Chunk::Code("async function go() {}\n"),
+        ]
+    );
+}
+
+#[test]
+fn export_generator_function() {
+    let src = "export function* go() {}\n";
+    assert_eq!(
+        scan("ffi.mjs", src).unwrap(),
+        vec![
+            Chunk::Export(Export::Declaration { name: "go".into() }),
+            Chunk::Code("function* go() {}\n"),
+        ]
+    );
+}
+
+#[test]
+fn export_renamed_names() {
+    let src = "const a = 1;\nexport { a as b, a };\n";
+    assert_eq!(
+        scan("ffi.mjs", src).unwrap(),
+        vec![
+            Chunk::Code("const a = 1;\n"),
+            Chunk::Export(Export::Names {
+                names: names(&[("a", "b"), ("a", "a")]),
+                from: None,
+            }),
+            Chunk::Code("\n"),
+        ]
+    );
+}
+
+#[test]
+fn export_names_from() {
+    let src = "export { a as b } from \"./other.mjs\";\n";
+    assert_eq!(
+        scan("ffi.mjs", src).unwrap(),
+        vec![
+            Chunk::Export(Export::Names {
+                names: names(&[("a", "b")]),
+                from: Some("./other.mjs".into()),
+            }),
+            Chunk::Code("\n"),
+        ]
+    );
+}
+
+#[test]
+fn export_all_from() {
+    let src = "export * from \"./one.mjs\";\nexport * as two from './two.mjs'\n";
+    assert_eq!(
+        scan("ffi.mjs", src).unwrap(),
+        vec![
+            Chunk::Export(Export::All {
+                name: None,
+                from: "./one.mjs".into(),
+            }),
+            Chunk::Code("\n"),
+            Chunk::Export(Export::All {
+                name: Some("two".into()),
+                from: "./two.mjs".into(),
+            }),
+            Chunk::Code("\n"),
+        ]
+    );
+}
+
+#[test]
+fn multi_line_import() {
+    let src = "const a = 1;\nimport {\n  one,\n  two as three,\n} from \"./other.mjs\";\n";
+    assert_eq!(
+        scan("ffi.mjs", src).unwrap(),
+        vec![
+            Chunk::Code("const a = 1;\n"),
+            Chunk::Import(EsImport {
+                specifier: "./other.mjs".into(),
+                line: 2,
+                default: None,
+                namespace: None,
+                members: names(&[("one", "one"), ("two", "three")]),
+            }),
+            Chunk::Code("\n"),
+        ]
+    );
+}
+
+#[test]
+fn default_and_namespace_import() {
+    let src = "import thing, * as other from \"./other.mjs\";\n";
+    assert_eq!(
+        scan("ffi.mjs", src).unwrap(),
+        vec![
+            Chunk::Import(EsImport {
+                specifier: "./other.mjs".into(),
+                line: 1,
+                default: Some("thing".into()),
+                namespace: Some("other".into()),
+                members: vec![],
+            }),
+            Chunk::Code("\n"),
+        ]
+    );
+}
+
+#[test]
+fn dynamic_import_and_import_meta_are_code() {
+    let src = "import(\"./other.mjs\");\nimport.meta.url;\n";
+    assert_eq!(scan("ffi.mjs", src).unwrap(), vec![Chunk::Code(src)]);
+}
+
+#[test]
+fn statements_in_strings_comments_and_templates_are_code() {
+    let src = r#"const a = `
+export const b = 1;
+`;
+/*
+import { c } from "./c.mjs";
+*/
+// export const d = 1;
+const e = "\
+export const f = 1;";
+const g = 'import "./g.mjs"';
+"#;
+    assert_eq!(scan("ffi.mjs", src).unwrap(), vec![Chunk::Code(src)]);
+}
+
+#[test]
+fn statement_after_a_template_is_scanned() {
+    let src = "const a = `\n`;\nexport const b = 1;\n";
+    assert_eq!(
+        scan("ffi.mjs", src).unwrap(),
+        vec![
+            Chunk::Code("const a = `\n`;\n"),
+            Chunk::Export(Export::Declaration { name: "b".into() }),
+            Chunk::Code("const b = 1;\n"),
+        ]
+    );
+}
+
+#[test]
+fn default_export_is_unsupported() {
+    let src = "const a = 1;\nexport default a;\n";
+    assert_eq!(
+        scan_export("ffi.mjs", src, 13),
+        Err(Error::UnsupportedSyntax {
+            path: "ffi.mjs".into(),
+            line: 2,
+            syntax: "default exports",
+        })
+    );
+}
+
+#[test]
+fn destructuring_export_is_unsupported() {
+    let src = "export const { a } = b;\n";
+    assert_eq!(
+        scan_export("ffi.mjs", src, 0),
+        Err(Error::UnsupportedSyntax {
+            path: "ffi.mjs".into(),
+            line: 1,
+            syntax: "exported destructuring declarations",
+        })
+    );
+}
+
+#[test]
+fn import_with_unsupported_clause() {
+    let src = "import { a-b } from \"./other.mjs\";\n";
+    assert_eq!(
+        scan_import("ffi.mjs", src, 0),
+        Err(Error::UnsupportedSyntax {
+            path: "ffi.mjs".into(),
+            line: 1,
+            syntax: "this import statement",
+        })
+    );
+}
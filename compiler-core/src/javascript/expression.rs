@@ -1,22 +1,28 @@
+use ecow::eco_format;
 use vec1::Vec1;
 
 use super::{
-    pattern::{Assignment, CompiledPattern},
+    pattern::{Assignment, CompiledPattern, ASSIGNMENT_VAR},
     *,
 };
 use crate::{
     ast::*,
+    config::JavaScriptIntegers,
     javascript::endianness::Endianness,
     line_numbers::LineNumbers,
     pretty::*,
     type_::{ModuleValueConstructor, Type, ValueConstructor, ValueConstructorVariant},
 };
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum Position {
     Tail,
     NotTail,
+    /// The value is assigned to the named variable rather than returned. This
+    /// allows expressions such as `case` to be rendered as statements where they
+    /// would otherwise be wrapped in an immediately invoked function.
+    Assign(EcoString),
 }
 
 impl Position {
@@ -27,6 +33,13 @@ impl Position {
     pub fn is_tail(&self) -> bool {
         matches!(self, Self::Tail)
     }
+
+    /// Returns `true` if the value is used by a statement, either by returning
+    /// it or by assigning it to a variable.
+    #[must_use]
+    pub fn is_statement(&self) -> bool {
+        !matches!(self, Self::NotTail)
+    }
 }
 
 #[derive(Debug)]
@@ -45,6 +58,17 @@ pub(crate) struct Generator<'module> {
     // at the top level of the function to use in place of pushing new stack
     // frames.
     pub tail_recursion_used: bool,
+    // How Gleam's Int type is represented at runtime.
+    pub integers: JavaScriptIntegers,
+    // The names of the functions that are compiled into one loop along with
+    // the current function, and the index of the current function among them.
+    // Tail calls to any of them reassign the arguments of the loop and the
+    // function it runs next rather than pushing a new stack frame.
+    pub mutual_recursion: Option<(&'module [EcoString], usize)>,
+    // The names of variables that are declared before the expression giving
+    // their value is generated. No variables within the expression can be
+    // given these names, as they would shadow the variable being assigned.
+    reserved_vars: HashSet<EcoString>,
 }
 
 impl<'module> Generator<'module> {
@@ -56,6 +80,7 @@ impl<'module> Generator<'module> {
         function_arguments: Vec<Option<&'module EcoString>>,
         tracker: &'module mut UsageTracker,
         mut current_scope_vars: im::HashMap<EcoString, usize>,
+        integers: JavaScriptIntegers,
     ) -> Self {
         let mut function_name = Some(function_name);
         for &name in function_arguments.iter().flatten() {
@@ -78,6 +103,9 @@ impl<'module> Generator<'module> {
             current_scope_vars,
             function_position: Position::Tail,
             scope_position: Position::Tail,
+            integers,
+            mutual_recursion: None,
+            reserved_vars: HashSet::new(),
         }
     }
 
@@ -94,11 +122,26 @@ impl<'module> Generator<'module> {
     }
 
     pub fn next_local_var<'a>(&mut self, name: &'a EcoString) -> Document<'a> {
-        let next = self.current_scope_vars.get(name).map_or(0, |i| i + 1);
+        let next = self.next_local_var_number(name);
         let _ = self.current_scope_vars.insert(name.clone(), next);
         self.local_var(name)
     }
 
+    /// The number of the next variable with the given name, skipping any that
+    /// are reserved.
+    fn next_local_var_number(&self, name: &EcoString) -> usize {
+        let mut next = self.current_scope_vars.get(name).map_or(0, |i| i + 1);
+        while self.reserved_vars.contains(&local_var_name(name, next)) {
+            next += 1;
+        }
+        next
+    }
+
+    /// The name `next_local_var` will give the variable, without defining it.
+    fn next_local_var_name(&self, name: &EcoString) -> EcoString {
+        local_var_name(name, self.next_local_var_number(name))
+    }
+
     pub fn function_body<'a>(
         &mut self,
         body: &'a [TypedStatement],
@@ -112,6 +155,22 @@ impl<'module> Generator<'module> {
         }
     }
 
+    /// The body of a function that is compiled into a loop along with the
+    /// functions it is mutually recursive with. The loop's arguments are
+    /// numbered as the functions' arguments have different names.
+    pub fn mutually_recursive_function_body<'a>(
+        &mut self,
+        body: &'a [TypedStatement],
+        args: &'a [TypedArg],
+    ) -> Output<'a> {
+        let body = self.statements(body)?;
+        let loop_assignments = concat(args.iter().enumerate().flat_map(|(i, arg)| {
+            let var = maybe_escape_identifier_doc(arg.get_variable_name()?);
+            Some(docvec!["let ", var, " = loop$", i, ";", line()])
+        }));
+        Ok(docvec![loop_assignments, body])
+    }
+
     fn tail_call_loop<'a>(&mut self, body: Document<'a>, args: &'a [TypedArg]) -> Output<'a> {
         let loop_assignments = concat(args.iter().flat_map(Arg::get_variable_name).map(|name| {
             let var = maybe_escape_identifier_doc(name);
@@ -139,7 +198,7 @@ impl<'module> Generator<'module> {
         let document = match expression {
             TypedExpr::String { value, .. } => Ok(string(value)),
 
-            TypedExpr::Int { value, .. } => Ok(int(value)),
+            TypedExpr::Int { value, .. } => Ok(int_literal(value, self.integers)),
             TypedExpr::Float { value, .. } => Ok(float(value)),
 
             TypedExpr::List { elements, tail, .. } => self.not_in_tail_position(|gen| match tail {
@@ -222,148 +281,31 @@ impl<'module> Generator<'module> {
     fn bit_array<'a>(&mut self, segments: &'a [TypedExprBitArraySegment]) -> Output<'a> {
         self.tracker.bit_array_literal_used = true;
 
-        use BitArrayOption as Opt;
-
         // Collect all the values used in segments.
         let segments_array = array(segments.iter().map(|segment| {
             let value = self.not_in_tail_position(|gen| gen.wrap_expression(&segment.value))?;
-
-            if segment.type_ == crate::type_::int() || segment.type_ == crate::type_::float() {
-                let details = self.sized_bit_array_segment_details(segment)?;
-
-                if segment.type_ == crate::type_::int() {
-                    if details.has_explicit_size {
-                        self.tracker.sized_integer_segment_used = true;
-                        Ok(docvec![
-                            "sizedInt(",
-                            value,
-                            ", ",
-                            details.size,
-                            ", ",
-                            bool(details.endianness.is_big()),
-                            ")"
-                        ])
-                    } else {
-                        Ok(value)
-                    }
-                } else {
-                    self.tracker.float_bit_array_segment_used = true;
-                    Ok(docvec![
-                        "sizedFloat(",
-                        value,
-                        ", ",
-                        details.size,
-                        ", ",
-                        bool(details.endianness.is_big()),
-                        ")"
-                    ])
-                }
-            } else {
-                match segment.options.as_slice() {
-                    // UTF8 strings
-                    [Opt::Utf8 { .. }] => {
-                        self.tracker.string_bit_array_segment_used = true;
-                        Ok(docvec!["stringBits(", value, ")"])
-                    }
-
-                    // UTF8 codepoints
-                    [Opt::Utf8Codepoint { .. }] => {
-                        self.tracker.codepoint_bit_array_segment_used = true;
-                        Ok(docvec!["codepointBits(", value, ")"])
-                    }
-
-                    // Bit arrays
-                    [Opt::Bytes { .. } | Opt::Bits { .. }] => Ok(docvec![value, ".buffer"]),
-
-                    // Anything else
-                    _ => Err(Error::Unsupported {
-                        feature: "This bit array segment option".into(),
-                        location: segment.location,
-                    }),
-                }
-            }
+            let details = sized_bit_array_segment_details(
+                segment,
+                |size| match size {
+                    TypedExpr::Int { value, .. } => value.parse().ok(),
+                    _ => None,
+                },
+                |size| {
+                    let size = self.not_in_tail_position(|gen| gen.wrap_expression(size))?;
+                    Ok(int_to_number(size, self.integers))
+                },
+            )?;
+            bit_array_segment(self.tracker, segment, value, details)
         }))?;
 
         Ok(docvec!["toBitArray(", segments_array, ")"])
     }
 
-    fn sized_bit_array_segment_details<'a>(
-        &mut self,
-        segment: &'a TypedExprBitArraySegment,
-    ) -> Result<SizedBitArraySegmentDetails<'a>, Error> {
-        use BitArrayOption as Opt;
-
-        if segment
-            .options
-            .iter()
-            .any(|x| matches!(x, Opt::Native { .. }))
-        {
-            return Err(Error::Unsupported {
-                feature: "This bit array segment option".into(),
-                location: segment.location,
-            });
-        }
-
-        let endianness = if segment
-            .options
-            .iter()
-            .any(|x| matches!(x, Opt::Little { .. }))
-        {
-            Endianness::Little
-        } else {
-            Endianness::Big
-        };
-
-        let size = segment
-            .options
-            .iter()
-            .find(|x| matches!(x, Opt::Size { .. }));
-
-        let has_explicit_size = size.is_some();
-
-        let size = match size {
-            Some(Opt::Size { value: size, .. }) => {
-                let size_int = match *size.clone() {
-                    TypedExpr::Int {
-                        location: _,
-                        typ: _,
-                        value,
-                    } => value.parse().unwrap_or(0),
-                    _ => 0,
-                };
-
-                if size_int > 0 && size_int % 8 != 0 {
-                    return Err(Error::Unsupported {
-                        feature: "Non byte aligned array".into(),
-                        location: segment.location,
-                    });
-                }
-
-                self.not_in_tail_position(|gen| gen.wrap_expression(size))?
-            }
-            _ => {
-                let default_size = if segment.type_ == crate::type_::int() {
-                    8usize
-                } else {
-                    64usize
-                };
-
-                docvec![default_size]
-            }
-        };
-
-        Ok(SizedBitArraySegmentDetails {
-            has_explicit_size,
-            size,
-            endianness,
-        })
-    }
-
     pub fn wrap_return<'a>(&mut self, document: Document<'a>) -> Document<'a> {
-        if self.scope_position.is_tail() {
-            docvec!["return ", document, ";"]
-        } else {
-            document
+        match &self.scope_position {
+            Position::Tail => docvec!["return ", document, ";"],
+            Position::Assign(name) => docvec![name.clone(), " = ", document, ";"],
+            Position::NotTail => document,
         }
     }
 
@@ -371,11 +313,25 @@ impl<'module> Generator<'module> {
     where
         CompileFn: Fn(&mut Self) -> Output<'a>,
     {
-        let function_position = self.function_position;
-        let scope_position = self.scope_position;
+        let function_position = std::mem::replace(&mut self.function_position, Position::NotTail);
+        let scope_position = std::mem::replace(&mut self.scope_position, Position::NotTail);
+
+        let result = compile(self);
+
+        self.function_position = function_position;
+        self.scope_position = scope_position;
+        result
+    }
+
+    /// Compiles an expression so that its value is assigned to the given
+    /// variable, which must already have been declared.
+    fn assigned_to<'a, CompileFn>(&mut self, name: EcoString, compile: CompileFn) -> Output<'a>
+    where
+        CompileFn: FnOnce(&mut Self) -> Output<'a>,
+    {
+        let function_position = std::mem::replace(&mut self.function_position, Position::NotTail);
+        let scope_position = std::mem::replace(&mut self.scope_position, Position::Assign(name));
 
-        self.function_position = Position::NotTail;
-        self.scope_position = Position::NotTail;
         let result = compile(self);
 
         self.function_position = function_position;
@@ -402,6 +358,12 @@ impl<'module> Generator<'module> {
     /// required due to being a JS statement, or in parens if required due to
     /// being an operator or a function literal.
     pub fn child_expression<'a>(&mut self, expression: &'a TypedExpr) -> Output<'a> {
+        // Here the document is a statement, such as `return <expr>;`, so it
+        // needs no wrapping.
+        if self.scope_position.is_statement() {
+            return self.expression(expression);
+        }
+
         match expression {
             TypedExpr::BinOp { name, .. } if name.is_operator_to_wrap() => {}
             TypedExpr::Fn { .. } => {}
@@ -410,12 +372,7 @@ impl<'module> Generator<'module> {
         }
 
         let document = self.expression(expression)?;
-        Ok(if self.scope_position.is_tail() {
-            // Here the document is a return statement: `return <expr>;`
-            document
-        } else {
-            docvec!("(", document, ")")
-        })
+        Ok(docvec!("(", document, ")"))
     }
 
     /// Wrap an expression in an immediately involked function expression
@@ -427,11 +384,8 @@ impl<'module> Generator<'module> {
     where
         ToDoc: FnOnce(&mut Self, &'a T) -> Output<'a>,
     {
-        // Save initial state
-        let scope_position = self.scope_position;
-
-        // Set state for in this iife
-        self.scope_position = Position::Tail;
+        // Save initial state and set state for in this iife
+        let scope_position = std::mem::replace(&mut self.scope_position, Position::Tail);
         let current_scope_vars = self.current_scope_vars.clone();
 
         // Generate the expression
@@ -453,7 +407,7 @@ impl<'module> Generator<'module> {
     ) -> Output<'a> {
         match &constructor.variant {
             ValueConstructorVariant::LocalConstant { literal } => {
-                constant_expression(Context::Function, self.tracker, literal)
+                constant_expression(Context::Function, self.tracker, self.integers, literal)
             }
             ValueConstructorVariant::Record { arity, .. } => {
                 Ok(self.record_constructor(constructor.type_.clone(), None, name, *arity))
@@ -529,7 +483,7 @@ impl<'module> Generator<'module> {
     }
 
     fn block<'a>(&mut self, statements: &'a Vec1<TypedStatement>) -> Output<'a> {
-        if statements.len() == 1 {
+        if !block_requires_scope(statements) {
             match statements.first() {
                 Statement::Expression(expression) => self.child_expression(expression),
 
@@ -541,6 +495,10 @@ impl<'module> Generator<'module> {
                     unreachable!("use statements must not be present for JavaScript generation")
                 }
             }
+        } else if self.scope_position.is_statement() {
+            // The value is returned or assigned by the block's last statement,
+            // so it can be rendered as a JavaScript block.
+            self.scoped_statements(statements)
         } else {
             self.immediately_involked_function_expression(statements, |gen, statements| {
                 gen.statements(statements)
@@ -548,14 +506,53 @@ impl<'module> Generator<'module> {
         }
     }
 
+    /// Statements within a JavaScript block, so the variables they define go
+    /// out of scope afterwards.
+    fn scoped_statements<'a>(&mut self, statements: &'a [TypedStatement]) -> Output<'a> {
+        let scope = self.current_scope_vars.clone();
+        let result = self.statements(statements);
+        self.current_scope_vars = scope;
+
+        // If the value isn't used then the last statement is rendered as is,
+        // and may need ending like any other.
+        let semicolon = match statements.last() {
+            Some(statement) if !self.scope_position.is_statement() => requires_semicolon(statement),
+            _ => false,
+        };
+        let result = if semicolon {
+            docvec![result?, ";"]
+        } else {
+            result?
+        };
+        Ok(docvec![
+            "{",
+            docvec![line(), result].nest(INDENT),
+            line(),
+            "}"
+        ])
+    }
+
     fn statements<'a>(&mut self, statements: &'a [TypedStatement]) -> Output<'a> {
         let count = statements.len();
         let mut documents = Vec::with_capacity(count * 3);
         for (i, statement) in statements.iter().enumerate() {
             if i + 1 < count {
-                documents.push(self.not_in_tail_position(|gen| gen.statement(statement))?);
-                if requires_semicolon(statement) {
-                    documents.push(";".to_doc());
+                match statement {
+                    // The value of a block that is not used doesn't need to be
+                    // returned from a function, only its statements run.
+                    Statement::Expression(TypedExpr::Block { statements, .. })
+                        if block_requires_scope(statements) =>
+                    {
+                        documents.push(
+                            self.not_in_tail_position(|gen| gen.scoped_statements(statements))?,
+                        );
+                    }
+                    _ => {
+                        documents.push(self.not_in_tail_position(|gen| gen.statement(statement))?);
+                        if requires_semicolon(statement) {
+                            documents.push(";".to_doc());
+                        }
+                    }
                 }
                 documents.push(line());
             } else {
@@ -581,57 +578,84 @@ impl<'module> Generator<'module> {
         // If it is a simple assignment to a variable we can generate a normal
         // JS assignment
         if let TypedPattern::Variable { name, .. } = pattern {
+            // A value that would be wrapped in a function is instead rendered
+            // as statements assigning to the already declared variable.
+            if requires_statements(value) {
+                // Value must be rendered before the variable is defined for
+                // variable numbering
+                let js_name = self.next_local_var_name(name);
+                let _ = self.reserved_vars.insert(js_name.clone());
+                let value = self.assigned_to(js_name.clone(), |gen| gen.expression(value));
+                let _ = self.reserved_vars.remove(&js_name);
+                let value = value?;
+                let _ = self.next_local_var(name);
+                let doc = docvec!["let ", js_name.clone(), ";", line(), value];
+                return Ok(self.then_use_value(doc, js_name.to_doc()).force_break());
+            }
+
             // Subject must be rendered before the variable for variable numbering
             let subject = self.not_in_tail_position(|gen| gen.wrap_expression(value))?;
             let js_name = self.next_local_var(name);
-            return Ok(if self.scope_position.is_tail() {
-                docvec![
-                    "let ",
-                    js_name.clone(),
-                    " = ",
-                    subject,
-                    ";",
-                    line(),
-                    "return ",
-                    js_name,
-                    ";"
-                ]
-            } else {
-                docvec!["let ", js_name, " = ", subject, ";"]
-            }
-            .force_break());
+            let doc = docvec!["let ", js_name.clone(), " = ", subject, ";"];
+            return Ok(self.then_use_value(doc, js_name).force_break());
         }
 
         // Otherwise we need to compile the patterns
+        let subject_name = self.next_local_var_name(&ASSIGNMENT_VAR.into());
         let (subject, subject_assignment) = pattern::assign_subject(self, value);
         // Value needs to be rendered before traversing pattern to have correctly incremented variables.
-        let value = self.not_in_tail_position(|gen| gen.wrap_expression(value))?;
+        let (value, declaration) = match &subject_assignment {
+            // A value that would be wrapped in a function is instead rendered
+            // as statements assigning to the already declared subject variable.
+            Some(name) if requires_statements(value) => {
+                let value = self.assigned_to(subject_name, |gen| gen.expression(value))?;
+                (
+                    value.clone(),
+                    docvec!["let ", name.clone(), ";", line(), value],
+                )
+            }
+            Some(name) => {
+                let value = self.not_in_tail_position(|gen| gen.wrap_expression(value))?;
+                (
+                    value.clone(),
+                    docvec!["let ", name.clone(), " = ", value, ";"],
+                )
+            }
+            None => (
+                self.not_in_tail_position(|gen| gen.wrap_expression(value))?,
+                nil(),
+            ),
+        };
         let mut pattern_generator = pattern::Generator::new(self);
         pattern_generator.traverse_pattern(&subject, pattern)?;
         let compiled = pattern_generator.take_compiled();
 
-        // If we are in tail position we can return value being assigned
-        let afterwards = if self.scope_position.is_tail() {
-            docvec![
-                line(),
-                "return ",
-                subject_assignment.clone().unwrap_or_else(|| value.clone()),
-                ";"
-            ]
-        } else {
-            nil()
-        };
-
         let compiled =
             self.pattern_into_assignment_doc(compiled, subject, pattern.location(), *kind)?;
         // If there is a subject name given create a variable to hold it for
         // use in patterns
-        let doc = match subject_assignment {
-            Some(name) => docvec!("let ", name, " = ", value, ";", line(), compiled),
+        let doc = match subject_assignment.clone() {
+            Some(_) => docvec!(declaration, line(), compiled),
             None => compiled,
         };
 
-        Ok(doc.append(afterwards).force_break())
+        // If the value is used we can return or assign the value being assigned
+        let value = subject_assignment.unwrap_or(value);
+        Ok(self.then_use_value(doc, value).force_break())
+    }
+
+    /// Follows an assignment with returning or assigning the assigned value,
+    /// if it is used.
+    fn then_use_value<'a>(
+        &mut self,
+        assignment: Document<'a>,
+        value: Document<'a>,
+    ) -> Document<'a> {
+        if self.scope_position.is_statement() {
+            docvec![assignment, line(), self.wrap_return(value)]
+        } else {
+            assignment
+        }
     }
 
     fn case<'a>(
@@ -755,11 +779,9 @@ impl<'module> Generator<'module> {
     }
 
     fn call<'a>(&mut self, fun: &'a TypedExpr, arguments: &'a [CallArg<TypedExpr>]) -> Output<'a> {
-        let scope_position = self.scope_position;
-        let function_position = self.function_position;
+        let scope_position = std::mem::replace(&mut self.scope_position, Position::NotTail);
+        let function_position = std::mem::replace(&mut self.function_position, Position::NotTail);
 
-        self.scope_position = Position::NotTail;
-        self.function_position = Position::NotTail;
         let arguments = arguments
             .iter()
             .map(|element| self.wrap_expression(&element.value))
@@ -776,6 +798,16 @@ impl<'module> Generator<'module> {
         fun: &'a TypedExpr,
         arguments: Vec<Document<'a>>,
     ) -> Output<'a> {
+        // Tail call optimisation for mutual recursion. If we are calling one of
+        // the functions compiled into the same loop as the current function and
+        // we are in tail position we can set the function for the loop to run
+        // next along with its arguments.
+        if self.function_position.is_tail() {
+            if let Some(index) = self.mutual_recursion_index(fun) {
+                return Ok(self.mutually_recursive_call(index, arguments));
+            }
+        }
+
         match fun {
             // Qualified record construction
             TypedExpr::ModuleSelect {
@@ -857,12 +889,42 @@ impl<'module> Generator<'module> {
         }
     }
 
+    /// The index of the function being called among those compiled into one
+    /// loop with the current function, if it is one of them.
+    fn mutual_recursion_index(&self, fun: &TypedExpr) -> Option<usize> {
+        let (functions, _) = self.mutual_recursion?;
+        match fun {
+            TypedExpr::Var {
+                constructor:
+                    ValueConstructor {
+                        variant: ValueConstructorVariant::ModuleFn { name, module, .. },
+                        ..
+                    },
+                ..
+            } if *module == self.module_name => functions.iter().position(|f| f == name),
+            _ => None,
+        }
+    }
+
+    fn mutually_recursive_call<'a>(
+        &mut self,
+        index: usize,
+        arguments: Vec<Document<'a>>,
+    ) -> Document<'a> {
+        let mut docs = Vec::with_capacity(arguments.len() * 2 + 1);
+        if self.mutual_recursion.map(|(_, current)| current) != Some(index) {
+            docs.push(docvec!["loop$function = ", index, ";"]);
+        }
+        for (i, argument) in arguments.into_iter().enumerate() {
+            docs.push(docvec!["loop$", i, " = ", argument, ";"]);
+        }
+        join(docs, line())
+    }
+
     fn fn_<'a>(&mut self, arguments: &'a [TypedArg], body: &'a [TypedStatement]) -> Output<'a> {
         // New function, this is now the tail position
-        let function_position = self.function_position;
-        let scope_position = self.scope_position;
-        self.function_position = Position::Tail;
-        self.scope_position = Position::Tail;
+        let function_position = std::mem::replace(&mut self.function_position, Position::Tail);
+        let scope_position = std::mem::replace(&mut self.scope_position, Position::Tail);
 
         // And there's a new scope
         let scope = self.current_scope_vars.clone();
@@ -874,6 +936,7 @@ impl<'module> Generator<'module> {
         // mistakenly trigger tail call optimisation
         let mut name = None;
         std::mem::swap(&mut self.function_name, &mut name);
+        let mutual_recursion = self.mutual_recursion.take();
 
         // Generate the function body
         let result = self.statements(body);
@@ -883,6 +946,7 @@ impl<'module> Generator<'module> {
         self.scope_position = scope_position;
         self.current_scope_vars = scope;
         std::mem::swap(&mut self.function_name, &mut name);
+        self.mutual_recursion = mutual_recursion;
 
         Ok(docvec!(
             docvec!(
@@ -1026,8 +1090,7 @@ impl<'module> Generator<'module> {
     }
 
     fn todo<'a>(&mut self, message: Option<&'a TypedExpr>, location: &'a SrcSpan) -> Output<'a> {
-        let scope_position = self.scope_position;
-        self.scope_position = Position::NotTail;
+        let scope_position = std::mem::replace(&mut self.scope_position, Position::NotTail);
 
         let message = match message {
             Some(m) => self.expression(m)?,
@@ -1043,8 +1106,7 @@ impl<'module> Generator<'module> {
     }
 
     fn panic<'a>(&mut self, location: &'a SrcSpan, message: Option<&'a TypedExpr>) -> Output<'a> {
-        let scope_position = self.scope_position;
-        self.scope_position = Position::NotTail;
+        let scope_position = std::mem::replace(&mut self.scope_position, Position::NotTail);
 
         let message = match message {
             Some(m) => self.expression(m)?,
@@ -1239,6 +1301,23 @@ pub fn int(value: &str) -> Document<'_> {
     out.to_doc()
 }
 
+/// An Int literal, with the `n` suffix when Ints are represented as BigInts.
+pub fn int_literal(value: &str, integers: JavaScriptIntegers) -> Document<'_> {
+    match integers {
+        JavaScriptIntegers::Number => int(value),
+        JavaScriptIntegers::BigInt => docvec![int(value.trim_start_matches('+')), "n"],
+    }
+}
+
+/// Converts an Int to a JavaScript number, for the places where the prelude
+/// expects one, such as the size of a bit array segment.
+pub(crate) fn int_to_number(value: Document<'_>, integers: JavaScriptIntegers) -> Document<'_> {
+    match integers {
+        JavaScriptIntegers::Number => value,
+        JavaScriptIntegers::BigInt => docvec!["Number(", value, ")"],
+    }
+}
+
 pub fn float(value: &str) -> Document<'_> {
     let mut out = EcoString::with_capacity(value.len());
 
@@ -1261,13 +1340,14 @@ pub fn float(value: &str) -> Document<'_> {
 pub(crate) fn guard_constant_expression<'a>(
     assignments: &mut Vec<Assignment<'a>>,
     tracker: &mut UsageTracker,
+    integers: JavaScriptIntegers,
     expression: &'a TypedConstant,
 ) -> Output<'a> {
     match expression {
         Constant::Tuple { elements, .. } => array(
             elements
                 .iter()
-                .map(|e| guard_constant_expression(assignments, tracker, e)),
+                .map(|e| guard_constant_expression(assignments, tracker, integers, e)),
         ),
 
         Constant::List { elements, .. } => {
@@ -1275,7 +1355,7 @@ pub(crate) fn guard_constant_expression<'a>(
             list(
                 elements
                     .iter()
-                    .map(|e| guard_constant_expression(assignments, tracker, e)),
+                    .map(|e| guard_constant_expression(assignments, tracker, integers, e)),
             )
         }
         Constant::Record { typ, name, .. } if typ.is_bool() && name == "True" => {
@@ -1303,14 +1383,16 @@ pub(crate) fn guard_constant_expression<'a>(
             }
             let field_values: Vec<_> = args
                 .iter()
-                .map(|arg| guard_constant_expression(assignments, tracker, &arg.value))
+                .map(|arg| guard_constant_expression(assignments, tracker, integers, &arg.value))
                 .try_collect()?;
             Ok(construct_record(module.as_deref(), name, field_values))
         }
 
-        Constant::BitArray { segments, .. } => bit_array(tracker, segments, |tracker, constant| {
-            guard_constant_expression(assignments, tracker, constant)
-        }),
+        Constant::BitArray { segments, .. } => {
+            bit_array(tracker, integers, segments, |tracker, constant| {
+                guard_constant_expression(assignments, tracker, integers, constant)
+            })
+        }
 
         Constant::Var { name, .. } => Ok(assignments
             .iter()
@@ -1318,7 +1400,7 @@ pub(crate) fn guard_constant_expression<'a>(
             .map(|assignment| assignment.subject.clone().append(assignment.path.clone()))
             .unwrap_or_else(|| maybe_escape_identifier_doc(name))),
 
-        expression => constant_expression(Context::Function, tracker, expression),
+        expression => constant_expression(Context::Function, tracker, integers, expression),
     }
 }
 
@@ -1337,16 +1419,17 @@ pub enum Context {
 pub(crate) fn constant_expression<'a>(
     context: Context,
     tracker: &mut UsageTracker,
+    integers: JavaScriptIntegers,
     expression: &'a TypedConstant,
 ) -> Output<'a> {
     match expression {
-        Constant::Int { value, .. } => Ok(int(value)),
+        Constant::Int { value, .. } => Ok(int_literal(value, integers)),
         Constant::Float { value, .. } => Ok(float(value)),
         Constant::String { value, .. } => Ok(string(value)),
         Constant::Tuple { elements, .. } => array(
             elements
                 .iter()
-                .map(|e| constant_expression(context, tracker, e)),
+                .map(|e| constant_expression(context, tracker, integers, e)),
         ),
 
         Constant::List { elements, .. } => {
@@ -1354,7 +1437,7 @@ pub(crate) fn constant_expression<'a>(
             let list = list(
                 elements
                     .iter()
-                    .map(|e| constant_expression(context, tracker, e)),
+                    .map(|e| constant_expression(context, tracker, integers, e)),
             )?;
 
             match context {
@@ -1388,7 +1471,7 @@ pub(crate) fn constant_expression<'a>(
             }
             let field_values: Vec<_> = args
                 .iter()
-                .map(|arg| constant_expression(context, tracker, &arg.value))
+                .map(|arg| constant_expression(context, tracker, integers, &arg.value))
                 .try_collect()?;
 
             let constructor = construct_record(module.as_deref(), name, field_values);
@@ -1399,8 +1482,8 @@ pub(crate) fn constant_expression<'a>(
         }
 
         Constant::BitArray { segments, .. } => {
-            let bit_array = bit_array(tracker, segments, |tracker, expr| {
-                constant_expression(context, tracker, expr)
+            let bit_array = bit_array(tracker, integers, segments, |tracker, expr| {
+                constant_expression(context, tracker, integers, expr)
             })?;
             match context {
                 Context::Constant => Ok(docvec!["/* @__PURE__ */ ", bit_array]),
@@ -1421,8 +1504,8 @@ pub(crate) fn constant_expression<'a>(
         }),
 
         Constant::StringConcatenation { left, right, .. } => {
-            let left = constant_expression(context, tracker, left)?;
-            let right = constant_expression(context, tracker, right)?;
+            let left = constant_expression(context, tracker, integers, left)?;
+            let right = constant_expression(context, tracker, integers, right)?;
             Ok(docvec!(left, " + ", right))
         }
 
@@ -1432,87 +1515,143 @@ pub(crate) fn constant_expression<'a>(
 
 fn bit_array<'a>(
     tracker: &mut UsageTracker,
+    integers: JavaScriptIntegers,
     segments: &'a [BitArraySegment<TypedConstant, Arc<Type>>],
     mut constant_expr_fun: impl FnMut(&mut UsageTracker, &'a TypedConstant) -> Output<'a>,
 ) -> Output<'a> {
     tracker.bit_array_literal_used = true;
 
-    use BitArrayOption as Opt;
-
     let segments_array = array(segments.iter().map(|segment| {
         let value = constant_expr_fun(tracker, &segment.value)?;
+        let details = sized_bit_array_segment_details(
+            segment,
+            |size| match size {
+                Constant::Int { value, .. } => value.parse().ok(),
+                _ => None,
+            },
+            |size| Ok(int_to_number(constant_expr_fun(tracker, size)?, integers)),
+        )?;
+        bit_array_segment(tracker, segment, value, details)
+    }))?;
 
-        if segment.type_ == crate::type_::int() || segment.type_ == crate::type_::float() {
-            let details =
-                sized_bit_array_segment_details(segment, tracker, &mut constant_expr_fun)?;
-
-            if segment.type_ == crate::type_::int() {
-                if details.has_explicit_size {
-                    tracker.sized_integer_segment_used = true;
-                    Ok(docvec![
-                        "sizedInt(",
-                        value,
-                        ", ",
-                        details.size,
-                        ", ",
-                        bool(details.endianness.is_big()),
-                        ")"
-                    ])
-                } else {
-                    Ok(value)
-                }
-            } else {
-                tracker.float_bit_array_segment_used = true;
+    Ok(docvec!["toBitArray(", segments_array, ")"])
+}
+
+/// The value of a single segment of a bit array, converted to what
+/// `toBitArray` in the prelude expects.
+fn bit_array_segment<'a, Value>(
+    tracker: &mut UsageTracker,
+    segment: &BitArraySegment<Value, Arc<Type>>,
+    value: Document<'a>,
+    details: SizedBitArraySegmentDetails<'a>,
+) -> Output<'a> {
+    use BitArrayOption as Opt;
+
+    let endianness = bool(details.endianness.is_big());
+
+    if segment.type_ == crate::type_::int() {
+        return match details.size {
+            Some(size) => {
+                tracker.sized_integer_segment_used = true;
                 Ok(docvec![
-                    "sizedFloat(",
+                    "sizedInt(",
                     value,
                     ", ",
-                    details.size,
+                    size,
                     ", ",
-                    bool(details.endianness.is_big()),
+                    endianness,
                     ")"
                 ])
             }
-        } else {
-            match segment.options.as_slice() {
-                // UTF8 strings
-                [Opt::Utf8 { .. }] => {
-                    tracker.string_bit_array_segment_used = true;
-                    Ok(docvec!["stringBits(", value, ")"])
-                }
+            None => Ok(value),
+        };
+    }
 
-                // UTF8 codepoints
-                [Opt::Utf8Codepoint { .. }] => {
-                    tracker.codepoint_bit_array_segment_used = true;
-                    Ok(docvec!["codepointBits(", value, ")"])
-                }
+    if segment.type_ == crate::type_::float() {
+        tracker.float_bit_array_segment_used = true;
+        let size = details.size.unwrap_or_else(|| docvec!["64"]);
+        return Ok(docvec![
+            "sizedFloat(",
+            value,
+            ", ",
+            size,
+            ", ",
+            endianness,
+            ")"
+        ]);
+    }
 
-                // Bit strings
-                [Opt::Bits { .. }] => Ok(docvec![value, ".buffer"]),
+    for option in segment.options.iter() {
+        match option {
+            // UTF8 strings
+            Opt::Utf8 { .. } => {
+                tracker.string_bit_array_segment_used = true;
+                return Ok(docvec!["stringBits(", value, ")"]);
+            }
 
-                // Anything else
-                _ => Err(Error::Unsupported {
-                    feature: "This bit array segment option".into(),
-                    location: segment.location,
-                }),
+            // UTF8 codepoints
+            Opt::Utf8Codepoint { .. } => {
+                tracker.codepoint_bit_array_segment_used = true;
+                return Ok(docvec!["codepointBits(", value, ")"]);
+            }
+
+            // UTF16 strings
+            Opt::Utf16 { .. } => {
+                tracker.utf16_string_bit_array_segment_used = true;
+                return Ok(docvec!["stringToUtf16(", value, ", ", endianness, ")"]);
+            }
+
+            // UTF16 codepoints
+            Opt::Utf16Codepoint { .. } => {
+                tracker.utf16_codepoint_bit_array_segment_used = true;
+                return Ok(docvec!["codepointToUtf16(", value, ", ", endianness, ")"]);
+            }
+
+            // UTF32 strings
+            Opt::Utf32 { .. } => {
+                tracker.utf32_string_bit_array_segment_used = true;
+                return Ok(docvec!["stringToUtf32(", value, ", ", endianness, ")"]);
+            }
+
+            // UTF32 codepoints
+            Opt::Utf32Codepoint { .. } => {
+                tracker.utf32_codepoint_bit_array_segment_used = true;
+                return Ok(docvec!["codepointToUtf32(", value, ", ", endianness, ")"]);
             }
+
+            // Bit arrays
+            Opt::Bytes { .. } | Opt::Bits { .. } => {
+                return match details.size {
+                    Some(size) => {
+                        tracker.sized_bits_segment_used = true;
+                        Ok(docvec!["sizedBits(", value, ", ", size, ")"])
+                    }
+                    None if matches!(option, Opt::Bytes { .. }) => Ok(docvec![value, ".buffer"]),
+                    None => Ok(value),
+                };
+            }
+
+            _ => (),
         }
-    }))?;
+    }
 
-    Ok(docvec!["toBitArray(", segments_array, ")"])
+    Err(Error::Unsupported {
+        feature: "This bit array segment option".into(),
+        location: segment.location,
+    })
 }
 
 #[derive(Debug)]
 struct SizedBitArraySegmentDetails<'a> {
-    has_explicit_size: bool,
-    size: Document<'a>,
+    /// The explicit size of the segment in bits, if it has one.
+    size: Option<Document<'a>>,
     endianness: Endianness,
 }
 
-fn sized_bit_array_segment_details<'a>(
-    segment: &'a BitArraySegment<TypedConstant, Arc<Type>>,
-    tracker: &mut UsageTracker,
-    constant_expr_fun: &mut impl FnMut(&mut UsageTracker, &'a TypedConstant) -> Output<'a>,
+fn sized_bit_array_segment_details<'a, Value>(
+    segment: &'a BitArraySegment<Value, Arc<Type>>,
+    literal_size: impl Fn(&Value) -> Option<usize>,
+    mut size_doc: impl FnMut(&'a Value) -> Output<'a>,
 ) -> Result<SizedBitArraySegmentDetails<'a>, Error> {
     use BitArrayOption as Opt;
 
@@ -1537,44 +1676,44 @@ fn sized_bit_array_segment_details<'a>(
         Endianness::Big
     };
 
-    let size = segment
-        .options
-        .iter()
-        .find(|x| matches!(x, Opt::Size { .. }));
-
-    let has_explicit_size = size.is_some();
-
-    let size = match size {
-        Some(Opt::Size { value: size, .. }) => {
-            let size_int = match *size.clone() {
-                Constant::Int { location: _, value } => value.parse().unwrap_or(0),
-                _ => 0,
-            };
-            if size_int > 0 && size_int % 8 != 0 {
-                return Err(Error::Unsupported {
-                    feature: "Non byte aligned array".into(),
-                    location: segment.location,
-                });
-            }
-
-            constant_expr_fun(tracker, size)?
-        }
-        _ => {
-            let default_size = if segment.type_ == crate::type_::int() {
-                8usize
-            } else {
-                64usize
-            };
-
-            docvec![default_size]
+    let size = match segment.options.iter().find_map(|x| match x {
+        Opt::Size { value, .. } => Some(value),
+        _ => None,
+    }) {
+        Some(size) => {
+            let unit = bit_array_segment_unit(&segment.options);
+            // A literal size too large to be multiplied at compile time is
+            // multiplied at runtime instead.
+            let literal_size = literal_size(size).and_then(|size| size.checked_mul(unit as usize));
+            Some(match literal_size {
+                Some(size) => docvec![size],
+                None if unit == 1 => size_doc(size)?,
+                None => docvec![size_doc(size)?, " * ", unit],
+            })
         }
+        None => None,
     };
 
-    Ok(SizedBitArraySegmentDetails {
-        has_explicit_size,
-        size,
-        endianness,
-    })
+    Ok(SizedBitArraySegmentDetails { size, endianness })
+}
+
+/// The number of bits in each unit of the size of a segment.
+pub(crate) fn bit_array_segment_unit<Value>(options: &[BitArrayOption<Value>]) -> u8 {
+    use BitArrayOption as Opt;
+
+    options
+        .iter()
+        .find_map(|option| match option {
+            Opt::Unit { value, .. } => Some(*value),
+            _ => None,
+        })
+        .unwrap_or_else(|| {
+            if options.iter().any(|x| matches!(x, Opt::Bytes { .. })) {
+                8
+            } else {
+                1
+            }
+        })
 }
 
 pub fn string(value: &str) -> Document<'_> {
@@ -1725,6 +1864,37 @@ pub fn is_js_scalar(t: Arc<Type>) -> bool {
     t.is_int() || t.is_float() || t.is_bool() || t.is_nil() || t.is_string()
 }
 
+/// The JavaScript name of the given numbered variable.
+fn local_var_name(name: &str, number: usize) -> EcoString {
+    match number {
+        0 => maybe_escape_identifier_string(name).into(),
+        n if name == "$" => eco_format!("${n}"),
+        n => eco_format!("{name}${n}"),
+    }
+}
+
+/// Whether the expression is rendered as statements, so must be wrapped in an
+/// immediately invoked function when used as a value. A `panic` or `todo` is
+/// rendered as a statement too, but as it never has a value it is left wrapped.
+fn requires_statements(expression: &TypedExpr) -> bool {
+    match expression {
+        TypedExpr::Case { .. } | TypedExpr::Pipeline { .. } => true,
+        TypedExpr::Block { statements, .. } => block_requires_scope(statements),
+        _ => false,
+    }
+}
+
+/// Whether a block is rendered as statements within their own scope, rather
+/// than as the value of its only statement. A lone `let assert` still needs its
+/// pattern checking.
+fn block_requires_scope(statements: &[TypedStatement]) -> bool {
+    match statements {
+        [Statement::Assignment(assignment)] => assignment.kind.is_assert(),
+        [_] => false,
+        _ => true,
+    }
+}
+
 fn requires_semicolon(statement: &TypedStatement) -> bool {
     match statement {
         Statement::Expression(
@@ -91,6 +91,10 @@ impl<'a> Import<'a> {
     }
 
     pub fn into_doc(self, codegen_target: JavaScriptCodegenTarget) -> Document<'a> {
+        if codegen_target == JavaScriptCodegenTarget::CommonJs {
+            return self.into_require_doc();
+        }
+
         let path = Document::String(self.path.clone());
         let import_modifier = if codegen_target == JavaScriptCodegenTarget::TypeScriptDeclarations {
             "type "
@@ -112,7 +116,10 @@ impl<'a> Import<'a> {
         if self.unqualified.is_empty() {
             alias_imports
         } else {
-            let members = self.unqualified.into_iter().map(Member::into_doc);
+            let members = self
+                .unqualified
+                .into_iter()
+                .map(|member| member.into_doc(" as "));
             let members = join(members, break_(",", ", "));
             let members = docvec![
                 docvec![break_("", " "), members].nest(INDENT),
@@ -132,6 +139,35 @@ impl<'a> Import<'a> {
             ]
         }
     }
+
+    /// The import as CommonJS `require` calls.
+    fn into_require_doc(self) -> Document<'a> {
+        let require = docvec!["require(\"", Document::String(self.path.clone()), "\");"];
+        let alias_imports = concat(self.aliases.into_iter().sorted().map(|alias| {
+            docvec![
+                "const ",
+                Document::String(alias),
+                " = ",
+                require.clone(),
+                line()
+            ]
+        }));
+        if self.unqualified.is_empty() {
+            alias_imports
+        } else {
+            let members = self
+                .unqualified
+                .into_iter()
+                .map(|member| member.into_doc(": "));
+            let members = join(members, break_(",", ", "));
+            let members = docvec![
+                docvec![break_("", " "), members].nest(INDENT),
+                break_(",", " ")
+            ]
+            .group();
+            docvec![alias_imports, "const {", members, "} = ", require, line()]
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -141,10 +177,11 @@ pub struct Member<'a> {
 }
 
 impl<'a> Member<'a> {
-    fn into_doc(self) -> Document<'a> {
+    /// The member, renamed with the given separator if it has an alias.
+    fn into_doc(self, separator: &'static str) -> Document<'a> {
         match self.alias {
             None => self.name,
-            Some(alias) => docvec![self.name, " as ", alias],
+            Some(alias) => docvec![self.name, separator, alias],
         }
     }
 }
@@ -238,3 +275,48 @@ import { one, two } from "./zzz";
         .to_string()
     );
 }
+
+#[test]
+fn into_require_doc() {
+    let mut imports = Imports::new();
+    imports.register_module("./gleam/empty".into(), [], []);
+    imports.register_module("./multiple/times".into(), ["wibble".into()], []);
+    imports.register_module(
+        "./multiple/times".into(),
+        [],
+        [Member {
+            name: "one".to_doc(),
+            alias: None,
+        }],
+    );
+    imports.register_module(
+        "./other".into(),
+        [],
+        [
+            Member {
+                name: "one".to_doc(),
+                alias: Some("onee".to_doc()),
+            },
+            Member {
+                name: "two".to_doc(),
+                alias: Some("twoo".to_doc()),
+            },
+            Member {
+                name: "three".to_doc(),
+                alias: None,
+            },
+        ],
+    );
+
+    assert_eq!(
+        line()
+            .append(imports.into_doc(JavaScriptCodegenTarget::CommonJs))
+            .to_pretty_string(40),
+        r#"
+const wibble = require("./multiple/times");
+const { one } = require("./multiple/times");
+const { one: onee, two: twoo, three } = require("./other");
+"#
+        .to_string()
+    );
+}
@@ -0,0 +1,145 @@
+//! Private functions that call each other in tail position are compiled into a
+//! single function containing a loop, in the same way a self recursive function
+//! is, so that they can recurse without growing the stack.
+//!
+//! The loop takes the index of the function to run along with its arguments.
+//! A tail call to one of the functions sets these and goes around the loop
+//! again, while each function remains as a wrapper that starts the loop so it
+//! can be called as usual.
+
+use std::collections::HashMap;
+
+use ecow::{eco_format, EcoString};
+use itertools::Itertools;
+use petgraph::{
+    algo::tarjan_scc,
+    graph::{DiGraph, NodeIndex},
+};
+
+use crate::{
+    ast::{Definition, Statement, TypedExpr, TypedFunction, TypedModule, TypedStatement},
+    build::Target,
+    type_::ValueConstructorVariant,
+};
+
+#[derive(Debug)]
+pub(crate) struct MutualRecursion<'a> {
+    /// The name of the function containing the loop.
+    pub name: EcoString,
+    /// The functions, in the order they are defined in the module.
+    pub functions: Vec<&'a TypedFunction>,
+    pub names: Vec<EcoString>,
+    /// Whether the functions are compiled separately after all, as one of them
+    /// uses a feature not supported on JavaScript.
+    pub separate: bool,
+}
+
+impl MutualRecursion<'_> {
+    /// The number of arguments the loop takes for the functions' arguments.
+    pub fn arity(&self) -> usize {
+        self.functions
+            .iter()
+            .map(|function| function.arguments.len())
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+/// Finds the groups of private functions in a module that are mutually
+/// recursive through tail calls.
+pub(crate) fn find(module: &TypedModule) -> Vec<MutualRecursion<'_>> {
+    let functions = module
+        .definitions
+        .iter()
+        .filter_map(|definition| match definition {
+            Definition::Function(function)
+                if function.publicity.is_private()
+                    && function.external_javascript.is_none()
+                    && function.implementations.supports(Target::JavaScript) =>
+            {
+                let (_, name) = function.name.as_ref()?;
+                Some((name, function))
+            }
+            _ => None,
+        })
+        .collect_vec();
+    let indices: HashMap<&EcoString, usize> = functions
+        .iter()
+        .enumerate()
+        .map(|(index, (name, _))| (*name, index))
+        .collect();
+
+    // Each function's node has the same index as the function itself.
+    let mut graph = DiGraph::<usize, ()>::new();
+    for index in 0..functions.len() {
+        let _ = graph.add_node(index);
+    }
+    for (index, (_, function)) in functions.iter().enumerate() {
+        let mut called = vec![];
+        statements_tail_calls(&module.name, &function.body, &mut called);
+        for callee in called.into_iter().filter_map(|name| indices.get(name)) {
+            let _ = graph.update_edge(NodeIndex::new(index), NodeIndex::new(*callee), ());
+        }
+    }
+
+    tarjan_scc(&graph)
+        .into_iter()
+        .filter(|component| component.len() > 1)
+        .map(|component| {
+            let group = component
+                .into_iter()
+                .map(|node| graph[node])
+                .sorted()
+                .filter_map(|index| functions.get(index).copied())
+                .collect_vec();
+            let names = group.iter().map(|(name, _)| (*name).clone()).collect_vec();
+            MutualRecursion {
+                name: eco_format!("loop${}", names.join("$")),
+                functions: group.into_iter().map(|(_, function)| function).collect(),
+                names,
+                separate: false,
+            }
+        })
+        .collect()
+}
+
+/// The functions of the module that are called in tail position by the last of
+/// the given statements.
+fn statements_tail_calls<'a>(
+    module: &str,
+    statements: &'a [TypedStatement],
+    called: &mut Vec<&'a EcoString>,
+) {
+    if let Some(Statement::Expression(expression)) = statements.last() {
+        tail_calls(module, expression, called);
+    }
+}
+
+fn tail_calls<'a>(module: &str, expression: &'a TypedExpr, called: &mut Vec<&'a EcoString>) {
+    match expression {
+        TypedExpr::Call { fun, .. } => {
+            if let TypedExpr::Var { constructor, .. } = fun.as_ref() {
+                match &constructor.variant {
+                    ValueConstructorVariant::ModuleFn {
+                        name,
+                        module: function_module,
+                        ..
+                    } if function_module == module => called.push(name),
+                    _ => (),
+                }
+            }
+        }
+
+        TypedExpr::Case { clauses, .. } => {
+            for clause in clauses {
+                tail_calls(module, &clause.then, called);
+            }
+        }
+
+        TypedExpr::Block { statements, .. } => statements_tail_calls(module, statements, called),
+
+        TypedExpr::Pipeline { finally, .. } => tail_calls(module, finally, called),
+
+        _ => (),
+    }
+}
@@ -1,4 +1,7 @@
-use std::sync::{Arc, OnceLock};
+use std::{
+    collections::HashSet,
+    sync::{Arc, OnceLock},
+};
 
 use super::{expression::is_js_scalar, *};
 use crate::{
@@ -29,6 +32,33 @@ enum Index<'a> {
     BinaryFromSlice(usize, usize),
     SliceAfter(usize),
     StringPrefixSlice(usize),
+    // The bit level indexes below are used for segments that are not byte
+    // aligned, or that have a size only known at runtime.
+    IntFromBits {
+        start: Document<'a>,
+        end: Document<'a>,
+        endianness: Endianness,
+        is_signed: bool,
+    },
+    // Used for all Int segments when Ints are represented as BigInts.
+    BigIntFromBits {
+        start: Document<'a>,
+        end: Document<'a>,
+        endianness: Endianness,
+        is_signed: bool,
+    },
+    FloatFromBits {
+        start: Document<'a>,
+        end: Document<'a>,
+        endianness: Endianness,
+    },
+    BitsFromSlice(Document<'a>, Document<'a>),
+    BitsAfter(Document<'a>),
+    CodepointAt {
+        start: Document<'a>,
+        unit_size: usize,
+        endianness: Endianness,
+    },
 }
 
 #[derive(Debug)]
@@ -39,32 +69,82 @@ pub(crate) struct Generator<'module_ctx, 'expression_gen, 'a> {
     assignments: Vec<Assignment<'a>>,
 }
 
-struct Offset {
-    bytes: usize,
+/// The offset in bits of a segment from the start of a bit array, made up of a
+/// number of bits known at compile time and any sizes only known at runtime.
+#[derive(Debug, Clone)]
+struct Offset<'a> {
+    bits: usize,
+    dynamic: Vec<Document<'a>>,
     open_ended: bool,
 }
 
-impl Offset {
+impl<'a> Offset<'a> {
     pub fn new() -> Self {
         Self {
-            bytes: 0,
+            bits: 0,
+            dynamic: vec![],
             open_ended: false,
         }
     }
     // This should never be called on an open ended offset
     // However previous checks ensure bit_array segments without a size are only
     // allowed at the end of a pattern
-    pub fn increment(&mut self, step: usize) {
-        self.bytes += step
+    pub fn increment(&mut self, step: SegmentSize<'a>) {
+        match step {
+            SegmentSize::Static(bits) => match self.bits.checked_add(bits) {
+                Some(total) => self.bits = total,
+                None => self.dynamic.push(bits.to_doc()),
+            },
+            SegmentSize::Dynamic(bits) => self.dynamic.push(bits),
+        }
     }
     pub fn set_open_ended(&mut self) {
         self.open_ended = true
     }
+    /// The offset in bytes, if it is known at compile time and is a whole
+    /// number of bytes.
+    pub fn bytes(&self) -> Option<usize> {
+        if self.dynamic.is_empty() {
+            SegmentSize::Static(self.bits).bytes()
+        } else {
+            None
+        }
+    }
+    pub fn after(&self, step: &SegmentSize<'a>) -> Self {
+        let mut offset = self.clone();
+        offset.increment(step.clone());
+        offset
+    }
+    pub fn to_doc(&self) -> Document<'a> {
+        match (self.bits, self.dynamic.as_slice()) {
+            (bits, []) => bits.to_doc(),
+            (0, dynamic) => join(dynamic.iter().cloned(), " + ".to_doc()),
+            (bits, dynamic) => docvec![bits, " + ", join(dynamic.iter().cloned(), " + ".to_doc())],
+        }
+    }
+}
+
+/// The size in bits of a segment of a bit array pattern.
+#[derive(Debug, Clone)]
+enum SegmentSize<'a> {
+    Static(usize),
+    Dynamic(Document<'a>),
+}
+
+impl<'a> SegmentSize<'a> {
+    /// The size in bytes, if it is known at compile time and is a whole
+    /// number of bytes.
+    fn bytes(&self) -> Option<usize> {
+        match self {
+            SegmentSize::Static(bits) if bits % 8 == 0 => Some(bits / 8),
+            SegmentSize::Static(_) | SegmentSize::Dynamic(_) => None,
+        }
+    }
 }
 
 #[derive(Debug)]
-struct SizedBitArraySegmentDetails {
-    size: usize,
+struct SizedBitArraySegmentDetails<'a> {
+    size: SegmentSize<'a>,
     endianness: Endianness,
     is_signed: bool,
 }
@@ -136,6 +216,65 @@ impl<'module_ctx, 'expression_gen, 'a> Generator<'module_ctx, 'expression_gen, '
         self.path.push(Index::SliceAfter(i));
     }
 
+    fn push_int_from_bits(
+        &mut self,
+        start: Document<'a>,
+        end: Document<'a>,
+        endianness: Endianness,
+        is_signed: bool,
+    ) {
+        self.path.push(Index::IntFromBits {
+            start,
+            end,
+            endianness,
+            is_signed,
+        });
+    }
+
+    fn push_big_int_from_bits(
+        &mut self,
+        start: Document<'a>,
+        end: Document<'a>,
+        endianness: Endianness,
+        is_signed: bool,
+    ) {
+        self.path.push(Index::BigIntFromBits {
+            start,
+            end,
+            endianness,
+            is_signed,
+        });
+    }
+
+    fn push_float_from_bits(
+        &mut self,
+        start: Document<'a>,
+        end: Document<'a>,
+        endianness: Endianness,
+    ) {
+        self.path.push(Index::FloatFromBits {
+            start,
+            end,
+            endianness,
+        });
+    }
+
+    fn push_bits_from_slice(&mut self, start: Document<'a>, end: Document<'a>) {
+        self.path.push(Index::BitsFromSlice(start, end));
+    }
+
+    fn push_bits_after(&mut self, start: Document<'a>) {
+        self.path.push(Index::BitsAfter(start));
+    }
+
+    fn push_codepoint_at(&mut self, start: Document<'a>, unit_size: usize, endianness: Endianness) {
+        self.path.push(Index::CodepointAt {
+            start,
+            unit_size,
+            endianness,
+        });
+    }
+
     fn push_string_times(&mut self, s: &'a str, times: usize) {
         for _ in 0..times {
             self.push_string(s);
@@ -192,6 +331,68 @@ impl<'module_ctx, 'expression_gen, 'a> Generator<'module_ctx, 'expression_gen, '
             }
             Index::SliceAfter(i) => docvec!(".sliceAfter(", i, ")"),
             Index::StringPrefixSlice(i) => docvec!(".slice(", i, ")"),
+            Index::IntFromBits {
+                start,
+                end,
+                endianness,
+                is_signed,
+            } => docvec!(
+                ".intFromBits(",
+                start.clone(),
+                ", ",
+                end.clone(),
+                ", ",
+                bool(endianness.is_big()),
+                ", ",
+                bool(*is_signed),
+                ")"
+            ),
+            Index::BigIntFromBits {
+                start,
+                end,
+                endianness,
+                is_signed,
+            } => docvec!(
+                ".bigIntFromBits(",
+                start.clone(),
+                ", ",
+                end.clone(),
+                ", ",
+                bool(endianness.is_big()),
+                ", ",
+                bool(*is_signed),
+                ")"
+            ),
+            Index::FloatFromBits {
+                start,
+                end,
+                endianness,
+            } => docvec!(
+                ".floatFromBits(",
+                start.clone(),
+                ", ",
+                end.clone(),
+                ", ",
+                bool(endianness.is_big()),
+                ")"
+            ),
+            Index::BitsFromSlice(start, end) => {
+                docvec!(".bitsFromSlice(", start.clone(), ", ", end.clone(), ")")
+            }
+            Index::BitsAfter(start) => docvec!(".bitsAfter(", start.clone(), ")"),
+            Index::CodepointAt {
+                start,
+                unit_size,
+                endianness,
+            } => docvec!(
+                ".codepointAt(",
+                start.clone(),
+                ", ",
+                unit_size,
+                ", ",
+                bool(endianness.is_big()),
+                ")"
+            ),
         }))
     }
 
@@ -330,6 +531,26 @@ impl<'module_ctx, 'expression_gen, 'a> Generator<'module_ctx, 'expression_gen, '
                 docvec!(left, " * ", right)
             }
 
+            // BigInt division throws when dividing by zero, so the prelude
+            // functions are used instead.
+            ClauseGuard::DivInt { left, right, .. }
+                if self.expression_generator.integers == JavaScriptIntegers::BigInt =>
+            {
+                let left = self.guard(left)?;
+                let right = self.guard(right)?;
+                self.expression_generator.tracker.int_division_used = true;
+                docvec!("divideInt", wrap_args([left, right]))
+            }
+
+            ClauseGuard::RemainderInt { left, right, .. }
+                if self.expression_generator.integers == JavaScriptIntegers::BigInt =>
+            {
+                let left = self.guard(left)?;
+                let right = self.guard(right)?;
+                self.expression_generator.tracker.int_remainder_used = true;
+                docvec!("remainderInt", wrap_args([left, right]))
+            }
+
             ClauseGuard::DivFloat { left, right, .. } | ClauseGuard::DivInt { left, right, .. } => {
                 let left = self.wrapped_guard(left)?;
                 let right = self.wrapped_guard(right)?;
@@ -382,6 +603,7 @@ impl<'module_ctx, 'expression_gen, 'a> Generator<'module_ctx, 'expression_gen, '
                 return expression::guard_constant_expression(
                     &mut self.assignments,
                     self.expression_generator.tracker,
+                    self.expression_generator.integers,
                     constant,
                 )
             }
@@ -410,7 +632,8 @@ impl<'module_ctx, 'expression_gen, 'a> Generator<'module_ctx, 'expression_gen, '
                 Ok(())
             }
             Pattern::Int { value, .. } => {
-                self.push_equality_check(subject.clone(), expression::int(value));
+                let value = expression::int_literal(value, self.expression_generator.integers);
+                self.push_equality_check(subject.clone(), value);
                 Ok(())
             }
             Pattern::Float { value, .. } => {
@@ -568,96 +791,182 @@ impl<'module_ctx, 'expression_gen, 'a> Generator<'module_ctx, 'expression_gen, '
                 use BitArrayOption as Opt;
 
                 let mut offset = Offset::new();
+                // Variables bound to unsigned integers earlier in the pattern
+                // are never negative, so they need no check when used as the
+                // size of a later segment.
+                let mut unsigned_variables = HashSet::new();
                 for segment in segments {
                     if segment.type_ == crate::type_::int()
                         || segment.type_ == crate::type_::float()
                     {
-                        let details = Self::sized_bit_array_segment_details(segment)?;
+                        let details =
+                            self.sized_bit_array_segment_details(segment, &unsigned_variables)?;
 
-                        let start = offset.bytes;
-                        let increment = details.size / 8;
-                        let end = offset.bytes + increment;
+                        let end = offset.after(&details.size);
+                        let aligned = offset.bytes().zip(details.size.bytes());
 
                         if segment.type_ == crate::type_::int() {
-                            if details.size == 8 && !details.is_signed {
-                                self.push_byte_at(offset.bytes);
-                            } else {
-                                self.push_int_from_slice(
+                            match aligned {
+                                _ if self.expression_generator.integers
+                                    == JavaScriptIntegers::BigInt =>
+                                {
+                                    self.push_big_int_from_bits(
+                                        offset.to_doc(),
+                                        end.to_doc(),
+                                        details.endianness,
+                                        details.is_signed,
+                                    )
+                                }
+                                Some((start, 1)) if !details.is_signed => self.push_byte_at(start),
+                                Some((start, size)) => self.push_int_from_slice(
                                     start,
-                                    end,
+                                    start + size,
                                     details.endianness,
                                     details.is_signed,
-                                );
+                                ),
+                                None => self.push_int_from_bits(
+                                    offset.to_doc(),
+                                    end.to_doc(),
+                                    details.endianness,
+                                    details.is_signed,
+                                ),
+                            }
+                            if let (false, Pattern::Variable { name, .. }) =
+                                (details.is_signed, segment.value.as_ref())
+                            {
+                                let _ = unsigned_variables.insert(name);
                             }
                         } else {
-                            self.push_float_from_slice(start, end, details.endianness);
+                            match aligned {
+                                Some((start, size)) => self.push_float_from_slice(
+                                    start,
+                                    start + size,
+                                    details.endianness,
+                                ),
+                                None => self.push_float_from_bits(
+                                    offset.to_doc(),
+                                    end.to_doc(),
+                                    details.endianness,
+                                ),
+                            }
                         }
 
                         self.traverse_pattern(subject, &segment.value)?;
                         self.pop();
-                        offset.increment(increment);
-                    } else {
-                        match segment.options.as_slice() {
-                            [Opt::Bytes { .. }] => {
-                                self.push_rest_from(offset.bytes);
+                        offset = end;
+                    } else if let Some(option) = segment
+                        .options
+                        .iter()
+                        .find(|x| matches!(x, Opt::Bytes { .. } | Opt::Bits { .. }))
+                    {
+                        match self.bit_array_segment_size(segment, &unsigned_variables)? {
+                            None => {
+                                match offset.bytes() {
+                                    Some(start) => self.push_rest_from(start),
+                                    None => self.push_bits_after(offset.to_doc()),
+                                }
                                 self.traverse_pattern(subject, &segment.value)?;
                                 self.pop();
+                                if matches!(option, Opt::Bytes { .. }) {
+                                    let offset = match (offset.bytes(), offset.dynamic.len()) {
+                                        (Some(_), _) => None,
+                                        (None, 0) => Some(offset.to_doc()),
+                                        (None, _) => Some(docvec!["(", offset.to_doc(), ")"]),
+                                    };
+                                    self.push_bit_array_tail_check(subject.clone(), offset);
+                                }
                                 offset.set_open_ended();
-                                Ok(())
                             }
 
-                            [Opt::Bytes { .. }, Opt::Size { value: size, .. }]
-                            | [Opt::Size { value: size, .. }, Opt::Bytes { .. }] => match &**size {
-                                Pattern::Int { value, .. } => {
-                                    let start = offset.bytes;
-                                    let increment = value.parse::<usize>().expect(
-                                        "part of an Int node should always parse as integer",
-                                    );
-                                    offset.increment(increment);
-                                    let end = offset.bytes;
-
-                                    self.push_binary_from_slice(start, end);
-                                    self.traverse_pattern(subject, &segment.value)?;
-                                    self.pop();
-                                    Ok(())
-                                }
-
-                                _ => Err(Error::Unsupported {
-                                    feature: "This bit array size option in patterns".into(),
-                                    location: segment.location,
-                                }),
-                            },
-
-                            [Opt::Utf8 { .. }] => match segment.value.as_ref() {
-                                Pattern::String { value, .. } => {
-                                    for byte in value.as_bytes() {
-                                        self.push_byte_at(offset.bytes);
-                                        self.push_equality_check(
-                                            subject.clone(),
-                                            EcoString::from(format!("0x{:X}", byte)).to_doc(),
-                                        );
-                                        self.pop();
-                                        offset.increment(1);
+                            Some(size) => {
+                                let end = offset.after(&size);
+                                match offset.bytes().zip(size.bytes()) {
+                                    Some((start, size)) => {
+                                        self.push_binary_from_slice(start, start + size)
+                                    }
+                                    None => {
+                                        self.push_bits_from_slice(offset.to_doc(), end.to_doc())
                                     }
-
-                                    Ok(())
                                 }
-
-                                _ => Err(Error::Unsupported {
-                                    feature: "This bit array segment option in patterns".into(),
-                                    location: segment.location,
-                                }),
-                            },
-
-                            _ => Err(Error::Unsupported {
+                                self.traverse_pattern(subject, &segment.value)?;
+                                self.pop();
+                                offset = end;
+                            }
+                        }
+                    } else if let Some(unit_size) = segment.options.iter().find_map(|x| match x {
+                        Opt::Utf8 { .. } => Some(8),
+                        Opt::Utf16 { .. } => Some(16),
+                        Opt::Utf32 { .. } => Some(32),
+                        _ => None,
+                    }) {
+                        let Pattern::String { value, .. } = segment.value.as_ref() else {
+                            return Err(Error::Unsupported {
                                 feature: "This bit array segment option in patterns".into(),
                                 location: segment.location,
-                            }),
-                        }?;
+                            });
+                        };
+
+                        let endianness = Self::segment_endianness(segment)?;
+                        for byte in encode_string(value, unit_size, endianness) {
+                            match offset.bytes() {
+                                Some(index) => self.push_byte_at(index),
+                                None => {
+                                    let end = offset.after(&SegmentSize::Static(8));
+                                    self.push_int_from_bits(
+                                        offset.to_doc(),
+                                        end.to_doc(),
+                                        Endianness::Big,
+                                        false,
+                                    )
+                                }
+                            }
+                            self.push_equality_check(
+                                subject.clone(),
+                                EcoString::from(format!("0x{:X}", byte)).to_doc(),
+                            );
+                            self.pop();
+                            offset.increment(SegmentSize::Static(8));
+                        }
+                    } else if let Some(unit_size) = segment.options.iter().find_map(|x| match x {
+                        Opt::Utf8Codepoint { .. } => Some(8),
+                        Opt::Utf16Codepoint { .. } => Some(16),
+                        Opt::Utf32Codepoint { .. } => Some(32),
+                        _ => None,
+                    }) {
+                        let endianness = Self::segment_endianness(segment)?;
+                        let is_big = bool(endianness.is_big());
+                        let start = offset.to_doc();
+                        self.push_codepoint_at(start.clone(), unit_size, endianness);
+                        self.traverse_pattern(subject, &segment.value)?;
+                        self.pop();
+
+                        // The size of a codepoint is only known at runtime, and
+                        // is `NaN` if the bits are not a valid codepoint, which
+                        // then fails the length check.
+                        offset.increment(SegmentSize::Dynamic(docvec![
+                            subject.clone(),
+                            self.path_document(),
+                            ".codepointSize(",
+                            start,
+                            ", ",
+                            unit_size,
+                            ", ",
+                            is_big,
+                            ")"
+                        ]));
+                    } else {
+                        return Err(Error::Unsupported {
+                            feature: "This bit array segment option in patterns".into(),
+                            location: segment.location,
+                        });
                     }
                 }
 
-                self.push_bit_array_length_check(subject.clone(), offset.bytes, offset.open_ended);
+                self.push_bit_array_length_check(
+                    subject.clone(),
+                    offset.to_doc(),
+                    offset.open_ended,
+                );
                 Ok(())
             }
             Pattern::VarUsage { location, .. } => Err(Error::Unsupported {
@@ -669,8 +978,35 @@ impl<'module_ctx, 'expression_gen, 'a> Generator<'module_ctx, 'expression_gen, '
     }
 
     fn sized_bit_array_segment_details(
+        &mut self,
+        segment: &'a BitArraySegment<Pattern<Arc<Type>>, Arc<Type>>,
+        unsigned_variables: &HashSet<&EcoString>,
+    ) -> Result<SizedBitArraySegmentDetails<'a>, Error> {
+        use BitArrayOption as Opt;
+
+        let endianness = Self::segment_endianness(segment)?;
+
+        let size = match self.bit_array_segment_size(segment, unsigned_variables)? {
+            Some(size) => size,
+            None if segment.type_ == crate::type_::int() => SegmentSize::Static(8),
+            None => SegmentSize::Static(64),
+        };
+
+        let is_signed = segment
+            .options
+            .iter()
+            .any(|x| matches!(x, Opt::Signed { .. }));
+
+        Ok(SizedBitArraySegmentDetails {
+            size,
+            endianness,
+            is_signed,
+        })
+    }
+
+    fn segment_endianness(
         segment: &BitArraySegment<Pattern<Arc<Type>>, Arc<Type>>,
-    ) -> Result<SizedBitArraySegmentDetails, Error> {
+    ) -> Result<Endianness, Error> {
         use BitArrayOption as Opt;
 
         if segment
@@ -684,60 +1020,71 @@ impl<'module_ctx, 'expression_gen, 'a> Generator<'module_ctx, 'expression_gen, '
             });
         }
 
-        let endianness = if segment
+        if segment
             .options
             .iter()
             .any(|x| matches!(x, Opt::Little { .. }))
         {
-            Endianness::Little
+            Ok(Endianness::Little)
         } else {
-            Endianness::Big
+            Ok(Endianness::Big)
+        }
+    }
+
+    /// The size in bits of a segment with an explicit size, which may be a
+    /// variable bound earlier in the pattern or elsewhere in scope.
+    fn bit_array_segment_size(
+        &mut self,
+        segment: &'a BitArraySegment<Pattern<Arc<Type>>, Arc<Type>>,
+        unsigned_variables: &HashSet<&EcoString>,
+    ) -> Result<Option<SegmentSize<'a>>, Error> {
+        use BitArrayOption as Opt;
+
+        let Some(size) = segment.options.iter().find_map(|x| match x {
+            Opt::Size { value, .. } => Some(value),
+            _ => None,
+        }) else {
+            return Ok(None);
         };
+        let unit = expression::bit_array_segment_unit(&segment.options);
 
-        let size = match segment
-            .options
-            .iter()
-            .find(|x| matches!(x, Opt::Size { .. }))
-        {
-            Some(Opt::Size { value: size, .. }) => match &**size {
-                Pattern::Int { value, .. } => Ok(value
+        match &**size {
+            Pattern::Int { value, .. } => {
+                // A size too large to be known at compile time is multiplied
+                // at runtime instead, where it will never match.
+                let size = value
                     .parse::<usize>()
-                    .expect("part of an Int node should always parse as integer")),
-                _ => Err(Error::Unsupported {
-                    feature: "This bit array size option in patterns".into(),
-                    location: segment.location,
-                }),
-            },
+                    .ok()
+                    .and_then(|size| size.checked_mul(unit as usize));
+                Ok(Some(match size {
+                    Some(size) => SegmentSize::Static(size),
+                    None if unit == 1 => SegmentSize::Dynamic(expression::int(value)),
+                    None => SegmentSize::Dynamic(docvec![expression::int(value), " * ", unit]),
+                }))
+            }
 
-            _ => {
-                let default_size = if segment.type_ == crate::type_::int() {
-                    8usize
-                } else {
-                    64usize
+            Pattern::VarUsage { name, .. } => {
+                let variable = match self.path_doc_from_assignments(name) {
+                    Some(path) => path,
+                    None => self.local_var(name),
                 };
-
-                Ok(default_size)
+                if !unsigned_variables.contains(name) {
+                    self.push_non_negative_check(variable.clone());
+                }
+                let variable =
+                    expression::int_to_number(variable, self.expression_generator.integers);
+                Ok(Some(SegmentSize::Dynamic(if unit == 1 {
+                    variable
+                } else {
+                    docvec![variable, " * ", unit]
+                })))
             }
-        }?;
 
-        // 16-bit floats are not supported
-        if segment.type_ == crate::type_::float() && size == 16usize {
-            return Err(Error::Unsupported {
+            _ => Err(Error::Unsupported {
                 feature: "This bit array size option in patterns".into(),
                 location: segment.location,
-            });
+            }),
         }
-
-        let is_signed = segment
-            .options
-            .iter()
-            .any(|x| matches!(x, Opt::Signed { .. }));
-
-        Ok(SizedBitArraySegmentDetails {
-            size,
-            endianness,
-            is_signed,
-        })
     }
 
     fn push_assignment(&mut self, subject: Document<'a>, name: &'a EcoString) {
@@ -808,16 +1155,28 @@ impl<'module_ctx, 'expression_gen, 'a> Generator<'module_ctx, 'expression_gen, '
     fn push_bit_array_length_check(
         &mut self,
         subject: Document<'a>,
-        expected_bytes: usize,
+        expected_bits: Document<'a>,
         has_tail_spread: bool,
     ) {
         self.checks.push(Check::BitArrayLength {
-            expected_bytes,
+            expected_bits,
             has_tail_spread,
             subject,
             path: self.path_document(),
         })
     }
+
+    fn push_bit_array_tail_check(&mut self, subject: Document<'a>, offset: Option<Document<'a>>) {
+        self.checks.push(Check::BitArrayTailIsBytes {
+            offset,
+            subject,
+            path: self.path_document(),
+        })
+    }
+
+    fn push_non_negative_check(&mut self, value: Document<'a>) {
+        self.checks.push(Check::NonNegative { value })
+    }
 }
 
 #[derive(Debug)]
@@ -872,9 +1231,19 @@ pub enum Check<'a> {
     BitArrayLength {
         subject: Document<'a>,
         path: Document<'a>,
-        expected_bytes: usize,
+        expected_bits: Document<'a>,
         has_tail_spread: bool,
     },
+    /// Whether the bits of a bit array after the offset are a whole number of
+    /// bytes. The offset is `None` when it is a whole number of bytes itself.
+    BitArrayTailIsBytes {
+        subject: Document<'a>,
+        path: Document<'a>,
+        offset: Option<Document<'a>>,
+    },
+    NonNegative {
+        value: Document<'a>,
+    },
     StringPrefix {
         subject: Document<'a>,
         path: Document<'a>,
@@ -962,20 +1331,43 @@ impl<'a> Check<'a> {
             Check::BitArrayLength {
                 subject,
                 path,
-                expected_bytes,
+                expected_bits,
                 has_tail_spread,
             } => {
-                let length_check = Document::String(if has_tail_spread {
-                    format!(".length >= {expected_bytes}")
-                } else {
-                    format!(".length == {expected_bytes}")
-                });
+                let operator = if has_tail_spread { " >= " } else { " == " };
+                let length_check = docvec![".bitSize", operator, expected_bits];
                 if match_desired {
                     docvec![subject, path, length_check,]
                 } else {
                     docvec!["!(", subject, path, length_check, ")",]
                 }
             }
+            Check::BitArrayTailIsBytes {
+                subject,
+                path,
+                offset,
+            } => {
+                let operator = if match_desired { " === " } else { " !== " };
+                match offset {
+                    Some(offset) => {
+                        docvec![
+                            "(",
+                            subject,
+                            path,
+                            ".bitSize - ",
+                            offset,
+                            ") % 8",
+                            operator,
+                            "0"
+                        ]
+                    }
+                    None => docvec![subject, path, ".bitSize % 8", operator, "0"],
+                }
+            }
+            Check::NonNegative { value } => {
+                let operator = if match_desired { " >= " } else { " < " };
+                docvec![value, operator, "0"]
+            }
             Check::StringPrefix {
                 subject,
                 path,
@@ -998,6 +1390,8 @@ impl<'a> Check<'a> {
             | Check::Equal { .. }
             | Check::ListLength { .. }
             | Check::BitArrayLength { .. }
+            | Check::BitArrayTailIsBytes { .. }
+            | Check::NonNegative { .. }
             | Check::StringPrefix { .. }
             | Check::Booly { .. } => false,
             Check::Guard { .. } => true,
@@ -1040,6 +1434,25 @@ pub(crate) fn assign_subjects<'a>(
 }
 
 /// Calculates the length of str as utf16 without escape characters.
+/// The bytes of a string literal in a bit array pattern, encoded as UTF-8,
+/// UTF-16 or UTF-32.
+fn encode_string(value: &EcoString, unit_size: usize, endianness: Endianness) -> Vec<u8> {
+    let string = convert_string_escape_chars(value);
+    match (unit_size, endianness.is_big()) {
+        (16, true) => string.encode_utf16().flat_map(u16::to_be_bytes).collect(),
+        (16, false) => string.encode_utf16().flat_map(u16::to_le_bytes).collect(),
+        (32, true) => string
+            .chars()
+            .flat_map(|c| u32::from(c).to_be_bytes())
+            .collect(),
+        (32, false) => string
+            .chars()
+            .flat_map(|c| u32::from(c).to_le_bytes())
+            .collect(),
+        _ => string.as_bytes().to_vec(),
+    }
+}
+
 fn utf16_no_escape_len(str: &EcoString) -> usize {
     convert_string_escape_chars(str).encode_utf16().count()
 }
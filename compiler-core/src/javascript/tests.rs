@@ -1,21 +1,27 @@
 use crate::{
     analyse::TargetSupport,
     build::{Origin, Target},
-    config::PackageConfig,
+    config::{JavaScriptIntegers, JavaScriptModuleFormat, PackageConfig},
     javascript::*,
     uid::UniqueIdGenerator,
     warning::{TypeWarningEmitter, WarningEmitter},
 };
 use camino::{Utf8Path, Utf8PathBuf};
 
+mod api;
 mod assignments;
+mod big_ints;
 mod bit_arrays;
 mod blocks;
 mod bools;
+mod bundle;
 mod case;
 mod case_clause_guards;
+mod checked_externals;
+mod commonjs;
 mod consts;
 mod custom_types;
+mod documentation;
 mod externals;
 mod functions;
 mod generics;
@@ -148,7 +154,25 @@ pub fn compile(src: &str, deps: Vec<(&str, &str, &str)>) -> TypedModule {
     .expect("should successfully infer")
 }
 
+fn options() -> CodegenOptions {
+    CodegenOptions {
+        target_support: TargetSupport::NotEnforced,
+        typescript: TypeScriptDeclarations::None,
+        integers: JavaScriptIntegers::Number,
+        module_format: JavaScriptModuleFormat::Esm,
+        check_externals: false,
+    }
+}
+
 pub fn compile_js(src: &str, deps: Vec<(&str, &str, &str)>) -> String {
+    compile_js_with_integers(src, deps, JavaScriptIntegers::Number)
+}
+
+pub fn compile_js_with_integers(
+    src: &str,
+    deps: Vec<(&str, &str, &str)>,
+    integers: JavaScriptIntegers,
+) -> String {
     let ast = compile(src, deps);
     let line_numbers = LineNumbers::new(src);
     module(
@@ -156,13 +180,79 @@ pub fn compile_js(src: &str, deps: Vec<(&str, &str, &str)>) -> String {
         &line_numbers,
         Utf8Path::new(""),
         &"".into(),
-        TargetSupport::NotEnforced,
-        TypeScriptDeclarations::None,
+        CodegenOptions {
+            integers,
+            ..options()
+        },
+    )
+    .unwrap()
+}
+
+pub fn compile_js_with_checked_externals(src: &str, deps: Vec<(&str, &str, &str)>) -> String {
+    let ast = compile(src, deps);
+    let line_numbers = LineNumbers::new(src);
+    module(
+        &ast,
+        &line_numbers,
+        Utf8Path::new(""),
+        &"".into(),
+        CodegenOptions {
+            check_externals: true,
+            ..options()
+        },
+    )
+    .unwrap()
+}
+
+pub fn compile_commonjs(
+    src: &str,
+    deps: Vec<(&str, &str, &str)>,
+    typescript: TypeScriptDeclarations,
+) -> String {
+    let ast = compile(src, deps);
+    let line_numbers = LineNumbers::new(src);
+    module(
+        &ast,
+        &line_numbers,
+        Utf8Path::new(""),
+        &"".into(),
+        CodegenOptions {
+            typescript,
+            module_format: JavaScriptModuleFormat::CommonJs,
+            ..options()
+        },
     )
     .unwrap()
 }
 
 pub fn compile_ts(src: &str, deps: Vec<(&str, &str, &str)>) -> String {
+    compile_ts_with_integers(src, deps, JavaScriptIntegers::Number)
+}
+
+pub fn compile_ts_with_integers(
+    src: &str,
+    deps: Vec<(&str, &str, &str)>,
+    integers: JavaScriptIntegers,
+) -> String {
+    let ast = compile(src, deps);
+    ts_declaration(
+        &ast,
+        Utf8Path::new(""),
+        &src.into(),
+        integers,
+        JavaScriptModuleFormat::Esm,
+    )
+    .unwrap()
+}
+
+pub fn compile_commonjs_ts(src: &str, deps: Vec<(&str, &str, &str)>) -> String {
     let ast = compile(src, deps);
-    ts_declaration(&ast, Utf8Path::new(""), &src.into()).unwrap()
+    ts_declaration(
+        &ast,
+        Utf8Path::new(""),
+        &src.into(),
+        JavaScriptIntegers::Number,
+        JavaScriptModuleFormat::CommonJs,
+    )
+    .unwrap()
 }
@@ -0,0 +1,187 @@
+use crate::{
+    config::{JavaScriptIntegers, JavaScriptModuleFormat},
+    javascript::{api, ApiResults},
+};
+
+const OPTION_MODULE: &str = r#"
+pub type Option(a) {
+  Some(a)
+  None
+}
+"#;
+
+macro_rules! assert_api {
+    ($results:expr, $src:expr $(,)?) => {{
+        let module = $crate::javascript::tests::compile(
+            $src,
+            vec![("gleam_stdlib", "gleam/option", OPTION_MODULE)],
+        );
+        let api = api(
+            &module,
+            $results,
+            JavaScriptIntegers::Number,
+            JavaScriptModuleFormat::Esm,
+        );
+        let output = format!(
+            "// JavaScript\n{}\n// TypeScript\n{}",
+            api.javascript, api.typescript
+        );
+        insta::assert_snapshot!(insta::internals::AutoName, output, $src);
+    }};
+
+    ($src:expr $(,)?) => {{
+        assert_api!(ApiResults::Union, $src)
+    }};
+}
+
+#[test]
+fn unconverted_values() {
+    assert_api!(
+        r#"
+/// Adds two numbers together.
+pub fn add(a: Int, b: Int) -> Int {
+  a + b
+}
+
+pub fn greet(name: String) -> String {
+  "Hello, " <> name
+}
+
+fn private() {
+  Nil
+}
+"#
+    );
+}
+
+#[test]
+fn lists() {
+    assert_api!(
+        r#"
+pub fn reverse(list: List(a)) -> List(a) {
+  list
+}
+
+pub fn nested(lists: List(List(Int))) -> List(List(Int)) {
+  lists
+}
+"#
+    );
+}
+
+#[test]
+fn options() {
+    assert_api!(
+        r#"
+import gleam/option.{type Option}
+
+pub fn first(list: List(a)) -> Option(a) {
+  case list {
+    [x, ..] -> option.Some(x)
+    [] -> option.None
+  }
+}
+
+pub fn unwrap(option: Option(Int), default: Int) -> Int {
+  case option {
+    option.Some(x) -> x
+    option.None -> default
+  }
+}
+"#
+    );
+}
+
+#[test]
+fn results_as_unions() {
+    assert_api!(
+        r#"
+pub fn parse(input: String) -> Result(Int, String) {
+  case input {
+    "1" -> Ok(1)
+    _ -> Error("Not a number")
+  }
+}
+
+pub fn all(results: List(Result(Int, Nil))) -> Result(List(Int), Nil) {
+  Error(Nil)
+}
+"#
+    );
+}
+
+#[test]
+fn results_as_thrown_errors() {
+    assert_api!(
+        ApiResults::Throw,
+        r#"
+pub fn parse(input: String) -> Result(Int, String) {
+  case input {
+    "1" -> Ok(1)
+    _ -> Error("Not a number")
+  }
+}
+
+pub fn sizes(input: List(String)) -> Result(List(Int), List(String)) {
+  Error(input)
+}
+
+pub fn is_ok(result: Result(Int, String)) -> Bool {
+  case result {
+    Ok(_) -> True
+    Error(_) -> False
+  }
+}
+"#
+    );
+}
+
+#[test]
+fn tuples() {
+    assert_api!(
+        r#"
+pub fn swap(pair: #(List(Int), String)) -> #(String, List(Int)) {
+  #(pair.1, pair.0)
+}
+
+pub fn same(pair: #(Int, String)) -> #(Int, String) {
+  pair
+}
+"#
+    );
+}
+
+#[test]
+fn custom_types() {
+    assert_api!(
+        r#"
+import gleam/option.{type Option}
+
+pub type Shape {
+  Circle(radius: Float)
+  Square(side: Float)
+}
+
+pub fn shapes(sizes: List(Float)) -> List(Shape) {
+  []
+}
+
+pub fn smallest(shapes: List(Shape)) -> Option(Option(Shape)) {
+  option.None
+}
+"#
+    );
+}
+
+#[test]
+fn externals() {
+    assert_api!(
+        r#"
+@external(javascript, "./ffi.mjs", "now")
+pub fn now() -> Int
+
+@external(erlang, "erlang", "halt")
+pub fn halt(code: Int) -> Nil
+"#
+    );
+}
@@ -0,0 +1,151 @@
+use crate::config::JavaScriptIntegers;
+
+macro_rules! assert_big_int_js {
+    ($src:expr $(,)?) => {{
+        let output = $crate::javascript::tests::compile_js_with_integers(
+            $src,
+            vec![],
+            JavaScriptIntegers::BigInt,
+        );
+        insta::assert_snapshot!(insta::internals::AutoName, output, $src);
+    }};
+}
+
+macro_rules! assert_big_int_ts_def {
+    ($src:expr $(,)?) => {{
+        let output = $crate::javascript::tests::compile_ts_with_integers(
+            $src,
+            vec![],
+            JavaScriptIntegers::BigInt,
+        );
+        insta::assert_snapshot!(insta::internals::AutoName, output, $src);
+    }};
+}
+
+#[test]
+fn int_literals() {
+    assert_big_int_js!(
+        r#"
+fn go() {
+    1
+    -3
+    4001
+    0b00001111
+    0o17
+    0xF
+    1_000
+    9_007_199_254_740_993
+}
+"#,
+    );
+}
+
+#[test]
+fn int_constants() {
+    assert_big_int_js!(
+        r#"
+pub const a = 1
+pub const b = #(2, [3])
+pub const c = <<4, 5:size(16)>>
+"#,
+    );
+}
+
+#[test]
+fn int_operators() {
+    assert_big_int_js!(
+        r#"
+fn go(a, b) {
+    a + b
+    a - b
+    a * b
+    a / b
+    a % b
+    let c = -a
+    a > c
+}
+"#,
+    );
+}
+
+#[test]
+fn int_patterns() {
+    assert_big_int_js!(
+        r#"
+fn go(x) {
+    case x {
+        0 -> 1
+        -1 -> 2
+        _ -> 3
+    }
+}
+"#,
+    );
+}
+
+#[test]
+fn int_guards() {
+    assert_big_int_js!(
+        r#"
+fn go(x, y) {
+    case x {
+        _ if x / y > 1 -> 1
+        _ if x % y == 0 -> 2
+        _ -> 3
+    }
+}
+"#,
+    );
+}
+
+#[test]
+fn bit_array_segments() {
+    assert_big_int_js!(
+        r#"
+fn go(x, size) {
+    <<x, x:size(64), x:size(size), x:size(size)-unit(8)>>
+}
+"#,
+    );
+}
+
+#[test]
+fn bit_array_patterns() {
+    assert_big_int_js!(
+        r#"
+fn go(x) {
+    let assert <<a, b:size(64)-little-signed, c:size(a), d:size(3), _:bits>> = x
+    #(a, b, c, d)
+}
+"#,
+    );
+}
+
+#[test]
+fn bit_array_string_pattern() {
+    assert_big_int_js!(
+        r#"
+fn go(x) {
+    let assert <<"a":utf8, rest:bits>> = x
+    rest
+}
+"#,
+    );
+}
+
+#[test]
+fn typescript_int() {
+    assert_big_int_ts_def!(
+        r#"
+pub const answer = 42
+
+pub fn add(a: Int, b: Int) -> Int {
+    a + b
+}
+
+pub fn half(a: Float) -> Float {
+    a /. 2.0
+}
+"#,
+    );
+}
@@ -569,9 +569,6 @@ fn go() {
     );
 }
 
-// This test would ideally also result in go() being deleted like the previous tests
-// but we can not know for sure what the value of a variable is going to be
-// so right now go() is not deleted.
 #[test]
 fn not_byte_aligned_variable() {
     assert_js!(
@@ -583,3 +580,255 @@ fn go() {
 "#,
     );
 }
+
+#[test]
+fn not_byte_aligned_little_endian() {
+    assert_js!(
+        r#"
+fn go() {
+  <<2748:12-little, 1:1, 0:3>>
+}
+"#,
+    );
+}
+
+#[test]
+fn sized_with_unit() {
+    assert_js!(
+        r#"
+fn go(x) {
+  <<1:size(2)-unit(4), 2:size(x)-unit(8)>>
+}
+"#,
+    );
+}
+
+#[test]
+fn sized_with_unit_overflowing_size() {
+    assert_js!(
+        r#"
+fn go() {
+  <<1:size(4611686018427387904)-unit(8)>>
+}
+"#,
+    );
+}
+
+#[test]
+fn match_sized_with_unit_overflowing_size() {
+    assert_js!(
+        r#"
+fn go(x) {
+  let assert <<_:size(4611686018427387904)-unit(8), _:size(99999999999999999999999)>> = x
+}
+"#,
+    );
+}
+
+#[test]
+fn sized_bits() {
+    assert_js!(
+        r#"
+fn go(x) {
+  <<x:bits-size(4), x:bits-size(2)-unit(8)>>
+}
+"#,
+    );
+}
+
+#[test]
+fn utf16() {
+    assert_js!(
+        r#"
+fn go(x) {
+  <<"Gleam":utf16, x:utf16-little>>
+}
+"#,
+    );
+}
+
+#[test]
+fn utf16_codepoint() {
+    assert_js!(
+        r#"
+fn go(x) {
+  <<x:utf16_codepoint>>
+}
+"#,
+    );
+}
+
+#[test]
+fn utf32() {
+    assert_js!(
+        r#"
+fn go(x) {
+  <<"Gleam":utf32, x:utf32-little>>
+}
+"#,
+    );
+}
+
+#[test]
+fn utf32_codepoint() {
+    assert_js!(
+        r#"
+fn go(x) {
+  <<x:utf32_codepoint>>
+}
+"#,
+    );
+}
+
+#[test]
+fn float_16() {
+    assert_js!(
+        r#"
+fn go() {
+  <<1.5:float-size(16)>>
+}
+"#,
+    );
+}
+
+#[test]
+fn match_not_byte_aligned() {
+    assert_js!(
+        r#"
+fn go(x) {
+  let assert <<a:4, b:4-little, c:size(12)-signed, 1:4>> = x
+  #(a, b, c)
+}
+"#,
+    );
+}
+
+#[test]
+fn match_not_byte_aligned_rest() {
+    assert_js!(
+        r#"
+fn go(x) {
+  let assert <<_:3, rest:bits>> = x
+  rest
+}
+"#,
+    );
+}
+
+#[test]
+fn match_not_byte_aligned_rest_bytes() {
+    assert_js!(
+        r#"
+fn go(x) {
+  let assert <<_:3, rest:bytes>> = x
+  rest
+}
+"#,
+    );
+}
+
+#[test]
+fn match_dynamic_size() {
+    assert_js!(
+        r#"
+fn go(x) {
+  let assert <<size, data:bytes-size(size), rest:bits>> = x
+  #(data, rest)
+}
+"#,
+    );
+}
+
+#[test]
+fn match_dynamic_size_signed() {
+    assert_js!(
+        r#"
+fn go(x) {
+  let assert <<size:signed, value:size(size), 0>> = x
+  value
+}
+"#,
+    );
+}
+
+#[test]
+fn match_dynamic_size_from_scope() {
+    assert_js!(
+        r#"
+fn go(x, size) {
+  case x {
+    <<value:size(size)-unit(4), rest:bits>> -> #(value, rest)
+    _ -> #(0, x)
+  }
+}
+"#,
+    );
+}
+
+#[test]
+fn match_bits_sized() {
+    assert_js!(
+        r#"
+fn go(x) {
+  let assert <<a:bits-size(4), b:bytes-size(1), _:bits>> = x
+  #(a, b)
+}
+"#,
+    );
+}
+
+#[test]
+fn match_float_16() {
+    assert_js!(
+        r#"
+fn go(x) {
+  let assert <<a:float-size(16), b:float-size(16)-little>> = x
+  a +. b
+}
+"#,
+    );
+}
+
+#[test]
+fn match_utf16() {
+    assert_js!(
+        r#"
+fn go(x) {
+  let assert <<"Gleam 👍":utf16>> = x
+  let assert <<"Gleam":utf16-little>> = x
+}
+"#,
+    );
+}
+
+#[test]
+fn match_utf32() {
+    assert_js!(
+        r#"
+fn go(x) {
+  let assert <<_:4, "G":utf32, "👍":utf32-little>> = x
+}
+"#,
+    );
+}
+
+#[test]
+fn match_utf8_codepoint() {
+    assert_js!(
+        r#"
+fn go(x) {
+  let assert <<a:utf8_codepoint, b:utf16_codepoint, c:utf32_codepoint, rest:bytes>> = x
+  #(a, b, c, rest)
+}
+"#,
+    );
+}
+
+#[test]
+fn const_not_byte_aligned() {
+    assert_js!(
+        r#"
+pub const data = <<1:4, "Gleam":utf16-little, <<1:1>>:bits>>
+"#,
+    );
+}
@@ -215,3 +215,49 @@ fn b() {
 "#
     );
 }
+
+#[test]
+fn block_in_tail_position() {
+    assert_js!(
+        r#"
+pub fn go() {
+  let x = 1
+  {
+    let x = 2
+    x
+  }
+}
+"#
+    );
+}
+
+#[test]
+fn block_with_only_let_assert() {
+    assert_js!(
+        r#"
+pub fn go(x) {
+  let y = {
+    let assert Ok(z) = x
+  }
+  y
+}
+"#
+    );
+}
+
+#[test]
+fn let_assert_in_block_argument() {
+    assert_js!(
+        r#"
+fn id(x) {
+  x
+}
+
+pub fn go(x) {
+  id({
+    let assert Ok(z) = x
+  })
+}
+"#
+    );
+}
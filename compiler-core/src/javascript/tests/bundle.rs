@@ -0,0 +1,187 @@
+use crate::{
+    build::{Module, Origin},
+    config::JavaScriptIntegers,
+    io::{memory::InMemoryFileSystem, FileSystemWriter},
+    javascript::tests::compile,
+    parse::extra::ModuleExtra,
+};
+use camino::{Utf8Path, Utf8PathBuf};
+use std::time::SystemTime;
+
+/// Bundle the module `my/mod`, with the given FFI modules written to the
+/// build directory, leaving the prelude out of the output.
+fn bundle(src: &str, ffi: Vec<(&str, &str)>) -> Result<String, crate::Error> {
+    let io = InMemoryFileSystem::new();
+    let build = Utf8Path::new("/build");
+    for (path, code) in ffi {
+        io.write(&build.join(path), code).unwrap();
+    }
+    let module = Module {
+        name: "my/mod".into(),
+        code: src.into(),
+        mtime: SystemTime::UNIX_EPOCH,
+        input_path: Utf8PathBuf::from("/src/my/mod.gleam"),
+        origin: Origin::Src,
+        ast: compile(src, vec![]),
+        extra: ModuleExtra::new(),
+        dependencies: vec![],
+    };
+    let output = crate::javascript::bundle(
+        &io,
+        build,
+        vec![module],
+        "my/mod",
+        JavaScriptIntegers::Number,
+    )?;
+    Ok(without_prelude(output))
+}
+
+fn without_prelude(output: String) -> String {
+    let Some(start) = output.find("// prelude.mjs\n") else {
+        return output;
+    };
+    let end = start + output[start..].find("})();\n\n").expect("prelude end") + 7;
+    format!("{}{}", &output[..start], &output[end..])
+}
+
+#[test]
+fn unused_definitions_are_removed() {
+    let output = bundle(
+        r#"
+pub type Used {
+  Used(Int)
+  Unused
+}
+
+const used_const = 1
+
+const unused_const = 2
+
+pub fn main() {
+  wibble(used_const)
+}
+
+fn wibble(x) {
+  Used(x)
+}
+
+pub fn wobble() {
+  unused_const
+}
+"#,
+        vec![],
+    )
+    .unwrap();
+    insta::assert_snapshot!(output);
+}
+
+#[test]
+fn ffi_modules_are_included() {
+    let output = bundle(
+        r#"
+@external(javascript, "./ffi.mjs", "log")
+fn log(x: String) -> Nil
+
+@external(javascript, "./ffi.mjs", "unused")
+fn unused() -> Nil
+
+pub fn main() {
+  log("Hello")
+}
+"#,
+        vec![(
+            "thepackage/my/ffi.mjs",
+            r#"export function log(x) {
+  console.log(x);
+}
+
+export function unused() {}
+"#,
+        )],
+    )
+    .unwrap();
+    insta::assert_snapshot!(output);
+}
+
+#[test]
+fn ffi_modules_can_import_the_gleam_module_using_them() {
+    let output = bundle(
+        r#"
+pub type Box {
+  Box(String)
+}
+
+@external(javascript, "./ffi.mjs", "box")
+fn box(x: String) -> Box
+
+pub fn main() {
+  box("Hello")
+}
+"#,
+        vec![(
+            "thepackage/my/ffi.mjs",
+            r#"import { Box } from "./mod.mjs";
+
+export function box(x) {
+  return new Box(x);
+}
+"#,
+        )],
+    )
+    .unwrap();
+    insta::assert_snapshot!(output);
+}
+
+#[test]
+fn external_packages_are_imported() {
+    let output = bundle(
+        r#"
+@external(javascript, "node:fs", "readFileSync")
+fn read(path: String) -> String
+
+pub fn main() {
+  read("file.txt")
+}
+"#,
+        vec![],
+    )
+    .unwrap();
+    insta::assert_snapshot!(output);
+}
+
+#[test]
+fn missing_ffi_module() {
+    let error = bundle(
+        r#"
+@external(javascript, "./ffi.mjs", "log")
+fn log(x: String) -> Nil
+
+pub fn main() {
+  log("Hello")
+}
+"#,
+        vec![],
+    )
+    .unwrap_err();
+    assert!(matches!(error, crate::Error::FileIo { .. }));
+}
+
+#[test]
+fn default_exports_are_unsupported() {
+    let error = bundle(
+        r#"
+@external(javascript, "./ffi.mjs", "log")
+fn log(x: String) -> Nil
+
+pub fn main() {
+  log("Hello")
+}
+"#,
+        vec![(
+            "thepackage/my/ffi.mjs",
+            "export function log() {}\nexport default log;\n",
+        )],
+    )
+    .unwrap_err();
+    insta::assert_snapshot!(insta::internals::AutoName, error.pretty_string());
+}
@@ -267,3 +267,89 @@ pub fn main() {
 "#
     )
 }
+
+#[test]
+fn assignment_to_pattern() {
+    assert_js!(
+        r#"
+pub fn go(x) {
+  let #(a, b) = case x {
+    True -> #(1, 2)
+    False -> #(3, 4)
+  }
+  a + b
+}
+"#
+    );
+}
+
+#[test]
+fn let_assert_to_case() {
+    assert_js!(
+        r#"
+pub fn go(x) {
+  let assert Ok(a) = case x {
+    True -> Ok(1)
+    False -> Error(Nil)
+  }
+  a
+}
+"#
+    );
+}
+
+#[test]
+fn nested_case_assignment() {
+    assert_js!(
+        r#"
+pub fn go(x, y) {
+  let a = case x {
+    True -> {
+      let b = case y {
+        True -> 1
+        False -> 2
+      }
+      b + 1
+    }
+    False -> 0
+  }
+  a
+}
+"#
+    );
+}
+
+#[test]
+fn case_argument_is_not_hoisted() {
+    assert_js!(
+        r#"
+fn f(a, b) {
+  a + b
+}
+
+pub fn go(x) {
+  f(1, case x {
+    True -> 1
+    False -> 2
+  })
+}
+"#
+    );
+}
+
+#[test]
+fn assignment_shadowed_within_case() {
+    assert_js!(
+        r#"
+pub fn go(x) {
+  let x = case x {
+    _ -> {
+      let x = x + 1
+      x * 10
+    }
+  }
+  x
+}
+"#
+    );
+}
@@ -0,0 +1,111 @@
+macro_rules! assert_checked_js {
+    ($src:expr $(,)?) => {{
+        let output = $crate::javascript::tests::compile_js_with_checked_externals($src, vec![]);
+        insta::assert_snapshot!(insta::internals::AutoName, output, $src);
+    }};
+
+    (($dep_package:expr, $dep_name:expr, $dep_src:expr), $src:expr $(,)?) => {{
+        let output = $crate::javascript::tests::compile_js_with_checked_externals(
+            $src,
+            vec![($dep_package, $dep_name, $dep_src)],
+        );
+        insta::assert_snapshot!(insta::internals::AutoName, output, $src);
+    }};
+}
+
+#[test]
+fn public_external_returning_result() {
+    assert_checked_js!(
+        r#"
+/// Reads a number
+@external(javascript, "./ffi.mjs", "read")
+pub fn read(key: String) -> Result(Int, String)
+"#
+    );
+}
+
+#[test]
+fn private_external_returning_list_of_tuples() {
+    assert_checked_js!(
+        r#"
+@external(javascript, "./ffi.mjs", "pairs")
+fn pairs(_: Int, _: Int) -> List(#(String, Float, Bool))
+
+pub fn main() {
+  pairs(1, 2)
+}
+"#
+    );
+}
+
+#[test]
+fn external_returning_function_and_nil() {
+    assert_checked_js!(
+        r#"
+@external(javascript, "./ffi.mjs", "callback")
+pub fn callback() -> fn(Int) -> BitArray
+
+@external(javascript, "./ffi.mjs", "log")
+pub fn log(message: String) -> Nil
+"#
+    );
+}
+
+#[test]
+fn external_returning_type_variable_is_not_checked() {
+    assert_checked_js!(
+        r#"
+@external(javascript, "./ffi.mjs", "identity")
+pub fn identity(value: a) -> a
+
+@external(javascript, "./ffi.mjs", "wrap")
+pub fn wrap(value: a) -> Result(a, Nil)
+"#
+    );
+}
+
+#[test]
+fn external_returning_custom_type() {
+    assert_checked_js!(
+        r#"
+pub type Shape {
+  Circle(radius: Float)
+  Square(side: Float)
+}
+
+pub type Handle
+
+@external(javascript, "./ffi.mjs", "shape")
+pub fn shape() -> Shape
+
+@external(javascript, "./ffi.mjs", "handle")
+pub fn handle() -> Handle
+"#
+    );
+}
+
+#[test]
+fn external_returning_imported_custom_type_is_not_checked() {
+    assert_checked_js!(
+        ("thepackage", "other", "pub type Shape { Circle }"),
+        r#"
+import other
+
+@external(javascript, "./ffi.mjs", "shape")
+pub fn shape() -> other.Shape
+"#
+    );
+}
+
+#[test]
+fn external_with_escaped_name() {
+    assert_checked_js!(
+        r#"
+@external(javascript, "./ffi.mjs", "then")
+pub fn then(value: Int) -> Int
+
+@external(javascript, "./ffi.mjs", "then")
+pub fn class(value: Int) -> Int
+"#
+    );
+}
@@ -0,0 +1,139 @@
+use crate::codegen::TypeScriptDeclarations;
+
+macro_rules! assert_commonjs {
+    (($dep_package:expr, $dep_name:expr, $dep_src:expr), $src:expr $(,)?) => {{
+        let output = $crate::javascript::tests::compile_commonjs(
+            $src,
+            vec![($dep_package, $dep_name, $dep_src)],
+            TypeScriptDeclarations::None,
+        );
+        insta::assert_snapshot!(insta::internals::AutoName, output, $src);
+    }};
+
+    ($src:expr $(,)?) => {{
+        let output =
+            $crate::javascript::tests::compile_commonjs($src, vec![], TypeScriptDeclarations::None);
+        insta::assert_snapshot!(insta::internals::AutoName, output, $src);
+    }};
+}
+
+macro_rules! assert_commonjs_ts_def {
+    (($dep_package:expr, $dep_name:expr, $dep_src:expr), $src:expr $(,)?) => {{
+        let output = $crate::javascript::tests::compile_commonjs_ts(
+            $src,
+            vec![($dep_package, $dep_name, $dep_src)],
+        );
+        insta::assert_snapshot!(insta::internals::AutoName, output, $src);
+    }};
+}
+
+#[test]
+fn empty_module() {
+    assert_commonjs!("");
+}
+
+#[test]
+fn public_and_private_definitions() {
+    assert_commonjs!(
+        r#"
+pub type Shape {
+  Circle(radius: Float)
+  Square(Float)
+}
+
+pub opaque type Secret {
+  Secret(String)
+}
+
+pub const pi = 3.14
+
+const tau = 6.28
+
+pub fn area(shape) {
+  case shape {
+    Circle(radius) -> pi *. radius *. radius
+    Square(side) -> side *. side
+  }
+}
+
+fn double(x) {
+  x *. tau /. pi
+}
+
+pub fn then(x) {
+  double(x)
+}
+"#
+    );
+}
+
+#[test]
+fn imports() {
+    assert_commonjs!(
+        (
+            "other_package",
+            "other/module",
+            "pub fn go() { 1 } pub type T { T }"
+        ),
+        r#"
+import other/module.{go as run, T} as other
+
+pub fn main() {
+  #(run(), other.go(), T, [1, 2], Ok(1))
+}
+"#
+    );
+}
+
+#[test]
+fn externals() {
+    assert_commonjs!(
+        r#"
+@external(javascript, "./ffi.cjs", "now")
+pub fn now() -> Int
+
+@external(javascript, "./ffi.cjs", "random")
+fn random() -> Float
+
+pub fn go() {
+  random()
+}
+"#
+    );
+}
+
+#[test]
+fn type_reference() {
+    let output = crate::javascript::tests::compile_commonjs(
+        "pub fn go() { 1 }",
+        vec![],
+        TypeScriptDeclarations::Emit,
+    );
+    insta::assert_snapshot!(output);
+}
+
+#[test]
+fn typescript_declarations() {
+    assert_commonjs_ts_def!(
+        ("other_package", "other/module", "pub type T { T }"),
+        r#"
+import other/module
+
+pub fn go() -> #(module.T, Result(Int, Nil)) {
+  #(module.T, Ok(1))
+}
+"#
+    );
+}
+
+#[test]
+fn prelude() {
+    let prelude = crate::javascript::prelude(crate::config::JavaScriptModuleFormat::CommonJs);
+    assert!(prelude.starts_with("\"use strict\";\n"));
+    assert!(!prelude.contains("\nexport "));
+    assert!(prelude.contains("\nclass CustomType {"));
+    assert!(prelude.contains("\nfunction toList("));
+    assert!(prelude.contains("\n  CustomType,\n"));
+    assert!(prelude.contains("\n  toList,\n"));
+    assert!(prelude.ends_with("};\n"));
+}
@@ -668,6 +668,20 @@ fn opaque_types_typescript() {
     );
 }
 
+#[test]
+fn opaque_generic_type_typescript() {
+    assert_ts_def!(
+        r#"pub opaque type Stack(a) {
+  Stack(items: List(a))
+}
+
+pub fn new() -> Stack(a) {
+  Stack([])
+}
+"#
+    );
+}
+
 // https://github.com/gleam-lang/gleam/issues/1650
 #[test]
 fn types_must_be_rendered_before_functions() {
@@ -0,0 +1,118 @@
+use crate::{assert_js, assert_ts_def};
+
+#[test]
+fn function_documentation() {
+    assert_js!(
+        r#"
+/// Adds two numbers together.
+///
+/// It's the `+` operator as a function.
+pub fn add(a, b) {
+  a + b
+}
+"#
+    );
+}
+
+#[test]
+fn deprecated_function() {
+    assert_js!(
+        r#"
+/// Adds two numbers together.
+@deprecated("Use the `+` operator instead")
+pub fn add(a, b) {
+  a + b
+}
+"#
+    );
+}
+
+#[test]
+fn deprecated_function_without_documentation() {
+    assert_js!(
+        r#"
+@deprecated("Use the `+` operator instead")
+pub fn add(a, b) {
+  a + b
+}
+"#
+    );
+}
+
+#[test]
+fn constant_documentation() {
+    assert_js!(
+        r#"
+/// The answer to life, the universe, and everything.
+pub const answer = 42
+"#
+    );
+}
+
+#[test]
+fn constructor_documentation() {
+    assert_js!(
+        r#"
+/// A shape.
+pub type Shape {
+  /// A circle with a radius.
+  Circle(radius: Float)
+  /// A square with the length of a side.
+  Square(Float)
+}
+"#
+    );
+}
+
+#[test]
+fn deprecated_type_constructors() {
+    assert_js!(
+        r#"
+@deprecated("Use the new shape type")
+pub type Shape {
+  /// A circle with a radius.
+  Circle(radius: Float)
+  Point
+}
+"#
+    );
+}
+
+#[test]
+fn documentation_ending_the_comment() {
+    assert_js!(
+        r#"
+/// Don't end the comment with */ too early.
+pub fn go() {
+  Nil
+}
+"#
+    );
+}
+
+#[test]
+fn typescript_documentation() {
+    assert_ts_def!(
+        r#"
+/// The answer to life, the universe, and everything.
+pub const answer = 42
+
+/// Adds two numbers together.
+@deprecated("Use the `+` operator instead")
+pub fn add(a: Int, b: Int) -> Int {
+  a + b
+}
+
+/// A shape.
+pub type Shape {
+  /// A circle with a radius.
+  Circle(radius: Float)
+  Point
+}
+
+/// A number of metres.
+pub type Metres =
+  Float
+"#
+    );
+}
@@ -286,7 +286,7 @@ pub fn should_not_be_generated(x: Int) -> Int
 fn erlang_bit_patterns() {
     assert_js!(
         r#"
-pub fn should_not_be_generated(x) {
+pub fn rest_of_bits(x) {
   case x {
     <<_, rest:bits>> -> rest
     _ -> x
@@ -25,6 +25,17 @@ pub fn main() {
     );
 }
 
+#[test]
+fn unused_type_parameter_typescript() {
+    assert_ts_def!(
+        r#"pub type Box(a, tag) {
+  Box(value: a)
+  Empty
+}
+"#,
+    );
+}
+
 #[test]
 fn tuple_generics_typescript() {
     assert_ts_def!(
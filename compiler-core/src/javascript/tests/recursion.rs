@@ -59,3 +59,161 @@ pub fn map(map) {
 "#
     );
 }
+
+#[test]
+fn tco_block_in_tail_position() {
+    assert_js!(
+        r#"
+pub fn main(x) {
+  {
+    let y = x - 1
+    main(y)
+  }
+}
+"#
+    );
+}
+
+#[test]
+fn mutual_tco() {
+    assert_js!(
+        r#"
+pub fn is_even(x) {
+  even(x)
+}
+
+fn even(x) {
+  case x {
+    0 -> True
+    _ -> odd(x - 1)
+  }
+}
+
+fn odd(x) {
+  case x {
+    0 -> False
+    _ -> even(x - 1)
+  }
+}
+"#
+    );
+}
+
+#[test]
+fn mutual_tco_with_different_arities() {
+    assert_js!(
+        r#"
+fn count(list, total) {
+  case list {
+    [] -> total
+    [_, ..rest] -> skip(rest, total + 1, 1)
+  }
+}
+
+/// Skips some elements.
+fn skip(list, total, n) {
+  case n, list {
+    0, _ -> count(list, total)
+    _, [] -> total
+    _, [_, ..rest] -> skip(rest, total, n - 1)
+  }
+}
+
+pub fn main() {
+  count([1, 2, 3], 0)
+}
+"#
+    );
+}
+
+#[test]
+fn mutual_tco_through_pipes_and_blocks() {
+    assert_js!(
+        r#"
+fn ping(x) {
+  let y = x - 1
+  y |> pong
+}
+
+fn pong(x) {
+  case x > 0 {
+    True -> {
+      let z = x
+      ping(z)
+    }
+    False -> x
+  }
+}
+
+pub fn main() {
+  ping(10)
+}
+"#
+    );
+}
+
+#[test]
+fn not_mutual_tco_as_not_tail_call() {
+    assert_js!(
+        r#"
+fn ping(x) {
+  case x {
+    0 -> 0
+    _ -> 1 + pong(x - 1)
+  }
+}
+
+fn pong(x) {
+  ping(x)
+}
+
+pub fn main() {
+  ping(10)
+}
+"#
+    );
+}
+
+#[test]
+fn not_mutual_tco_as_public() {
+    assert_js!(
+        r#"
+pub fn ping(x) {
+  case x {
+    0 -> 0
+    _ -> pong(x - 1)
+  }
+}
+
+fn pong(x) {
+  ping(x)
+}
+"#
+    );
+}
+
+#[test]
+fn not_mutual_tco_in_anonymous_function() {
+    assert_js!(
+        r#"
+fn ping(x) {
+  case x {
+    0 -> 0
+    _ -> pong(x - 1)
+  }
+}
+
+fn pong(x) {
+  let f = fn() { ping(x) }
+  case x {
+    0 -> f()
+    _ -> ping(x - 1)
+  }
+}
+
+pub fn main() {
+  ping(10)
+}
+"#
+    );
+}
@@ -12,6 +12,7 @@
 //! <https://www.typescriptlang.org/docs/handbook/declaration-files/introduction.html>
 
 use crate::ast::AssignName;
+use crate::config::{JavaScriptIntegers, JavaScriptModuleFormat};
 use crate::type_::{is_prelude_module, PRELUDE_MODULE_NAME};
 use crate::{
     ast::{
@@ -21,18 +22,18 @@ use crate::{
     docvec,
     javascript::JavaScriptCodegenTarget,
     pretty::{break_, Document, Documentable},
-    type_::{Type, TypeVar},
+    type_::{Deprecation, Type, TypeVar},
 };
 use ecow::EcoString;
 use itertools::Itertools;
 use std::{collections::HashMap, ops::Deref, sync::Arc};
 
-use super::{import::Imports, join, line, lines, wrap_args, Output, INDENT};
+use super::{import::Imports, join, jsdoc, line, lines, wrap_args, Output, INDENT};
 
 /// When rendering a type variable to an TypeScript type spec we need all type
 /// variables with the same id to end up with the same name in the generated
 /// TypeScript. This function converts a usize into base 26 A-Z for this purpose.
-fn id_to_type_var(id: u64) -> Document<'static> {
+pub(super) fn id_to_type_var(id: u64) -> Document<'static> {
     if id < 26 {
         return std::iter::once(
             std::char::from_u32((id % 26 + 65) as u32).expect("id_to_type_var 0"),
@@ -51,26 +52,6 @@ fn id_to_type_var(id: u64) -> Document<'static> {
     name.into_iter().collect::<EcoString>().to_doc()
 }
 
-fn name_with_generics<'a>(
-    name: Document<'a>,
-    types: impl IntoIterator<Item = &'a Arc<Type>>,
-) -> Document<'a> {
-    let generic_usages = collect_generic_usages(HashMap::new(), types);
-    let generic_names: Vec<Document<'_>> = generic_usages
-        .keys()
-        .map(|id| id_to_type_var(*id))
-        .collect();
-
-    docvec![
-        name,
-        if generic_names.is_empty() {
-            super::nil()
-        } else {
-            wrap_generic_args(generic_names)
-        },
-    ]
-}
-
 /// A generic can either be rendered as an actual type variable such as `A` or `B`,
 /// or it can be rendered as `any` depending on how many usages it has. If it
 /// has only 1 usage it is an `any` type. If it has more than 1 usage it is a
@@ -80,7 +61,7 @@ fn name_with_generics<'a>(
 ///     fn(a) -> String       // `a` is `any`
 ///     `fn()` -> Result(a, b)  // `a` and `b` are `any`
 ///     fn(a) -> a            // `a` is a generic
-fn collect_generic_usages<'a>(
+pub(super) fn collect_generic_usages<'a>(
     mut ids: HashMap<u64, u64>,
     types: impl IntoIterator<Item = &'a Arc<Type>>,
 ) -> HashMap<u64, u64> {
@@ -129,6 +110,34 @@ fn tuple<'a>(elems: impl IntoIterator<Item = Document<'a>>) -> Document<'a> {
         .group()
 }
 
+/// The type parameters of a custom type, if it has any.
+fn generic_parameters(parameters: &[Document<'static>]) -> Document<'static> {
+    if parameters.is_empty() {
+        super::nil()
+    } else {
+        wrap_generic_args(parameters.iter().cloned())
+    }
+}
+
+/// A function type using the given type parameters as both its argument and
+/// its return type, which makes a type containing it invariant in them.
+fn invariant(parameters: &[Document<'static>]) -> Document<'static> {
+    let parameters = tuple(parameters.iter().cloned());
+    docvec!["(_: ", parameters.clone(), ") => ", parameters]
+}
+
+/// An object type holding a property keyed by a type's unique symbol, which
+/// no other type has. The property also uses the type's parameters so that
+/// different instances of the type are distinct.
+fn branded(brand: Document<'static>, parameters: &[Document<'static>]) -> Document<'static> {
+    let value = if parameters.is_empty() {
+        "true".to_doc()
+    } else {
+        invariant(parameters)
+    };
+    docvec!["{ readonly [", brand, "]: ", value, " }"]
+}
+
 fn wrap_generic_args<'a, I>(args: I) -> Document<'a>
 where
     I: IntoIterator<Item = Document<'a>>,
@@ -144,7 +153,7 @@ where
 /// Returns a name that can be used as a TypeScript type name. If there is a
 /// naming clash a '_' will be appended.
 ///
-fn ts_safe_type_name(mut name: String) -> String {
+pub(super) fn ts_safe_type_name(mut name: String) -> String {
     if matches!(
         name.as_str(),
         "any"
@@ -178,16 +187,24 @@ pub struct TypeScriptGenerator<'a> {
     aliased_module_names: HashMap<&'a str, &'a str>,
     tracker: UsageTracker,
     current_module_name_segments_count: usize,
+    integers: JavaScriptIntegers,
+    module_format: JavaScriptModuleFormat,
 }
 
 impl<'a> TypeScriptGenerator<'a> {
-    pub fn new(module: &'a TypedModule) -> Self {
+    pub fn new(
+        module: &'a TypedModule,
+        integers: JavaScriptIntegers,
+        module_format: JavaScriptModuleFormat,
+    ) -> Self {
         let current_module_name_segments_count = module.name.split('/').count();
         Self {
             module,
             aliased_module_names: HashMap::new(),
             tracker: UsageTracker::default(),
             current_module_name_segments_count,
+            integers,
+            module_format,
         }
     }
 
@@ -274,19 +291,20 @@ impl<'a> TypeScriptGenerator<'a> {
         // DUPE: current_module_name_segments_count
         // TODO: strip shared prefixed between current module and imported
         // module to avoid descending and climbing back out again
+        let extension = self.module_format.declaration_extension();
         if package == self.module.type_info.package || package.is_empty() {
             // Same package
             match self.current_module_name_segments_count {
-                1 => format!("./{module}.d.mts"),
+                1 => format!("./{module}.{extension}"),
                 _ => {
                     let prefix = "../".repeat(self.current_module_name_segments_count - 1);
-                    format!("{prefix}{module}.d.mts")
+                    format!("{prefix}{module}.{extension}")
                 }
             }
         } else {
             // Different package
             let prefix = "../".repeat(self.current_module_name_segments_count);
-            format!("{prefix}{package}/{module}.d.mts")
+            format!("{prefix}{package}/{module}.{extension}")
         }
     }
 
@@ -296,8 +314,15 @@ impl<'a> TypeScriptGenerator<'a> {
                 alias,
                 publicity,
                 type_,
+                documentation,
+                deprecation,
                 ..
-            }) if publicity.is_importable() => vec![self.type_alias(alias, type_)],
+            }) if publicity.is_importable() => {
+                let jsdoc = jsdoc(documentation.as_ref().map(|(_, doc)| doc), deprecation);
+                vec![self
+                    .type_alias(alias, type_)
+                    .map(|alias| jsdoc.append(alias))]
+            }
             Definition::TypeAlias(TypeAlias { .. }) => vec![],
 
             Definition::Import(Import { .. }) => vec![],
@@ -308,18 +333,32 @@ impl<'a> TypeScriptGenerator<'a> {
                 opaque,
                 name,
                 typed_parameters,
+                documentation,
+                deprecation,
                 ..
-            }) if publicity.is_importable() => {
-                self.custom_type_definition(name, typed_parameters, constructors, *opaque)
-            }
+            }) if publicity.is_importable() => self.custom_type_definition(
+                name,
+                typed_parameters,
+                constructors,
+                *opaque,
+                documentation.as_ref().map(|(_, doc)| doc),
+                deprecation,
+            ),
             Definition::CustomType(CustomType { .. }) => vec![],
 
             Definition::ModuleConstant(ModuleConstant {
                 publicity,
                 name,
                 value,
+                documentation,
+                deprecation,
                 ..
-            }) if publicity.is_importable() => vec![self.module_constant(name, value)],
+            }) if publicity.is_importable() => {
+                let jsdoc = jsdoc(documentation.as_ref().map(|(_, doc)| doc), deprecation);
+                vec![self
+                    .module_constant(name, value)
+                    .map(|constant| jsdoc.append(constant))]
+            }
             Definition::ModuleConstant(ModuleConstant { .. }) => vec![],
 
             Definition::Function(Function {
@@ -327,9 +366,14 @@ impl<'a> TypeScriptGenerator<'a> {
                 name: Some((_, name)),
                 publicity,
                 return_type,
+                documentation,
+                deprecation,
                 ..
             }) if publicity.is_importable() => {
-                vec![self.module_function(name, arguments, return_type)]
+                let jsdoc = jsdoc(documentation.as_ref().map(|(_, doc)| doc), deprecation);
+                vec![self
+                    .module_function(name, arguments, return_type)
+                    .map(|function| jsdoc.append(function))]
             }
             Definition::Function(Function { .. }) => vec![],
         }
@@ -353,33 +397,68 @@ impl<'a> TypeScriptGenerator<'a> {
     /// append a "$" symbol to the emitted TypeScript type to prevent those
     /// naming classes.
     ///
+    /// Opaque types and external types, which have no constructors, are
+    /// branded with a unique symbol so that only values of the type itself can
+    /// be used as one, rather than any value of the same shape.
+    ///
     fn custom_type_definition(
         &mut self,
         name: &'a str,
         typed_parameters: &'a [Arc<Type>],
         constructors: &'a [TypedRecordConstructor],
         opaque: bool,
+        documentation: Option<&'a EcoString>,
+        deprecation: &'a Deprecation,
     ) -> Vec<Output<'a>> {
+        let parameters = typed_parameters
+            .iter()
+            .map(|parameter| self.do_print_force_generic_param(parameter))
+            .collect_vec();
+
         let mut definitions: Vec<Output<'_>> = constructors
             .iter()
-            .map(|constructor| Ok(self.record_definition(constructor, opaque)))
+            .map(|constructor| {
+                let documentation = constructor.documentation.as_ref().map(|(_, doc)| doc);
+                let definition = self.record_definition(constructor, &parameters, opaque);
+                Ok(jsdoc(documentation, deprecation).append(definition))
+            })
             .collect();
 
+        let union = join(
+            constructors.iter().map(|constructor| {
+                docvec![
+                    super::maybe_escape_identifier_doc(&constructor.name),
+                    generic_parameters(&parameters)
+                ]
+            }),
+            break_("| ", " | "),
+        );
+        let brand = Document::String(format!("{name}$brand"));
         let definition = if constructors.is_empty() {
-            "any".to_doc()
+            branded(brand.clone(), &parameters)
+        } else if opaque {
+            docvec![
+                if constructors.len() > 1 {
+                    union.surround("(", ")")
+                } else {
+                    union
+                },
+                " & ",
+                branded(brand.clone(), &parameters)
+            ]
         } else {
-            let constructors = constructors.iter().map(|x| {
-                name_with_generics(
-                    super::maybe_escape_identifier_doc(&x.name),
-                    x.arguments.iter().map(|a| &a.type_),
-                )
-            });
-            join(constructors, break_("| ", " | "))
+            union
         };
 
+        if opaque || constructors.is_empty() {
+            definitions.push(Ok(docvec!["declare const ", brand, ": unique symbol;"]));
+        }
+
         definitions.push(Ok(docvec![
+            jsdoc(documentation, deprecation),
             "export type ",
-            name_with_generics(Document::String(format!("{name}$")), typed_parameters),
+            Document::String(format!("{name}$")),
+            generic_parameters(&parameters),
             " = ",
             definition,
             ";",
@@ -388,29 +467,48 @@ impl<'a> TypeScriptGenerator<'a> {
         definitions
     }
 
+    /// The class of a custom type's constructor. It takes all of the type's
+    /// parameters, even those its fields don't use, along with a private
+    /// member using them so that TypeScript treats the class as invariant in
+    /// them, as Gleam does, rather than ignoring the unused ones.
+    ///
     fn record_definition(
         &mut self,
         constructor: &'a TypedRecordConstructor,
+        parameters: &[Document<'static>],
         opaque: bool,
     ) -> Document<'a> {
         self.set_prelude_used();
         let head = docvec![
             // opaque type constructors are not exposed to JS
             if opaque {
-                super::nil()
+                "declare ".to_doc()
             } else {
                 "export ".to_doc()
             },
             "class ",
-            name_with_generics(
-                super::maybe_escape_identifier_doc(&constructor.name),
-                constructor.arguments.iter().map(|a| &a.type_)
-            ),
+            super::maybe_escape_identifier_doc(&constructor.name),
+            generic_parameters(parameters),
             " extends _.CustomType {"
         ];
 
+        let variance = if parameters.is_empty() {
+            super::nil()
+        } else {
+            docvec![
+                line(),
+                "private readonly $variance?: ",
+                invariant(parameters),
+                ";"
+            ]
+            .nest(INDENT)
+        };
+
         if constructor.arguments.is_empty() {
-            return head.append("}");
+            if parameters.is_empty() {
+                return head.append("}");
+            }
+            return docvec![head, variance, line(), "}"];
         };
 
         let class_body = docvec![
@@ -448,7 +546,7 @@ impl<'a> TypeScriptGenerator<'a> {
         ]
         .nest(INDENT);
 
-        docvec![head, class_body, line(), "}"]
+        docvec![head, class_body, variance, line(), "}"]
     }
 
     fn module_constant(&mut self, name: &'a str, value: &'a TypedConstant) -> Output<'a> {
@@ -555,7 +653,7 @@ impl<'a> TypeScriptGenerator<'a> {
         generic_usages: Option<&HashMap<u64, u64>>,
     ) -> Document<'static> {
         match type_ {
-            Type::Var { type_: typ } => self.print_var(&typ.borrow(), generic_usages, false),
+            Type::Var { type_: typ } => self.print_var(&typ.borrow(), generic_usages),
 
             Type::Named {
                 name, module, args, ..
@@ -571,29 +669,18 @@ impl<'a> TypeScriptGenerator<'a> {
         }
     }
 
+    /// Prints a type with each of its type variables as a TypeScript generic,
+    /// however deeply they are nested, as the type parameters of a custom
+    /// type's classes are.
+    ///
     fn do_print_force_generic_param(&mut self, type_: &Type) -> Document<'static> {
-        match type_ {
-            Type::Var { type_: typ } => self.print_var(&typ.borrow(), None, true),
-
-            Type::Named {
-                name, module, args, ..
-            } if is_prelude_module(module) => self.print_prelude_type(name, args, None),
-
-            Type::Named {
-                name, args, module, ..
-            } => self.print_type_app(name, args, module, None),
-
-            Type::Fn { args, retrn } => self.print_fn(args, retrn, None),
-
-            Type::Tuple { elems } => tuple(elems.iter().map(|e| self.do_print(e, None))),
-        }
+        self.do_print(type_, Some(&HashMap::new()))
     }
 
     fn print_var(
         &mut self,
         type_: &TypeVar,
         generic_usages: Option<&HashMap<u64, u64>>,
-        force_generic_id: bool,
     ) -> Document<'static> {
         match type_ {
             TypeVar::Unbound { id } | TypeVar::Generic { id } => match &generic_usages {
@@ -602,13 +689,7 @@ impl<'a> TypeScriptGenerator<'a> {
                     Some(&1) => "any".to_doc(),
                     _ => id_to_type_var(*id),
                 },
-                None => {
-                    if force_generic_id {
-                        id_to_type_var(*id)
-                    } else {
-                        "any".to_doc()
-                    }
-                }
+                None => "any".to_doc(),
             },
             TypeVar::Link { type_: typ } => self.do_print(typ, generic_usages),
         }
@@ -627,7 +708,11 @@ impl<'a> TypeScriptGenerator<'a> {
     ) -> Document<'static> {
         match name {
             "Nil" => "undefined".to_doc(),
-            "Int" | "Float" => "number".to_doc(),
+            "Int" => match self.integers {
+                JavaScriptIntegers::Number => "number".to_doc(),
+                JavaScriptIntegers::BigInt => "bigint".to_doc(),
+            },
+            "Float" => "number".to_doc(),
             "UtfCodepoint" => {
                 self.tracker.prelude_used = true;
                 "_.UtfCodepoint".to_doc()
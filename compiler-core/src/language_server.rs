@@ -4,6 +4,7 @@ mod completer;
 mod engine;
 mod feedback;
 mod files;
+mod formatting;
 mod messages;
 mod progress;
 mod router;
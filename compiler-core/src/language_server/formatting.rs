@@ -0,0 +1,95 @@
+use crate::{ast::SrcSpan, line_numbers::LineNumbers, warning::WarningEmitter, Error, Result};
+use camino::Utf8Path;
+use ecow::EcoString;
+use lsp_types::{Position, Range, TextEdit};
+use similar::{DiffTag, TextDiff};
+
+/// Formats a module, returning the edits to the lines that the formatter
+/// changed rather than replacing the whole document.
+///
+pub fn format_document(src: &EcoString, path: &Utf8Path) -> Result<Vec<TextEdit>> {
+    let formatted = format(src, path)?;
+    Ok(line_edits(src, &formatted, |_, _| true))
+}
+
+/// Formats the definitions that the given range is within, returning the
+/// edits to the lines that the formatter changed in them.
+///
+/// An empty range can be used to format the definition at a position, such as
+/// after a closing brace has been typed.
+///
+pub fn format_range(src: &EcoString, path: &Utf8Path, range: Range) -> Result<Vec<TextEdit>> {
+    let formatted = format(src, path)?;
+    let parsed = crate::parse::parse_module(path.to_owned(), src, &WarningEmitter::null())
+        .map_err(|error| Error::Parse {
+            path: path.to_path_buf(),
+            src: src.clone(),
+            error,
+        })?;
+
+    let line_numbers = LineNumbers::new(src);
+    let range_start = line_numbers.byte_index(range.start.line, range.start.character);
+    let range_end = line_numbers.byte_index(range.end.line, range.end.character);
+
+    // Each definition is taken to start just after the previous one ended, so
+    // that any comments, documentation and attributes before it are included.
+    let mut region_start = None;
+    let mut selected: Option<SrcSpan> = None;
+    for definition in &parsed.module.definitions {
+        let end = definition.definition.end_position();
+        let starts_before_range_end = !matches!(region_start, Some(start) if start >= range_end);
+        if starts_before_range_end && range_start <= end {
+            let region_start = region_start.unwrap_or(0);
+            let start = selected.map_or(region_start, |span| span.start);
+            selected = Some(SrcSpan::new(start, end));
+        }
+        region_start = Some(end);
+    }
+    let selected = selected.unwrap_or(SrcSpan::new(range_start, range_end));
+
+    // Line numbers are 1 indexed, while LSP lines are 0 indexed.
+    let first_line = line_numbers.line_number(selected.start) as usize - 1;
+    let last_line = line_numbers.line_number(selected.end) as usize - 1;
+
+    Ok(line_edits(src, &formatted, |start, end| {
+        if start == end {
+            first_line <= start && start <= last_line + 1
+        } else {
+            start <= last_line && first_line < end
+        }
+    }))
+}
+
+fn format(src: &EcoString, path: &Utf8Path) -> Result<String> {
+    let mut formatted = String::new();
+    crate::format::pretty(&mut formatted, src, path)?;
+    Ok(formatted)
+}
+
+/// The edits needed to turn `src` into `formatted`, one for each group of
+/// changed lines. Only the groups for which `include` returns true for their
+/// 0 indexed start and end lines in `src` are returned.
+fn line_edits(src: &str, formatted: &str, include: impl Fn(usize, usize) -> bool) -> Vec<TextEdit> {
+    let diff = TextDiff::from_lines(src, formatted);
+    let new_lines = diff.new_slices();
+
+    diff.ops()
+        .iter()
+        .filter(|op| op.tag() != DiffTag::Equal)
+        .filter(|op| include(op.old_range().start, op.old_range().end))
+        .map(|op| {
+            let old = op.old_range();
+            let new_text = new_lines
+                .get(op.new_range())
+                .expect("Diff range must be within new lines")
+                .concat();
+            TextEdit {
+                range: Range::new(
+                    Position::new(old.start as u32, 0),
+                    Position::new(old.end as u32, 0),
+                ),
+                new_text,
+            }
+        })
+        .collect()
+}
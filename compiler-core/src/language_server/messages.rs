@@ -8,7 +8,7 @@ use lsp_types::{
     notification::{DidChangeTextDocument, DidCloseTextDocument, DidSaveTextDocument},
     request::{
         CodeActionRequest, Completion, DocumentSymbolRequest, Formatting, HoverRequest,
-        SignatureHelpRequest,
+        OnTypeFormatting, RangeFormatting, SignatureHelpRequest,
     },
 };
 use std::time::Duration;
@@ -22,6 +22,8 @@ pub enum Message {
 #[derive(Debug)]
 pub enum Request {
     Format(lsp::DocumentFormattingParams),
+    FormatRange(lsp::DocumentRangeFormattingParams),
+    FormatOnType(lsp::DocumentOnTypeFormattingParams),
     Hover(lsp::HoverParams),
     GoToDefinition(lsp::GotoDefinitionParams),
     Completion(lsp::CompletionParams),
@@ -38,6 +40,14 @@ impl Request {
                 let params = cast_request::<Formatting>(request);
                 Some(Message::Request(id, Request::Format(params)))
             }
+            "textDocument/rangeFormatting" => {
+                let params = cast_request::<RangeFormatting>(request);
+                Some(Message::Request(id, Request::FormatRange(params)))
+            }
+            "textDocument/onTypeFormatting" => {
+                let params = cast_request::<OnTypeFormatting>(request);
+                Some(Message::Request(id, Request::FormatOnType(params)))
+            }
             "textDocument/hover" => {
                 let params = cast_request::<HoverRequest>(request);
                 Some(Message::Request(id, Request::Hover(params)))
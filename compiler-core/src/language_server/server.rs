@@ -9,6 +9,7 @@ use crate::{
         engine::{self, LanguageServerEngine},
         feedback::{Feedback, FeedbackBookKeeper},
         files::FileSystemProxy,
+        formatting,
         router::Router,
         src_span_to_lsp_range, DownloadDependencies, MakeLocker,
     },
@@ -19,8 +20,7 @@ use camino::{Utf8Path, Utf8PathBuf};
 use debug_ignore::DebugIgnore;
 use itertools::Itertools;
 use lsp_types::{
-    self as lsp, HoverProviderCapability, InitializeParams, Position, PublishDiagnosticsParams,
-    Range, TextEdit, Url,
+    self as lsp, HoverProviderCapability, InitializeParams, PublishDiagnosticsParams, Range, Url,
 };
 use serde_json::Value as Json;
 use std::collections::{HashMap, HashSet};
@@ -98,6 +98,8 @@ where
     fn handle_request(&mut self, id: lsp_server::RequestId, request: Request) {
         let (payload, feedback) = match request {
             Request::Format(param) => self.format(param),
+            Request::FormatRange(param) => self.format_range(param),
+            Request::FormatOnType(param) => self.format_on_type(param),
             Request::Hover(param) => self.hover(param),
             Request::GoToDefinition(param) => self.goto_definition(param),
             Request::Completion(param) => self.completion(param),
@@ -273,26 +275,53 @@ where
 
     fn format(&mut self, params: lsp::DocumentFormattingParams) -> (Json, Feedback) {
         let path = super::path(&params.text_document.uri);
-        let mut new_text = String::new();
 
         let src = match self.io.read(&path) {
             Ok(src) => src.into(),
             Err(error) => return self.path_error_response(path, error),
         };
 
-        if let Err(error) = crate::format::pretty(&mut new_text, &src, &path) {
-            return self.path_error_response(path, error);
+        match formatting::format_document(&src, &path) {
+            Ok(edits) => (
+                serde_json::to_value(edits).expect("to JSON value"),
+                Feedback::default(),
+            ),
+            Err(error) => self.path_error_response(path, error),
         }
+    }
 
-        let line_count = src.lines().count() as u32;
+    fn format_range(&mut self, params: lsp::DocumentRangeFormattingParams) -> (Json, Feedback) {
+        let path = super::path(&params.text_document.uri);
 
-        let edit = TextEdit {
-            range: Range::new(Position::new(0, 0), Position::new(line_count, 0)),
-            new_text,
+        let src = match self.io.read(&path) {
+            Ok(src) => src.into(),
+            Err(error) => return self.path_error_response(path, error),
         };
-        let json = serde_json::to_value(vec![edit]).expect("to JSON value");
 
-        (json, Feedback::default())
+        match formatting::format_range(&src, &path, params.range) {
+            Ok(edits) => (
+                serde_json::to_value(edits).expect("to JSON value"),
+                Feedback::default(),
+            ),
+            Err(error) => self.path_error_response(path, error),
+        }
+    }
+
+    fn format_on_type(&mut self, params: lsp::DocumentOnTypeFormattingParams) -> (Json, Feedback) {
+        let position = params.text_document_position;
+        let path = super::path(&position.text_document.uri);
+        let range = Range::new(position.position, position.position);
+
+        // The rest of the module is likely to be mid-edit, so any errors are
+        // not reported while typing and nothing is formatted instead.
+        let edits = match self.io.read(&path) {
+            Ok(src) => formatting::format_range(&src.into(), &path, range).unwrap_or_default(),
+            Err(_) => vec![],
+        };
+        (
+            serde_json::to_value(edits).expect("to JSON value"),
+            Feedback::default(),
+        )
     }
 
     fn hover(&mut self, params: lsp::HoverParams) -> (Json, Feedback) {
@@ -414,8 +443,11 @@ fn initialisation_handshake(connection: &lsp_server::Connection) -> InitializePa
         code_action_provider: Some(lsp::CodeActionProviderCapability::Simple(true)),
         code_lens_provider: None,
         document_formatting_provider: Some(lsp::OneOf::Left(true)),
-        document_range_formatting_provider: None,
-        document_on_type_formatting_provider: None,
+        document_range_formatting_provider: Some(lsp::OneOf::Left(true)),
+        document_on_type_formatting_provider: Some(lsp::DocumentOnTypeFormattingOptions {
+            first_trigger_character: "}".into(),
+            more_trigger_character: None,
+        }),
         rename_provider: None,
         document_link_provider: None,
         color_provider: None,
@@ -3,6 +3,7 @@ mod compilation;
 mod completion;
 mod definition;
 mod document_symbols;
+mod formatting;
 mod hover;
 mod signature_help;
 
@@ -106,6 +107,7 @@ impl LanguageServerTestIO {
             name: name.into(),
             source: ManifestPackageSource::Hex {
                 outer_checksum: Base16Checksum(vec![]),
+                repository: None,
             },
             build_tools: vec!["gleam".into()],
             ..Default::default()
@@ -296,6 +298,7 @@ fn add_package_from_manifest<B>(
         match package.source {
             ManifestPackageSource::Hex { .. } => Requirement::Hex {
                 version: Range::new("1.0.0".into()),
+                repository: None,
             },
             ManifestPackageSource::Local { ref path } => Requirement::Path { path: path.into() },
             ManifestPackageSource::Git { ref repo, .. } => Requirement::Git { git: repo.clone() },
@@ -315,6 +318,7 @@ fn add_dev_package_from_manifest<B>(
         match package.source {
             ManifestPackageSource::Hex { .. } => Requirement::Hex {
                 version: Range::new("1.0.0".into()),
+                repository: None,
             },
             ManifestPackageSource::Local { ref path } => Requirement::Path { path: path.into() },
             ManifestPackageSource::Git { ref repo, .. } => Requirement::Git { git: repo.clone() },
@@ -470,6 +474,7 @@ impl<'a> TestProject<'a> {
                 name: "indirect_hex".into(),
                 source: ManifestPackageSource::Hex {
                     outer_checksum: Base16Checksum(vec![]),
+                    repository: None,
                 },
                 build_tools: vec!["gleam".into()],
                 ..Default::default()
@@ -485,6 +490,7 @@ impl<'a> TestProject<'a> {
                 name: "dev_hex".into(),
                 source: ManifestPackageSource::Hex {
                     outer_checksum: Base16Checksum(vec![]),
+                    repository: None,
                 },
                 build_tools: vec!["gleam".into()],
                 ..Default::default()
@@ -0,0 +1,193 @@
+use lsp_types::{Range, TextEdit};
+
+use super::*;
+use crate::language_server::formatting::{format_document, format_range};
+
+fn apply_edits(src: &str, edits: Vec<TextEdit>) -> String {
+    let mut lines: Vec<String> = src.split_inclusive('\n').map(String::from).collect();
+    // Edits are applied last to first so earlier line numbers stay correct.
+    for edit in edits.into_iter().rev() {
+        let start = edit.range.start.line as usize;
+        let end = edit.range.end.line as usize;
+        let _ = lines.splice(start..end, std::iter::once(edit.new_text));
+    }
+    lines.concat()
+}
+
+fn range(start_line: u32, end_line: u32) -> Range {
+    Range::new(Position::new(start_line, 0), Position::new(end_line, 0))
+}
+
+fn format_lines(src: &str, range: Range) -> String {
+    let edits = format_range(&src.into(), Utf8Path::new("src/app.gleam"), range).unwrap();
+    apply_edits(src, edits)
+}
+
+const UNFORMATTED: &str = "pub fn one() {
+    1
+}
+
+pub fn two() {
+  let x =    2
+  x
+}
+
+pub fn three() {
+      3
+}
+";
+
+#[test]
+fn format_document_only_edits_changed_lines() {
+    let edits = format_document(&UNFORMATTED.into(), Utf8Path::new("src/app.gleam")).unwrap();
+
+    assert_eq!(
+        edits,
+        vec![
+            TextEdit {
+                range: range(1, 2),
+                new_text: "  1\n".into(),
+            },
+            TextEdit {
+                range: range(5, 6),
+                new_text: "  let x = 2\n".into(),
+            },
+            TextEdit {
+                range: range(10, 11),
+                new_text: "  3\n".into(),
+            },
+        ]
+    );
+}
+
+#[test]
+fn format_range_within_a_definition() {
+    assert_eq!(
+        format_lines(UNFORMATTED, range(5, 5)),
+        "pub fn one() {
+    1
+}
+
+pub fn two() {
+  let x = 2
+  x
+}
+
+pub fn three() {
+      3
+}
+"
+    );
+}
+
+#[test]
+fn format_range_spanning_definitions() {
+    assert_eq!(
+        format_lines(UNFORMATTED, range(0, 5)),
+        "pub fn one() {
+  1
+}
+
+pub fn two() {
+  let x = 2
+  x
+}
+
+pub fn three() {
+      3
+}
+"
+    );
+}
+
+#[test]
+fn format_range_includes_preceding_documentation_and_attributes() {
+    let src = "pub fn one() {
+  1
+}
+
+/// Some documentation
+@deprecated(  \"Use one\"  )
+pub fn two() {
+  let x =    2
+  x
+}
+";
+
+    assert_eq!(
+        format_lines(src, range(7, 7)),
+        "pub fn one() {
+  1
+}
+
+/// Some documentation
+@deprecated(\"Use one\")
+pub fn two() {
+  let x = 2
+  x
+}
+"
+    );
+}
+
+#[test]
+fn format_range_within_a_multi_line_constant_value() {
+    let src = "pub const numbers = [
+  1,
+      2,
+  3,
+]
+
+pub fn two() {
+      2
+}
+";
+
+    assert_eq!(
+        format_lines(src, range(2, 2)),
+        "pub const numbers = [1, 2, 3]
+
+pub fn two() {
+      2
+}
+"
+    );
+}
+
+#[test]
+fn format_on_type_after_closing_brace() {
+    // The position just after the closing brace of `one`, which shouldn't
+    // format the definition that follows.
+    let position = Position::new(2, 1);
+    let src = "pub fn one() {
+    1
+}
+pub fn two() {
+      2
+}
+";
+    let edits = format_range(
+        &src.into(),
+        Utf8Path::new("src/app.gleam"),
+        Range::new(position, position),
+    )
+    .unwrap();
+
+    assert_eq!(
+        apply_edits(src, edits),
+        "pub fn one() {
+  1
+}
+
+pub fn two() {
+      2
+}
+"
+    );
+}
+
+#[test]
+fn format_range_invalid_syntax() {
+    let src = "pub fn one() {";
+    assert!(format_range(&src.into(), Utf8Path::new("src/app.gleam"), range(0, 0)).is_err());
+}
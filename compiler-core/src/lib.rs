@@ -81,7 +81,9 @@ pub mod package_interface;
 pub mod parse;
 pub mod paths;
 pub mod pretty;
+pub mod repl;
 pub mod requirement;
+pub mod sbom;
 pub mod strings;
 pub mod type_;
 pub mod uid;
@@ -79,10 +79,18 @@ impl Manifest {
             }
 
             match source {
-                ManifestPackageSource::Hex { outer_checksum } => {
+                ManifestPackageSource::Hex {
+                    outer_checksum,
+                    repository,
+                } => {
                     buffer.push_str(r#", source = "hex", outer_checksum = ""#);
                     buffer.push_str(&outer_checksum.to_string());
                     buffer.push('"');
+                    if let Some(repository) = repository {
+                        buffer.push_str(r#", repository = ""#);
+                        buffer.push_str(repository);
+                        buffer.push('"');
+                    }
                 }
                 ManifestPackageSource::Git { repo, commit } => {
                     buffer.push_str(r#", source = "git", repo = ""#);
@@ -186,7 +194,17 @@ impl ManifestPackage {
 #[serde(tag = "source")]
 pub enum ManifestPackageSource {
     #[serde(rename = "hex")]
-    Hex { outer_checksum: Base16Checksum },
+    Hex {
+        outer_checksum: Base16Checksum,
+        /// The name of the Hex repository the package comes from, if it is
+        /// not hex.pm.
+        #[serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "crate::config::repository_name::deserialize_option"
+        )]
+        repository: Option<EcoString>,
+    },
     #[serde(rename = "git")]
     Git { repo: EcoString, commit: EcoString },
     #[serde(rename = "local")]
@@ -250,6 +268,7 @@ mod tests {
                     requirements: vec![],
                     source: ManifestPackageSource::Hex {
                         outer_checksum: Base16Checksum(vec![1, 22]),
+                        repository: None,
                     },
                 },
                 ManifestPackage {
@@ -260,6 +279,7 @@ mod tests {
                     requirements: vec!["zzz".into(), "gleam_stdlib".into()],
                     source: ManifestPackageSource::Hex {
                         outer_checksum: Base16Checksum(vec![3, 22]),
+                        repository: None,
                     },
                 },
                 ManifestPackage {
@@ -270,6 +290,7 @@ mod tests {
                     requirements: vec![],
                     source: ManifestPackageSource::Hex {
                         outer_checksum: Base16Checksum(vec![3, 22]),
+                        repository: None,
                     },
                 },
                 ManifestPackage {
@@ -301,6 +322,7 @@ mod tests {
                     requirements: vec!["gleam_stdlib".into()],
                     source: ManifestPackageSource::Hex {
                         outer_checksum: Base16Checksum(vec![3, 46]),
+                        repository: None,
                     },
                 },
             ],
@@ -332,6 +354,62 @@ zzz = { version = "> 0.0.0" }
         );
     }
 
+    #[test]
+    fn manifest_toml_format_with_repository() {
+        let manifest = Manifest {
+            requirements: [(
+                "internal_lib".into(),
+                Requirement::Hex {
+                    version: hexpm::version::Range::new("~> 1.0".into()),
+                    repository: Some("internal".into()),
+                },
+            )]
+            .into(),
+            packages: vec![ManifestPackage {
+                name: "internal_lib".into(),
+                version: Version::new(1, 0, 2),
+                build_tools: ["gleam".into()].into(),
+                otp_app: None,
+                requirements: vec![],
+                source: ManifestPackageSource::Hex {
+                    outer_checksum: Base16Checksum(vec![1, 22]),
+                    repository: Some("internal".into()),
+                },
+            }],
+        };
+
+        let buffer = manifest.to_toml(HOME.into());
+        assert_eq!(
+            buffer,
+            r#"# This file was generated by Gleam
+# You typically do not need to edit this file
+
+packages = [
+  { name = "internal_lib", version = "1.0.2", build_tools = ["gleam"], requirements = [], source = "hex", outer_checksum = "0116", repository = "internal" },
+]
+
+[requirements]
+internal_lib = { version = "~> 1.0", repository = "internal" }
+"#
+        );
+        assert_eq!(toml::from_str::<Manifest>(&buffer).unwrap(), manifest);
+    }
+
+    #[test]
+    fn manifest_with_repository_path() {
+        let toml = r#"
+packages = [
+  { name = "internal_lib", version = "1.0.2", build_tools = ["gleam"], requirements = [], source = "hex", outer_checksum = "0116", repository = "../other" },
+]
+
+[requirements]
+"#;
+        assert!(toml::from_str::<Manifest>(toml)
+            .unwrap_err()
+            .to_string()
+            .contains("Hex repository names may only contain lowercase letters"));
+    }
+
     #[cfg(windows)]
     #[test]
     fn manifest_toml_format_with_unc() {
@@ -360,6 +438,7 @@ zzz = { version = "> 0.0.0" }
                     requirements: vec![],
                     source: ManifestPackageSource::Hex {
                         outer_checksum: Base16Checksum(vec![1, 22]),
+                        repository: None,
                     },
                 },
                 ManifestPackage {
@@ -370,6 +449,7 @@ zzz = { version = "> 0.0.0" }
                     requirements: vec!["zzz".into(), "gleam_stdlib".into()],
                     source: ManifestPackageSource::Hex {
                         outer_checksum: Base16Checksum(vec![3, 22]),
+                        repository: None,
                     },
                 },
                 ManifestPackage {
@@ -380,6 +460,7 @@ zzz = { version = "> 0.0.0" }
                     requirements: vec![],
                     source: ManifestPackageSource::Hex {
                         outer_checksum: Base16Checksum(vec![3, 22]),
+                        repository: None,
                     },
                 },
                 ManifestPackage {
@@ -411,6 +492,7 @@ zzz = { version = "> 0.0.0" }
                     requirements: vec!["gleam_stdlib".into()],
                     source: ManifestPackageSource::Hex {
                         outer_checksum: Base16Checksum(vec![3, 46]),
+                        repository: None,
                     },
                 },
             ],
@@ -452,6 +534,7 @@ zzz = { version = "> 0.0.0" }
                 version: Version::new(1, 0, 0),
                 source: ManifestPackageSource::Hex {
                     outer_checksum: Base16Checksum(vec![]),
+                    repository: None,
                 },
             }
         }
@@ -0,0 +1,464 @@
+//! Comparing the public interfaces of two versions of a package, so that the
+//! changes between them can be checked against the change in version number.
+
+use std::{collections::HashMap, fmt::Write};
+
+use ecow::EcoString;
+use hexpm::version::Version;
+use itertools::Itertools;
+
+use super::{
+    ConstantInterface, FunctionInterface, ImplementationsInterface, ModuleInterface,
+    PackageInterface, ParameterInterface, TypeAliasInterface, TypeConstructorInterface,
+    TypeDefinitionInterface, TypeInterface,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// How a change to the public interface of a package affects the code that
+/// uses it, following semantic versioning.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ChangeKind {
+    /// Nothing that code using the package can observe, such as a change to
+    /// the documentation.
+    Patch,
+    /// Something new that existing code using the package is unaffected by.
+    Additive,
+    /// A change that can make existing code using the package fail to
+    /// compile, or behave differently.
+    Breaking,
+}
+
+impl ChangeKind {
+    fn heading(&self) -> &'static str {
+        match self {
+            ChangeKind::Patch => "Patch changes",
+            ChangeKind::Additive => "Additive changes",
+            ChangeKind::Breaking => "Breaking changes",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    pub kind: ChangeKind,
+    pub description: EcoString,
+}
+
+/// The changes between the public interfaces of two versions of a package.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ApiDiff {
+    changes: Vec<Change>,
+}
+
+impl ApiDiff {
+    pub fn new(old: &PackageInterface, new: &PackageInterface) -> Self {
+        let mut diff = Self::default();
+        for (name, old_module) in old.modules.iter().sorted_by_key(|(name, _)| *name) {
+            match new.modules.get(name) {
+                Some(new_module) => diff.module(name, old_module, new_module),
+                None => diff.breaking(format!("The module `{name}` was removed")),
+            }
+        }
+        for name in new.modules.keys().sorted() {
+            if !old.modules.contains_key(name) {
+                diff.additive(format!("The module `{name}` was added"));
+            }
+        }
+        diff
+    }
+
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    /// The most severe kind of change, or `None` if nothing changed.
+    pub fn kind(&self) -> Option<ChangeKind> {
+        self.changes.iter().map(|change| change.kind).max()
+    }
+
+    /// The lowest version that these changes can be published as, given the
+    /// version they were made to. Before version 1.0.0 breaking changes only
+    /// need a new minor version and additive ones a new patch version.
+    pub fn minimum_version(&self, previous: &Version) -> Version {
+        let Version {
+            major,
+            minor,
+            patch,
+            ..
+        } = *previous;
+        match (self.kind(), major) {
+            (Some(ChangeKind::Breaking), 0) => Version::new(0, minor + 1, 0),
+            (Some(ChangeKind::Breaking), _) => Version::new(major + 1, 0, 0),
+            (Some(ChangeKind::Additive), 0) => Version::new(0, minor, patch + 1),
+            (Some(ChangeKind::Additive), _) => Version::new(major, minor + 1, 0),
+            (Some(ChangeKind::Patch) | None, _) => Version::new(major, minor, patch + 1),
+        }
+    }
+
+    /// Whether the changes are breaking but the version does not say so. Any
+    /// pre-release of the required version is accepted.
+    pub fn is_unmarked_breaking_change(&self, previous: &Version, version: &Version) -> bool {
+        let minimum = self.minimum_version(previous);
+        self.kind() == Some(ChangeKind::Breaking)
+            && (version.major, version.minor, version.patch)
+                < (minimum.major, minimum.minor, minimum.patch)
+    }
+
+    /// The changes grouped by kind, most severe first.
+    pub fn to_pretty_string(&self) -> String {
+        if self.changes.is_empty() {
+            return "No changes to the public API\n".into();
+        }
+        let mut output = String::new();
+        for (kind, changes) in &self
+            .changes
+            .iter()
+            .sorted_by_key(|change| std::cmp::Reverse(change.kind))
+            .chunk_by(|change| change.kind)
+        {
+            if !output.is_empty() {
+                output.push('\n');
+            }
+            let _ = writeln!(output, "{}:", kind.heading());
+            for change in changes {
+                let _ = writeln!(output, "  - {}", change.description);
+            }
+        }
+        output
+    }
+
+    fn push(&mut self, kind: ChangeKind, description: String) {
+        self.changes.push(Change {
+            kind,
+            description: description.into(),
+        });
+    }
+
+    fn breaking(&mut self, description: String) {
+        self.push(ChangeKind::Breaking, description)
+    }
+
+    fn additive(&mut self, description: String) {
+        self.push(ChangeKind::Additive, description)
+    }
+
+    fn patch(&mut self, description: String) {
+        self.push(ChangeKind::Patch, description)
+    }
+
+    fn module(&mut self, module: &str, old: &ModuleInterface, new: &ModuleInterface) {
+        if old.documentation != new.documentation {
+            self.patch(format!("The documentation of module `{module}` changed"));
+        }
+        self.definitions("type", module, &old.types, &new.types, Self::type_);
+        self.definitions(
+            "type alias",
+            module,
+            &old.type_aliases,
+            &new.type_aliases,
+            Self::type_alias,
+        );
+        self.definitions(
+            "constant",
+            module,
+            &old.constants,
+            &new.constants,
+            Self::constant,
+        );
+        self.definitions(
+            "function",
+            module,
+            &old.functions,
+            &new.functions,
+            Self::function,
+        );
+    }
+
+    /// Record the definitions that were removed or added, comparing the ones
+    /// found in both versions.
+    fn definitions<T>(
+        &mut self,
+        what: &str,
+        module: &str,
+        old: &HashMap<EcoString, T>,
+        new: &HashMap<EcoString, T>,
+        compare: impl Fn(&mut Self, &str, &T, &T),
+    ) {
+        for (name, old_definition) in old.iter().sorted_by_key(|(name, _)| *name) {
+            let qualified = format!("{module}.{name}");
+            match new.get(name) {
+                Some(new_definition) => compare(self, &qualified, old_definition, new_definition),
+                None => self.breaking(format!("The {what} `{qualified}` was removed")),
+            }
+        }
+        for name in new.keys().sorted() {
+            if !old.contains_key(name) {
+                self.additive(format!("The {what} `{module}.{name}` was added"));
+            }
+        }
+    }
+
+    fn documentation(
+        &mut self,
+        what: &str,
+        name: &str,
+        old: &Option<EcoString>,
+        new: &Option<EcoString>,
+    ) {
+        if old != new {
+            self.patch(format!("The documentation of {what} `{name}` changed"));
+        }
+    }
+
+    fn deprecation<T: PartialEq>(
+        &mut self,
+        what: &str,
+        name: &str,
+        old: &Option<T>,
+        new: &Option<T>,
+    ) {
+        match (old, new) {
+            (None, Some(_)) => self.additive(format!("The {what} `{name}` was deprecated")),
+            (Some(_), None) => self.patch(format!("The {what} `{name}` is no longer deprecated")),
+            (Some(old), Some(new)) if old != new => self.patch(format!(
+                "The deprecation message of {what} `{name}` changed"
+            )),
+            (None, None) | (Some(_), Some(_)) => (),
+        }
+    }
+
+    fn implementations(
+        &mut self,
+        what: &str,
+        name: &str,
+        old: &ImplementationsInterface,
+        new: &ImplementationsInterface,
+    ) {
+        let targets = [
+            ("Erlang", old.can_run_on_erlang, new.can_run_on_erlang),
+            (
+                "JavaScript",
+                old.can_run_on_javascript,
+                new.can_run_on_javascript,
+            ),
+        ];
+        for (target, old, new) in targets {
+            match (old, new) {
+                (true, false) => {
+                    self.breaking(format!("The {what} `{name}` can no longer run on {target}"))
+                }
+                (false, true) => {
+                    self.additive(format!("The {what} `{name}` can now run on {target}"))
+                }
+                (true, true) | (false, false) => (),
+            }
+        }
+    }
+
+    fn type_(&mut self, name: &str, old: &TypeDefinitionInterface, new: &TypeDefinitionInterface) {
+        self.documentation("type", name, &old.documentation, &new.documentation);
+        self.deprecation("type", name, &old.deprecation, &new.deprecation);
+        if old.parameters != new.parameters {
+            self.breaking(format!(
+                "The number of type parameters of type `{name}` changed from {} to {}",
+                old.parameters, new.parameters
+            ));
+        }
+
+        // An opaque type has no public constructors.
+        match (old.constructors.is_empty(), new.constructors.is_empty()) {
+            (true, true) => return,
+            (true, false) => {
+                self.additive(format!(
+                    "The constructors of type `{name}` were made public"
+                ));
+                return;
+            }
+            (false, true) => {
+                self.breaking(format!("The type `{name}` was made opaque"));
+                return;
+            }
+            (false, false) => (),
+        }
+
+        let module = name.rsplit_once('.').map_or("", |(module, _)| module);
+        for old_constructor in &old.constructors {
+            let qualified = format!("{module}.{}", old_constructor.name);
+            let new_constructor = new
+                .constructors
+                .iter()
+                .find(|constructor| constructor.name == old_constructor.name);
+            match new_constructor {
+                Some(new_constructor) => {
+                    self.constructor(&qualified, old_constructor, new_constructor)
+                }
+                None => self.breaking(format!("The constructor `{qualified}` was removed")),
+            }
+        }
+        for new_constructor in &new.constructors {
+            if !old
+                .constructors
+                .iter()
+                .any(|constructor| constructor.name == new_constructor.name)
+            {
+                // Case expressions that match on every constructor of the type
+                // will no longer be exhaustive.
+                self.breaking(format!(
+                    "The constructor `{module}.{}` was added to the type `{name}`",
+                    new_constructor.name
+                ));
+            }
+        }
+    }
+
+    fn constructor(
+        &mut self,
+        name: &str,
+        old: &TypeConstructorInterface,
+        new: &TypeConstructorInterface,
+    ) {
+        self.documentation("constructor", name, &old.documentation, &new.documentation);
+        let constructor = |parameters: &[ParameterInterface]| {
+            let (_, constructor) = name.rsplit_once('.').unwrap_or(("", name));
+            if parameters.is_empty() {
+                constructor.to_string()
+            } else {
+                format!("{constructor}({})", pretty_parameters(parameters))
+            }
+        };
+        match parameters_change(&old.parameters, &new.parameters) {
+            Some(ChangeKind::Breaking) => self.breaking(format!(
+                "The constructor `{name}` changed from `{}` to `{}`",
+                constructor(&old.parameters),
+                constructor(&new.parameters),
+            )),
+            Some(_) => self.additive(format!(
+                "Labels were added to the fields of constructor `{name}`: `{}`",
+                constructor(&new.parameters),
+            )),
+            None => (),
+        }
+    }
+
+    fn type_alias(&mut self, name: &str, old: &TypeAliasInterface, new: &TypeAliasInterface) {
+        self.documentation("type alias", name, &old.documentation, &new.documentation);
+        self.deprecation("type alias", name, &old.deprecation, &new.deprecation);
+        if old.parameters != new.parameters || old.alias != new.alias {
+            self.breaking(format!(
+                "The type alias `{name}` changed from `{}` to `{}`",
+                pretty_type(&old.alias),
+                pretty_type(&new.alias),
+            ));
+        }
+    }
+
+    fn constant(&mut self, name: &str, old: &ConstantInterface, new: &ConstantInterface) {
+        self.documentation("constant", name, &old.documentation, &new.documentation);
+        self.deprecation("constant", name, &old.deprecation, &new.deprecation);
+        self.implementations("constant", name, &old.implementations, &new.implementations);
+        if old.type_ != new.type_ {
+            self.breaking(format!(
+                "The type of constant `{name}` changed from `{}` to `{}`",
+                pretty_type(&old.type_),
+                pretty_type(&new.type_),
+            ));
+        }
+    }
+
+    fn function(&mut self, name: &str, old: &FunctionInterface, new: &FunctionInterface) {
+        self.documentation("function", name, &old.documentation, &new.documentation);
+        self.deprecation("function", name, &old.deprecation, &new.deprecation);
+        self.implementations("function", name, &old.implementations, &new.implementations);
+        let change = match parameters_change(&old.parameters, &new.parameters) {
+            _ if old.return_ != new.return_ => Some(ChangeKind::Breaking),
+            change => change,
+        };
+        match change {
+            Some(ChangeKind::Breaking) => self.breaking(format!(
+                "The function `{name}` changed from `{}` to `{}`",
+                pretty_function(old),
+                pretty_function(new),
+            )),
+            Some(_) => self.additive(format!(
+                "Labels were added to the parameters of function `{name}`: `{}`",
+                pretty_function(new),
+            )),
+            None => (),
+        }
+    }
+}
+
+/// Labelling a parameter that was not labelled before is additive, as calls
+/// that do not use the label still work. Any other change is breaking.
+fn parameters_change(old: &[ParameterInterface], new: &[ParameterInterface]) -> Option<ChangeKind> {
+    if old.len() != new.len() {
+        return Some(ChangeKind::Breaking);
+    }
+    old.iter()
+        .zip(new)
+        .filter_map(|(old, new)| {
+            if old.type_ != new.type_ {
+                return Some(ChangeKind::Breaking);
+            }
+            match (&old.label, &new.label) {
+                (None, None) => None,
+                (None, Some(_)) => Some(ChangeKind::Additive),
+                (Some(old), Some(new)) if old == new => None,
+                (Some(_), _) => Some(ChangeKind::Breaking),
+            }
+        })
+        .max()
+}
+
+fn pretty_function(function: &FunctionInterface) -> String {
+    format!(
+        "fn({}) -> {}",
+        pretty_parameters(&function.parameters),
+        pretty_type(&function.return_)
+    )
+}
+
+fn pretty_parameters(parameters: &[ParameterInterface]) -> String {
+    parameters
+        .iter()
+        .map(|parameter| match &parameter.label {
+            Some(label) => format!("{label}: {}", pretty_type(&parameter.type_)),
+            None => pretty_type(&parameter.type_),
+        })
+        .join(", ")
+}
+
+fn pretty_type(type_: &TypeInterface) -> String {
+    match type_ {
+        TypeInterface::Tuple { elements } => {
+            format!("#({})", elements.iter().map(pretty_type).join(", "))
+        }
+        TypeInterface::Fn {
+            parameters,
+            return_,
+        } => format!(
+            "fn({}) -> {}",
+            parameters.iter().map(pretty_type).join(", "),
+            pretty_type(return_)
+        ),
+        TypeInterface::Variable { id } => type_variable_name(*id),
+        TypeInterface::Named {
+            name, parameters, ..
+        } if parameters.is_empty() => name.to_string(),
+        TypeInterface::Named {
+            name, parameters, ..
+        } => format!("{name}({})", parameters.iter().map(pretty_type).join(", ")),
+    }
+}
+
+/// Type variables are numbered from 0, and named `a` to `z`, then `a1` and so
+/// on.
+fn type_variable_name(id: u64) -> String {
+    let letter = char::from(b'a' + (id % 26) as u8);
+    match id / 26 {
+        0 => letter.to_string(),
+        round => format!("{letter}{round}"),
+    }
+}
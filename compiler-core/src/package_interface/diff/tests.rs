@@ -0,0 +1,207 @@
+use hexpm::version::Version;
+
+use super::{ApiDiff, ChangeKind};
+use crate::package_interface::{tests::compile_package, PackageInterface};
+
+fn interface(src: &str) -> PackageInterface {
+    serde_json::from_str(&compile_package(None, src, None)).expect("from json")
+}
+
+fn api_diff(old: &str, new: &str) -> ApiDiff {
+    ApiDiff::new(&interface(old), &interface(new))
+}
+
+macro_rules! assert_api_diff {
+    ($old:expr, $new:expr $(,)?) => {{
+        let output = api_diff($old, $new).to_pretty_string();
+        let sources = format!("{}\n----- NEW -----\n{}", $old, $new);
+        insta::assert_snapshot!(insta::internals::AutoName, output, &sources);
+    }};
+}
+
+#[test]
+fn no_changes() {
+    let src = "pub fn wibble(a: Int) -> Int { a }";
+    assert_eq!(api_diff(src, src).kind(), None);
+    assert_api_diff!(src, src);
+}
+
+#[test]
+fn function_removed_and_added() {
+    assert_api_diff!("pub fn wibble() { 1 }", "pub fn wobble() { 1 }");
+}
+
+#[test]
+fn function_signature_changed() {
+    assert_api_diff!(
+        "pub fn wibble(a: Int, b: List(x)) -> x { todo }",
+        "pub fn wibble(a: Float, b: List(x)) -> x { todo }"
+    );
+}
+
+#[test]
+fn generic_function_with_renamed_type_variables() {
+    let diff = api_diff(
+        "pub fn wibble(a: a, b: b) -> a { a }",
+        "pub fn wibble(x: one, y: two) -> one { x }",
+    );
+    assert_eq!(diff.kind(), None);
+}
+
+#[test]
+fn function_return_type_changed() {
+    assert_api_diff!(
+        "pub fn wibble() -> Int { 1 }",
+        "pub fn wibble() -> #(Int, fn(Int) -> String) { todo }"
+    );
+}
+
+#[test]
+fn function_label_added() {
+    assert_api_diff!(
+        "pub fn wibble(a: Int) -> Int { a }",
+        "pub fn wibble(wobble a: Int) -> Int { a }"
+    );
+}
+
+#[test]
+fn function_label_renamed() {
+    assert_api_diff!(
+        "pub fn wibble(wobble a: Int) -> Int { a }",
+        "pub fn wibble(wubble a: Int) -> Int { a }"
+    );
+}
+
+#[test]
+fn function_parameter_added() {
+    assert_api_diff!(
+        "pub fn wibble(a: Int) -> Int { a }",
+        "pub fn wibble(a: Int, b: Int) -> Int { a + b }"
+    );
+}
+
+#[test]
+fn documentation_changed() {
+    assert_api_diff!(
+        "/// Wibble\npub fn wibble() { 1 }\n/// Wobble\npub type Wobble { Wobble }",
+        "/// Wibble!\npub fn wibble() { 1 }\npub type Wobble { Wobble }"
+    );
+}
+
+#[test]
+fn deprecated() {
+    assert_api_diff!(
+        "pub fn wibble() { 1 }",
+        "@deprecated(\"Use wobble\")\npub fn wibble() { 1 }"
+    );
+}
+
+#[test]
+fn constructor_added() {
+    assert_api_diff!(
+        "pub type Wibble { Wibble Wobble }",
+        "pub type Wibble { Wibble Wobble Wubble }"
+    );
+}
+
+#[test]
+fn constructor_removed() {
+    assert_api_diff!(
+        "pub type Wibble { Wibble Wobble }",
+        "pub type Wibble { Wibble }"
+    );
+}
+
+#[test]
+fn constructor_field_changed() {
+    assert_api_diff!(
+        "pub type Wibble { Wibble(Int) }",
+        "pub type Wibble { Wibble(name: String) }"
+    );
+}
+
+#[test]
+fn constructor_label_added() {
+    assert_api_diff!(
+        "pub type Wibble { Wibble(Int) }",
+        "pub type Wibble { Wibble(count: Int) }"
+    );
+}
+
+#[test]
+fn type_made_opaque() {
+    assert_api_diff!(
+        "pub type Wibble { Wibble(Int) }",
+        "pub opaque type Wibble { Wibble(Int) }"
+    );
+}
+
+#[test]
+fn type_made_public() {
+    assert_api_diff!(
+        "pub opaque type Wibble { Wibble(Int) }",
+        "pub type Wibble { Wibble(Int) }"
+    );
+}
+
+#[test]
+fn type_parameter_added() {
+    assert_api_diff!(
+        "pub type Wibble { Wibble }",
+        "pub type Wibble(a) { Wibble }"
+    );
+}
+
+#[test]
+fn type_alias_changed() {
+    assert_api_diff!(
+        "pub type Wibble = List(Int)",
+        "pub type Wibble = List(String)"
+    );
+}
+
+#[test]
+fn constant_type_changed() {
+    assert_api_diff!("pub const wibble = 1", "pub const wibble = 1.0");
+}
+
+#[test]
+fn function_no_longer_runs_on_javascript() {
+    assert_api_diff!(
+        "pub fn wibble() -> Int { 1 }",
+        "@external(erlang, \"wibble\", \"wobble\")\npub fn wibble() -> Int"
+    );
+}
+
+#[test]
+fn minimum_version() {
+    let breaking = api_diff("pub fn wibble() { 1 }", "");
+    let additive = api_diff("", "pub fn wibble() { 1 }");
+    let patch = api_diff("pub fn wibble() { 1 }", "/// Wibble\npub fn wibble() { 1 }");
+    assert_eq!(breaking.kind(), Some(ChangeKind::Breaking));
+    assert_eq!(additive.kind(), Some(ChangeKind::Additive));
+    assert_eq!(patch.kind(), Some(ChangeKind::Patch));
+
+    let stable = Version::new(1, 2, 3);
+    assert_eq!(breaking.minimum_version(&stable), Version::new(2, 0, 0));
+    assert_eq!(additive.minimum_version(&stable), Version::new(1, 3, 0));
+    assert_eq!(patch.minimum_version(&stable), Version::new(1, 2, 4));
+
+    let unstable = Version::new(0, 2, 3);
+    assert_eq!(breaking.minimum_version(&unstable), Version::new(0, 3, 0));
+    assert_eq!(additive.minimum_version(&unstable), Version::new(0, 2, 4));
+    assert_eq!(patch.minimum_version(&unstable), Version::new(0, 2, 4));
+}
+
+#[test]
+fn unmarked_breaking_change() {
+    let breaking = api_diff("pub fn wibble() { 1 }", "");
+    let additive = api_diff("", "pub fn wibble() { 1 }");
+    let previous = Version::new(1, 2, 3);
+    let parse = |version| Version::parse(version).expect("version");
+
+    assert!(breaking.is_unmarked_breaking_change(&previous, &parse("1.3.0")));
+    assert!(!breaking.is_unmarked_breaking_change(&previous, &parse("2.0.0")));
+    assert!(!breaking.is_unmarked_breaking_change(&previous, &parse("2.0.0-rc1")));
+    assert!(!additive.is_unmarked_breaking_change(&previous, &parse("1.2.4")));
+}
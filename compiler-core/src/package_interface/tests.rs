@@ -166,6 +166,8 @@ fn package_from_module(module: Module) -> Package {
             internal_modules: Some(vec![GlobBuilder::new("internals/*")
                 .build()
                 .expect("internals glob")]),
+            hex_repositories: std::collections::HashMap::new(),
+            licence_policy: Default::default(),
         },
         modules: vec![module],
     }
@@ -168,10 +168,7 @@ pub fn parse_module(
     Ok(parsed)
 }
 
-//
-// Test Interface
-//
-#[cfg(test)]
+/// Parses a sequence of statements, as found in the body of a function.
 pub fn parse_statement_sequence(src: &str) -> Result<Vec1<UntypedStatement>, ParseError> {
     let lex = lexer::make_tokenizer(src);
     let mut parser = Parser::new(lex);
@@ -48,6 +48,25 @@ impl ProjectPaths {
         self.root.join("test")
     }
 
+    pub fn vendor_directory(&self) -> Utf8PathBuf {
+        self.root.join("vendor")
+    }
+
+    pub fn vendor_packages_toml(&self) -> Utf8PathBuf {
+        self.vendor_directory().join("packages.toml")
+    }
+
+    pub fn vendor_package(&self, package_name: &str) -> Utf8PathBuf {
+        self.vendor_directory().join(package_name)
+    }
+
+    /// The Hex tarball a vendored package was taken from, which its sources
+    /// are checked against.
+    pub fn vendor_package_tarball(&self, package_name: &str, version: &str) -> Utf8PathBuf {
+        self.vendor_directory()
+            .join(format!("{package_name}-{version}.tar"))
+    }
+
     pub fn build_directory(&self) -> Utf8PathBuf {
         self.root.join("build")
     }
@@ -82,6 +101,12 @@ impl ProjectPaths {
         self.build_directory().join("erlang-shipment")
     }
 
+    pub fn javascript_bundle(&self, module: &str) -> Utf8PathBuf {
+        self.build_directory()
+            .join("javascript-bundle")
+            .join(format!("{}.mjs", module.replace('/', "@")))
+    }
+
     pub fn build_documentation_directory(&self, package: &str) -> Utf8PathBuf {
         self.build_directory_for_mode(Mode::Dev)
             .join("docs")
@@ -120,17 +145,71 @@ impl ProjectPaths {
         self.build_directory_for_target(mode, target)
             .join("gleam_version")
     }
+
+    /// A path to a file that contains the code generation settings the
+    /// artifacts were built with. If this file does not match the current
+    /// settings we will rebuild from scratch
+    pub fn build_codegen_settings(&self, mode: Mode, target: Target) -> Utf8PathBuf {
+        self.build_directory_for_target(mode, target)
+            .join("codegen_settings")
+    }
 }
 
 pub fn global_package_cache_package_tarball(package_name: &str, version: &str) -> Utf8PathBuf {
-    global_packages_cache().join(format!("{package_name}-{version}.tar"))
+    PackageCache::global().package_tarball(None, package_name, version)
 }
 
-fn global_packages_cache() -> Utf8PathBuf {
-    default_global_gleam_cache()
-        .join("hex")
-        .join("hexpm")
-        .join("packages")
+pub fn global_packages_cache() -> Utf8PathBuf {
+    PackageCache::global().packages(None)
+}
+
+/// The directory downloaded Hex packages are cached in, shared by all
+/// projects. This is the user's cache directory unless another is given.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageCache {
+    root: Utf8PathBuf,
+}
+
+impl PackageCache {
+    pub fn new(root: Utf8PathBuf) -> Self {
+        Self { root }
+    }
+
+    pub fn global() -> Self {
+        Self::new(default_global_gleam_cache())
+    }
+
+    /// The directory packages are cached in. Packages from one of the Hex
+    /// repositories configured in `gleam.toml` are kept apart from the hex.pm
+    /// packages, as the repository could have its own packages with the same
+    /// names and versions.
+    ///
+    /// The directory is named after the repository's URL rather than the name
+    /// the project gives it, so that projects giving the same name to
+    /// different repositories do not share cached packages.
+    pub fn packages(&self, repository_url: Option<&http::Uri>) -> Utf8PathBuf {
+        let Some(repository_url) = repository_url else {
+            return self.root.join("hex").join("hexpm").join("packages");
+        };
+        let url = repository_url.to_string();
+        let host = repository_url.host().unwrap_or("repository");
+        let hash = xxhash_rust::xxh3::xxh3_64(url.trim_end_matches('/').as_bytes());
+        self.root
+            .join("hex")
+            .join("repositories")
+            .join(format!("{host}-{hash:016x}"))
+            .join("packages")
+    }
+
+    pub fn package_tarball(
+        &self,
+        repository_url: Option<&http::Uri>,
+        package_name: &str,
+        version: &str,
+    ) -> Utf8PathBuf {
+        self.packages(repository_url)
+            .join(format!("{package_name}-{version}.tar"))
+    }
 }
 
 pub fn default_global_gleam_cache() -> Utf8PathBuf {
@@ -163,4 +242,30 @@ fn paths() {
 
     assert!(global_package_cache_package_tarball("elli", "1.0.0")
         .ends_with("hex/hexpm/packages/elli-1.0.0.tar"));
+
+    let cache = PackageCache::new("/cache".into());
+    assert_eq!(
+        cache.package_tarball(None, "elli", "1.0.0"),
+        Utf8PathBuf::from("/cache/hex/hexpm/packages/elli-1.0.0.tar")
+    );
+
+    let url = "https://hex.example.com/repo/".parse().unwrap();
+    let tarball = cache.package_tarball(Some(&url), "elli", "1.0.0");
+    assert!(tarball.ends_with("packages/elli-1.0.0.tar"));
+    let directory = tarball.parent().and_then(Utf8Path::parent).unwrap();
+    assert_eq!(
+        directory.parent(),
+        Some(Utf8Path::new("/cache/hex/repositories"))
+    );
+    assert!(directory
+        .file_name()
+        .unwrap()
+        .starts_with("hex.example.com-"));
+
+    // The same URL with or without a trailing slash is the same repository,
+    // while another repository on the same host is not.
+    let same = "https://hex.example.com/repo".parse().unwrap();
+    assert_eq!(cache.packages(Some(&url)), cache.packages(Some(&same)));
+    let other = "https://hex.example.com/other/".parse().unwrap();
+    assert_ne!(cache.packages(Some(&url)), cache.packages(Some(&other)));
 }
@@ -0,0 +1,555 @@
+//! The state of a `gleam repl` session.
+//!
+//! Each input is compiled as a Gleam module against the modules of the
+//! project. Definitions are compiled into a new definitions module each time
+//! they are given, which imports the definitions of the earlier ones that
+//! haven't since been replaced. Keeping earlier definitions in the module they
+//! were first compiled in means values created with them stay valid, as on
+//! JavaScript each module has its own classes for custom types. Statements are
+//! compiled into an input module with a function that the runtime calls to
+//! evaluate them.
+//!
+//! The values of variables bound by earlier statements are kept by the
+//! runtime, and the input module reads them back annotated with the types
+//! they were inferred to have, so earlier statements are never run again.
+
+#[cfg(test)]
+mod tests;
+
+use std::sync::Arc;
+
+use camino::Utf8PathBuf;
+use ecow::EcoString;
+use itertools::Itertools;
+use strum::IntoEnumIterator;
+
+use crate::{
+    ast::{
+        visit::{self, Visit},
+        AssignName, Definition, Pattern, SrcSpan, Statement, TypedExpr, UntypedPattern,
+    },
+    build::Module,
+    parse::error::{LexicalErrorType, ParseError, ParseErrorType},
+    type_::{
+        prelude::PreludeType,
+        printer::{NamedTypeNames, Printer, TypeNames},
+        Type, TypeVar, TypedCallArg, PRELUDE_MODULE_NAME,
+    },
+    warning::WarningEmitter,
+    Error, Result,
+};
+
+/// The module that statements are compiled into.
+pub const INPUT_MODULE: &str = "gleam_repl_input";
+
+/// The function of the input module that evaluates the statements.
+pub const INPUT_FUNCTION: &str = "gleam_repl_run";
+
+const DEFINITIONS_MODULE: &str = "gleam_repl_definitions";
+
+/// The package that the modules compiled for each input belong to. They and
+/// the runtime are written to the directory of this package in the build
+/// directory, apart from the packages of the project.
+pub const PACKAGE: &str = "gleam@@repl";
+
+/// The runtime that evaluates input on the Erlang target, which is compiled
+/// to the `gleam@@repl` module.
+pub const ERLANG_RUNTIME: &str = include_str!("../templates/gleam@@repl.erl");
+
+/// The runtime that evaluates input on the JavaScript target, which is
+/// written as `gleam@@repl.mjs` next to the modules compiled for each input.
+pub const JAVASCRIPT_RUNTIME: &str = include_str!("../templates/gleam@@repl.mjs");
+
+#[derive(Debug, Default)]
+pub struct Repl {
+    imports: Vec<Import>,
+    /// The definitions modules that have been compiled, oldest first.
+    definitions_modules: Vec<DefinitionsModule>,
+    /// The number of definitions modules that have been created.
+    versions: usize,
+    /// Variables bound by earlier statements, in the order they were bound.
+    bindings: Vec<(EcoString, Arc<Type>)>,
+}
+
+#[derive(Debug, Clone)]
+struct Import {
+    module: EcoString,
+    /// The name the module is used with, if it isn't discarded.
+    used_name: Option<EcoString>,
+    /// The types imported unqualified, with the names they are used with.
+    types: Vec<(EcoString, EcoString)>,
+    src: EcoString,
+}
+
+/// A definitions module, with the names of the types and values it defines
+/// that haven't been replaced by a later definition.
+#[derive(Debug, Clone)]
+struct DefinitionsModule {
+    name: EcoString,
+    types: Vec<EcoString>,
+    values: Vec<EcoString>,
+}
+
+impl DefinitionsModule {
+    /// Modules are always imported so that the types of values created with
+    /// definitions that have since been replaced can still be referred to.
+    fn import(&self) -> String {
+        let names = (self.types.iter())
+            .map(|name| format!("type {name}"))
+            .chain(self.values.iter().map(|name| name.to_string()))
+            .join(", ");
+        format!("import {}.{{{names}}}", self.name)
+    }
+
+    fn remove(&mut self, types: &[EcoString], values: &[EcoString]) {
+        self.types.retain(|name| !types.contains(name));
+        self.values.retain(|name| !values.contains(name));
+    }
+}
+
+#[derive(Debug)]
+pub enum Input {
+    /// More lines are needed before the input can be parsed, such as when a
+    /// bracket or string hasn't been closed yet.
+    Incomplete,
+    /// The input parsed and a module has to be compiled for it.
+    Module(PendingModule),
+}
+
+/// A module for some input that has to be compiled and, for statements,
+/// evaluated before the REPL can accept it.
+#[derive(Debug)]
+pub struct PendingModule {
+    pub name: EcoString,
+    pub code: EcoString,
+    kind: PendingKind,
+}
+
+#[derive(Debug)]
+enum PendingKind {
+    Definitions {
+        imports: Vec<Import>,
+        /// The earlier definitions modules, without the names the new module
+        /// replaces.
+        modules: Vec<DefinitionsModule>,
+    },
+    Statements,
+}
+
+impl PendingModule {
+    /// Whether the module evaluates statements, rather than only holding
+    /// definitions.
+    pub fn is_statements(&self) -> bool {
+        matches!(self.kind, PendingKind::Statements)
+    }
+}
+
+impl Repl {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse some input, returning the module to compile for it.
+    pub fn read(&mut self, src: &str) -> Result<Input> {
+        if is_definitions(src) {
+            self.read_definitions(src)
+        } else {
+            self.read_statements(src)
+        }
+    }
+
+    fn read_definitions(&mut self, src: &str) -> Result<Input> {
+        let path = Utf8PathBuf::from("repl");
+        let parsed = match crate::parse::parse_module(path.clone(), src, &WarningEmitter::null()) {
+            Ok(parsed) => parsed,
+            Err(error) if is_incomplete(&error) => return Ok(Input::Incomplete),
+            Err(error) => {
+                return Err(Error::Parse {
+                    path,
+                    src: src.into(),
+                    error,
+                })
+            }
+        };
+
+        let mut imports = self.imports.clone();
+        let mut definitions = vec![];
+        let mut types = vec![];
+        let mut values = vec![];
+        // Each definition's source starts where the previous one ended so any
+        // documentation and attributes are kept with it.
+        let mut start = 0;
+        for definition in parsed.module.definitions {
+            let definition = definition.definition;
+            let end = definition.end_position();
+            let definition_src = src.get(start as usize..end as usize).unwrap_or_default();
+            let location = definition.location().start - start;
+            start = end;
+
+            let is_private = match definition {
+                Definition::Import(import) => {
+                    let used_name = import.used_name();
+                    imports.retain(|existing| {
+                        existing.used_name.is_none() || existing.used_name != used_name
+                    });
+                    imports.push(Import {
+                        types: import
+                            .unqualified_types
+                            .iter()
+                            .map(|type_| (type_.name.clone(), type_.used_name().clone()))
+                            .collect(),
+                        module: import.module,
+                        used_name,
+                        src: definition_src.trim().into(),
+                    });
+                    continue;
+                }
+                Definition::Function(function) => {
+                    values.extend(function.name.map(|(_, name)| name));
+                    function.publicity.is_private()
+                }
+                Definition::TypeAlias(alias) => {
+                    types.push(alias.alias);
+                    alias.publicity.is_private()
+                }
+                Definition::CustomType(custom_type) => {
+                    types.push(custom_type.name);
+                    values.extend(custom_type.constructors.into_iter().map(|c| c.name));
+                    custom_type.publicity.is_private()
+                }
+                Definition::ModuleConstant(constant) => {
+                    values.push(constant.name);
+                    constant.publicity.is_private()
+                }
+            };
+
+            // Definitions are made public so that later modules can import
+            // them.
+            if is_private {
+                let (before, after) = definition_src.split_at(location as usize);
+                definitions.push(format!("{before}pub {after}").trim().to_string());
+            } else {
+                definitions.push(definition_src.trim().to_string());
+            }
+        }
+
+        let mut modules = self.definitions_modules.clone();
+        for module in &mut modules {
+            module.remove(&types, &values);
+        }
+
+        self.versions += 1;
+        let name = EcoString::from(format!("{DEFINITIONS_MODULE}_{}", self.versions));
+        let code = (imports.iter())
+            .map(|import| import.src.to_string())
+            .chain(modules.iter().map(DefinitionsModule::import))
+            .chain(definitions)
+            .join("\n\n")
+            .into();
+        Ok(Input::Module(PendingModule {
+            name,
+            code,
+            kind: PendingKind::Definitions { imports, modules },
+        }))
+    }
+
+    fn read_statements(&mut self, src: &str) -> Result<Input> {
+        let statements = match crate::parse::parse_statement_sequence(src) {
+            Ok(statements) => statements,
+            Err(error) if is_incomplete(&error) => return Ok(Input::Incomplete),
+            Err(error) => {
+                return Err(Error::Parse {
+                    path: Utf8PathBuf::from("repl"),
+                    src: src.into(),
+                    error,
+                })
+            }
+        };
+
+        let mut names = self.type_names();
+        let mut code = EcoString::new();
+        for import in &self.imports {
+            code.push_str(&import.src);
+            code.push('\n');
+        }
+        for module in &self.definitions_modules {
+            code.push_str(&module.import());
+            code.push('\n');
+        }
+        code.push_str(&format!(
+            r#"
+@external(erlang, "gleam@@repl", "binding")
+@external(javascript, "./gleam@@repl.mjs", "binding")
+fn gleam_repl_binding(name: String) -> a
+
+@external(erlang, "gleam@@repl", "bind")
+@external(javascript, "./gleam@@repl.mjs", "bind")
+fn gleam_repl_bind(name: String, value: a) -> Nil
+
+@external(erlang, "gleam@@repl", "result")
+@external(javascript, "./gleam@@repl.mjs", "result")
+fn gleam_repl_result(value: a) -> Nil
+
+pub fn {INPUT_FUNCTION}() -> Nil {{
+"#
+        ));
+
+        for (name, type_) in &self.bindings {
+            match annotation(&mut names, type_) {
+                Some(type_) => code.push_str(&format!(
+                    "let {name}: {type_} = gleam_repl_binding(\"{name}\")\n"
+                )),
+                None => code.push_str(&format!("let {name} = gleam_repl_binding(\"{name}\")\n")),
+            }
+        }
+
+        // The value of the last statement is given to the runtime, after all
+        // the variables that the statements bind.
+        let last = statements.last();
+        let last_start = match last {
+            Statement::Expression(expression) => expression.start_byte_index(),
+            Statement::Assignment(assignment) => assignment.location.start,
+            Statement::Use(use_) => use_.location.start,
+        } as usize;
+        let (before, last_src) = src.split_at(last_start);
+        code.push_str(before);
+        match last {
+            Statement::Assignment(assignment) => {
+                // The value of an assignment is everything after the `=`
+                // that follows the pattern and any annotation.
+                let pattern_end = assignment
+                    .annotation
+                    .as_ref()
+                    .map_or(assignment.pattern.location().end, |annotation| {
+                        annotation.location().end
+                    }) as usize;
+                let equals = src
+                    .get(pattern_end..)
+                    .and_then(|rest| rest.find('='))
+                    .map_or(src.len(), |index| pattern_end + index + 1);
+                let (pattern, value) = src.split_at(equals);
+                let pattern = pattern.get(last_start..).unwrap_or_default();
+                let value = value.trim_start();
+                code.push_str(&format!(
+                    "let gleam_repl_value = {value}\n{pattern} gleam_repl_value\n"
+                ));
+            }
+            Statement::Expression(_) | Statement::Use(_) => {
+                code.push_str(&format!("let gleam_repl_value = {last_src}\n"));
+            }
+        }
+
+        let mut bound = vec![];
+        for statement in &statements {
+            match statement {
+                Statement::Expression(_) => (),
+                Statement::Assignment(assignment) => bound_names(&assignment.pattern, &mut bound),
+                Statement::Use(use_) => {
+                    for assignment in &use_.assignments {
+                        bound_names(&assignment.pattern, &mut bound);
+                    }
+                }
+            }
+        }
+        for name in bound.iter().unique() {
+            code.push_str(&format!("gleam_repl_bind(\"{name}\", {name})\n"));
+        }
+        code.push_str("gleam_repl_result(gleam_repl_value)\n}\n");
+
+        Ok(Input::Module(PendingModule {
+            name: INPUT_MODULE.into(),
+            code,
+            kind: PendingKind::Statements,
+        }))
+    }
+
+    /// Accept the input that a module was compiled for, once it has been
+    /// compiled and evaluated without error. For statements the type of their
+    /// value is returned.
+    pub fn accept(&mut self, pending: PendingModule, module: &Module) -> Option<EcoString> {
+        match pending.kind {
+            PendingKind::Definitions { imports, modules } => {
+                let interface = &module.ast.type_info;
+                self.imports = imports;
+                self.definitions_modules = modules;
+                self.definitions_modules.push(DefinitionsModule {
+                    name: module.name.clone(),
+                    types: interface.types.keys().sorted().cloned().collect(),
+                    values: interface.values.keys().sorted().cloned().collect(),
+                });
+                None
+            }
+
+            PendingKind::Statements => {
+                let mut evaluated = Evaluated::default();
+                evaluated.visit_typed_module(&module.ast);
+                for (name, type_) in evaluated.bindings {
+                    self.bindings.retain(|(existing, _)| *existing != name);
+                    self.bindings.push((name, type_));
+                }
+                let type_ = evaluated.value?;
+                let mut names = self.type_names();
+                Some(Printer::new(&mut names).print_type(&type_))
+            }
+        }
+    }
+
+    /// The names that types have in the input module.
+    fn type_names(&self) -> TypeNames {
+        let mut names = TypeNames::new(INPUT_MODULE.into());
+        for type_ in PreludeType::iter() {
+            let name = EcoString::from(type_.name());
+            names.named_type_in_scope(PRELUDE_MODULE_NAME.into(), name.clone(), name);
+        }
+        for import in &self.imports {
+            if let Some(used_name) = &import.used_name {
+                names.imported_module(import.module.clone(), used_name.clone());
+            }
+            for (name, used_name) in &import.types {
+                names.named_type_in_scope(import.module.clone(), name.clone(), used_name.clone());
+            }
+        }
+        for module in &self.definitions_modules {
+            names.imported_module(module.name.clone(), module.name.clone());
+            for name in &module.types {
+                names.named_type_in_scope(module.name.clone(), name.clone(), name.clone());
+            }
+        }
+        names
+    }
+}
+
+/// Whether some input is imports and definitions rather than statements.
+fn is_definitions(src: &str) -> bool {
+    let src = src.trim_start();
+    let keyword = src
+        .split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .next()
+        .unwrap_or_default();
+    match keyword {
+        "import" | "pub" | "type" | "const" => true,
+        // Anonymous functions are expressions.
+        "fn" => !src["fn".len()..].trim_start().starts_with('('),
+        _ => src.starts_with('@') || src.starts_with("///"),
+    }
+}
+
+fn is_incomplete(error: &ParseError) -> bool {
+    match &error.error {
+        ParseErrorType::UnexpectedEof => true,
+        ParseErrorType::LexError { error } => error.error == LexicalErrorType::UnexpectedStringEnd,
+        _ => false,
+    }
+}
+
+/// The annotation for a variable of a type, if the type can be written in the
+/// input module.
+fn annotation(names: &mut TypeNames, type_: &Type) -> Option<EcoString> {
+    if !is_nameable(names, type_) {
+        return None;
+    }
+    Some(Printer::new(names).print_type(type_))
+}
+
+fn is_nameable(names: &TypeNames, type_: &Type) -> bool {
+    match type_ {
+        Type::Named {
+            module, name, args, ..
+        } => {
+            !matches!(
+                names.named_type(module, name),
+                NamedTypeNames::Unimported(_)
+            ) && args.iter().all(|arg| is_nameable(names, arg))
+        }
+        Type::Fn { args, retrn } => {
+            args.iter().all(|arg| is_nameable(names, arg)) && is_nameable(names, retrn)
+        }
+        Type::Var { type_ } => match &*type_.borrow() {
+            TypeVar::Link { type_ } => is_nameable(names, type_),
+            TypeVar::Unbound { .. } | TypeVar::Generic { .. } => true,
+        },
+        Type::Tuple { elems } => elems.iter().all(|elem| is_nameable(names, elem)),
+    }
+}
+
+fn bound_names(pattern: &UntypedPattern, names: &mut Vec<EcoString>) {
+    match pattern {
+        Pattern::Variable { name, .. } => names.push(name.clone()),
+        Pattern::Assign { name, pattern, .. } => {
+            bound_names(pattern, names);
+            names.push(name.clone());
+        }
+        Pattern::List { elements, tail, .. } => {
+            for element in elements {
+                bound_names(element, names);
+            }
+            if let Some(tail) = tail {
+                bound_names(tail, names);
+            }
+        }
+        Pattern::Constructor { arguments, .. } => {
+            for argument in arguments {
+                bound_names(&argument.value, names);
+            }
+        }
+        Pattern::Tuple { elems, .. } => {
+            for elem in elems {
+                bound_names(elem, names);
+            }
+        }
+        Pattern::BitArray { segments, .. } => {
+            for segment in segments {
+                bound_names(&segment.value, names);
+            }
+        }
+        Pattern::StringPrefix {
+            left_side_assignment,
+            right_side_assignment,
+            ..
+        } => {
+            if let Some((name, _)) = left_side_assignment {
+                names.push(name.clone());
+            }
+            if let AssignName::Variable(name) = right_side_assignment {
+                names.push(name.clone());
+            }
+        }
+        Pattern::Int { .. }
+        | Pattern::Float { .. }
+        | Pattern::String { .. }
+        | Pattern::VarUsage { .. }
+        | Pattern::Discard { .. }
+        | Pattern::Invalid { .. } => (),
+    }
+}
+
+/// Finds the types of the variables and value that an input module gives to
+/// the runtime.
+#[derive(Debug, Default)]
+struct Evaluated {
+    bindings: Vec<(EcoString, Arc<Type>)>,
+    value: Option<Arc<Type>>,
+}
+
+impl<'ast> Visit<'ast> for Evaluated {
+    fn visit_typed_expr_call(
+        &mut self,
+        location: &'ast SrcSpan,
+        typ: &'ast Arc<Type>,
+        fun: &'ast TypedExpr,
+        args: &'ast [TypedCallArg],
+    ) {
+        if let TypedExpr::Var { name, .. } = fun {
+            match (name.as_str(), args) {
+                ("gleam_repl_bind", [name, value]) => {
+                    if let TypedExpr::String { value: name, .. } = &name.value {
+                        self.bindings.push((name.clone(), value.value.type_()));
+                    }
+                }
+                ("gleam_repl_result", [value]) => self.value = Some(value.value.type_()),
+                _ => (),
+            }
+        }
+        visit::visit_typed_expr_call(self, location, typ, fun, args);
+    }
+}
@@ -0,0 +1,75 @@
+use super::{Input, Repl};
+
+fn module(repl: &mut Repl, src: &str) -> String {
+    match repl.read(src).expect("input should parse") {
+        Input::Module(pending) => pending.code.to_string(),
+        Input::Incomplete => panic!("input should be complete"),
+    }
+}
+
+fn is_incomplete(src: &str) -> bool {
+    matches!(Repl::new().read(src), Ok(Input::Incomplete))
+}
+
+#[test]
+fn expression() {
+    insta::assert_snapshot!(module(&mut Repl::new(), "1 + 2"));
+}
+
+#[test]
+fn statements() {
+    insta::assert_snapshot!(module(
+        &mut Repl::new(),
+        "let x = 1
+let #(y, z) = #(x, 2)
+y + z"
+    ));
+}
+
+#[test]
+fn last_statement_is_assignment() {
+    insta::assert_snapshot!(module(
+        &mut Repl::new(),
+        "let assert [first, ..rest]: List(Int) = [1, 2, 3]"
+    ));
+}
+
+#[test]
+fn anonymous_function_is_an_expression() {
+    insta::assert_snapshot!(module(&mut Repl::new(), "fn(x) { x }(1)"));
+}
+
+#[test]
+fn definitions_are_made_public() {
+    insta::assert_snapshot!(module(
+        &mut Repl::new(),
+        r#"import gleam/io
+
+type Wibble {
+  Wibble
+  Wobble(Int)
+}
+
+/// Some documentation
+@deprecated("Use something else")
+fn wibble() {
+  1
+}
+
+pub const wobble = 2"#
+    ));
+}
+
+#[test]
+fn incomplete_input() {
+    assert!(is_incomplete("fn(x) {"));
+    assert!(is_incomplete("let x = [1, 2,"));
+    assert!(is_incomplete("\"Hello"));
+    assert!(is_incomplete("pub type Wibble {"));
+    assert!(!is_incomplete("1 + 2"));
+}
+
+#[test]
+fn invalid_input() {
+    assert!(Repl::new().read("1 +* 2").is_err());
+}
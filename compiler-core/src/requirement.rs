@@ -13,15 +13,24 @@ use serde::Deserialize;
 #[derive(Deserialize, Debug, PartialEq, Eq, Clone)]
 #[serde(untagged, remote = "Self")]
 pub enum Requirement {
-    Hex { version: Range },
-    Path { path: Utf8PathBuf },
-    Git { git: EcoString },
+    Hex {
+        version: Range,
+        #[serde(default)]
+        repository: Option<EcoString>,
+    },
+    Path {
+        path: Utf8PathBuf,
+    },
+    Git {
+        git: EcoString,
+    },
 }
 
 impl Requirement {
     pub fn hex(range: &str) -> Requirement {
         Requirement::Hex {
             version: Range::new(range.to_string()),
+            repository: None,
         }
     }
 
@@ -35,9 +44,17 @@ impl Requirement {
 
     pub fn to_toml(&self, root_path: &Utf8Path) -> String {
         match self {
-            Requirement::Hex { version: range } => {
-                format!(r#"{{ version = "{}" }}"#, range)
-            }
+            Requirement::Hex {
+                version: range,
+                repository: None,
+            } => format!(r#"{{ version = "{}" }}"#, range),
+            Requirement::Hex {
+                version: range,
+                repository: Some(repository),
+            } => format!(
+                r#"{{ version = "{}", repository = "{}" }}"#,
+                range, repository
+            ),
             Requirement::Path { path } => {
                 format!(
                     r#"{{ path = "{}" }}"#,
@@ -56,9 +73,24 @@ impl Serialize for Requirement {
     where
         S: Serializer,
     {
-        let mut map = serializer.serialize_map(Some(1))?;
+        let entries = match self {
+            Requirement::Hex {
+                repository: Some(_),
+                ..
+            } => 2,
+            _ => 1,
+        };
+        let mut map = serializer.serialize_map(Some(entries))?;
         match self {
-            Requirement::Hex { version: range } => map.serialize_entry("version", range)?,
+            Requirement::Hex {
+                version: range,
+                repository,
+            } => {
+                map.serialize_entry("version", range)?;
+                if let Some(repository) = repository {
+                    map.serialize_entry("repository", repository)?;
+                }
+            }
             Requirement::Path { path } => map.serialize_entry("path", path)?,
             Requirement::Git { git: url } => map.serialize_entry("git", url)?,
         }
@@ -99,7 +131,15 @@ impl<'de> Visitor<'de> for RequirementVisitor {
     where
         M: MapAccess<'de>,
     {
-        Requirement::deserialize(de::value::MapAccessDeserializer::new(visitor))
+        let requirement = Requirement::deserialize(de::value::MapAccessDeserializer::new(visitor))?;
+        if let Requirement::Hex {
+            repository: Some(repository),
+            ..
+        } = &requirement
+        {
+            crate::config::repository_name::check(repository)?;
+        }
+        Ok(requirement)
     }
 }
 
@@ -123,12 +163,20 @@ mod tests {
         let toml = r#"
             short = "~> 0.5"
             hex = { version = "~> 1.0.0" }
+            private = { version = "~> 1.0.0", repository = "internal" }
             local = { path = "/path/to/package" }
             github = { git = "https://github.com/gleam-lang/otp.git" }
         "#;
         let deps: HashMap<String, Requirement> = toml::from_str(toml).unwrap();
         assert_eq!(deps["short"], Requirement::hex("~> 0.5"));
         assert_eq!(deps["hex"], Requirement::hex("~> 1.0.0"));
+        assert_eq!(
+            deps["private"],
+            Requirement::Hex {
+                version: Range::new("~> 1.0.0".into()),
+                repository: Some("internal".into()),
+            }
+        );
         assert_eq!(deps["local"], Requirement::path("/path/to/package"));
         assert_eq!(
             deps["github"],
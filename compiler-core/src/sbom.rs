@@ -0,0 +1,471 @@
+//! Software bills of materials, listing the packages a project is built from
+//! in the CycloneDX or SPDX JSON formats.
+
+#[cfg(test)]
+mod tests;
+
+use std::collections::HashMap;
+
+use ecow::EcoString;
+use itertools::Itertools;
+use serde::Serialize;
+use strum::{Display, EnumString, VariantNames};
+
+use crate::{
+    config::{PackageConfig, SpdxLicense},
+    manifest::{Base16Checksum, Manifest, ManifestPackageSource},
+    version::COMPILER_VERSION,
+};
+
+#[derive(Debug, Display, EnumString, VariantNames, Clone, Copy, PartialEq, Eq)]
+#[strum(serialize_all = "lowercase")]
+pub enum SbomFormat {
+    CycloneDx,
+    Spdx,
+}
+
+/// A package in the bill of materials, either the root package or one of the
+/// packages in its manifest.
+#[derive(Debug)]
+struct Component<'a> {
+    name: &'a EcoString,
+    version: String,
+    source: Source<'a>,
+    licences: &'a [SpdxLicense],
+    dependencies: Vec<&'a EcoString>,
+}
+
+#[derive(Debug)]
+enum Source<'a> {
+    Root,
+    Hex {
+        outer_checksum: &'a Base16Checksum,
+        repository_url: Option<String>,
+    },
+    Git {
+        repo: &'a EcoString,
+        commit: &'a EcoString,
+    },
+    Local,
+}
+
+impl Component<'_> {
+    fn reference(&self) -> String {
+        format!("{}@{}", self.name, self.version)
+    }
+
+    fn spdx_id(&self) -> String {
+        format!("SPDXRef-Package-{}", self.name.as_str().replace('_', "-"))
+    }
+
+    /// The package URL of a Hex package, as specified by
+    /// <https://github.com/package-url/purl-spec>.
+    fn purl(&self) -> Option<String> {
+        match &self.source {
+            Source::Hex {
+                repository_url: None,
+                ..
+            } => Some(format!("pkg:hex/{}@{}", self.name, self.version)),
+            Source::Hex {
+                repository_url: Some(url),
+                ..
+            } => Some(format!(
+                "pkg:hex/{}@{}?repository_url={url}",
+                self.name, self.version
+            )),
+            Source::Root | Source::Git { .. } | Source::Local => None,
+        }
+    }
+
+    fn sha256(&self) -> Option<String> {
+        match &self.source {
+            Source::Hex { outer_checksum, .. } => Some(base16::encode_lower(&outer_checksum.0)),
+            Source::Root | Source::Git { .. } | Source::Local => None,
+        }
+    }
+
+    fn licence_expression(&self) -> Option<String> {
+        if self.licences.is_empty() {
+            return None;
+        }
+        Some(
+            self.licences
+                .iter()
+                .map(|licence| licence.as_ref())
+                .join(" OR "),
+        )
+    }
+}
+
+/// Generate a bill of materials for the root package and every package in
+/// its manifest. `licences` holds the licences each package declares in its
+/// `gleam.toml`, and `timestamp` is the RFC 3339 time the document is
+/// created at.
+pub fn generate(
+    format: SbomFormat,
+    root: &PackageConfig,
+    manifest: &Manifest,
+    licences: &HashMap<EcoString, Vec<SpdxLicense>>,
+    timestamp: &str,
+) -> String {
+    let root_component = Component {
+        name: &root.name,
+        version: root.version.to_string(),
+        source: Source::Root,
+        licences: &root.licences,
+        dependencies: manifest.requirements.keys().sorted().collect(),
+    };
+    let components = manifest
+        .packages
+        .iter()
+        .sorted_by(|a, b| a.name.cmp(&b.name))
+        .map(|package| Component {
+            name: &package.name,
+            version: package.version.to_string(),
+            source: match &package.source {
+                ManifestPackageSource::Hex {
+                    outer_checksum,
+                    repository,
+                } => Source::Hex {
+                    outer_checksum,
+                    repository_url: repository
+                        .as_ref()
+                        .and_then(|name| root.hex_repositories.get(name))
+                        .map(|repository| repository.repository_url.to_string()),
+                },
+                ManifestPackageSource::Git { repo, commit } => Source::Git { repo, commit },
+                ManifestPackageSource::Local { .. } => Source::Local,
+            },
+            licences: licences
+                .get(&package.name)
+                .map(Vec::as_slice)
+                .unwrap_or_default(),
+            dependencies: package.requirements.iter().sorted().collect(),
+        })
+        .collect_vec();
+
+    let json = match format {
+        SbomFormat::CycloneDx => {
+            serde_json::to_string_pretty(&cyclonedx(&root_component, &components, timestamp))
+        }
+        SbomFormat::Spdx => {
+            serde_json::to_string_pretty(&spdx(&root_component, &components, timestamp))
+        }
+    };
+    json.expect("SBOM serialisation")
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct CycloneDxDocument {
+    bom_format: &'static str,
+    spec_version: &'static str,
+    version: u32,
+    metadata: CycloneDxMetadata,
+    components: Vec<CycloneDxComponent>,
+    dependencies: Vec<CycloneDxDependency>,
+}
+
+#[derive(Serialize, Debug)]
+struct CycloneDxMetadata {
+    timestamp: String,
+    tools: CycloneDxTools,
+    component: CycloneDxComponent,
+}
+
+#[derive(Serialize, Debug)]
+struct CycloneDxTools {
+    components: Vec<CycloneDxTool>,
+}
+
+#[derive(Serialize, Debug)]
+struct CycloneDxTool {
+    r#type: &'static str,
+    name: &'static str,
+    version: &'static str,
+}
+
+#[derive(Serialize, Debug)]
+struct CycloneDxComponent {
+    r#type: &'static str,
+    #[serde(rename = "bom-ref")]
+    bom_ref: String,
+    name: EcoString,
+    version: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    purl: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    hashes: Vec<CycloneDxHash>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    licenses: Vec<CycloneDxLicence>,
+    #[serde(rename = "externalReferences", skip_serializing_if = "Vec::is_empty")]
+    external_references: Vec<CycloneDxExternalReference>,
+}
+
+#[derive(Serialize, Debug)]
+struct CycloneDxHash {
+    alg: &'static str,
+    content: String,
+}
+
+#[derive(Serialize, Debug)]
+struct CycloneDxLicence {
+    license: CycloneDxLicenceId,
+}
+
+#[derive(Serialize, Debug)]
+struct CycloneDxLicenceId {
+    id: String,
+}
+
+#[derive(Serialize, Debug)]
+struct CycloneDxExternalReference {
+    r#type: &'static str,
+    url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    comment: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct CycloneDxDependency {
+    r#ref: String,
+    depends_on: Vec<String>,
+}
+
+fn cyclonedx(
+    root: &Component<'_>,
+    components: &[Component<'_>],
+    timestamp: &str,
+) -> CycloneDxDocument {
+    let references: HashMap<_, _> = components
+        .iter()
+        .map(|component| (component.name, component.reference()))
+        .collect();
+    let dependency = |component: &Component<'_>| CycloneDxDependency {
+        r#ref: component.reference(),
+        depends_on: component
+            .dependencies
+            .iter()
+            .filter_map(|name| references.get(name).cloned())
+            .collect(),
+    };
+
+    CycloneDxDocument {
+        bom_format: "CycloneDX",
+        spec_version: "1.5",
+        version: 1,
+        metadata: CycloneDxMetadata {
+            timestamp: timestamp.into(),
+            tools: CycloneDxTools {
+                components: vec![CycloneDxTool {
+                    r#type: "application",
+                    name: "gleam",
+                    version: COMPILER_VERSION,
+                }],
+            },
+            component: cyclonedx_component(root),
+        },
+        components: components.iter().map(cyclonedx_component).collect(),
+        dependencies: std::iter::once(root)
+            .chain(components)
+            .map(dependency)
+            .collect(),
+    }
+}
+
+fn cyclonedx_component(component: &Component<'_>) -> CycloneDxComponent {
+    let external_references = match &component.source {
+        Source::Git { repo, commit } => vec![CycloneDxExternalReference {
+            r#type: "vcs",
+            url: repo.to_string(),
+            comment: Some(format!("commit {commit}")),
+        }],
+        Source::Root | Source::Hex { .. } | Source::Local => vec![],
+    };
+    CycloneDxComponent {
+        r#type: match component.source {
+            Source::Root => "application",
+            Source::Hex { .. } | Source::Git { .. } | Source::Local => "library",
+        },
+        bom_ref: component.reference(),
+        name: component.name.clone(),
+        version: component.version.clone(),
+        purl: component.purl(),
+        hashes: component
+            .sha256()
+            .map(|content| CycloneDxHash {
+                alg: "SHA-256",
+                content,
+            })
+            .into_iter()
+            .collect(),
+        licenses: component
+            .licences
+            .iter()
+            .map(|licence| CycloneDxLicence {
+                license: CycloneDxLicenceId {
+                    id: licence.to_string(),
+                },
+            })
+            .collect(),
+        external_references,
+    }
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SpdxDocument {
+    spdx_version: &'static str,
+    data_license: &'static str,
+    #[serde(rename = "SPDXID")]
+    spdx_id: &'static str,
+    name: String,
+    document_namespace: String,
+    creation_info: SpdxCreationInfo,
+    packages: Vec<SpdxPackage>,
+    relationships: Vec<SpdxRelationship>,
+}
+
+#[derive(Serialize, Debug)]
+struct SpdxCreationInfo {
+    created: String,
+    creators: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SpdxPackage {
+    name: EcoString,
+    #[serde(rename = "SPDXID")]
+    spdx_id: String,
+    version_info: String,
+    download_location: String,
+    files_analyzed: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    checksums: Vec<SpdxChecksum>,
+    license_concluded: &'static str,
+    license_declared: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    external_refs: Vec<SpdxExternalRef>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SpdxChecksum {
+    algorithm: &'static str,
+    checksum_value: String,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SpdxExternalRef {
+    reference_category: &'static str,
+    reference_type: &'static str,
+    reference_locator: String,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct SpdxRelationship {
+    spdx_element_id: String,
+    relationship_type: &'static str,
+    related_spdx_element: String,
+}
+
+fn spdx(root: &Component<'_>, components: &[Component<'_>], timestamp: &str) -> SpdxDocument {
+    let ids: HashMap<_, _> = components
+        .iter()
+        .map(|component| (component.name, component.spdx_id()))
+        .collect();
+
+    let describes = SpdxRelationship {
+        spdx_element_id: "SPDXRef-DOCUMENT".into(),
+        relationship_type: "DESCRIBES",
+        related_spdx_element: root.spdx_id(),
+    };
+    let depends_on = std::iter::once(root)
+        .chain(components)
+        .flat_map(|component| {
+            component
+                .dependencies
+                .iter()
+                .filter_map(|name| ids.get(name))
+                .map(|id| SpdxRelationship {
+                    spdx_element_id: component.spdx_id(),
+                    relationship_type: "DEPENDS_ON",
+                    related_spdx_element: id.clone(),
+                })
+        });
+
+    SpdxDocument {
+        spdx_version: "SPDX-2.3",
+        data_license: "CC0-1.0",
+        spdx_id: "SPDXRef-DOCUMENT",
+        name: root.reference(),
+        document_namespace: format!(
+            "https://gleam.run/spdx/{}-{}-{timestamp}",
+            root.name, root.version
+        ),
+        creation_info: SpdxCreationInfo {
+            created: timestamp.into(),
+            creators: vec![format!("Tool: gleam-{COMPILER_VERSION}")],
+        },
+        packages: std::iter::once(root)
+            .chain(components)
+            .map(spdx_package)
+            .collect(),
+        relationships: std::iter::once(describes).chain(depends_on).collect(),
+    }
+}
+
+fn spdx_package(component: &Component<'_>) -> SpdxPackage {
+    let download_location = match &component.source {
+        Source::Hex {
+            repository_url: None,
+            ..
+        } => format!(
+            "https://repo.hex.pm/tarballs/{}-{}.tar",
+            component.name, component.version
+        ),
+        Source::Hex {
+            repository_url: Some(url),
+            ..
+        } => format!(
+            "{}/tarballs/{}-{}.tar",
+            url.trim_end_matches('/'),
+            component.name,
+            component.version
+        ),
+        Source::Git { repo, commit } => format!("git+{repo}@{commit}"),
+        Source::Root | Source::Local => "NOASSERTION".into(),
+    };
+    SpdxPackage {
+        name: component.name.clone(),
+        spdx_id: component.spdx_id(),
+        version_info: component.version.clone(),
+        download_location,
+        files_analyzed: false,
+        checksums: component
+            .sha256()
+            .map(|checksum_value| SpdxChecksum {
+                algorithm: "SHA256",
+                checksum_value,
+            })
+            .into_iter()
+            .collect(),
+        license_concluded: "NOASSERTION",
+        license_declared: component
+            .licence_expression()
+            .unwrap_or_else(|| "NOASSERTION".into()),
+        external_refs: component
+            .purl()
+            .map(|purl| SpdxExternalRef {
+                reference_category: "PACKAGE-MANAGER",
+                reference_type: "purl",
+                reference_locator: purl,
+            })
+            .into_iter()
+            .collect(),
+    }
+}
@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+
+use super::{generate, SbomFormat};
+use crate::{
+    config::{PackageConfig, SpdxLicense},
+    manifest::Manifest,
+    version::COMPILER_VERSION,
+};
+
+const MANIFEST: &str = r#"
+packages = [
+  { name = "gleam_json", version = "1.0.1", build_tools = ["gleam"], requirements = ["gleam_stdlib", "thoas"], otp_app = "gleam_json", source = "hex", outer_checksum = "9063D14D25406326C0255BDA0021541E797D8A7A12573D849462CAFED459F6EB" },
+  { name = "gleam_stdlib", version = "0.39.0", build_tools = ["gleam"], requirements = [], otp_app = "gleam_stdlib", source = "hex", outer_checksum = "2D7DE885A6EA7F1D5015D1698920C9BAF7241102836CE0C3837A4F160128A9C4" },
+  { name = "thoas", version = "1.2.1", build_tools = ["rebar3"], requirements = [], otp_app = "thoas", source = "hex", outer_checksum = "E38697EDFFD6E91BD12CEA41B155115282630075C2A727E7A6B2947F5408B86A" },
+  { name = "wibble", version = "0.1.0", build_tools = ["gleam"], requirements = ["gleam_stdlib"], source = "local", path = "../wibble" },
+]
+
+[requirements]
+gleam_json = { version = ">= 1.0.0 and < 2.0.0" }
+wibble = { path = "../wibble" }
+"#;
+
+fn sbom(format: SbomFormat) -> String {
+    let manifest: Manifest = toml::from_str(MANIFEST).expect("manifest");
+    let root = PackageConfig {
+        name: "app".into(),
+        licences: vec![SpdxLicense {
+            licence: "Apache-2.0".into(),
+        }],
+        ..Default::default()
+    };
+    let licences = HashMap::from([
+        (
+            "gleam_json".into(),
+            vec![SpdxLicense {
+                licence: "Apache-2.0".into(),
+            }],
+        ),
+        (
+            "gleam_stdlib".into(),
+            vec![
+                SpdxLicense {
+                    licence: "Apache-2.0".into(),
+                },
+                SpdxLicense {
+                    licence: "MIT".into(),
+                },
+            ],
+        ),
+    ]);
+    generate(format, &root, &manifest, &licences, "2024-07-01T12:00:00Z")
+        .replace(COMPILER_VERSION, "<gleam version>")
+}
+
+#[test]
+fn cyclonedx() {
+    insta::assert_snapshot!(sbom(SbomFormat::CycloneDx));
+}
+
+#[test]
+fn spdx() {
+    insta::assert_snapshot!(sbom(SbomFormat::Spdx));
+}
@@ -175,7 +175,7 @@ impl Type {
             Self::Named { module, name, .. } if "BitArray" == name && is_prelude_module(module) => {
                 true
             }
-            Self::Var { type_ } => type_.borrow().is_nil(),
+            Self::Var { type_ } => type_.borrow().is_bit_array(),
             _ => false,
         }
     }
@@ -868,6 +868,13 @@ impl TypeVar {
         }
     }
 
+    pub fn is_bit_array(&self) -> bool {
+        match self {
+            Self::Link { type_ } => type_.is_bit_array(),
+            Self::Unbound { .. } | Self::Generic { .. } => false,
+        }
+    }
+
     pub fn is_bool(&self) -> bool {
         match self {
             Self::Link { type_ } => type_.is_bool(),
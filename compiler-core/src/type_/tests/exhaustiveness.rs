@@ -445,6 +445,97 @@ pub fn main(x) {
     );
 }
 
+#[test]
+fn string_prefix() {
+    assert_no_warnings!(
+        r#"
+pub fn main(x) {
+  case x {
+    "abc" -> 1
+    "ab" <> _ -> 1
+    "a" <> _ -> 1
+    _ -> 1
+  }
+}
+"#
+    );
+}
+
+#[test]
+fn string_prefix_empty() {
+    assert_no_warnings!(
+        r#"
+pub fn main(x) {
+  case x {
+    "a" -> 1
+    "" <> rest -> {
+      let _ = rest
+      1
+    }
+  }
+}
+"#
+    );
+}
+
+#[test]
+fn string_prefix_1() {
+    assert_module_error!(
+        r#"
+pub fn main(x) {
+  case x {
+    "a" <> _ -> 1
+    "b" <> _ -> 1
+  }
+}
+"#
+    );
+}
+
+#[test]
+fn redundant_string_prefix() {
+    assert_warning!(
+        r#"
+pub fn main(x) {
+  case x {
+    "a" <> _ -> 1
+    "ab" <> _ -> 2
+    _ -> 3
+  }
+}
+"#
+    );
+}
+
+#[test]
+fn redundant_string_after_prefix() {
+    assert_warning!(
+        r#"
+pub fn main(x) {
+  case x {
+    "a" <> _ -> 1
+    "abc" -> 2
+    _ -> 3
+  }
+}
+"#
+    );
+}
+
+#[test]
+fn redundant_after_empty_string_prefix() {
+    assert_warning!(
+        r#"
+pub fn main(x) {
+  case x {
+    "" <> _ -> 1
+    _ -> 2
+  }
+}
+"#
+    );
+}
+
 #[test]
 fn bit_array() {
     assert_no_warnings!(
@@ -490,6 +581,196 @@ pub fn main(x) {
     );
 }
 
+#[test]
+fn bit_array_bits() {
+    assert_no_warnings!(
+        r#"
+pub fn main(x) {
+  case x {
+    <<>> -> 1
+    <<0:1, _:bits>> -> 1
+    <<1:1, _:bits>> -> 1
+  }
+}
+"#
+    );
+}
+
+#[test]
+fn bit_array_bytes() {
+    assert_module_error!(
+        r#"
+pub fn main(x) {
+  case x {
+    <<>> -> 1
+    <<_, _:bytes>> -> 1
+  }
+}
+"#
+    );
+}
+
+#[test]
+fn bit_array_protocol() {
+    assert_no_warnings!(
+        r#"
+pub fn main(x) {
+  case x {
+    <<0, _:bytes>> -> 1
+    <<1, _length:16, _:bytes>> -> 1
+    <<_, _:bytes>> -> 1
+    <<>> -> 1
+    _ -> 1
+  }
+}
+"#
+    );
+}
+
+#[test]
+fn bit_array_different_segment_sizes() {
+    assert_no_warnings!(
+        r#"
+pub fn main(x) {
+  case x {
+    <<0:4, _:4>> -> 1
+    <<16:8>> -> 1
+    <<_:4, _:4>> -> 1
+    _ -> 1
+  }
+}
+"#
+    );
+}
+
+#[test]
+fn bit_array_strings() {
+    assert_no_warnings!(
+        r#"
+pub fn main(x) {
+  case x {
+    <<"GET":utf8, _:bits>> -> 1
+    <<"POST":utf8, _:bits>> -> 1
+    _ -> 1
+  }
+}
+"#
+    );
+}
+
+#[test]
+fn bit_array_float_segments_are_not_exhaustive() {
+    assert_module_error!(
+        r#"
+pub fn main(x) {
+  case x {
+    <<_:float>> -> 1
+  }
+}
+"#
+    );
+}
+
+#[test]
+fn bit_array_variable_size_segments() {
+    assert_no_warnings!(
+        r#"
+pub fn main(x) {
+  case x {
+    <<size, _:size(size)>> -> 1
+    <<_:8>> -> 1
+    _ -> 1
+  }
+}
+"#
+    );
+}
+
+#[test]
+fn bit_array_segment_size_overflow() {
+    assert_no_warnings!(
+        r#"
+pub fn main(x) {
+  case x {
+    <<_:size(100000000000000000000000000000000000000)-unit(8)>> -> 1
+    <<_:int-size(100000000000000000000000000000000000000)-unit(8)>> -> 1
+    _ -> 1
+  }
+}
+"#
+    );
+}
+
+#[test]
+fn redundant_bit_array() {
+    assert_warning!(
+        r#"
+pub fn main(x) {
+  case x {
+    <<_:8, _:bits>> -> 1
+    <<1, 2>> -> 2
+    _ -> 3
+  }
+}
+"#
+    );
+}
+
+#[test]
+fn redundant_bit_array_after_bytes() {
+    assert_warning!(
+        r#"
+pub fn main(x) {
+  case x {
+    <<_:bytes>> -> 1
+    <<1:16>> -> 2
+    _ -> 3
+  }
+}
+"#
+    );
+}
+
+#[test]
+fn redundant_bit_array_after_exhaustive_patterns() {
+    assert_warning!(
+        r#"
+pub fn main(x) {
+  case x {
+    <<>> -> 1
+    <<0:1, _:bits>> -> 2
+    <<1:1, _:bits>> -> 3
+    _ -> 4
+  }
+}
+"#
+    );
+}
+
+#[test]
+fn bit_array_let() {
+    assert_no_warnings!(
+        r#"
+pub fn main(x) {
+  let <<_:bits>> = x
+  0
+}
+"#
+    );
+}
+
+#[test]
+fn bit_array_let_1() {
+    assert_module_error!(
+        r#"
+pub fn main(x) {
+  let <<_:8, _:bits>> = x
+  0
+}
+"#
+    );
+}
+
 #[test]
 fn int() {
     assert_no_warnings!(
@@ -0,0 +1,162 @@
+-module('gleam@@repl').
+-compile([no_auto_import, nowarn_unused_vars]).
+
+-export([main/0, binding/1, bind/2, result/1]).
+
+%% The runtime for `gleam repl` on the Erlang target.
+%%
+%% Commands are read from standard input, one per line, each naming an Erlang
+%% module that the REPL generated:
+%%
+%%   load Path   Compile and load the module.
+%%   eval Path   Compile and load the module, then evaluate its input.
+%%
+%% Once a command is done a line starting with a null character is printed,
+%% holding the inspected value of the input or that an error happened.
+
+main() ->
+    ok = io:setopts([{encoding, unicode}]),
+    loop().
+
+loop() ->
+    case io:get_line("") of
+        eof ->
+            erlang:halt(0);
+        {error, _} ->
+            erlang:halt(1);
+        Line ->
+            handle(string:trim(unicode:characters_to_list(Line))),
+            loop()
+    end.
+
+handle("load " ++ Path) ->
+    reply(load(Path));
+handle("eval " ++ Path) ->
+    case load(Path) of
+        {ok, Module} -> reply(evaluate(Module));
+        Error -> reply(Error)
+    end;
+handle(Command) ->
+    reply({error, io_lib:format("Unknown command ~ts", [Command])}).
+
+load(Path) ->
+    case compile:file(Path, [binary, return_errors]) of
+        {ok, Module, Binary} ->
+            _ = code:purge(Module),
+            {module, Module} = code:load_binary(Module, Path, Binary),
+            {ok, Module};
+        {error, Errors, _Warnings} ->
+            {error, io_lib:format("~p", [Errors])}
+    end.
+
+evaluate(Module) ->
+    _ = erase(gleam_repl_result),
+    try
+        Module:gleam_repl_run(),
+        {value, inspect(get(gleam_repl_result))}
+    catch
+        Class:Reason ->
+            {error, describe(Class, Reason)}
+    end.
+
+reply({ok, _}) ->
+    io:put_chars([0, "ok\n"]);
+reply({value, Value}) ->
+    io:put_chars([0, "value ", Value, "\n"]);
+reply({error, Message}) ->
+    io:put_chars([Message, "\n", 0, "error\n"]).
+
+describe(error, #{gleam_error := Kind, message := Message} = Error) ->
+    Value = case Error of
+        #{value := V} -> [", value: ", inspect(V)];
+        _ -> []
+    end,
+    [atom_to_list(Kind), " error: ", Message, Value];
+describe(Class, Reason) ->
+    io_lib:format("~p: ~tp", [Class, Reason]).
+
+binding(Name) ->
+    get({gleam_repl_binding, Name}).
+
+bind(Name, Value) ->
+    _ = put({gleam_repl_binding, Name}, Value),
+    nil.
+
+result(Value) ->
+    _ = put(gleam_repl_result, Value),
+    nil.
+
+%% Values are shown using Gleam syntax where possible.
+
+inspect(true) ->
+    "True";
+inspect(false) ->
+    "False";
+inspect(nil) ->
+    "Nil";
+inspect(Map) when is_map(Map) ->
+    Pairs = [["#(", inspect(K), ", ", inspect(V), ")"] || {K, V} <- maps:to_list(Map)],
+    ["dict.from_list([", lists:join(", ", Pairs), "])"];
+inspect(Atom) when is_atom(Atom) ->
+    constructor_name(Atom);
+inspect(Int) when is_integer(Int) ->
+    integer_to_list(Int);
+inspect(Float) when is_float(Float) ->
+    io_lib_format:fwrite_g(Float);
+inspect(Binary) when is_binary(Binary) ->
+    case unicode:characters_to_list(Binary) of
+        Chars when is_list(Chars) -> [$", [escape(C) || C <- Chars], $"];
+        _ -> inspect_bit_array(Binary)
+    end;
+inspect(Bits) when is_bitstring(Bits) ->
+    inspect_bit_array(Bits);
+inspect(List) when is_list(List) ->
+    case proper_list(List) of
+        true -> ["[", lists:join(", ", [inspect(X) || X <- List]), "]"];
+        false -> inspect_erlang(List)
+    end;
+inspect(Tuple) when is_tuple(Tuple) ->
+    case tuple_to_list(Tuple) of
+        [Atom | Args] when is_atom(Atom), Atom =/= true, Atom =/= false, Atom =/= nil ->
+            [constructor_name(Atom), "(", lists:join(", ", [inspect(X) || X <- Args]), ")"];
+        Elements ->
+            ["#(", lists:join(", ", [inspect(X) || X <- Elements]), ")"]
+    end;
+inspect(Fun) when is_function(Fun) ->
+    {arity, Arity} = erlang:fun_info(Fun, arity),
+    Args = [[$a + N] || N <- lists:seq(0, Arity - 1)],
+    ["//fn(", lists:join(", ", Args), ") { ... }"];
+inspect(Other) ->
+    inspect_erlang(Other).
+
+inspect_erlang(Term) ->
+    ["//erl(", io_lib:format("~w", [Term]), ")"].
+
+inspect_bit_array(Bits) ->
+    ["<<", lists:join(", ", bit_array_segments(Bits)), ">>"].
+
+bit_array_segments(<<>>) ->
+    [];
+bit_array_segments(<<Byte, Rest/bitstring>>) ->
+    [integer_to_list(Byte) | bit_array_segments(Rest)];
+bit_array_segments(Rest) ->
+    Size = bit_size(Rest),
+    <<Value:Size>> = Rest,
+    [[integer_to_list(Value), ":size(", integer_to_list(Size), ")"]].
+
+proper_list([]) -> true;
+proper_list([_ | Tail]) -> proper_list(Tail);
+proper_list(_) -> false.
+
+constructor_name(Atom) ->
+    Words = string:split(atom_to_list(Atom), "_", all),
+    [string:titlecase(Word) || Word <- Words].
+
+escape($") -> "\\\"";
+escape($\\) -> "\\\\";
+escape($\n) -> "\\n";
+escape($\r) -> "\\r";
+escape($\t) -> "\\t";
+escape($\f) -> "\\f";
+escape(C) when C < 32 -> io_lib:format("\\u{~4.16.0B}", [C]);
+escape(C) -> C.
@@ -0,0 +1,144 @@
+// The runtime for `gleam repl` on the JavaScript target.
+//
+// Commands are read from standard input, one per line, each naming a
+// JavaScript module that the REPL generated:
+//
+//   load Path   Nothing is done, as modules are loaded when imported.
+//   eval Path   Import the module, then evaluate its input.
+//
+// Once a command is done a line starting with a null character is printed,
+// holding the inspected value of the input or that an error happened.
+
+import { createInterface } from "node:readline";
+import { pathToFileURL } from "node:url";
+import { BitArray, CustomType, List, UtfCodepoint } from "../prelude.mjs";
+
+const bindings = new Map();
+let evaluations = 0;
+let value = undefined;
+
+export function binding(name) {
+  return bindings.get(name);
+}
+
+export function bind(name, value) {
+  bindings.set(name, value);
+  return undefined;
+}
+
+export function result(result) {
+  value = result;
+  return undefined;
+}
+
+async function handle(line) {
+  const [command, ...rest] = line.trim().split(" ");
+  const path = rest.join(" ");
+  try {
+    if (command === "eval") {
+      process.stdout.write(`\0value ${inspect(await evaluate(path))}\n`);
+    } else if (command === "load") {
+      process.stdout.write("\0ok\n");
+    } else {
+      throw new globalThis.Error(`Unknown command ${command}`);
+    }
+  } catch (error) {
+    process.stdout.write(`${describe(error)}\n\0error\n`);
+  }
+}
+
+async function evaluate(path) {
+  value = undefined;
+  // Each input module is imported with a different URL so it is loaded again
+  // rather than taken from the module cache.
+  const url = `${pathToFileURL(path).href}?${++evaluations}`;
+  const module = await import(url);
+  module.gleam_repl_run();
+  return value;
+}
+
+function describe(error) {
+  if (error?.gleam_error) {
+    const value = "value" in error ? `, value: ${inspect(error.value)}` : "";
+    return `${error.gleam_error} error: ${error.message}${value}`;
+  }
+  return String(error?.stack ?? error);
+}
+
+// Commands are handled one at a time, in the order they were given.
+let queue = Promise.resolve();
+createInterface({ input: process.stdin, terminal: false }).on("line", (line) => {
+  queue = queue.then(() => handle(line));
+});
+
+// Values are shown using Gleam syntax where possible.
+function inspect(v) {
+  const t = typeof v;
+  if (v === true) return "True";
+  if (v === false) return "False";
+  if (v === null) return "//js(null)";
+  if (v === undefined) return "Nil";
+  if (t === "string") return inspectString(v);
+  if (t === "bigint" || t === "number") return v.toString();
+  if (Array.isArray(v)) return `#(${v.map(inspect).join(", ")})`;
+  if (v instanceof List) return `[${v.toArray().map(inspect).join(", ")}]`;
+  if (v instanceof UtfCodepoint) {
+    return `//utfcodepoint(${String.fromCodePoint(v.value)})`;
+  }
+  if (v instanceof BitArray) return `<<${Array.from(v.buffer).join(", ")}>>`;
+  if (v instanceof CustomType) return inspectCustomType(v);
+  if (t === "function") {
+    const args = Array.from({ length: v.length }, (_, i) =>
+      String.fromCharCode(97 + i),
+    );
+    return `//fn(${args.join(", ")}) { ... }`;
+  }
+  try {
+    return `//js(${JSON.stringify(v)})`;
+  } catch {
+    return `//js(${String(v)})`;
+  }
+}
+
+function inspectString(string) {
+  let escaped = "";
+  for (const char of string) {
+    switch (char) {
+      case '"':
+        escaped += '\\"';
+        break;
+      case "\\":
+        escaped += "\\\\";
+        break;
+      case "\n":
+        escaped += "\\n";
+        break;
+      case "\r":
+        escaped += "\\r";
+        break;
+      case "\t":
+        escaped += "\\t";
+        break;
+      case "\f":
+        escaped += "\\f";
+        break;
+      default:
+        if (char < " ") {
+          const code = char.charCodeAt(0).toString(16).toUpperCase();
+          escaped += `\\u{${code.padStart(4, "0")}}`;
+        } else {
+          escaped += char;
+        }
+    }
+  }
+  return `"${escaped}"`;
+}
+
+function inspectCustomType(record) {
+  const fields = Object.keys(record).map((label) => {
+    const value = inspect(record[label]);
+    return isNaN(parseInt(label)) ? `${label}: ${value}` : value;
+  });
+  const name = record.constructor.name;
+  return fields.length ? `${name}(${fields.join(", ")})` : name;
+}
@@ -23,6 +23,7 @@ export class NonEmpty<T> extends List<T> {}
 
 export class BitArray {
   buffer: Uint8Array;
+  bitSize: number;
   get length(): number;
   byteAt(index: number): number;
   floatFromSlice(index: number, end: number, isBigEndian: boolean): number;
@@ -34,19 +35,42 @@ export class BitArray {
   ): number;
   binaryFromSlice(state: number, end: number): BitArray;
   sliceAfter(index: number): BitArray;
+  intFromBits(
+    start: number,
+    end: number,
+    isBigEndian: boolean,
+    isSigned: boolean
+  ): number;
+  bigIntFromBits(
+    start: number,
+    end: number,
+    isBigEndian: boolean,
+    isSigned: boolean
+  ): bigint;
+  floatFromBits(start: number, end: number, isBigEndian: boolean): number;
+  bitsFromSlice(start: number, end: number): BitArray;
+  bitsAfter(start: number): BitArray;
+  codepointSize(start: number, unitSize: number, isBigEndian: boolean): number;
+  codepointAt(
+    start: number,
+    unitSize: number,
+    isBigEndian: boolean
+  ): UtfCodepoint;
 }
 
 export class UtfCodepoint {
   value: string;
 }
 
-export function toBitArray(segments: Array<number | Uint8Array>): BitArray;
+export function toBitArray(
+  segments: Array<number | bigint | Uint8Array | BitArray>
+): BitArray;
 
 export function sizedInt(
-  int: number,
+  int: number | bigint,
   size: number,
   isBigEndian: boolean
-): Uint8Array;
+): Uint8Array | BitArray;
 
 export function byteArrayToInt(
   byteArray: Uint8Array,
@@ -56,6 +80,14 @@ export function byteArrayToInt(
   isSigned: boolean
 ): number;
 
+export function byteArrayToBigInt(
+  byteArray: Uint8Array,
+  start: number,
+  end: number,
+  isBigEndian: boolean,
+  isSigned: boolean
+): bigint;
+
 export function byteArrayToFloat(
   byteArray: Uint8Array,
   start: number,
@@ -67,12 +99,28 @@ export function stringBits(string: string): Uint8Array;
 
 export function codepointBits(codepoint: UtfCodepoint): Uint8Array;
 
+export function stringToUtf16(string: string, isBigEndian: boolean): Uint8Array;
+
+export function codepointToUtf16(
+  codepoint: UtfCodepoint,
+  isBigEndian: boolean
+): Uint8Array;
+
+export function stringToUtf32(string: string, isBigEndian: boolean): Uint8Array;
+
+export function codepointToUtf32(
+  codepoint: UtfCodepoint,
+  isBigEndian: boolean
+): Uint8Array;
+
 export function sizedFloat(
   float: number,
   size: number,
   isBigEndian: boolean
 ): Uint8Array;
 
+export function sizedBits(bitArray: BitArray, size: number): BitArray;
+
 export class Result<T, E> extends CustomType {
   static isResult(data: unknown): boolean;
   isOk(): boolean;
@@ -91,7 +139,9 @@ export class Error<T, E> extends Result<T, E> {
 export function isEqual(a: any, b: any): boolean;
 
 export function remainderInt(a: number, b: number): number;
+export function remainderInt(a: bigint, b: bigint): bigint;
 
 export function divideInt(a: number, b: number): number;
+export function divideInt(a: bigint, b: bigint): bigint;
 
 export function divideFloat(a: number, b: number): number;
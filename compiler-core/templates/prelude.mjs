@@ -91,11 +91,13 @@ export class NonEmpty extends List {
 }
 
 export class BitArray {
-  constructor(buffer) {
+  constructor(buffer, bitSize) {
     if (!(buffer instanceof Uint8Array)) {
       throw "BitArray can only be constructed from a Uint8Array";
     }
     this.buffer = buffer;
+    // Any bits of the final byte beyond the bit size are always zero.
+    this.bitSize = bitSize ?? buffer.length * 8;
   }
 
   // @internal
@@ -125,7 +127,71 @@ export class BitArray {
 
   // @internal
   sliceAfter(index) {
-    return new BitArray(this.buffer.slice(index));
+    return new BitArray(this.buffer.slice(index), this.bitSize - index * 8);
+  }
+
+  // The methods below take offsets in bits rather than bytes. Reading outside
+  // of the bit array gives a value that is never used, as the length of the
+  // bit array is always checked when matching on it.
+
+  // @internal
+  intFromBits(start, end, isBigEndian, isSigned) {
+    if (!this.hasBits(start, end)) return 0;
+    if (start % 8 === 0 && end % 8 === 0) {
+      return this.intFromSlice(start / 8, end / 8, isBigEndian, isSigned);
+    }
+    return bitsToInt(sliceBits(this.buffer, start, end), end - start, isBigEndian, isSigned);
+  }
+
+  // @internal
+  bigIntFromBits(start, end, isBigEndian, isSigned) {
+    if (!this.hasBits(start, end)) return 0n;
+    if (start % 8 === 0 && end % 8 === 0) {
+      return byteArrayToBigInt(this.buffer, start / 8, end / 8, isBigEndian, isSigned);
+    }
+    const bytes = sliceBits(this.buffer, start, end);
+    return bitsToBigInt(bytes, end - start, isBigEndian, isSigned);
+  }
+
+  // @internal
+  floatFromBits(start, end, isBigEndian) {
+    const size = end - start;
+    if (!this.hasBits(start, end) || (size !== 16 && size !== 32 && size !== 64)) {
+      return NaN;
+    }
+    const bytes = sliceBits(this.buffer, start, end);
+    return byteArrayToFloat(bytes, 0, bytes.length, isBigEndian);
+  }
+
+  // @internal
+  bitsFromSlice(start, end) {
+    if (!this.hasBits(start, end)) {
+      const msg = `Unable to take bits ${start} to ${end} of a bit array of ${this.bitSize} bits`;
+      throw new globalThis.Error(msg);
+    }
+    return new BitArray(sliceBits(this.buffer, start, end), end - start);
+  }
+
+  // @internal
+  bitsAfter(start) {
+    return this.bitsFromSlice(start, this.bitSize);
+  }
+
+  // @internal
+  codepointSize(start, unitSize, isBigEndian) {
+    const codepoint = decodeCodepoint(this, start, unitSize, isBigEndian);
+    return codepoint ? codepoint.size : NaN;
+  }
+
+  // @internal
+  codepointAt(start, unitSize, isBigEndian) {
+    const codepoint = decodeCodepoint(this, start, unitSize, isBigEndian);
+    return new UtfCodepoint(codepoint?.value);
+  }
+
+  // @internal
+  hasBits(start, end) {
+    return start >= 0 && start <= end && end <= this.bitSize;
   }
 }
 
@@ -138,20 +204,151 @@ export class UtfCodepoint {
 // @internal
 export function toBitArray(segments) {
   let size = (segment) =>
-    segment instanceof Uint8Array ? segment.byteLength : 1;
-  let bytes = segments.reduce((acc, segment) => acc + size(segment), 0);
-  let view = new DataView(new ArrayBuffer(bytes));
+    segment instanceof Uint8Array
+      ? segment.byteLength * 8
+      : segment instanceof BitArray
+        ? segment.bitSize
+        : 8;
+  let bitSize = segments.reduce((acc, segment) => acc + size(segment), 0);
+  let bytes = new Uint8Array(Math.ceil(bitSize / 8));
   let cursor = 0;
   for (let segment of segments) {
     if (segment instanceof Uint8Array) {
-      new Uint8Array(view.buffer).set(segment, cursor);
-      cursor += segment.byteLength;
+      writeBits(bytes, cursor, segment, segment.byteLength * 8);
+    } else if (segment instanceof BitArray) {
+      writeBits(bytes, cursor, segment.buffer, segment.bitSize);
+    } else if (typeof segment === "bigint") {
+      writeBits(bytes, cursor, [Number(BigInt.asUintN(8, segment))], 8);
     } else {
-      view.setInt8(cursor, segment);
-      cursor++;
+      writeBits(bytes, cursor, [segment & 0xff], 8);
+    }
+    cursor += size(segment);
+  }
+  return new BitArray(bytes, bitSize);
+}
+
+// Write the first `size` bits of the source bytes into the target bytes,
+// starting at the given bit offset. Any bits of the source beyond its size
+// must be zero.
+function writeBits(target, cursor, source, size) {
+  const byteCount = Math.ceil(size / 8);
+  const index = Math.floor(cursor / 8);
+  const shift = cursor % 8;
+  if (shift === 0) {
+    for (let i = 0; i < byteCount; i++) target[index + i] = source[i];
+    return;
+  }
+  for (let i = 0; i < byteCount; i++) {
+    target[index + i] |= source[i] >> shift;
+    if (index + i + 1 < target.length) {
+      target[index + i + 1] |= source[i] << (8 - shift);
     }
   }
-  return new BitArray(new Uint8Array(view.buffer));
+}
+
+// Copy the bits from start to end into new bytes, with any bits of the final
+// byte beyond them set to zero.
+function sliceBits(buffer, start, end) {
+  const size = end - start;
+  const bytes = new Uint8Array(Math.ceil(size / 8));
+  const index = Math.floor(start / 8);
+  const shift = start % 8;
+  for (let i = 0; i < bytes.length; i++) {
+    bytes[i] =
+      shift === 0
+        ? buffer[index + i]
+        : (buffer[index + i] << shift) | ((buffer[index + i + 1] ?? 0) >> (8 - shift));
+  }
+  if (size % 8 !== 0) {
+    bytes[bytes.length - 1] &= 0xff << (8 - (size % 8));
+  }
+  return bytes;
+}
+
+// Read an integer from bytes holding a number of bits that is not a multiple
+// of 8. Little endian integers hold their least significant bytes first, with
+// the most significant bits in the final partial byte.
+function bitsToInt(bytes, size, isBigEndian, isSigned) {
+  const remainder = size % 8;
+  const whole = Math.floor(size / 8);
+  const last = bytes[whole] >> (8 - remainder);
+  let value;
+  if (isBigEndian) {
+    value = byteArrayToInt(bytes, 0, whole, true, false) * 2 ** remainder + last;
+  } else {
+    value = last * 2 ** (whole * 8) + byteArrayToInt(bytes, 0, whole, false, false);
+  }
+  if (isSigned && value >= 2 ** (size - 1)) {
+    value -= 2 ** size;
+  }
+  return value;
+}
+
+// The BigInt equivalent of `bitsToInt`.
+function bitsToBigInt(bytes, size, isBigEndian, isSigned) {
+  const remainder = BigInt(size % 8);
+  const whole = Math.floor(size / 8);
+  const last = BigInt(bytes[whole] >> (8 - Number(remainder)));
+  let value;
+  if (isBigEndian) {
+    value = (byteArrayToBigInt(bytes, 0, whole, true, false) << remainder) + last;
+  } else {
+    value = (last << BigInt(whole * 8)) + byteArrayToBigInt(bytes, 0, whole, false, false);
+  }
+  return isSigned ? BigInt.asIntN(size, value) : value;
+}
+
+// Decode the UTF-8, UTF-16 or UTF-32 codepoint at the given bit offset,
+// returning its value and its size in bits, or undefined if it is invalid.
+function decodeCodepoint(bitArray, start, unitSize, isBigEndian) {
+  const unit = (index) =>
+    bitArray.intFromBits(
+      start + index * unitSize,
+      start + (index + 1) * unitSize,
+      isBigEndian,
+      false,
+    );
+  if (!bitArray.hasBits(start, start + unitSize)) return undefined;
+
+  let value;
+  let units = 1;
+  if (unitSize === 8) {
+    const first = unit(0);
+    if (first < 0x80) {
+      value = first;
+    } else if (first >= 0xc0 && first < 0xe0) {
+      [value, units] = [first & 0x1f, 2];
+    } else if (first >= 0xe0 && first < 0xf0) {
+      [value, units] = [first & 0x0f, 3];
+    } else if (first >= 0xf0 && first < 0xf8) {
+      [value, units] = [first & 0x07, 4];
+    } else {
+      return undefined;
+    }
+    if (!bitArray.hasBits(start, start + units * 8)) return undefined;
+    for (let i = 1; i < units; i++) {
+      const byte = unit(i);
+      if ((byte & 0xc0) !== 0x80) return undefined;
+      value = value * 64 + (byte & 0x3f);
+    }
+    const minimum = [0, 0, 0x80, 0x800, 0x10000][units];
+    if (value < minimum) return undefined;
+  } else if (unitSize === 16) {
+    value = unit(0);
+    if (value >= 0xd800 && value < 0xdc00) {
+      if (!bitArray.hasBits(start, start + 32)) return undefined;
+      const low = unit(1);
+      if (low < 0xdc00 || low >= 0xe000) return undefined;
+      [value, units] = [0x10000 + (value - 0xd800) * 0x400 + (low - 0xdc00), 2];
+    }
+  } else {
+    value = unit(0);
+  }
+
+  if (value > 0x10ffff || (value >= 0xd800 && value < 0xe000)) {
+    return undefined;
+  }
+  return { value, size: units * unitSize };
 }
 
 // @internal
@@ -160,9 +357,11 @@ export function sizedInt(value, size, isBigEndian) {
   if (size < 0) {
     return new Uint8Array();
   }
+  if (typeof value === "bigint") {
+    return sizedBigInt(value, size, isBigEndian);
+  }
   if (size % 8 != 0) {
-    const msg = `Bit arrays must be byte aligned on JavaScript, got size of ${size} bits`;
-    throw new globalThis.Error(msg);
+    return unalignedSizedInt(value, size, isBigEndian);
   }
 
   const byteArray = new Uint8Array(size / 8);
@@ -189,6 +388,55 @@ export function sizedInt(value, size, isBigEndian) {
   return byteArray.reverse();
 }
 
+// An integer with a size that is not a multiple of 8 bits. Little endian
+// integers hold their least significant bytes first, with the most significant
+// bits in the final partial byte.
+function unalignedSizedInt(value, size, isBigEndian) {
+  const remainder = size % 8;
+  const whole = Math.floor(size / 8);
+  const modulus = 2 ** size;
+  value = ((value % modulus) + modulus) % modulus;
+
+  const bytes = new Uint8Array(whole + 1);
+  let last;
+  if (isBigEndian) {
+    bytes.set(sizedInt(Math.floor(value / 2 ** remainder), whole * 8, true));
+    last = value % 2 ** remainder;
+  } else {
+    bytes.set(sizedInt(value % 2 ** (whole * 8), whole * 8, false));
+    last = Math.floor(value / 2 ** (whole * 8));
+  }
+  bytes[whole] = last << (8 - remainder);
+  return new BitArray(bytes, size);
+}
+
+// The BigInt equivalent of `sizedInt`, which needs no special handling of
+// sizes that are not a multiple of 8 bits as BigInts never lose precision.
+function sizedBigInt(value, size, isBigEndian) {
+  const remainder = size % 8;
+  const whole = Math.floor(size / 8);
+  const bytes = new Uint8Array(Math.ceil(size / 8));
+  value = BigInt.asUintN(size, value);
+
+  if (isBigEndian) {
+    value <<= BigInt((8 - remainder) % 8);
+    for (let i = bytes.length - 1; i >= 0; i--) {
+      bytes[i] = Number(value & 0xffn);
+      value >>= 8n;
+    }
+  } else {
+    for (let i = 0; i < whole; i++) {
+      bytes[i] = Number(value & 0xffn);
+      value >>= 8n;
+    }
+    if (remainder !== 0) {
+      bytes[whole] = Number(value) << (8 - remainder);
+    }
+  }
+
+  return remainder === 0 ? bytes : new BitArray(bytes, size);
+}
+
 // @internal
 export function byteArrayToInt(byteArray, start, end, isBigEndian, isSigned) {
   let value = 0;
@@ -219,6 +467,23 @@ export function byteArrayToInt(byteArray, start, end, isBigEndian, isSigned) {
   return value;
 }
 
+// @internal
+export function byteArrayToBigInt(byteArray, start, end, isBigEndian, isSigned) {
+  let value = 0n;
+
+  if (isBigEndian) {
+    for (let i = start; i < end; i++) {
+      value = (value << 8n) + BigInt(byteArray[i]);
+    }
+  } else {
+    for (let i = end - 1; i >= start; i--) {
+      value = (value << 8n) + BigInt(byteArray[i]);
+    }
+  }
+
+  return isSigned ? BigInt.asIntN((end - start) * 8, value) : value;
+}
+
 // @internal
 export function byteArrayToFloat(byteArray, start, end, isBigEndian) {
   const view = new DataView(byteArray.buffer);
@@ -229,12 +494,52 @@ export function byteArrayToFloat(byteArray, start, end, isBigEndian) {
     return view.getFloat64(start, !isBigEndian)
   } else if (byteSize === 4) {
     return view.getFloat32(start, !isBigEndian)
+  } else if (byteSize === 2) {
+    return float16ToNumber(view.getUint16(start, !isBigEndian))
   } else {
-    const msg = `Sized floats must be 32-bit or 64-bit on JavaScript, got size of ${byteSize * 8} bits`;
+    const msg = `Sized floats must be 16-bit, 32-bit or 64-bit on JavaScript, got size of ${byteSize * 8} bits`;
     throw new globalThis.Error(msg);
   }
 }
 
+function float16ToNumber(bits) {
+  const sign = bits & 0x8000 ? -1 : 1;
+  const exponent = (bits >> 10) & 0x1f;
+  const fraction = bits & 0x3ff;
+  if (exponent === 0) {
+    return sign * fraction * 2 ** -24;
+  } else if (exponent === 0x1f) {
+    return fraction ? NaN : sign * Infinity;
+  } else {
+    return sign * (1 + fraction / 1024) * 2 ** (exponent - 15);
+  }
+}
+
+// Round a number to the nearest 16-bit float, with ties to even.
+function numberToFloat16(value) {
+  const view = new DataView(new ArrayBuffer(8));
+  view.setFloat64(0, value);
+  const high = view.getUint32(0);
+  const sign = (high >>> 16) & 0x8000;
+  const exponent = ((high >>> 20) & 0x7ff) - 1023;
+  if (Number.isNaN(value)) return 0x7e00;
+  if (exponent > 15) return sign | 0x7c00;
+
+  // The magnitude in units of the smallest 16-bit float, 2^-24, which is the
+  // unit of the fraction for subnormal numbers.
+  const magnitude = Math.abs(value);
+  const unit = exponent < -14 ? 2 ** -24 : 2 ** (exponent - 10);
+  const scaled = magnitude / unit;
+  let rounded = Math.round(scaled);
+  if (rounded - scaled === 0.5 && rounded % 2 !== 0) rounded -= 1;
+
+  if (exponent < -14) {
+    return sign | rounded;
+  }
+  // Rounding up may carry into the exponent, which the addition handles.
+  return sign | (((exponent + 15) << 10) + (rounded - 1024));
+}
+
 // @internal
 export function stringBits(string) {
   return new TextEncoder().encode(string);
@@ -245,10 +550,41 @@ export function codepointBits(codepoint) {
   return stringBits(String.fromCodePoint(codepoint.value));
 }
 
+// @internal
+export function stringToUtf16(string, isBigEndian) {
+  const bytes = new Uint8Array(string.length * 2);
+  const view = new DataView(bytes.buffer);
+  for (let i = 0; i < string.length; i++) {
+    view.setUint16(i * 2, string.charCodeAt(i), !isBigEndian);
+  }
+  return bytes;
+}
+
+// @internal
+export function codepointToUtf16(codepoint, isBigEndian) {
+  return stringToUtf16(String.fromCodePoint(codepoint.value), isBigEndian);
+}
+
+// @internal
+export function stringToUtf32(string, isBigEndian) {
+  const codepoints = Array.from(string, (character) => character.codePointAt(0));
+  const bytes = new Uint8Array(codepoints.length * 4);
+  const view = new DataView(bytes.buffer);
+  codepoints.forEach((codepoint, i) => {
+    view.setUint32(i * 4, codepoint, !isBigEndian);
+  });
+  return bytes;
+}
+
+// @internal
+export function codepointToUtf32(codepoint, isBigEndian) {
+  return stringToUtf32(String.fromCodePoint(codepoint.value), isBigEndian);
+}
+
 // @internal
 export function sizedFloat(float, size, isBigEndian) {
-  if (size !== 32 && size !== 64) {
-    const msg = `Sized floats must be 32-bit or 64-bit on JavaScript, got size of ${size} bits`;
+  if (size !== 16 && size !== 32 && size !== 64) {
+    const msg = `Sized floats must be 16-bit, 32-bit or 64-bit on JavaScript, got size of ${size} bits`;
     throw new globalThis.Error(msg);
   }
 
@@ -260,11 +596,18 @@ export function sizedFloat(float, size, isBigEndian) {
     view.setFloat64(0, float, !isBigEndian);
   } else if (size === 32) {
     view.setFloat32(0, float, !isBigEndian);
+  } else if (size === 16) {
+    view.setUint16(0, numberToFloat16(float), !isBigEndian);
   }
   
   return byteArray;
 }
 
+// @internal
+export function sizedBits(bitArray, size) {
+  return bitArray.bitsFromSlice(0, size);
+}
+
 export class Result extends CustomType {
   // @internal
   static isResult(data) {
@@ -387,7 +730,9 @@ function structurallyCompatibleObjects(a, b) {
 
 // @internal
 export function remainderInt(a, b) {
-  if (b === 0) {
+  if (typeof a === "bigint") {
+    return b === 0n ? 0n : a % b;
+  } else if (b === 0) {
     return 0;
   } else {
     return a % b;
@@ -396,6 +741,10 @@ export function remainderInt(a, b) {
 
 // @internal
 export function divideInt(a, b) {
+  if (typeof a === "bigint") {
+    // BigInt division already truncates towards zero.
+    return b === 0n ? 0n : a / b;
+  }
   return Math.trunc(divideFloat(a, b));
 }
 
@@ -418,3 +767,114 @@ export function makeError(variant, module, line, fn, message, extra) {
   for (let k in extra) error[k] = extra[k];
   return error;
 }
+
+// @internal
+export function checkExternal(value, type, module, line, fn, message) {
+  let problem = externalTypeProblem(value, type);
+  if (problem === undefined) return value;
+  throw makeError("external_type", module, line, fn, `${message}: ${problem}`, {
+    value,
+  });
+}
+
+// Describes how a value returned by an external function differs from the
+// shape of its declared type, or returns undefined if it does not. Types that
+// can't be checked, such as type variables and external types, are null.
+function externalTypeProblem(value, type) {
+  if (type === null) return undefined;
+
+  let isValid;
+  switch (typeof type === "string" ? type : type[0]) {
+    case "Int":
+      isValid = Number.isInteger(value);
+      break;
+    case "BigInt":
+      isValid = typeof value === "bigint";
+      break;
+    case "Float":
+      isValid = typeof value === "number";
+      break;
+    case "String":
+      isValid = typeof value === "string";
+      break;
+    case "Bool":
+      isValid = typeof value === "boolean";
+      break;
+    case "Nil":
+      isValid = value === undefined;
+      break;
+    case "BitArray":
+      isValid = value instanceof BitArray;
+      break;
+    case "UtfCodepoint":
+      isValid = value instanceof UtfCodepoint;
+      break;
+    case "Function":
+      isValid = typeof value === "function";
+      break;
+
+    case "List": {
+      if (!(value instanceof List)) break;
+      let index = 0;
+      for (let element of value) {
+        let problem = externalTypeProblem(element, type[1]);
+        if (problem !== undefined) {
+          return `${problem} in element ${index} of the list`;
+        }
+        index++;
+      }
+      return undefined;
+    }
+
+    case "Result": {
+      if (value instanceof Ok) {
+        let problem = externalTypeProblem(value[0], type[1]);
+        return problem && `${problem} in the Ok value`;
+      }
+      if (value instanceof Error) {
+        let problem = externalTypeProblem(value[0], type[2]);
+        return problem && `${problem} in the Error value`;
+      }
+      break;
+    }
+
+    case "Tuple": {
+      if (!Array.isArray(value) || value.length !== type.length - 1) break;
+      for (let index = 0; index < value.length; index++) {
+        let problem = externalTypeProblem(value[index], type[index + 1]);
+        if (problem !== undefined) {
+          return `${problem} in element ${index} of the tuple`;
+        }
+      }
+      return undefined;
+    }
+
+    case "CustomType":
+      isValid = type
+        .slice(2)
+        .some((constructor) => value instanceof constructor);
+      break;
+  }
+
+  if (isValid) return undefined;
+  return `expected ${externalTypeName(type)}, got ${externalValueName(value)}`;
+}
+
+function externalTypeName(type) {
+  if (type === "BigInt") return "Int";
+  if (type === "Function") return "a function";
+  if (typeof type === "string") return type;
+  if (type[0] === "Tuple") return `a tuple of ${type.length - 1} elements`;
+  if (type[0] === "CustomType") return type[1];
+  return type[0];
+}
+
+function externalValueName(value) {
+  if (typeof value === "string") return JSON.stringify(value);
+  if (typeof value === "bigint") return `${value}n`;
+  if (typeof value === "function") return "a function";
+  if (Array.isArray(value)) return `an array of ${value.length} elements`;
+  if (value === null || typeof value !== "object") return String(value);
+  let name = Object.getPrototypeOf(value)?.constructor?.name;
+  return name ? `an instance of ${name}` : "an object";
+}
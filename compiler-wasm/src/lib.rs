@@ -8,7 +8,7 @@ use gleam_core::{
     build::{
         Mode, NullTelemetry, PackageCompiler, StaleTracker, Target, TargetCodegenConfiguration,
     },
-    config::PackageConfig,
+    config::{JavaScriptModuleFormat, PackageConfig},
     io::{FileSystemReader, FileSystemWriter},
     uid::UniqueIdGenerator,
     warning::{VectorWarningEmitterIO, WarningEmitter},
@@ -182,6 +182,10 @@ fn do_compile_package(project: Project, target: Target) -> Result<(), Error> {
         Target::JavaScript => TargetCodegenConfiguration::JavaScript {
             emit_typescript_definitions: false,
             prelude_location: Utf8PathBuf::from("./gleam_prelude.mjs"),
+            integers: config.javascript.integers,
+            // The playground loads the modules in the browser as ES modules.
+            module_format: JavaScriptModuleFormat::Esm,
+            check_externals: false,
         },
     };
 
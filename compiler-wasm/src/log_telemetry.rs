@@ -19,6 +19,10 @@ impl Telemetry for LogTelemetry {
         tracing::info!("Resolving package versions");
     }
 
+    fn resolving_package_versions_offline(&self) {
+        tracing::info!("Resolving package versions offline");
+    }
+
     fn packages_downloaded(&self, _start: std::time::Instant, count: usize) {
         tracing::info!("Downloaded {} packages", count);
     }
@@ -0,0 +1 @@
+module.exports = { one: () => 1 };
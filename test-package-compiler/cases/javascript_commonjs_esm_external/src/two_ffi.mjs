@@ -0,0 +1 @@
+export function two() { return 2; }
@@ -205,6 +205,18 @@ fn imported_record_constructors() {
     );
 }
 
+#[rustfmt::skip]
+#[test]
+fn javascript_commonjs_esm_external() {
+    let output =
+        crate::prepare("./cases/javascript_commonjs_esm_external");
+    insta::assert_snapshot!(
+        "javascript_commonjs_esm_external",
+        output,
+        "./cases/javascript_commonjs_esm_external"
+    );
+}
+
 #[rustfmt::skip]
 #[test]
 fn javascript_d_ts() {
@@ -41,7 +41,13 @@ pub fn prepare(path: &str) -> String {
         },
         Target::JavaScript => TargetCodegenConfiguration::JavaScript {
             emit_typescript_definitions: config.javascript.typescript_declarations,
-            prelude_location: Utf8PathBuf::from("../prelude.mjs"),
+            prelude_location: Utf8PathBuf::from(format!(
+                "../prelude.{}",
+                config.javascript.module_format.extension()
+            )),
+            integers: config.javascript.integers,
+            module_format: config.javascript.module_format,
+            check_externals: false,
         },
     };
 
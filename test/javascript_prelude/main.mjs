@@ -5,11 +5,16 @@ import {
   List,
   Ok,
   UtfCodepoint,
+  checkExternal,
   codepointBits,
   divideFloat,
   divideInt,
   isEqual,
+  remainderInt,
+  sizedFloat,
+  sizedInt,
   stringBits,
+  stringToUtf16,
   toBitArray,
   toList,
 } from "./prelude.mjs";
@@ -143,6 +148,15 @@ assertNotEqual(
   new BitArray(new Uint8Array([1, 2, 3])),
 );
 
+assertEqual(
+  new BitArray(new Uint8Array([16]), 4),
+  new BitArray(new Uint8Array([16]), 4),
+);
+assertNotEqual(
+  new BitArray(new Uint8Array([16]), 4),
+  new BitArray(new Uint8Array([16]), 8),
+);
+
 assertEqual(new UtfCodepoint(128013), new UtfCodepoint(128013));
 assertNotEqual(new UtfCodepoint(128013), new UtfCodepoint(128014));
 
@@ -165,6 +179,77 @@ assertEqual(
   toBitArray([codepointBits(new UtfCodepoint(128013))]),
 );
 
+assertEqual(
+  new BitArray(new Uint8Array([255, 224]), 11),
+  toBitArray([sizedInt(1, 1, true), sizedInt(3, 2, true), 255]),
+);
+
+assertEqual(
+  new BitArray(new Uint8Array([188, 160]), 12),
+  toBitArray([sizedInt(0xabc, 12, false)]),
+);
+
+assertEqual(
+  new BitArray(new Uint8Array([1, 2, 48]), 20),
+  toBitArray([new BitArray(new Uint8Array([1, 0]), 12), 35]),
+);
+
+assertEqual(
+  new BitArray(new Uint8Array([60, 0])),
+  toBitArray([sizedFloat(1, 16, true)]),
+);
+
+assertEqual(
+  new BitArray(new Uint8Array([216, 61, 220, 13])),
+  toBitArray([stringToUtf16("🐍", true)]),
+);
+
+assertEqual(
+  new BitArray(new Uint8Array([255, 255, 255, 255, 255, 255, 255, 255])),
+  toBitArray([sizedInt(-1n, 64, true)]),
+);
+
+assertEqual(
+  new BitArray(new Uint8Array([2, 1, 0, 0, 0, 0, 0, 0, 1])),
+  toBitArray([sizedInt(2n ** 64n + 2n ** 8n + 2n, 72, false)]),
+);
+
+assertEqual(
+  new BitArray(new Uint8Array([188, 160]), 12),
+  toBitArray([sizedInt(0xabcn, 12, false)]),
+);
+
+assertEqual(
+  new BitArray(new Uint8Array([255, 224]), 11),
+  toBitArray([sizedInt(1n, 1, true), sizedInt(3n, 2, true), 255n]),
+);
+
+// Reading bits
+
+assertEqual(0xabc, toBitArray([sizedInt(0xabc, 12, false)]).intFromBits(0, 12, false, false));
+assertEqual(-1, toBitArray([255]).intFromBits(1, 5, true, true));
+assertEqual(1.5, toBitArray([0, sizedFloat(1.5, 16, true)]).floatFromBits(8, 24, true));
+assertEqual(true, Number.isNaN(toBitArray([0]).floatFromBits(0, 16, true)));
+assertEqual(
+  new BitArray(new Uint8Array([240]), 4),
+  toBitArray([255]).bitsAfter(4),
+);
+assertEqual(32, toBitArray([stringToUtf16("🐍", true)]).codepointSize(0, 16, true));
+assertEqual(true, Number.isNaN(toBitArray([0xc3, 0x28]).codepointSize(0, 8, true)));
+assertEqual(
+  new UtfCodepoint(128013),
+  toBitArray([stringBits("🐍")]).codepointAt(0, 8, true),
+);
+
+assertEqual(
+  2n ** 64n - 1n,
+  toBitArray([sizedInt(-1n, 64, true)]).bigIntFromBits(0, 64, true, false),
+);
+assertEqual(-1n, toBitArray([sizedInt(-1n, 64, true)]).bigIntFromBits(0, 64, false, true));
+assertEqual(0xabcn, toBitArray([sizedInt(0xabcn, 12, false)]).bigIntFromBits(0, 12, false, false));
+assertEqual(-1n, toBitArray([255]).bigIntFromBits(1, 5, true, true));
+assertEqual(5n, toBitArray([0, 5]).bigIntFromBits(4, 16, true, false));
+
 // toList
 
 assertEqual(toList([]), List.fromArray([]));
@@ -435,6 +520,15 @@ assertEqual(divideInt(-1, -2), 0);
 assertEqual(divideInt(-3, -2), 1);
 assertEqual(divideInt(-11, -3), 3);
 
+assertEqual(divideInt(1n, 0n), 0n);
+assertEqual(divideInt(11n, 3n), 3n);
+assertEqual(divideInt(-11n, 3n), -3n);
+assertEqual(divideInt(2n ** 64n, 2n), 2n ** 63n);
+
+assertEqual(remainderInt(1n, 0n), 0n);
+assertEqual(remainderInt(11n, 3n), 2n);
+assertEqual(remainderInt(-11n, 3n), -2n);
+
 assertEqual(divideFloat(1.5, 0.0), 0.0);
 assertEqual(divideFloat(1.5, 2.0), 0.75);
 assertEqual(divideFloat(1.5, 2.5), 0.6);
@@ -472,6 +566,66 @@ assertThrows("Should only construct BitArray from Uint8Array", () => {
   const bs = new BitArray(new ArrayBuffer(8));
 });
 
+// Test values returned by external functions are checked against their types
+
+function externalTypeError(value, type) {
+  try {
+    checkExternal(value, type, "my/mod", 1, "go", "Wrong");
+  } catch (error) {
+    return error.message;
+  }
+}
+
+assertEqual(checkExternal(1, "Int", "my/mod", 1, "go", "Wrong"), 1);
+assertEqual(externalTypeError(1n, "BigInt"), undefined);
+assertEqual(externalTypeError(1.5, "Float"), undefined);
+assertEqual(externalTypeError(undefined, "Nil"), undefined);
+assertEqual(externalTypeError(() => 1, "Function"), undefined);
+assertEqual(externalTypeError({}, null), undefined);
+assertEqual(externalTypeError(toList([1, 2]), ["List", "Int"]), undefined);
+assertEqual(
+  externalTypeError(new Ok([1, "a"]), [
+    "Result",
+    ["Tuple", "Int", "String"],
+    null,
+  ]),
+  undefined,
+);
+assertEqual(
+  externalTypeError(new ExampleRecordImpl(1, 2, 3), [
+    "CustomType",
+    "Example",
+    ExampleRecordImpl,
+  ]),
+  undefined,
+);
+assertEqual(externalTypeError(1.5, "Int"), "Wrong: expected Int, got 1.5");
+assertEqual(
+  externalTypeError(null, "String"),
+  "Wrong: expected String, got null",
+);
+assertEqual(externalTypeError(1, "BigInt"), "Wrong: expected Int, got 1");
+assertEqual(
+  externalTypeError(undefined, ["Result", "Int", "String"]),
+  "Wrong: expected Result, got undefined",
+);
+assertEqual(
+  externalTypeError(new Error(1), ["Result", "Int", "String"]),
+  "Wrong: expected String, got 1 in the Error value",
+);
+assertEqual(
+  externalTypeError(toList([1, "2"]), ["List", "Int"]),
+  'Wrong: expected Int, got "2" in element 1 of the list',
+);
+assertEqual(
+  externalTypeError([1], ["Tuple", "Int", "Int"]),
+  "Wrong: expected a tuple of 2 elements, got an array of 1 elements",
+);
+assertEqual(
+  externalTypeError(new Ok(1), ["CustomType", "Example", ExampleRecordImpl]),
+  "Wrong: expected Example, got an instance of Ok",
+);
+
 //
 // Summary
 //